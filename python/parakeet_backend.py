@@ -1,14 +1,24 @@
 #!/usr/bin/env python3
 import argparse
+import collections
+import contextlib
+import copy
 import errno
+import gc
 import inspect
+import itertools
 import json
 import os
+import queue
 import re
+import shutil
+import signal
 import socket
+import struct
 import subprocess
 import sys
 import tempfile
+import threading
 import time
 import wave
 from pathlib import Path
@@ -59,8 +69,44 @@ def parse_args() -> argparse.Namespace:
     parser.add_argument("--json", help="JSON request from Rust CLI")
     parser.add_argument("--serve", action="store_true", help="Run persistent backend daemon")
     parser.add_argument("--socket-path", default=str(PARAKEET_HOME_DEFAULT / "tmp/parakeet.sock"))
+    parser.add_argument("--listen-fd", type=int, help="Serve an inherited listening socket (systemd socket activation)")
     parser.add_argument("--service-model", default="nvidia/parakeet-tdt-0.6b-v3")
     parser.add_argument("--service-device", default="auto")
+    parser.add_argument("--max-models", type=int, default=2, help="Models the daemon keeps loaded at once")
+    parser.add_argument(
+        "--vram-budget-mb",
+        type=float,
+        default=0.0,
+        help="Evict least recently used models to keep GPU memory under this many MiB (0 disables)",
+    )
+    parser.add_argument(
+        "--per-user-homes",
+        action="store_true",
+        help="Check each request against the connecting user's file permissions (SO_PEERCRED)",
+    )
+    parser.add_argument("--allow-user", action="append", default=[], help="Serve only these users (name or uid)")
+    parser.add_argument("--allow-group", action="append", default=[], help="Serve members of these groups (name or gid)")
+    parser.add_argument(
+        "--preload-model",
+        action="append",
+        default=[],
+        help="Load this model at startup and run a warm-up inference (repeatable)",
+    )
+    parser.add_argument(
+        "--workers", type=int, default=1, help="Requests the daemon runs in parallel, spread over the visible GPUs"
+    )
+    parser.add_argument(
+        "--workers-per-gpu",
+        type=int,
+        default=0,
+        help="Run this many workers on every visible GPU instead of a fixed --workers count",
+    )
+    parser.add_argument(
+        "--idle-unload-sec",
+        type=float,
+        default=0.0,
+        help="Unload all models after this many idle seconds; the next request reloads (0 disables)",
+    )
     parser.add_argument("--verbose", action="store_true")
     args = parser.parse_args()
     if not args.serve and not args.json:
@@ -73,6 +119,12 @@ def read_request(raw_json: str) -> dict[str, Any]:
         req = json.loads(raw_json)
     except json.JSONDecodeError as exc:
         raise RuntimeError(f"invalid JSON request: {exc}") from exc
+    return validate_request(req)
+
+
+def validate_request(req: Any) -> dict[str, Any]:
+    if not isinstance(req, dict):
+        raise RuntimeError("request must be a JSON object")
     required = ["input", "model", "device", "format", "timestamps", "fuzzy_vocab", "verbose"]
     for key in required:
         if key not in req:
@@ -80,6 +132,88 @@ def read_request(raw_json: str) -> dict[str, Any]:
     return req
 
 
+def peer_credentials(conn: socket.socket) -> tuple[int, int] | None:
+    """(uid, gid) of the process on the other end of a unix socket, where the
+    platform reports it (Linux SO_PEERCRED)."""
+    if not hasattr(socket, "SO_PEERCRED"):
+        return None
+    raw = conn.getsockopt(socket.SOL_SOCKET, socket.SO_PEERCRED, struct.calcsize("3i"))
+    _pid, uid, gid = struct.unpack("3i", raw)
+    return uid, gid
+
+
+def peer_groups(uid: int, gid: int) -> set[int]:
+    try:
+        import pwd
+
+        return set(os.getgrouplist(pwd.getpwuid(uid).pw_name, gid))
+    except (ImportError, KeyError, OSError):
+        return {gid}
+
+
+def peer_allowed(path: Path, uid: int, groups: set[int], want: int) -> bool:
+    """Whether the permission bits of `path` grant `want` (4 read, 1 search) to the peer."""
+    st = path.stat()
+    if st.st_uid == uid:
+        shift = 6
+    elif st.st_gid in groups:
+        shift = 3
+    else:
+        shift = 0
+    return (st.st_mode >> shift) & want == want
+
+
+def resolve_ids(names: list[str], kind: str) -> set[int]:
+    """uids (kind "user") or gids (kind "group") for names or numeric ids."""
+    import grp
+    import pwd
+
+    ids = set()
+    for name in names:
+        if name.isdigit():
+            ids.add(int(name))
+            continue
+        try:
+            ids.add(pwd.getpwnam(name).pw_uid if kind == "user" else grp.getgrnam(name).gr_gid)
+        except KeyError:
+            raise RuntimeError(f"unknown {kind} in the daemon's allow list: {name}") from None
+    return ids
+
+
+def peer_authorized(peer: tuple[int, int], users: set[int], groups: set[int]) -> bool:
+    uid, gid = peer
+    # The daemon's own user (and root) can always reach it.
+    if uid in {0, os.geteuid()} or uid in users:
+        return True
+    return bool(groups & peer_groups(uid, gid))
+
+
+def check_peer_request(req: dict[str, Any], peer: tuple[int, int], parakeet_home: Path) -> None:
+    """Refuses requests for files the calling user couldn't open themselves,
+    so a shared daemon can't be used to read other users' audio or to write
+    into their job directories."""
+    uid, gid = peer
+    if uid == 0:
+        return
+    groups = peer_groups(uid, gid)
+    for key in ("input", "vocab"):
+        if not req.get(key):
+            continue
+        path = Path(req[key]).expanduser().resolve()
+        try:
+            readable = all(peer_allowed(d, uid, groups, 1) for d in path.parents) and peer_allowed(
+                path, uid, groups, 4
+            )
+        except OSError:
+            readable = False
+        if not readable:
+            raise RuntimeError(f"permission denied for uid {uid}: {key} {path}")
+    if req.get("work_dir"):
+        area = (parakeet_home / "users" / str(uid)).resolve()
+        if not Path(req["work_dir"]).expanduser().resolve().is_relative_to(area):
+            raise RuntimeError(f"work_dir must be inside {area} for uid {uid}")
+
+
 def ensure_runtime_dirs(parakeet_home: Path) -> None:
     for rel in [
         ".cache/home",
@@ -93,6 +227,96 @@ def ensure_runtime_dirs(parakeet_home: Path) -> None:
         (parakeet_home / rel).mkdir(parents=True, exist_ok=True)
 
 
+def run_ffmpeg(cmd: list[str]) -> subprocess.CompletedProcess:
+    try:
+        return subprocess.run(cmd, stdout=subprocess.PIPE, stderr=subprocess.PIPE, text=True)
+    except FileNotFoundError:
+        raise RuntimeError(
+            "ffmpeg not found on PATH; install it (apt install ffmpeg / brew install ffmpeg)"
+        ) from None
+
+
+# What the model consumes; NeMo resamples/downmixes anything else while loading.
+MODEL_SAMPLE_RATE = 16000
+MODEL_CHANNELS = 1
+
+
+def probe_audio_format(path: Path) -> dict[str, Any] | None:
+    """Codec, sample rate, channels and bit depth of the first audio stream,
+    via ffprobe when available (the wave module otherwise, WAV only)."""
+    if shutil.which("ffprobe"):
+        proc = subprocess.run(
+            [
+                "ffprobe",
+                "-v",
+                "error",
+                "-select_streams",
+                "a:0",
+                "-show_entries",
+                "stream=codec_name,sample_rate,channels,bits_per_sample,bits_per_raw_sample",
+                "-of",
+                "json",
+                str(path),
+            ],
+            stdout=subprocess.PIPE,
+            stderr=subprocess.PIPE,
+            text=True,
+        )
+        try:
+            stream = json.loads(proc.stdout)["streams"][0]
+        except (ValueError, KeyError, IndexError, TypeError):
+            stream = None
+        if stream is not None:
+            # Lossy codecs report 0; their bit depth is only decided when decoding.
+            bits = [int(stream.get(key) or 0) for key in ("bits_per_raw_sample", "bits_per_sample")]
+            return {
+                "codec": stream.get("codec_name"),
+                "sample_rate": int(stream.get("sample_rate") or 0) or None,
+                "channels": stream.get("channels"),
+                "bit_depth": next((b for b in bits if b > 0), None),
+            }
+    try:
+        with wave.open(str(path), "rb") as wf:
+            return {
+                "codec": f"pcm_s{wf.getsampwidth() * 8}le" if wf.getsampwidth() > 1 else "pcm_u8",
+                "sample_rate": wf.getframerate(),
+                "channels": wf.getnchannels(),
+                "bit_depth": wf.getsampwidth() * 8,
+            }
+    except Exception:
+        return None
+
+
+def describe_conversion(src: dict[str, Any] | None, dst: dict[str, Any] | None) -> list[str]:
+    if not src or not dst:
+        return []
+    changes = []
+    if src.get("codec") and dst.get("codec") and src["codec"] != dst["codec"]:
+        changes.append(f"decode {src['codec']} -> {dst['codec']}")
+    if src.get("sample_rate") and dst.get("sample_rate") and src["sample_rate"] != dst["sample_rate"]:
+        changes.append(f"resample {src['sample_rate']} -> {dst['sample_rate']} Hz")
+    if src.get("channels") and dst.get("channels") and src["channels"] != dst["channels"]:
+        verb = "downmix" if src["channels"] > dst["channels"] else "upmix"
+        changes.append(f"{verb} {src['channels']} -> {dst['channels']} channels")
+    if src.get("bit_depth") and dst.get("bit_depth") and src["bit_depth"] != dst["bit_depth"]:
+        changes.append(f"bit depth {src['bit_depth']} -> {dst['bit_depth']}")
+    return changes
+
+
+def preprocess_step(name: str, tool: str, src: dict[str, Any] | None, dst: dict[str, Any] | None) -> dict[str, Any]:
+    return {"name": name, "tool": tool, "from": src, "to": dst, "changes": describe_conversion(src, dst)}
+
+
+def loader_step(fmt: dict[str, Any] | None) -> dict[str, Any] | None:
+    """The implicit conversion NeMo applies to audio it is handed as-is."""
+    if not fmt:
+        return None
+    if fmt.get("sample_rate") in (None, MODEL_SAMPLE_RATE) and fmt.get("channels") in (None, MODEL_CHANNELS):
+        return None
+    target = {"codec": None, "sample_rate": MODEL_SAMPLE_RATE, "channels": MODEL_CHANNELS, "bit_depth": None}
+    return preprocess_step("load", "nemo", fmt, target)
+
+
 def normalize_audio(in_path: Path, temp_dir: Path, verbose: bool) -> Path:
     if in_path.suffix.lower() in {".wav", ".flac", ".mp3", ".m4a", ".ogg"}:
         return in_path
@@ -111,7 +335,7 @@ def normalize_audio(in_path: Path, temp_dir: Path, verbose: bool) -> Path:
     ]
     if verbose:
         print(f"[parakeet] converting input via ffmpeg: {' '.join(cmd)}", file=sys.stderr)
-    proc = subprocess.run(cmd, stdout=subprocess.PIPE, stderr=subprocess.PIPE, text=True)
+    proc = run_ffmpeg(cmd)
     if proc.returncode != 0:
         raise RuntimeError(
             "ffmpeg conversion failed. install ffmpeg or pass a supported audio file.\n"
@@ -120,6 +344,23 @@ def normalize_audio(in_path: Path, temp_dir: Path, verbose: bool) -> Path:
     return out_path
 
 
+def cut_audio(in_path: Path, temp_dir: Path, start: float | None, end: float | None, verbose: bool) -> Path:
+    out_path = temp_dir / f"{in_path.stem}.range.wav"
+    cmd = ["ffmpeg", "-y"]
+    if start:
+        cmd += ["-ss", f"{start:.3f}"]
+    cmd += ["-i", str(in_path)]
+    if end is not None:
+        cmd += ["-t", f"{end - (start or 0.0):.3f}"]
+    cmd += ["-ac", "1", "-ar", "16000", str(out_path)]
+    if verbose:
+        print(f"[parakeet] cutting input range via ffmpeg: {' '.join(cmd)}", file=sys.stderr)
+    proc = run_ffmpeg(cmd)
+    if proc.returncode != 0:
+        raise RuntimeError(f"ffmpeg range cut failed.\n{proc.stderr.strip()}")
+    return out_path
+
+
 def pick_device(requested: str) -> str:
     req = requested.lower()
     if req == "auto":
@@ -128,7 +369,12 @@ def pick_device(requested: str) -> str:
         if req == "cuda" and not torch.cuda.is_available():
             raise RuntimeError("device=cuda requested but CUDA is not available")
         return req
-    raise RuntimeError("invalid --device. allowed: auto|cpu|cuda")
+    if req.startswith("cuda:") and req[5:].isdigit():
+        visible = torch.cuda.device_count() if torch.cuda.is_available() else 0
+        if int(req[5:]) >= visible:
+            raise RuntimeError(f"device={req} requested but {visible} CUDA device(s) are visible")
+        return req
+    raise RuntimeError("invalid --device. allowed: auto|cpu|cuda|cuda:N")
 
 
 def load_vocab(path: Path | None) -> list[str]:
@@ -144,6 +390,10 @@ def load_vocab(path: Path | None) -> list[str]:
     return terms
 
 
+# Bump when apply_vocab_rules changes behavior; reported in the response's postprocessing list.
+VOCAB_RULES_VERSION = 1
+
+
 def apply_vocab_rules(text: str, vocab_terms: list[str], fuzzy_enabled: bool) -> str:
     if not vocab_terms:
         return text
@@ -184,6 +434,88 @@ def apply_vocab_rules(text: str, vocab_terms: list[str], fuzzy_enabled: bool) ->
     return re.sub(r"\b[\w'-]+\b", repl, updated)
 
 
+# Audio transcribed by `--language auto` to pick the language.
+LANGUAGE_PROBE_SEC = 20.0
+LANGUAGE_STOPWORDS: dict[str, set[str]] = {
+    "en": {"the", "and", "is", "are", "you", "that", "this", "with", "for", "have", "was", "not", "what", "it"},
+    "de": {"der", "die", "das", "und", "ist", "nicht", "ich", "sie", "wir", "mit", "auch", "ein", "eine", "zu"},
+    "fr": {"le", "la", "les", "et", "est", "pas", "je", "vous", "nous", "une", "des", "que", "pour", "avec"},
+    "es": {"el", "la", "los", "las", "y", "es", "no", "que", "de", "una", "por", "para", "con", "pero"},
+    "it": {"il", "che", "non", "sono", "una", "per", "con", "della", "questo", "anche", "ma", "ho", "gli"},
+    "pt": {"o", "os", "que", "não", "uma", "para", "com", "mas", "está", "você", "isso", "também", "ao"},
+    "nl": {"de", "het", "een", "en", "is", "niet", "ik", "je", "dat", "van", "met", "ook", "zijn", "wat"},
+    "pl": {"nie", "jest", "się", "że", "to", "na", "jak", "ale", "tak", "czy", "już", "tylko", "bardzo"},
+    "uk": {"що", "це", "як", "але", "так", "він", "вона", "вони", "ми", "ви", "та", "або", "дуже", "тут"},
+    "ru": {"что", "это", "как", "но", "так", "он", "она", "они", "мы", "вы", "и", "или", "очень", "здесь"},
+}
+
+
+def detect_language(text: str) -> str | None:
+    """
+    Cheap stopword/script vote over the transcript. Good enough to tell archives
+    apart by language; returns None when there is too little text to decide.
+    """
+    words = re.findall(r"[^\W\d_]+", text.lower())
+    if len(words) < 3:
+        return None
+
+    cyrillic = sum(1 for w in words if re.search(r"[а-яіїєґ]", w))
+    if cyrillic * 2 > len(words):
+        if re.search(r"[іїєґ]", text.lower()):
+            return "uk"
+        if re.search(r"[ыэъё]", text.lower()):
+            return "ru"
+
+    scores = {lang: sum(1 for w in words if w in stop) for lang, stop in LANGUAGE_STOPWORDS.items()}
+    best = max(scores, key=lambda lang: scores[lang])
+    if scores[best] == 0:
+        return None
+    return best
+
+
+def probe_language(
+    model: Any, input_path: Path, temp_dir: Path, start: float | None, end: float | None, device: str, verbose: bool
+) -> str | None:
+    """`--language auto`: transcribes the first LANGUAGE_PROBE_SEC of the
+    range and votes on the language of that text."""
+    probe_dir = temp_dir / "language-probe"
+    probe_dir.mkdir(parents=True, exist_ok=True)
+    probe_end = (start or 0.0) + LANGUAGE_PROBE_SEC
+    if end is not None:
+        probe_end = min(probe_end, end)
+    clip = cut_audio(input_path, probe_dir, start, probe_end, verbose)
+    with precision_context(model, device):
+        result = run_model(model, [str(clip)], {})
+    if not result:
+        return None
+    # split_nbest also copes with beam search returning every candidate (--nbest).
+    best, _ = split_nbest(result)
+    language = detect_language(hypothesis_text(best))
+    if verbose:
+        seconds = safe_audio_duration_sec(clip) or probe_end - (start or 0.0)
+        print(f"[parakeet] language probe ({seconds:.0f}s): {language or 'undecided'}", file=sys.stderr)
+    return language
+
+
+def source_language(requested: str | None, probed: str | None) -> str | None:
+    return requested if requested not in {None, "auto"} else probed
+
+
+def translate_options(requested: str | None, probed: str | None) -> dict[str, Any]:
+    """Speech translation to English with NeMo's multitask (Canary) models."""
+    source = source_language(requested, probed)
+    if source is None:
+        raise RuntimeError("--translate could not detect the source language; pass --language CODE")
+    return {"task": "ast", "source_lang": source, "target_lang": "en", "pnc": "yes"}
+
+
+def run_model(model: Any, audio_list: list[str], extra: dict[str, Any]) -> Any:
+    try:
+        return model.transcribe(paths2audio_files=audio_list, batch_size=1, num_workers=0, verbose=False, **extra)
+    except TypeError:
+        return model.transcribe(audio=audio_list, batch_size=1, num_workers=0, verbose=False, **extra)
+
+
 def to_markdown(text: str, source: Path, model_name: str, device: str) -> str:
     return (
         f"# Transcript\n\n"
@@ -194,6 +526,69 @@ def to_markdown(text: str, source: Path, model_name: str, device: str) -> str:
     )
 
 
+def extract_segments(
+    hypothesis: Any, vocab_terms: list[str], fuzzy_enabled: bool, offset: float = 0.0, confidence: bool = False
+) -> list[dict[str, Any]]:
+    stamps = getattr(hypothesis, "timestamp", None) or {}
+    word_conf = word_confidences(hypothesis) if confidence else []
+    segments = []
+    # Word confidences follow the hypothesis words in order; each segment
+    # takes its weakest word.
+    consumed = 0
+    for seg in stamps.get("segment", []) or []:
+        text = str(seg.get("segment", "")).strip()
+        if not text:
+            continue
+        item = {
+            "start": float(seg.get("start", 0.0)) + offset,
+            "end": float(seg.get("end", 0.0)) + offset,
+            "text": apply_vocab_rules(text, vocab_terms, fuzzy_enabled),
+        }
+        count = len(text.split())
+        scores = word_conf[consumed : consumed + count]
+        consumed += count
+        if scores:
+            item["confidence"] = round(min(scores), 4)
+        segments.append(item)
+    return segments
+
+
+def word_confidences(hypothesis: Any) -> list[float]:
+    """Per-word confidence (0-1) of a hypothesis decoded with
+    `preserve_word_confidence`; empty when the model did not provide it."""
+    values = getattr(hypothesis, "word_confidence", None) or []
+    scores = []
+    for value in values:
+        try:
+            scores.append(float(value.item() if hasattr(value, "item") else value))
+        except (TypeError, ValueError):
+            return []
+    return scores
+
+
+def extract_words(
+    hypothesis: Any, vocab_terms: list[str], fuzzy_enabled: bool, offset: float = 0.0, confidence: bool = False
+) -> list[dict[str, Any]]:
+    stamps = getattr(hypothesis, "timestamp", None) or {}
+    # Word confidences follow the timestamped words in order, so chunked runs
+    # can rebuild segment scores after stitching.
+    word_conf = word_confidences(hypothesis) if confidence else []
+    words = []
+    for idx, item in enumerate(stamps.get("word", []) or []):
+        text = str(item.get("word", "")).strip()
+        if not text:
+            continue
+        word = {
+            "text": apply_vocab_rules(text, vocab_terms, fuzzy_enabled),
+            "start": float(item.get("start", 0.0)) + offset,
+            "end": float(item.get("end", 0.0)) + offset,
+        }
+        if idx < len(word_conf):
+            word["confidence"] = round(word_conf[idx], 4)
+        words.append(word)
+    return words
+
+
 def safe_audio_duration_sec(path: Path) -> float | None:
     try:
         with wave.open(str(path), "rb") as wf:
@@ -205,23 +600,407 @@ def safe_audio_duration_sec(path: Path) -> float | None:
         return None
 
 
-def load_model(model_name: str, device: str, verbose: bool) -> tuple[Any, str, float]:
+def check_precision(precision: str, device: str) -> str:
+    """Validates `--precision` against the resolved device and returns the
+    precision the model runs at ("auto" keeps the fp32 weights)."""
+    if precision in {"auto", "fp32"}:
+        return "fp32"
+    if precision not in {"fp16", "bf16", "int8"}:
+        raise RuntimeError("invalid --precision. allowed: auto|fp32|fp16|bf16|int8")
+    on_cuda = device.startswith("cuda")
+    if precision == "fp16" and not on_cuda:
+        raise RuntimeError("precision=fp16 needs a CUDA device; use bf16 or int8 on CPU")
+    if precision == "int8" and on_cuda:
+        raise RuntimeError("precision=int8 (dynamic quantization) runs on CPU only; use fp16 or bf16 on a GPU")
+    if precision == "bf16" and on_cuda:
+        index = torch.device(device).index or 0
+        major, minor = torch.cuda.get_device_capability(index)
+        if major < 8:
+            name = torch.cuda.get_device_name(index)
+            raise RuntimeError(
+                f"precision=bf16 needs compute capability 8.0+ (Ampere or newer); "
+                f"{name} is {major}.{minor}, use fp16"
+            )
+    return precision
+
+
+def apply_precision(model: Any, precision: str) -> Any:
+    if precision == "int8":
+        model = torch.ao.quantization.quantize_dynamic(model, {torch.nn.Linear}, dtype=torch.qint8)
+    elif precision in {"fp16", "bf16"}:
+        model = model.to(torch.float16 if precision == "fp16" else torch.bfloat16)
+        # Feature extraction stays fp32; autocast (see `precision_context`) bridges the dtypes.
+        preprocessor = getattr(model, "preprocessor", None)
+        if preprocessor is not None:
+            preprocessor.float()
+    model._parakeet_precision = precision
+    return model
+
+
+def precision_context(model: Any, device: str) -> Any:
+    precision = getattr(model, "_parakeet_precision", "fp32")
+    if precision not in {"fp16", "bf16"}:
+        return contextlib.nullcontext()
+    dtype = torch.float16 if precision == "fp16" else torch.bfloat16
+    return torch.autocast(device_type=torch.device(device).type, dtype=dtype)
+
+
+# Bump when the pickled layout changes so older snapshots count as stale.
+SNAPSHOT_FORMAT = 1
+
+
+def snapshot_paths(model_name: str, device: str, precision: str) -> tuple[Path, Path]:
+    safe = re.sub(r"[^A-Za-z0-9._-]+", "_", model_name)
+    base = PARAKEET_HOME_DEFAULT / ".cache" / "snapshots" / f"{safe}-{device.split(':')[0]}-{precision}"
+    return Path(f"{base}.pt"), Path(f"{base}.json")
+
+
+def checkpoint_revision(model_name: str) -> str | None:
+    """Identifies the checkpoint a snapshot was built from: size and mtime of
+    a local .nemo file, else the Hugging Face cache's commit for the repo."""
+    path = Path(model_name).expanduser()
+    if path.suffix == ".nemo" and path.exists():
+        st = path.stat()
+        return f"{st.st_size}:{int(st.st_mtime)}"
+    hf_home = Path(os.environ.get("HF_HOME") or Path.home() / ".cache" / "huggingface")
+    hub = Path(os.environ.get("HF_HUB_CACHE") or hf_home / "hub")
+    ref = hub / ("models--" + model_name.replace("/", "--")) / "refs" / "main"
+    try:
+        return ref.read_text(encoding="utf-8").strip()
+    except OSError:
+        return None
+
+
+def snapshot_fingerprint(model_name: str, precision: str) -> dict[str, Any]:
+    try:
+        from importlib.metadata import version
+
+        nemo_version = version("nemo_toolkit")
+    except Exception:
+        nemo_version = None
+    return {
+        "format": SNAPSHOT_FORMAT,
+        "model": model_name,
+        "precision": precision,
+        "checkpoint": checkpoint_revision(model_name),
+        "torch": torch.__version__,
+        "nemo": nemo_version,
+    }
+
+
+def snapshot_trusted(path: Path) -> bool:
+    # Unpickling runs code: only files from this user or root that nobody else can write.
+    st = path.stat()
+    return st.st_uid in {0, os.geteuid()} and not st.st_mode & 0o022
+
+
+def load_snapshot(model_name: str, device: str, precision: str, verbose: bool) -> Any | None:
+    model_path, meta_path = snapshot_paths(model_name, device, precision)
+    if not model_path.exists():
+        return None
+    try:
+        if not (snapshot_trusted(model_path.parent) and snapshot_trusted(model_path) and snapshot_trusted(meta_path)):
+            print(f"[parakeet] fast start: ignoring {model_path} (owned by another user or writable by others)", file=sys.stderr)
+            return None
+        if json.loads(meta_path.read_text(encoding="utf-8")) != snapshot_fingerprint(model_name, precision):
+            if verbose:
+                print(f"[parakeet] fast start: snapshot of {model_name} is stale; rebuilding", file=sys.stderr)
+            return None
+        return torch.load(model_path, map_location=device, weights_only=False)
+    except Exception as exc:
+        print(f"[parakeet] fast start: snapshot unusable ({exc}); loading normally", file=sys.stderr)
+        return None
+
+
+def save_snapshot(model: Any, model_name: str, device: str, precision: str) -> None:
+    model_path, meta_path = snapshot_paths(model_name, device, precision)
+    tmp = model_path.with_name(f"{model_path.name}.{os.getpid()}.tmp")
+    try:
+        model_path.parent.mkdir(parents=True, exist_ok=True)
+        torch.save(model, tmp)
+        os.chmod(tmp, 0o644)
+        os.replace(tmp, model_path)
+        meta_path.write_text(json.dumps(snapshot_fingerprint(model_name, precision)), encoding="utf-8")
+    except Exception as exc:
+        print(f"[parakeet] fast start: could not write snapshot ({exc})", file=sys.stderr)
+        with contextlib.suppress(OSError):
+            tmp.unlink()
+
+
+def load_model(
+    model_name: str, device: str, verbose: bool, precision: str = "auto", fast_start: bool = False
+) -> tuple[Any, str, float]:
     t0 = time.perf_counter()
     resolved_device = pick_device(device)
+    precision = check_precision(precision, resolved_device)
+    if fast_start:
+        model = load_snapshot(model_name, resolved_device, precision, verbose)
+        if model is not None:
+            if verbose:
+                print(
+                    f"[parakeet] fast start: {model_name} from snapshot in {time.perf_counter() - t0:.1f}s",
+                    file=sys.stderr,
+                )
+            return model, resolved_device, time.perf_counter() - t0
     if verbose:
-        print(f"[parakeet] loading model: {model_name} on {resolved_device}", file=sys.stderr)
+        print(f"[parakeet] loading model: {model_name} on {resolved_device} ({precision})", file=sys.stderr)
 
     model = nemo_asr.models.ASRModel.from_pretrained(model_name=model_name)
     model = model.to(torch.device(resolved_device))
-    return model, resolved_device, time.perf_counter() - t0
+    model = apply_precision(model, precision)
+    load_sec = time.perf_counter() - t0
+    if fast_start:
+        # Written before any per-request decoding changes, so the snapshot matches a fresh load.
+        save_snapshot(model, model_name, resolved_device, precision)
+    return model, resolved_device, load_sec
+
+
+def allocated_mb(device: str) -> float:
+    if not device.startswith("cuda"):
+        return 0.0
+    return torch.cuda.memory_allocated(torch.device(device).index or 0) / (1024 * 1024)
+
+
+def reset_peak_mb(device: str) -> None:
+    if device.startswith("cuda") and torch.cuda.is_available():
+        torch.cuda.reset_peak_memory_stats(torch.device(device).index or 0)
+
+
+def peak_mb(device: str) -> float | None:
+    """Peak allocation since the last reset_peak_mb, loaded weights included."""
+    if not device.startswith("cuda") or not torch.cuda.is_available():
+        return None
+    return round(torch.cuda.max_memory_allocated(torch.device(device).index or 0) / (1024 * 1024), 1)
+
+
+class ModelCache:
+    """Models kept loaded by the daemon, so requests for a different model
+    (e.g. another --quality preset) don't reload it every time. The least
+    recently used model is dropped once more than `limit` are loaded or, with
+    a VRAM budget, once the GPU holds more than `budget_mb`; the model just
+    requested is never dropped for it."""
+
+    def __init__(self, limit: int, budget_mb: float = 0.0) -> None:
+        self.limit = max(1, limit)
+        self.budget_mb = budget_mb
+        self.lock = threading.Lock()
+        # Keyed by (model, device, precision).
+        self.models: collections.OrderedDict[tuple[str, str, str], Any] = collections.OrderedDict()
+        # GPU memory each model took when last loaded, to make room before loading it again.
+        self.footprint_mb: dict[tuple[str, str, str], float] = {}
+        self.evictions = 0
+
+    def get(
+        self, name: str, device: str, verbose: bool, precision: str = "auto", fast_start: bool = False
+    ) -> tuple[Any, str, float]:
+        resolved = pick_device(device)
+        key = (name, resolved, check_precision(precision, resolved))
+        with self.lock:
+            if key in self.models:
+                self.models.move_to_end(key)
+                return self.models[key], resolved, 0.0
+            self.evict(key, resolved, reserve_mb=self.footprint_mb.get(key, 0.0))
+        before_mb = allocated_mb(resolved)
+        model, resolved, load_sec = load_model(name, device, verbose, precision, fast_start)
+        with self.lock:
+            self.models[key] = model
+            if resolved.startswith("cuda"):
+                self.footprint_mb[key] = max(0.0, allocated_mb(resolved) - before_mb)
+            self.evict(key, resolved)
+        if resolved.startswith("cuda"):
+            torch.cuda.empty_cache()
+        return model, resolved, load_sec
+
+    def over_budget(self, device: str, reserve_mb: float) -> bool:
+        return self.budget_mb > 0 and allocated_mb(device) + reserve_mb > self.budget_mb
+
+    def evict(self, keep: tuple[str, str, str], device: str, reserve_mb: float = 0.0) -> None:
+        # Caller holds self.lock.
+        while True:
+            victims = [key for key in self.models if key != keep]
+            over_count = len(self.models) > self.limit
+            if not victims or not (over_count or self.over_budget(device, reserve_mb)):
+                return
+            evicted = victims[0]
+            del self.models[evicted]
+            self.evictions += 1
+            # Freed now, so the next budget check sees the memory returned.
+            gc.collect()
+            if evicted[1].startswith("cuda"):
+                torch.cuda.empty_cache()
+            reason = "model limit" if over_count else f"VRAM budget {self.budget_mb:.0f} MiB"
+            print(f"[parakeetd] unloaded model {evicted[0]} ({reason})", file=sys.stderr, flush=True)
+
+    def names(self) -> list[str]:
+        with self.lock:
+            return [name for name, _, _ in self.models]
+
+    def clear(self) -> int:
+        """Drops every loaded model and returns its memory (GPU included);
+        `get` loads them again on demand."""
+        with self.lock:
+            count = len(self.models)
+            on_cuda = any(device.startswith("cuda") for _, device, _ in self.models)
+            self.models.clear()
+        gc.collect()
+        if on_cuda:
+            torch.cuda.empty_cache()
+        return count
+
+
+class Worker:
+    """One slot of the daemon's pool, with its own models on its own device,
+    so several requests (e.g. the chunks of one long recording) run at once."""
+
+    def __init__(self, index: int, device: str, max_models: int, vram_budget_mb: float) -> None:
+        self.index = index
+        self.device = device
+        self.models = ModelCache(max_models, vram_budget_mb)
+
+    def request_device(self, requested: str) -> str:
+        # A pinned GPU stands in for "auto"/"cuda"; explicit choices are kept.
+        if self.device.startswith("cuda:") and requested.lower() in {"auto", "cuda"}:
+            return self.device
+        return requested
+
+
+def warm_up(model: Any, parakeet_home: Path) -> float:
+    """Runs one inference on a second of silence, so CUDA kernels and decoding
+    buffers are initialized before the first real request. Returns seconds."""
+    silence = parakeet_home / "tmp" / "warmup.wav"
+    if not silence.exists():
+        silence.parent.mkdir(parents=True, exist_ok=True)
+        with wave.open(str(silence), "wb") as out:
+            out.setnchannels(MODEL_CHANNELS)
+            out.setsampwidth(2)
+            out.setframerate(MODEL_SAMPLE_RATE)
+            out.writeframes(b"\x00\x00" * MODEL_SAMPLE_RATE)
+    t0 = time.perf_counter()
+    try:
+        model.transcribe(paths2audio_files=[str(silence)], batch_size=1, num_workers=0, verbose=False)
+    except TypeError:
+        model.transcribe(audio=[str(silence)], batch_size=1, num_workers=0, verbose=False)
+    return time.perf_counter() - t0
+
+
+def worker_devices(device: str, count: int) -> list[str]:
+    """Spreads workers round-robin over the visible GPUs when running on CUDA;
+    otherwise every worker uses `device`."""
+    if count <= 1 or pick_device(device) != "cuda" or device.lower().startswith("cuda:"):
+        return [device] * max(1, count)
+    gpus = torch.cuda.device_count()
+    return [f"cuda:{i % gpus}" for i in range(count)]
+
+
+def per_gpu_worker_count(device: str, per_gpu: int) -> int:
+    """Workers for `--workers-per-gpu`: that many on each visible GPU, or just
+    `per_gpu` when the daemon runs on the CPU or one pinned GPU."""
+    if pick_device(device) != "cuda" or device.lower().startswith("cuda:"):
+        return per_gpu
+    return per_gpu * torch.cuda.device_count()
+
+
+def parse_option_value(raw: str) -> Any:
+    """`--backend-arg` values: ints, floats, true/false and null, else the string."""
+    lowered = raw.lower()
+    if lowered in {"true", "false"}:
+        return lowered == "true"
+    if lowered in {"null", "none"}:
+        return None
+    for kind in (int, float):
+        try:
+            return kind(raw)
+        except ValueError:
+            pass
+    return raw
+
+
+def decoding_options(req: dict[str, Any]) -> dict[str, Any]:
+    """Dotted paths into the model's decoding config set by the request."""
+    options: dict[str, Any] = {}
+    if req.get("len_penalty") is not None:
+        options["beam.length_penalty"] = float(req["len_penalty"])
+    if req.get("temperature") is not None:
+        options["temperature"] = float(req["temperature"])
+    if int(req.get("nbest") or 1) > 1:
+        options["beam.return_best_hypothesis"] = False
+    if req.get("confidence"):
+        options["confidence_cfg.preserve_word_confidence"] = True
+    for key, value in (req.get("backend_args") or {}).items():
+        options[key] = parse_option_value(str(value))
+    return options
+
+
+def split_nbest(result: Any) -> tuple[Any, list[Any]]:
+    """Best hypothesis and the ranked candidates for the first file when beam
+    search returns all of them: older NeMo answers `(best, all)`, newer
+    versions a list of `NBestHypotheses` (or plain lists)."""
+    if isinstance(result, tuple) and len(result) == 2:
+        best, every = result
+        candidates = every[0] if every else best[:1]
+    else:
+        best, candidates = None, result[0]
+    candidates = getattr(candidates, "n_best_hypotheses", candidates)
+    if not isinstance(candidates, (list, tuple)):
+        candidates = [candidates]
+    if not candidates:
+        raise RuntimeError("beam search returned no hypotheses")
+    return (best[0] if best else candidates[0]), list(candidates)
+
+
+def hypothesis_text(hyp: Any) -> str:
+    return hyp.text.strip() if hasattr(hyp, "text") else str(hyp).strip()
+
+
+def hypothesis_score(hyp: Any) -> float | None:
+    score = getattr(hyp, "score", None)
+    try:
+        return float(score) if score is not None else None
+    except (TypeError, ValueError):
+        return None
+
+
+def apply_decoding(model: Any, beam_size: int | None, verbose: bool, options: dict[str, Any] | None = None) -> None:
+    """Switches the model between greedy (beam_size 1) and beam search decoding
+    and applies `options` on top of the model's own decoding config. The
+    strategy sticks to the model object, so it is only changed on demand."""
+    wanted = max(1, int(beam_size or 1))
+    options = options or {}
+    state = (wanted, tuple(sorted((key, repr(value)) for key, value in options.items())))
+    if getattr(model, "_parakeet_decoding", (1, ())) == state:
+        return
+    if not hasattr(model, "change_decoding_strategy"):
+        if options:
+            raise RuntimeError("model does not support decoding options")
+        print("[parakeet] warning: model does not support beam search; using greedy decoding", file=sys.stderr)
+        return
+    from omegaconf import OmegaConf, open_dict
+
+    # Each request starts from the checkpoint's config, so options don't leak between requests.
+    if not hasattr(model, "_parakeet_base_decoding"):
+        model._parakeet_base_decoding = copy.deepcopy(model.cfg.decoding)
+    cfg = copy.deepcopy(model._parakeet_base_decoding)
+    with open_dict(cfg):
+        if wanted > 1:
+            cfg.strategy = "beam"
+            cfg.beam.beam_size = wanted
+        else:
+            cfg.strategy = "greedy_batch"
+        for key, value in options.items():
+            parent, _, leaf = key.rpartition(".")
+            node = OmegaConf.select(cfg, parent) if parent else cfg
+            if node is None or leaf not in node:
+                known = ", ".join(sorted(str(k) for k in cfg.keys()))
+                raise RuntimeError(f"unknown decoding option: {key} (top-level options: {known})")
+            OmegaConf.update(cfg, key, value)
+    model.change_decoding_strategy(cfg)
+    model._parakeet_decoding = state
+    if verbose:
+        extra = "".join(f" {key}={value}" for key, value in options.items())
+        print(f"[parakeet] decoding: {cfg.strategy} beam_size={wanted}{extra}", file=sys.stderr)
 
 
-def transcribe(
-    req: dict[str, Any],
-    preloaded_model: Any | None = None,
-    preloaded_model_name: str | None = None,
-    preloaded_device: str | None = None,
-) -> dict[str, Any]:
+def transcribe(req: dict[str, Any], models: ModelCache | None = None) -> dict[str, Any]:
     patch_sampler_compat()
     started = time.perf_counter()
     parakeet_home = PARAKEET_HOME_DEFAULT
@@ -238,125 +1017,637 @@ def transcribe(
     fuzzy_vocab = bool(req["fuzzy_vocab"])
     verbose = bool(req["verbose"])
     vocab_path = Path(req["vocab"]).expanduser().resolve() if req.get("vocab") else None
+    only_language = (req.get("only_language") or "").lower() or None
+    language_mismatch_mode = req.get("language_mismatch") or "skip"
+    start_sec = req.get("start_sec")
+    end_sec = req.get("end_sec")
+    word_timestamps = bool(req.get("word_timestamps"))
+    precision = (req.get("precision") or "auto").lower()
+    fast_start = bool(req.get("fast_start"))
+    requested_language = (req.get("language") or "").lower() or None
+    translate = bool(req.get("translate"))
 
     vocab_terms = load_vocab(vocab_path)
 
     tmp_dir = parakeet_home / "tmp"
     tmp_dir.mkdir(parents=True, exist_ok=True)
 
-    with tempfile.TemporaryDirectory(dir=tmp_dir) as td:
-        normalized = normalize_audio(input_path, Path(td), verbose)
+    # The CLI hands each job its own workdir; it owns cleanup (and may keep it for debugging).
+    if req.get("work_dir"):
+        work_dir = Path(req["work_dir"]).expanduser().resolve()
+        work_dir.mkdir(parents=True, exist_ok=True)
+        scratch = contextlib.nullcontext(str(work_dir))
+    else:
+        scratch = tempfile.TemporaryDirectory(dir=tmp_dir)
+
+    with scratch as td:
+        if start_sec is not None or end_sec is not None:
+            normalized = cut_audio(input_path, Path(td), start_sec, end_sec, verbose)
+        else:
+            normalized = normalize_audio(input_path, Path(td), verbose)
         audio_duration = safe_audio_duration_sec(normalized)
 
-        model_load_sec = 0.0
-        if (
-            preloaded_model is not None
-            and preloaded_model_name == model_name
-            and preloaded_device == pick_device(req["device"])
-        ):
-            model = preloaded_model
-            resolved_device = preloaded_device
+        # Recorded so accuracy changes can be traced to conversions, not just the model.
+        source_format = probe_audio_format(input_path)
+        preprocessing = []
+        if normalized != input_path:
+            name = "cut" if start_sec is not None or end_sec is not None else "convert"
+            preprocessing.append(preprocess_step(name, "ffmpeg", source_format, probe_audio_format(normalized)))
         else:
-            model, resolved_device, model_load_sec = load_model(model_name, req["device"], verbose)
+            step = loader_step(source_format)
+            if step:
+                preprocessing.append(step)
 
-        infer_start = time.perf_counter()
-        audio_list = [str(normalized)]
-        try:
-            result = model.transcribe(paths2audio_files=audio_list, batch_size=1, num_workers=0, verbose=False)
-        except TypeError:
-            result = model.transcribe(audio=audio_list, batch_size=1, num_workers=0, verbose=False)
-        infer_sec = time.perf_counter() - infer_start
+        def load(name: str) -> tuple[Any, str, float]:
+            if models is not None:
+                loaded = models.get(name, req["device"], verbose, precision, fast_start)
+            else:
+                loaded = load_model(name, req["device"], verbose, precision, fast_start)
+            apply_decoding(loaded[0], req.get("beam_size"), verbose, decoding_options(req))
+            return loaded
 
-        if not result:
-            raise RuntimeError("empty transcription result")
+        model, resolved_device, model_load_sec = load(model_name)
 
-        first = result[0]
-        text = first.text.strip() if hasattr(first, "text") else str(first).strip()
-        text = apply_vocab_rules(text, vocab_terms, fuzzy_vocab)
+        reset_peak_mb(resolved_device)
+        infer_start = time.perf_counter()
+        probed = None
+        # Translation needs the source language: probe unless --language names it.
+        if requested_language == "auto" or (translate and requested_language is None):
+            probed = probe_language(model, input_path, Path(td), start_sec, end_sec, resolved_device, verbose)
+        # A prefix already in the wrong language is not worth transcribing in full.
+        skip_early = (
+            language_mismatch_mode == "skip" and only_language is not None and probed not in {None, only_language}
+        )
+        routes = req.get("language_models") or {}
+        routed = (routes.get(probed) or routes.get("*")) if probed and routes else None
+        if routed and routed != model_name and not skip_early:
+            if verbose:
+                print(f"[parakeet] language {probed}: switching to {routed} (language_models)", file=sys.stderr)
+            model_name = routed
+            model, resolved_device, route_load_sec = load(model_name)
+            model_load_sec += route_load_sec
+            # Loading is reported as model_load_sec, not inference.
+            infer_start += route_load_sec
+        nbest = max(1, int(req.get("nbest") or 1))
+        if skip_early:
+            first, candidates = None, []
+        else:
+            audio_list = [str(normalized)]
+            extra = {"timestamps": True} if timestamps or word_timestamps else {}
+            if translate:
+                extra.update(translate_options(requested_language, probed))
+            with precision_context(model, resolved_device):
+                try:
+                    result = run_model(model, audio_list, extra)
+                except TypeError as exc:
+                    if not translate:
+                        raise
+                    raise RuntimeError(
+                        f"{model_name} does not support translation; --translate needs a multitask model such as "
+                        f"nvidia/canary-1b-flash"
+                    ) from exc
+            if not result:
+                raise RuntimeError("empty transcription result")
+            if nbest > 1:
+                first, candidates = split_nbest(result)
+            else:
+                first = result[0]
+                candidates = [first]
+        infer_sec = time.perf_counter() - infer_start
 
-        if timestamps:
-            text = f"[timestamps not available in current simple mode]\n{text}"
+        text = apply_vocab_rules(hypothesis_text(first), vocab_terms, fuzzy_vocab) if first is not None else ""
+        alternatives = None
+        if req.get("nbest"):
+            alternatives = [
+                {
+                    "text": apply_vocab_rules(hypothesis_text(hyp), vocab_terms, fuzzy_vocab),
+                    "score": hypothesis_score(hyp),
+                }
+                for hyp in candidates[:nbest]
+            ]
+        # Translated text is English; the source language is what was asked for or probed.
+        language = source_language(requested_language, probed) if translate else probed or detect_language(text)
+        # Too little text to decide is unknown, not a mismatch; the CLI warns.
+        language_mismatch = only_language is not None and language is not None and language != only_language
+        skipped = language_mismatch and language_mismatch_mode == "skip"
+
+        segments = (
+            extract_segments(first, vocab_terms, fuzzy_vocab, start_sec or 0.0, bool(req.get("confidence")))
+            if timestamps
+            else None
+        )
+        words = (
+            extract_words(first, vocab_terms, fuzzy_vocab, start_sec or 0.0, bool(req.get("confidence")))
+            if word_timestamps
+            else None
+        )
 
         final_text = text if output_format == "text" else to_markdown(text, input_path, model_name, resolved_device)
 
-        if output_path:
+        postprocessing = []
+        if vocab_terms:
+            postprocessing.append(
+                {
+                    "name": "vocab",
+                    "version": VOCAB_RULES_VERSION,
+                    "params": {
+                        "terms": len(vocab_terms),
+                        "fuzzy": fuzzy_vocab,
+                        "fuzzy_score_cutoff": 90,
+                    },
+                }
+            )
+
+        if output_path and not skipped:
             output_path.parent.mkdir(parents=True, exist_ok=True)
             output_path.write_text(final_text, encoding="utf-8")
 
     total_sec = time.perf_counter() - started
     return {
         "transcript": final_text,
-        "output_path": str(output_path) if output_path else None,
+        "segments": segments,
+        "words": words,
+        "alternatives": alternatives,
+        "output_path": str(output_path) if output_path and not skipped else None,
         "source": str(input_path),
         "model": model_name,
         "device": resolved_device,
+        "precision": getattr(model, "_parakeet_precision", "fp32"),
         "format": output_format,
+        "language": language,
+        "task": "translate" if translate else "transcribe",
+        "language_mismatch": language_mismatch,
+        "skipped": skipped,
+        "preprocessing": preprocessing,
+        "postprocessing": postprocessing,
         "metrics": {
             "model_load_sec": model_load_sec,
             "inference_sec": infer_sec,
             "total_sec": total_sec,
             "audio_sec": audio_duration,
+            "peak_gpu_mb": peak_mb(resolved_device),
         },
     }
 
 
-def serve(socket_path: Path, model_name: str, device: str, verbose: bool) -> int:
-    patch_sampler_compat()
-    parakeet_home = PARAKEET_HOME_DEFAULT
-    ensure_runtime_dirs(parakeet_home)
+# Daemon framing: b"PK", protocol version (u8), message type (u8), payload length (u32 BE),
+# then a UTF-8 JSON payload. Connections that start with anything else are treated as the
+# legacy newline-delimited JSON protocol.
+FRAME_MAGIC = b"PK"
+FRAME_HEADER = struct.Struct(">2sBBI")
+PROTOCOL_VERSION = 1
+MAX_FRAME_BYTES = 64 * 1024 * 1024
+MSG_REQUEST = 1
+MSG_RESPONSE = 2
+MSG_ERROR = 3
+MSG_PING = 4
+MSG_PONG = 5
+MSG_WARM = 6
+
+
+def recv_exact(conn: socket.socket, size: int) -> bytes:
+    data = b""
+    while len(data) < size:
+        chunk = conn.recv(size - len(data))
+        if not chunk:
+            raise RuntimeError("connection closed mid-frame")
+        data += chunk
+    return data
 
-    model, resolved_device, load_sec = load_model(model_name, device, verbose)
-    socket_path.parent.mkdir(parents=True, exist_ok=True)
+
+def recv_frame(conn: socket.socket, prefix: bytes) -> tuple[int, int, Any]:
+    header = prefix + recv_exact(conn, FRAME_HEADER.size - len(prefix))
+    _, version, msg_type, length = FRAME_HEADER.unpack(header)
+    if length > MAX_FRAME_BYTES:
+        raise RuntimeError(f"frame too large: {length} bytes")
+    payload = recv_exact(conn, length)
+    return version, msg_type, json.loads(payload.decode("utf-8"))
+
+
+def send_frame(conn: socket.socket, msg_type: int, payload: Any) -> None:
+    body = json.dumps(payload, ensure_ascii=False).encode("utf-8")
+    conn.sendall(FRAME_HEADER.pack(FRAME_MAGIC, PROTOCOL_VERSION, msg_type, len(body)) + body)
+
+
+def gpu_memory(device: str) -> dict[str, float] | None:
+    if not device.startswith("cuda") or not torch.cuda.is_available():
+        return None
+    index = torch.device(device).index or 0
+    mib = 1024 * 1024
+    return {
+        "allocated_mb": round(torch.cuda.memory_allocated(index) / mib, 1),
+        "reserved_mb": round(torch.cuda.memory_reserved(index) / mib, 1),
+        "total_mb": round(torch.cuda.get_device_properties(index).total_memory / mib, 1),
+    }
+
+
+def handle_framed(conn: socket.socket, prefix: bytes, run_request: Any, pong: Any, warm: Any) -> None:
     try:
-        if socket_path.exists():
-            socket_path.unlink()
-    except Exception:
-        pass
+        version, msg_type, payload = recv_frame(conn, prefix)
+        if version > PROTOCOL_VERSION:
+            raise RuntimeError(f"unsupported protocol version {version} (daemon speaks {PROTOCOL_VERSION})")
+        if msg_type == MSG_PING:
+            send_frame(conn, MSG_PONG, pong())
+            return
+        if msg_type == MSG_WARM:
+            send_frame(conn, MSG_RESPONSE, warm(payload or {}))
+            return
+        if msg_type != MSG_REQUEST:
+            raise RuntimeError(f"unsupported message type {msg_type}")
+        send_frame(conn, MSG_RESPONSE, run_request(validate_request(payload)))
+    except Exception as exc:
+        send_frame(conn, MSG_ERROR, {"error": str(exc)})
 
-    server = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
-    server.bind(str(socket_path))
-    server.listen(16)
+
+def handle_legacy(conn: socket.socket, data: bytes, run_request: Any, pong: Any) -> None:
+    try:
+        while not data.endswith(b"\n"):
+            chunk = conn.recv(65536)
+            if not chunk:
+                break
+            data += chunk
+        raw = data.decode("utf-8", errors="ignore").strip()
+        try:
+            is_ping = json.loads(raw).get("type") == "ping"
+        except (json.JSONDecodeError, AttributeError):
+            is_ping = False
+        result = pong() if is_ping else run_request(read_request(raw))
+        conn.sendall((json.dumps(result, ensure_ascii=False) + "\n").encode("utf-8"))
+    except Exception as exc:
+        payload = {"error": str(exc)}
+        conn.sendall((json.dumps(payload, ensure_ascii=False) + "\n").encode("utf-8"))
+
+
+class ShutdownRequested(Exception):
+    pass
+
+
+def timestamp_output() -> threading.Thread:
+    """Prefix every line written to stdout/stderr, including torch and NeMo
+    output, with a UTC timestamp so `daemon logs --since` can filter by age."""
+    read_fd, write_fd = os.pipe()
+    log_fd = os.dup(2)
+    sys.stdout.flush()
+    sys.stderr.flush()
+    os.dup2(write_fd, 1)
+    os.dup2(write_fd, 2)
+    os.close(write_fd)
+
+    def pump() -> None:
+        with os.fdopen(read_fd, "rb") as src, os.fdopen(log_fd, "wb", buffering=0) as dst:
+            for line in src:
+                stamp = time.strftime("%Y-%m-%d %H:%M:%S", time.gmtime())
+                dst.write(stamp.encode("ascii") + b" " + line)
+
+    pumper = threading.Thread(target=pump, daemon=True)
+    pumper.start()
+    return pumper
+
+
+def serve(
+    socket_path: Path,
+    model_name: str,
+    device: str,
+    verbose: bool,
+    listen_fd: int | None = None,
+    max_models: int = 2,
+    idle_unload_sec: float = 0.0,
+    worker_count: int = 1,
+    preload_models: list[str] | None = None,
+    vram_budget_mb: float = 0.0,
+    per_user_homes: bool = False,
+    allow_users: list[str] | None = None,
+    allow_groups: list[str] | None = None,
+    workers_per_gpu: int = 0,
+) -> int:
+    log_pump = timestamp_output()
+    patch_sampler_compat()
+    parakeet_home = PARAKEET_HOME_DEFAULT
+    ensure_runtime_dirs(parakeet_home)
+    # Unknown names fail startup rather than silently locking people out.
+    allowed_uids = resolve_ids(allow_users or [], "user")
+    allowed_gids = resolve_ids(allow_groups or [], "group")
+    restricted = bool(allow_users or allow_groups)
+
+    if workers_per_gpu > 0:
+        worker_count = per_gpu_worker_count(device, workers_per_gpu)
+    pool = [
+        Worker(i, dev, max_models, vram_budget_mb) for i, dev in enumerate(worker_devices(device, worker_count))
+    ]
+    # Every worker loads up front, so the first parallel burst doesn't load N copies at once.
+    load_sec = 0.0
+    for worker in pool:
+        _, worker.device, sec = worker.models.get(model_name, worker.device, verbose)
+        load_sec += sec
+    resolved_device = pool[0].device
+    # Free workers. Requests waiting for one are woken in arrival order, so the
+    # chunks of a long recording start in sequence.
+    idle_workers: queue.Queue[Worker] = queue.Queue()
+    for worker in pool:
+        idle_workers.put(worker)
+    warm_lock = threading.Lock()
+
+    def warm_models(names: list[str]) -> list[dict[str, Any]]:
+        # Needs every worker; serialized so two warm-ups never hold half the pool each.
+        with warm_lock:
+            held = [idle_workers.get() for _ in pool]
+            try:
+                warmed = []
+                for worker in held:
+                    for name in names:
+                        model, dev, load = worker.models.get(name, worker.device, verbose)
+                        warmed.append(
+                            {
+                                "model": name,
+                                "device": dev,
+                                "worker": worker.index,
+                                "load_sec": round(load, 3),
+                                "warmup_sec": round(warm_up(model, parakeet_home), 3),
+                            }
+                        )
+                return warmed
+            finally:
+                for worker in held:
+                    idle_workers.put(worker)
+
+    for entry in warm_models(list(preload_models or [])):
+        print(
+            f"[parakeetd] preloaded {entry['model']} on {entry['device']} "
+            f"(load_sec={entry['load_sec']:.2f} warmup_sec={entry['warmup_sec']:.2f})",
+            file=sys.stderr,
+            flush=True,
+        )
+    # An inherited socket belongs to systemd: never unlink or rebind it.
+    owns_socket = listen_fd is None
+    if owns_socket:
+        socket_path.parent.mkdir(parents=True, exist_ok=True)
+        try:
+            if socket_path.exists():
+                socket_path.unlink()
+        except Exception:
+            pass
+        server = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+        server.bind(str(socket_path))
+        server.listen(16)
+    else:
+        server = socket.socket(fileno=listen_fd)
     print(
-        f"[parakeetd] ready socket={socket_path} model={model_name} device={resolved_device} load_sec={load_sec:.2f}",
+        f"[parakeetd] ready socket={socket_path} model={model_name} "
+        f"device={','.join(w.device for w in pool)} workers={len(pool)} load_sec={load_sec:.2f}",
         file=sys.stderr,
         flush=True,
     )
 
-    while True:
-        conn, _ = server.accept()
+    started = time.time()
+    # Connections are served on their own threads so pings answer while a job
+    # runs; each transcription holds one worker until it finishes.
+    stats_lock = threading.Lock()
+    stats: dict[str, Any] = {"served": 0, "failed": 0, "queued": 0, "busy": 0, "last_error": None}
+    # Per `key=value` tag, so a shared daemon's throughput can be attributed.
+    by_tag: dict[str, dict[str, Any]] = {}
+
+    def count_tags(tags: dict[str, str], outcome: str, result: dict[str, Any] | None) -> None:
+        metrics = (result or {}).get("metrics") or {}
+        for key, value in tags.items():
+            entry = by_tag.setdefault(
+                f"{key}={value}", {"served": 0, "failed": 0, "audio_sec": 0.0, "inference_sec": 0.0}
+            )
+            entry[outcome] += 1
+            entry["audio_sec"] += metrics.get("audio_sec") or 0.0
+            entry["inference_sec"] += metrics.get("inference_sec") or 0.0
+    # Requests accepted but not yet answered. Written next to the socket while
+    # shutting down, so whatever a drain timeout cuts short is on record; the
+    # file is removed once the drain completes.
+    pending: dict[int, dict[str, Any]] = {}
+    request_ids = itertools.count(1)
+    queue_path = socket_path.with_suffix(".queue.json")
+    draining = threading.Event()
+    last_activity = [time.monotonic()]
+
+    # With --idle-unload-sec, an idle daemon gives its (GPU) memory back and
+    # keeps only the socket; ModelCache.get reloads on the next request.
+    def unload_when_idle() -> None:
+        while not draining.wait(min(30.0, max(1.0, idle_unload_sec / 4))):
+            with stats_lock:
+                idle = time.monotonic() - last_activity[0]
+                quiet = not pending
+            if not quiet or idle < idle_unload_sec or not any(w.models.names() for w in pool):
+                continue
+            # Holding every worker keeps a request from starting mid-unload.
+            held: list[Worker] = []
+            try:
+                while len(held) < len(pool):
+                    held.append(idle_workers.get_nowait())
+            except queue.Empty:
+                pass
+            try:
+                count = sum(w.models.clear() for w in held) if len(held) == len(pool) else 0
+            finally:
+                for worker in held:
+                    idle_workers.put(worker)
+            if not count:
+                continue
+            print(
+                f"[parakeetd] idle for {idle:.0f}s; unloaded {count} model(s); the next request reloads",
+                file=sys.stderr,
+                flush=True,
+            )
+
+    def persist_queue() -> None:
+        with stats_lock:
+            entries = sorted(pending.values(), key=lambda entry: entry["accepted_at"])
+        try:
+            if not entries:
+                queue_path.unlink(missing_ok=True)
+                return
+            tmp = queue_path.with_suffix(".tmp")
+            tmp.write_text(
+                json.dumps({"pid": os.getpid(), "shutdown_at": round(time.time(), 3), "requests": entries}, indent=2),
+                encoding="utf-8",
+            )
+            tmp.replace(queue_path)
+        except OSError as exc:
+            print(f"[parakeetd] failed writing queue state {queue_path}: {exc}", file=sys.stderr, flush=True)
+
+    def pong() -> dict[str, Any]:
+        with stats_lock:
+            return {
+                "type": "pong",
+                "version": os.environ.get("PARAKEET_VERSION", "unknown"),
+                "protocol": PROTOCOL_VERSION,
+                "model": model_name,
+                "models": sorted({name for w in pool for name in w.models.names()}),
+                "device": resolved_device,
+                "max_models": max_models,
+                "vram_budget_mb": vram_budget_mb or None,
+                "evictions": sum(w.models.evictions for w in pool),
+                "per_user_homes": per_user_homes,
+                "allowed_users": allow_users or [],
+                "allowed_groups": allow_groups or [],
+                "workers": len(pool),
+                "worker_devices": [w.device for w in pool],
+                "workers_busy": stats["busy"],
+                "pid": os.getpid(),
+                "uptime_sec": round(time.time() - started, 3),
+                "gpu_memory": gpu_memory(resolved_device),
+                "requests_served": stats["served"],
+                "requests_failed": stats["failed"],
+                "queue_depth": stats["queued"],
+                "busy": stats["busy"] > 0,
+                "last_error": stats["last_error"],
+                "by_tag": copy.deepcopy(by_tag),
+                "idle_unload_sec": idle_unload_sec or None,
+            }
+
+    def warm(payload: dict[str, Any]) -> dict[str, Any]:
+        warmed = warm_models(payload.get("models") or [model_name])
+        with stats_lock:
+            last_activity[0] = time.monotonic()
+        for entry in warmed:
+            print(f"[parakeetd] warmed {entry['model']} on {entry['device']}", file=sys.stderr, flush=True)
+        return {"models": warmed}
+
+    def run_request(req: dict[str, Any]) -> dict[str, Any]:
+        request_id = next(request_ids)
+        tags = req.get("tags") or {}
+        with stats_lock:
+            stats["queued"] += 1
+            pending[request_id] = {
+                "input": req.get("input"),
+                "model": req.get("model") or model_name,
+                "state": "queued",
+                "accepted_at": round(time.time(), 3),
+                "tags": tags,
+            }
+        try:
+            worker = idle_workers.get()
+            try:
+                with stats_lock:
+                    stats["queued"] -= 1
+                    stats["busy"] += 1
+                    pending[request_id]["state"] = "running"
+                    pending[request_id]["worker"] = worker.index
+                job = dict(req, device=worker.request_device(req.get("device") or "auto"))
+                try:
+                    result = transcribe(job, worker.models)
+                except Exception as exc:
+                    with stats_lock:
+                        stats["failed"] += 1
+                        stats["last_error"] = {"message": str(exc), "at": round(time.time(), 3)}
+                        count_tags(tags, "failed", None)
+                    raise
+                finally:
+                    with stats_lock:
+                        stats["busy"] -= 1
+                with stats_lock:
+                    stats["served"] += 1
+                    count_tags(tags, "served", result)
+                return result
+            finally:
+                idle_workers.put(worker)
+        finally:
+            with stats_lock:
+                pending.pop(request_id, None)
+                last_activity[0] = time.monotonic()
+            if draining.is_set():
+                persist_queue()
+
+    def handle_conn(conn: socket.socket) -> None:
+        peer = peer_credentials(conn) if per_user_homes or restricted else None
+        run_for_peer, pong_for_peer, warm_for_peer = run_request, pong, warm
+        if restricted and (peer is None or not peer_authorized(peer, allowed_uids, allowed_gids)):
+            who = f"uid {peer[0]}" if peer else "a peer without credentials"
+            print(f"[parakeetd] refused connection from {who} (not allowed)", file=sys.stderr, flush=True)
+
+            def refuse(*_args: Any) -> dict[str, Any]:
+                raise RuntimeError(f"{who} is not allowed to use this daemon")
+
+            run_for_peer = pong_for_peer = warm_for_peer = refuse
+        elif per_user_homes:
+
+            def run_for_peer(req: dict[str, Any]) -> dict[str, Any]:
+                if peer is None:
+                    raise RuntimeError("per_user_homes needs SO_PEERCRED (Linux); request refused")
+                check_peer_request(req, peer, parakeet_home)
+                return run_request(req)
+
         with conn:
             try:
-                data = b""
-                while not data.endswith(b"\n"):
-                    chunk = conn.recv(65536)
-                    if not chunk:
-                        break
-                    data += chunk
-                if not data:
-                    continue
-
-                req = read_request(data.decode("utf-8", errors="ignore").strip())
-                result = transcribe(
-                    req,
-                    preloaded_model=model,
-                    preloaded_model_name=model_name,
-                    preloaded_device=resolved_device,
-                )
-                conn.sendall((json.dumps(result, ensure_ascii=False) + "\n").encode("utf-8"))
+                prefix = conn.recv(len(FRAME_MAGIC))
+                if not prefix:
+                    return
+                if prefix == FRAME_MAGIC[: len(prefix)] and len(prefix) < len(FRAME_MAGIC):
+                    prefix += recv_exact(conn, len(FRAME_MAGIC) - len(prefix))
+                if prefix == FRAME_MAGIC:
+                    handle_framed(conn, prefix, run_for_peer, pong_for_peer, warm_for_peer)
+                else:
+                    handle_legacy(conn, prefix, run_for_peer, pong_for_peer)
+            except OSError as send_exc:
+                if send_exc.errno not in {errno.EPIPE, errno.ECONNRESET, errno.ENOTCONN}:
+                    print(f"[parakeetd] connection error: {send_exc}", file=sys.stderr, flush=True)
             except Exception as exc:
-                payload = {"error": str(exc)}
-                try:
-                    conn.sendall((json.dumps(payload, ensure_ascii=False) + "\n").encode("utf-8"))
-                except OSError as send_exc:
-                    if send_exc.errno not in {errno.EPIPE, errno.ECONNRESET, errno.ENOTCONN}:
-                        raise
+                print(f"[parakeetd] connection error: {exc}", file=sys.stderr, flush=True)
+
+    # SIGTERM stops accepting new connections but lets in-flight requests
+    # finish, so `daemon stop`/`restart` never cut a transcription short.
+    def request_shutdown(signum: int, frame: Any) -> None:
+        raise ShutdownRequested()
+
+    signal.signal(signal.SIGTERM, request_shutdown)
+    if idle_unload_sec > 0:
+        threading.Thread(target=unload_when_idle, daemon=True).start()
+    workers: list[threading.Thread] = []
+    try:
+        while True:
+            conn, _ = server.accept()
+            worker = threading.Thread(target=handle_conn, args=(conn,), daemon=True)
+            worker.start()
+            workers = [w for w in workers if w.is_alive()] + [worker]
+    except ShutdownRequested:
+        pass
+
+    server.close()
+    if owns_socket:
+        try:
+            socket_path.unlink()
+        except FileNotFoundError:
+            pass
+    in_flight = [w for w in workers if w.is_alive()]
+    print(f"[parakeetd] shutting down; waiting for {len(in_flight)} in-flight connection(s)", file=sys.stderr, flush=True)
+    draining.set()
+    persist_queue()
+    for worker in in_flight:
+        worker.join()
+    persist_queue()
+    print("[parakeetd] stopped", file=sys.stderr, flush=True)
+    # Close the pipe's write ends so the log pump drains and exits before we do.
+    sys.stdout.flush()
+    os.close(1)
+    os.close(2)
+    log_pump.join(timeout=2)
+    return 0
 
 
 def main() -> int:
     args = parse_args()
     try:
         if args.serve:
-            return serve(Path(args.socket_path), args.service_model, args.service_device, args.verbose)
+            return serve(
+                Path(args.socket_path),
+                args.service_model,
+                args.service_device,
+                args.verbose,
+                args.listen_fd,
+                args.max_models,
+                args.idle_unload_sec,
+                args.workers,
+                args.preload_model,
+                args.vram_budget_mb,
+                args.per_user_homes,
+                args.allow_user,
+                args.allow_group,
+                args.workers_per_gpu,
+            )
 
         req = read_request(args.json)
         result = transcribe(req)
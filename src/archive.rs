@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+
+use crate::history::{self, HistoryEntry};
+use crate::{config, output, porcelain, tools, userhome};
+
+const DAY_SEC: u64 = 86_400;
+// Automatic archiving runs at most this often; the stamp file records the last run.
+const AUTO_INTERVAL_SEC: u64 = DAY_SEC;
+
+/// Where a history entry's output file went when it was archived.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct ArchivedOutput {
+    /// `$PARAKEET_HOME/archive/outputs-*.tar.zst`.
+    pub archive: String,
+    /// `<history id>/<file name>` inside the archive.
+    pub member: String,
+    pub archived_at: u64,
+}
+
+pub fn archive_dir() -> PathBuf {
+    userhome::dir().join("archive")
+}
+
+/// Moves the output files of history entries older than `days` into one new
+/// `tar.zst` under [`archive_dir`] and records the location in each entry.
+/// Only files still where the run wrote them are touched. With `porcelain`,
+/// each file gets a status line instead of the dry-run listing.
+pub fn archive_older_than(days: u32, dry_run: bool, porcelain: bool) -> Result<usize> {
+    let report = |path: &Path, status, archive: &str| {
+        if porcelain {
+            porcelain::record(&path.display().to_string(), status, None, archive);
+        }
+    };
+    let cutoff = output::unix_now().saturating_sub(u64::from(days) * DAY_SEC);
+    // Runs that reused an --out path overwrote each other; the newest owns the file.
+    let mut by_path: BTreeMap<PathBuf, HistoryEntry> = BTreeMap::new();
+    for entry in history::load_all()? {
+        if let Some(path) = &entry.output_path {
+            by_path.insert(PathBuf::from(path), entry);
+        }
+    }
+    let mut due: Vec<(HistoryEntry, PathBuf)> = by_path
+        .into_iter()
+        .filter(|(path, entry)| {
+            entry.archived.is_none() && entry.created_at < cutoff && path.is_file()
+        })
+        .map(|(path, entry)| (entry, path))
+        .collect();
+    if due.is_empty() {
+        return Ok(0);
+    }
+    if dry_run {
+        for (entry, path) in &due {
+            match porcelain {
+                true => report(path, porcelain::Status::Planned, ""),
+                false => println!("{}\t{}", entry.id, path.display()),
+            }
+        }
+        return Ok(due.len());
+    }
+
+    let tar = tools::require(&tools::TAR)?;
+    let zstd = tools::require(&tools::ZSTD)?;
+    let dir = archive_dir();
+    let now = output::unix_now();
+    let archive = dir.join(format!(
+        "outputs-{}-{now}.tar.zst",
+        output::format_date(now)
+    ));
+    let staging = dir.join(format!(".staging-{now}"));
+    fs::create_dir_all(&staging)
+        .with_context(|| format!("failed creating {}", staging.display()))?;
+    let result = (|| {
+        let mut members = Vec::new();
+        let mut sidecars = Vec::new();
+        for (entry, path) in &due {
+            let name = path.file_name().context("output path has no file name")?;
+            let member = Path::new(&entry.id).join(name);
+            fs::create_dir_all(staging.join(&entry.id))?;
+            fs::copy(path, staging.join(&member))
+                .with_context(|| format!("failed staging {}", path.display()))?;
+            members.push(member);
+            // Meeting labels and --meta fields travel with the transcript.
+            if !entry.response.metadata.is_empty() {
+                let sidecar = Path::new(&entry.id).join("metadata.json");
+                fs::write(
+                    staging.join(&sidecar),
+                    serde_json::to_vec_pretty(&entry.response.metadata)?,
+                )?;
+                sidecars.push(sidecar);
+            }
+        }
+        let all: Vec<PathBuf> = members.iter().chain(&sidecars).cloned().collect();
+        write_archive(&tar, &zstd, &staging, &all, &archive)?;
+        Ok::<_, anyhow::Error>(members)
+    })();
+    let _ = fs::remove_dir_all(&staging);
+    let members = result?;
+
+    // The archive is complete; only now update the index and drop the originals.
+    for ((entry, path), member) in due.iter_mut().zip(members) {
+        entry.archived = Some(ArchivedOutput {
+            archive: archive.display().to_string(),
+            member: member.display().to_string(),
+            archived_at: now,
+        });
+        history::save(entry)?;
+        if let Err(err) = fs::remove_file(&*path) {
+            eprintln!(
+                "[parakeet] archived but could not remove {}: {err}",
+                path.display()
+            );
+        }
+        report(
+            path,
+            porcelain::Status::Archived,
+            &archive.display().to_string(),
+        );
+    }
+    Ok(due.len())
+}
+
+fn write_archive(
+    tar: &Path,
+    zstd: &Path,
+    staging: &Path,
+    members: &[PathBuf],
+    archive: &Path,
+) -> Result<()> {
+    let partial = archive.with_extension("zst.partial");
+    let mut tar_proc = Command::new(tar)
+        .arg("-cf")
+        .arg("-")
+        .arg("-C")
+        .arg(staging)
+        .args(members)
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("failed running tar")?;
+    let tar_out = tar_proc
+        .stdout
+        .take()
+        .context("failed capturing tar output")?;
+    let zstd_status = Command::new(zstd)
+        .args(["-q", "-f", "-o"])
+        .arg(&partial)
+        .stdin(Stdio::from(tar_out))
+        .status()
+        .context("failed running zstd")?;
+    let tar_status = tar_proc.wait().context("failed running tar")?;
+    if !tar_status.success() || !zstd_status.success() {
+        let _ = fs::remove_file(&partial);
+        bail!(
+            "creating {} failed (tar: {tar_status}, zstd: {zstd_status})",
+            archive.display()
+        );
+    }
+    fs::rename(&partial, archive)
+        .with_context(|| format!("failed writing archive: {}", archive.display()))
+}
+
+/// The output file exactly as the run wrote it, from disk or its archive.
+pub fn read_output(entry: &HistoryEntry) -> Result<Vec<u8>> {
+    let Some(archived) = &entry.archived else {
+        let Some(path) = &entry.output_path else {
+            bail!("history entry {} has no output file", entry.id);
+        };
+        return fs::read(path).with_context(|| format!("failed reading output: {path}"));
+    };
+    let tar = tools::require(&tools::TAR)?;
+    let zstd = tools::require(&tools::ZSTD)?;
+    let mut unzstd = Command::new(zstd)
+        .args(["-d", "-c", "-q"])
+        .arg(&archived.archive)
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("failed running zstd")?;
+    let decompressed = unzstd
+        .stdout
+        .take()
+        .context("failed capturing zstd output")?;
+    let extracted = Command::new(tar)
+        .args(["-x", "-O", "-f", "-"])
+        .arg(&archived.member)
+        .stdin(Stdio::from(decompressed))
+        .output()
+        .context("failed running tar")?;
+    let status = unzstd.wait().context("failed running zstd")?;
+    if !status.success() || !extracted.status.success() {
+        bail!(
+            "failed extracting {} from {}: {}",
+            archived.member,
+            archived.archive,
+            String::from_utf8_lossy(&extracted.stderr).trim()
+        );
+    }
+    Ok(extracted.stdout)
+}
+
+/// Applies `archive_after_days` from config.json after a transcription, at
+/// most once a day. Failures are reported but never fail the run.
+pub fn run_policy() {
+    let days = match config::load() {
+        Ok(config) => config.archive_after_days,
+        Err(_) => return,
+    };
+    let Some(days) = days else {
+        return;
+    };
+    let stamp = archive_dir().join(".last-run");
+    let last = fs::read_to_string(&stamp)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<u64>().ok());
+    let now = output::unix_now();
+    if last.is_some_and(|last| now.saturating_sub(last) < AUTO_INTERVAL_SEC) {
+        return;
+    }
+    if fs::create_dir_all(archive_dir()).is_err() || fs::write(&stamp, now.to_string()).is_err() {
+        return;
+    }
+    match archive_older_than(days, false, false) {
+        Ok(0) => {}
+        Ok(count) => eprintln!("[parakeet] archived {count} output(s) older than {days} days"),
+        Err(err) => eprintln!("[parakeet] output archiving failed: {err:#}"),
+    }
+}
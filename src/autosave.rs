@@ -0,0 +1,133 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::redact::{self, Pii};
+use crate::{Segment, Word, subtitle};
+
+/// Keeps `<base>.json` and `<base>.txt` in step with a transcript that is
+/// still being assembled, so a crash loses at most the unit in flight. The
+/// unit is a `--chunk-minutes` chunk: the backend returns text per request,
+/// so there is nothing newer to save between chunks.
+///
+/// Each save replaces the files atomically (write to a temp file, then
+/// rename), so readers never see a half-written snapshot.
+///
+/// With `--redact`, snapshots are redacted like the final transcript, and
+/// their words are left out once anything was replaced.
+pub struct Autosave {
+    json_path: PathBuf,
+    text_path: PathBuf,
+    redact: Vec<Pii>,
+}
+
+pub struct Snapshot<'a> {
+    pub source: &'a str,
+    pub complete: bool,
+    pub units_done: usize,
+    pub units_total: usize,
+    pub transcript: &'a str,
+    pub words: &'a [Word],
+}
+
+impl Autosave {
+    pub fn new(base: &Path, redact: &[Pii]) -> Self {
+        Self {
+            json_path: with_suffix(base, "json"),
+            text_path: with_suffix(base, "txt"),
+            redact: redact.to_vec(),
+        }
+    }
+
+    pub fn save(&self, snapshot: &Snapshot<'_>) -> Result<()> {
+        let (transcript, words) = match redact::text(snapshot.transcript, &self.redact) {
+            Some(redacted) => (redacted, &[][..]),
+            None => (snapshot.transcript.to_string(), snapshot.words),
+        };
+        let json = serde_json::json!({
+            "source": snapshot.source,
+            "complete": snapshot.complete,
+            "units_done": snapshot.units_done,
+            "units_total": snapshot.units_total,
+            "transcript": transcript,
+            "words": words,
+        });
+        write_atomic(
+            &self.json_path,
+            serde_json::to_string_pretty(&json)?.as_bytes(),
+        )?;
+        write_atomic(&self.text_path, format!("{transcript}\n").as_bytes())
+    }
+}
+
+/// `--caption-file`: the last few words of the transcript so far, rewritten
+/// after every chunk for tools that show a file as a caption (OBS text
+/// sources). A `.vtt` path gets one WebVTT cue spanning those words.
+pub struct CaptionFile {
+    path: PathBuf,
+    words: usize,
+    redact: Vec<Pii>,
+}
+
+impl CaptionFile {
+    pub fn new(path: &Path, words: usize, redact: &[Pii]) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            words,
+            redact: redact.to_vec(),
+        }
+    }
+
+    pub fn save(&self, words: &[Word]) -> Result<()> {
+        let tail = &words[words.len().saturating_sub(self.words)..];
+        let mut text = tail
+            .iter()
+            .map(|w| w.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !self.redact.is_empty() {
+            // The whole text, so a number cut by the tail is still caught.
+            let all = words
+                .iter()
+                .map(|w| w.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if let Some(redacted) = redact::text(&all, &self.redact) {
+                let redacted: Vec<&str> = redacted.split_whitespace().collect();
+                text = redacted[redacted.len().saturating_sub(self.words)..].join(" ");
+            }
+        }
+        let content = match (self.path.extension(), tail.first(), tail.last()) {
+            (Some(ext), Some(first), Some(last)) if ext.eq_ignore_ascii_case("vtt") => {
+                subtitle::to_vtt(&[Segment {
+                    start: first.start,
+                    end: last.end,
+                    text,
+                    confidence: None,
+                    speaker: None,
+                }])
+            }
+            _ => format!("{text}\n"),
+        };
+        write_atomic(&self.path, content.as_bytes())
+    }
+}
+
+fn with_suffix(base: &Path, ext: &str) -> PathBuf {
+    let mut raw = base.as_os_str().to_owned();
+    raw.push(".");
+    raw.push(ext);
+    PathBuf::from(raw)
+}
+
+fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed creating autosave dir: {}", parent.display()))?;
+    }
+    let tmp = with_suffix(path, "tmp");
+    fs::write(&tmp, content)
+        .with_context(|| format!("failed writing autosave: {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("failed writing autosave: {}", path.display()))
+}
@@ -0,0 +1,276 @@
+//! `parakeet bench`: transcribes a sample file with each model/precision a
+//! few times and reports load time, inference time, real-time factors and
+//! peak GPU memory, to pick a setup for the hardware at hand.
+//!
+//! Cold runs start a one-off backend each time (`--no-daemon`), so they pay
+//! process start and model load; warm runs go through the daemon (started if
+//! needed) after an untimed run has loaded the model.
+
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, ValueEnum};
+
+use crate::{BackendMetrics, Precision, TranscribeCli, media, quality};
+
+#[derive(Debug, Parser)]
+pub struct BenchCli {
+    /// Sample recording to transcribe.
+    #[arg(long, short = 'i')]
+    input: PathBuf,
+
+    /// Model to benchmark (repeatable) [default: the default model].
+    #[arg(long = "model", value_name = "NAME")]
+    models: Vec<String>,
+
+    /// Weight precision to benchmark (repeatable) [default: auto].
+    #[arg(long = "precision", value_enum, value_name = "PRECISION")]
+    precisions: Vec<Precision>,
+
+    #[arg(long, default_value = "auto", value_parser = crate::parse_device)]
+    device: String,
+
+    /// Timed runs per model, precision and mode.
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    runs: u32,
+
+    #[arg(long, value_enum, default_value_t = Mode::Both)]
+    mode: Mode,
+
+    /// Print the results as JSON instead of a table.
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Mode {
+    Cold,
+    Warm,
+    Both,
+}
+
+impl Mode {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Cold => "cold",
+            Self::Warm => "warm",
+            Self::Both => "both",
+        }
+    }
+}
+
+/// Means over the timed runs of one model, precision and mode.
+#[derive(serde::Serialize)]
+struct Row {
+    model: String,
+    precision: String,
+    mode: &'static str,
+    runs: u32,
+    model_load_sec: f64,
+    inference_sec: f64,
+    total_sec: f64,
+    /// Including process start and, cold, the backend's imports.
+    wall_sec: f64,
+    /// Processing per audio second (`total_sec / audio_sec`), as in history.
+    rtf: Option<f64>,
+    /// Audio seconds per inference second.
+    realtime_factor: Option<f64>,
+    /// Highest over the runs.
+    peak_gpu_mb: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+pub async fn run(cli: BenchCli) -> Result<()> {
+    if !cli.input.exists() {
+        bail!("input does not exist: {}", cli.input.display());
+    }
+    media::validate(&cli.input)?;
+    let audio_sec = media::audio_sec(&cli.input);
+    let models = if cli.models.is_empty() {
+        vec![quality::DEFAULT_MODEL.to_string()]
+    } else {
+        cli.models.clone()
+    };
+    let precisions = if cli.precisions.is_empty() {
+        vec![Precision::Auto]
+    } else {
+        cli.precisions.clone()
+    };
+    let modes = match cli.mode {
+        Mode::Both => vec![Mode::Cold, Mode::Warm],
+        mode => vec![mode],
+    };
+
+    let mut rows = Vec::new();
+    for model in &models {
+        for precision in &precisions {
+            for mode in &modes {
+                let row = bench(&cli, model, *precision, *mode, audio_sec)
+                    .await
+                    .unwrap_or_else(|err| {
+                        eprintln!(
+                            "[parakeet] bench: {model} {} {} failed: {err:#}",
+                            precision.as_str(),
+                            mode.as_str()
+                        );
+                        Row {
+                            error: Some(format!("{err:#}")),
+                            ..Row::empty(model, *precision, *mode)
+                        }
+                    });
+                rows.push(row);
+            }
+        }
+    }
+
+    if cli.json {
+        let report = serde_json::json!({
+            "input": cli.input.display().to_string(),
+            "audio_sec": audio_sec,
+            "device": cli.device,
+            "results": rows,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).context("serialize bench report")?
+        );
+        return Ok(());
+    }
+    print_table(&rows);
+    Ok(())
+}
+
+async fn bench(
+    cli: &BenchCli,
+    model: &str,
+    precision: Precision,
+    mode: Mode,
+    audio_sec: Option<f64>,
+) -> Result<Row> {
+    let daemon: &[&str] = match mode {
+        Mode::Warm => &["--auto-daemon", "--require-daemon"],
+        _ => &["--no-daemon"],
+    };
+    let argv: Vec<String> = ["parakeet", "--progress", "none", "--no-history"]
+        .iter()
+        .chain(daemon)
+        .map(|arg| arg.to_string())
+        .chain(["--input".to_string(), cli.input.display().to_string()])
+        .chain(["--model".to_string(), model.to_string()])
+        .chain(["--precision".to_string(), precision.as_str().to_string()])
+        .chain(["--device".to_string(), cli.device.clone()])
+        .collect();
+    if mode == Mode::Warm {
+        eprintln!("[parakeet] bench: {model} {} loading", precision.as_str());
+        run_once(&argv).await?;
+    }
+
+    let mut total: Option<BackendMetrics> = None;
+    let mut wall = 0.0;
+    for run in 1..=cli.runs {
+        eprintln!(
+            "[parakeet] bench: {model} {} {} run {run}/{}",
+            precision.as_str(),
+            mode.as_str(),
+            cli.runs
+        );
+        let started = Instant::now();
+        let metrics = run_once(&argv).await?;
+        wall += started.elapsed().as_secs_f64();
+        match total.as_mut() {
+            Some(total) => total.add(&metrics),
+            None => total = Some(metrics),
+        }
+    }
+    let total = total.context("no timed runs")?;
+    let runs = f64::from(cli.runs);
+    let mut row = Row {
+        runs: cli.runs,
+        model_load_sec: total.model_load_sec / runs,
+        inference_sec: total.inference_sec / runs,
+        total_sec: total.total_sec / runs,
+        wall_sec: wall / runs,
+        peak_gpu_mb: total.peak_gpu_mb,
+        ..Row::empty(model, precision, mode)
+    };
+    row.rtf = audio_sec
+        .filter(|audio| *audio > 0.0)
+        .map(|audio| row.total_sec / audio);
+    row.realtime_factor = audio_sec
+        .filter(|_| row.inference_sec > 0.0)
+        .map(|audio| audio / row.inference_sec);
+    Ok(row)
+}
+
+async fn run_once(argv: &[String]) -> Result<BackendMetrics> {
+    let cli = TranscribeCli::try_parse_from(argv).context("invalid bench flags")?;
+    let (_, response) = crate::transcribe_job(cli).await?;
+    response.metrics.context("the backend reported no metrics")
+}
+
+impl Row {
+    fn empty(model: &str, precision: Precision, mode: Mode) -> Self {
+        Self {
+            model: model.to_string(),
+            precision: precision.as_str().to_string(),
+            mode: mode.as_str(),
+            runs: 0,
+            model_load_sec: 0.0,
+            inference_sec: 0.0,
+            total_sec: 0.0,
+            wall_sec: 0.0,
+            rtf: None,
+            realtime_factor: None,
+            peak_gpu_mb: None,
+            error: None,
+        }
+    }
+}
+
+fn print_table(rows: &[Row]) {
+    let width = rows
+        .iter()
+        .map(|row| row.model.len())
+        .max()
+        .unwrap_or_default()
+        .max("model".len());
+    println!(
+        "{:<width$}  {:<9}  {:<4}  {:>8}  {:>8}  {:>8}  {:>8}  {:>6}  {:>8}  {:>9}",
+        "model",
+        "precision",
+        "mode",
+        "load s",
+        "infer s",
+        "total s",
+        "wall s",
+        "rtf",
+        "speed",
+        "peak MiB"
+    );
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    for row in rows {
+        if let Some(error) = &row.error {
+            let first = error.lines().next().unwrap_or_default();
+            println!(
+                "{:<width$}  {:<9}  {:<4}  failed: {first}",
+                row.model, row.precision, row.mode
+            );
+            continue;
+        }
+        println!(
+            "{:<width$}  {:<9}  {:<4}  {:>8.2}  {:>8.2}  {:>8.2}  {:>8.2}  {:>6}  {:>8}  {:>9}",
+            row.model,
+            row.precision,
+            row.mode,
+            row.model_load_sec,
+            row.inference_sec,
+            row.total_sec,
+            row.wall_sec,
+            or_dash(row.rtf.map(|rtf| format!("{rtf:.3}"))),
+            or_dash(row.realtime_factor.map(|speed| format!("{speed:.1}x"))),
+            or_dash(row.peak_gpu_mb.map(|mb| format!("{mb:.0}"))),
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! `--calendar`: matches the recording time against an ICS calendar and
+//! labels the run with the overlapping meeting (`{meta.meeting}` and friends).
+//!
+//! Times with a `TZID` other than UTC are read in the local time zone, which
+//! is right for calendars exported on the recording machine. Recurring events
+//! are expanded for `FREQ=DAILY` and `FREQ=WEEKLY` (with `BYDAY`, `INTERVAL`,
+//! `COUNT`, `UNTIL` and `EXDATE`); other rules only match their first date.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+use crate::metadata::Metadata;
+use crate::{media, output, tools};
+
+const DAY_SEC: u64 = 86_400;
+// Expanding a recurrence stops after this many occurrences.
+const MAX_OCCURRENCES: usize = 5_000;
+// A property's parameters, names uppercased.
+type Params = Vec<(String, String)>;
+
+const WEEKDAYS: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+pub struct Meeting {
+    pub title: String,
+    pub start: u64,
+    pub attendees: Vec<String>,
+    pub organizer: Option<String>,
+    pub location: Option<String>,
+}
+
+impl Meeting {
+    /// Adds `meeting`, `meeting_date`, `meeting_start` (UTC), `meeting_attendees`,
+    /// `meeting_organizer` and `meeting_location`; values from `--meta` win.
+    pub fn label(&self, meta: &mut Metadata) {
+        let mut set = |key: &str, value: String| {
+            if !value.is_empty() {
+                meta.entry(key.to_string()).or_insert(value);
+            }
+        };
+        set("meeting", self.title.clone());
+        set("meeting_date", output::format_date(self.start));
+        set("meeting_start", output::format_datetime(self.start));
+        set("meeting_attendees", self.attendees.join(", "));
+        set(
+            "meeting_organizer",
+            self.organizer.clone().unwrap_or_default(),
+        );
+        set(
+            "meeting_location",
+            self.location.clone().unwrap_or_default(),
+        );
+    }
+}
+
+/// The meeting overlapping the recording most, if any. `recorded_at` overrides
+/// the start time read from the file.
+pub fn lookup(source: &str, input: &Path, recorded_at: Option<u64>) -> Result<Option<Meeting>> {
+    let (start, end) = recording_span(input, recorded_at)?;
+    let raw = read_source(source)?;
+    let mut best: Option<(u64, Meeting)> = None;
+    for event in parse_events(&raw) {
+        for (occ_start, occ_end) in event.occurrences(end) {
+            let overlap = occ_end.min(end).saturating_sub(occ_start.max(start));
+            let touches = occ_start <= end && occ_end >= start;
+            if touches && best.as_ref().is_none_or(|(most, _)| overlap > *most) {
+                best = Some((
+                    overlap,
+                    Meeting {
+                        title: event.summary.clone(),
+                        start: occ_start,
+                        attendees: event.attendees.clone(),
+                        organizer: event.organizer.clone(),
+                        location: event.location.clone(),
+                    },
+                ));
+            }
+        }
+    }
+    Ok(best.map(|(_, meeting)| meeting))
+}
+
+/// Unix seconds for a local `YYYY-MM-DD HH:MM[:SS]` (or `...THH:MM`).
+pub fn parse_local_time(raw: &str) -> std::result::Result<u64, String> {
+    let invalid = || format!("invalid time: {raw} (expected YYYY-MM-DD HH:MM[:SS])");
+    let (date, time) = raw.trim().split_once([' ', 'T']).ok_or_else(invalid)?;
+    let mut date = date.split('-').map(|p| p.parse::<i64>().ok());
+    let (Some(Some(y)), Some(Some(m)), Some(Some(d))) = (date.next(), date.next(), date.next())
+    else {
+        return Err(invalid());
+    };
+    let parts: Vec<u64> = time
+        .split(':')
+        .map(|p| p.parse().map_err(|_| invalid()))
+        .collect::<std::result::Result<_, _>>()?;
+    let secs = match parts.as_slice() {
+        [h, m] if *h < 24 && *m < 60 => h * 3600 + m * 60,
+        [h, m, s] if *h < 24 && *m < 60 && *s < 61 => h * 3600 + m * 60 + s,
+        _ => return Err(invalid()),
+    };
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return Err(invalid());
+    }
+    local_to_unix(output::days_from_civil(y, m, d), secs).ok_or_else(invalid)
+}
+
+/// When the recording ran: from `recorded_at`, else the container's
+/// `creation_time`, else the file's modification time (which recorders set
+/// when they stop, hence minus the duration).
+fn recording_span(input: &Path, recorded_at: Option<u64>) -> Result<(u64, u64)> {
+    let duration = media::probe_duration(input).ok().unwrap_or_default() as u64;
+    if let Some(start) = recorded_at.or_else(|| media::probe_creation_time(input)) {
+        return Ok((start, start + duration));
+    }
+    let modified = fs::metadata(input)
+        .and_then(|meta| meta.modified())
+        .with_context(|| format!("failed reading the time of {}", input.display()))?;
+    let end = modified
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    Ok((end.saturating_sub(duration), end))
+}
+
+fn read_source(source: &str) -> Result<String> {
+    let url = match source.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{rest}"),
+        None => source.to_string(),
+    };
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return fs::read_to_string(source)
+            .with_context(|| format!("failed reading calendar: {source}"));
+    }
+    let output = Command::new(tools::require(&tools::CURL)?)
+        .args(["-sS", "-f", "-L", "--max-time", "30"])
+        .arg(&url)
+        .output()
+        .context("failed running curl for the calendar")?;
+    if !output.status.success() {
+        bail!(
+            "failed fetching calendar {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// A wall-clock time: days since the epoch plus seconds into that day.
+#[derive(Clone, Copy)]
+struct Stamp {
+    days: i64,
+    secs: u64,
+    utc: bool,
+}
+
+impl Stamp {
+    fn unix(self) -> Option<u64> {
+        if self.utc {
+            u64::try_from(self.days)
+                .ok()
+                .map(|d| d * DAY_SEC + self.secs)
+        } else {
+            local_to_unix(self.days, self.secs)
+        }
+    }
+}
+
+#[derive(Default)]
+struct Event {
+    summary: String,
+    start: Option<Stamp>,
+    end: Option<Stamp>,
+    duration: Option<u64>,
+    all_day: bool,
+    cancelled: bool,
+    attendees: Vec<String>,
+    organizer: Option<String>,
+    location: Option<String>,
+    rrule: Option<String>,
+    exdates: Vec<u64>,
+}
+
+impl Event {
+    /// (start, end) unix times of each occurrence starting before `until`.
+    fn occurrences(&self, until: u64) -> Vec<(u64, u64)> {
+        let Some(first) = self.start.filter(|_| !self.all_day && !self.cancelled) else {
+            return Vec::new();
+        };
+        let length = match (self.end.and_then(Stamp::unix), first.unix()) {
+            (Some(end), Some(start)) => end.saturating_sub(start),
+            _ => self.duration.unwrap_or(0),
+        };
+        let mut starts = Vec::new();
+        match self.rrule.as_deref().map(Rule::parse) {
+            Some(Some(rule)) => rule.expand(first, until, &mut starts),
+            _ => starts.extend(first.unix()),
+        }
+        starts
+            .into_iter()
+            .filter(|start| !self.exdates.contains(start))
+            .map(|start| (start, start + length))
+            .collect()
+    }
+}
+
+struct Rule {
+    weekly: bool,
+    interval: i64,
+    count: Option<usize>,
+    until: Option<u64>,
+    // Weekdays (0 = Monday) for weekly rules; empty means DTSTART's.
+    by_day: Vec<i64>,
+}
+
+impl Rule {
+    fn parse(raw: &str) -> Option<Self> {
+        let mut rule = Rule {
+            weekly: false,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+        };
+        let mut freq = None;
+        for part in raw.split(';') {
+            let (key, value) = part.split_once('=')?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => freq = Some(value.to_ascii_uppercase()),
+                "INTERVAL" => rule.interval = value.parse().ok().filter(|n| *n > 0)?,
+                "COUNT" => rule.count = value.parse().ok(),
+                "UNTIL" => rule.until = parse_stamp(value, None).and_then(Stamp::unix),
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .filter_map(|day| {
+                            let code = day.trim_start_matches(|c: char| !c.is_ascii_alphabetic());
+                            WEEKDAYS.iter().position(|d| *d == code).map(|i| i as i64)
+                        })
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+        rule.weekly = match freq?.as_str() {
+            "DAILY" => false,
+            "WEEKLY" => true,
+            _ => return None,
+        };
+        Some(rule)
+    }
+
+    fn expand(&self, first: Stamp, until: u64, out: &mut Vec<u64>) {
+        // 1970-01-01 was a Thursday (3 with Monday = 0).
+        let weekday = |days: i64| (days + 3).rem_euclid(7);
+        let mut days: Vec<i64> = if self.weekly && !self.by_day.is_empty() {
+            let monday = first.days - weekday(first.days);
+            self.by_day.iter().map(|d| monday + d).collect()
+        } else {
+            vec![first.days]
+        };
+        days.sort_unstable();
+        let step = if self.weekly { 7 } else { 1 } * self.interval;
+        for period in 0.. {
+            for day in &days {
+                let day = day + period * step;
+                if day < first.days {
+                    continue;
+                }
+                let Some(start) = (Stamp { days: day, ..first }).unix() else {
+                    continue;
+                };
+                let past_until = self.until.is_some_and(|last| start > last);
+                let counted_out = self.count.is_some_and(|count| out.len() >= count);
+                if start > until || past_until || counted_out || out.len() >= MAX_OCCURRENCES {
+                    return;
+                }
+                out.push(start);
+            }
+        }
+    }
+}
+
+fn parse_events(raw: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut current: Option<Event> = None;
+    for line in unfold(raw) {
+        let Some((name, params, value)) = split_property(&line) else {
+            continue;
+        };
+        match (name.as_str(), value.as_str()) {
+            ("BEGIN", "VEVENT") => current = Some(Event::default()),
+            ("END", "VEVENT") => events.extend(current.take()),
+            _ => {}
+        }
+        let Some(event) = current.as_mut() else {
+            continue;
+        };
+        let tzid = param(&params, "TZID");
+        match name.as_str() {
+            "SUMMARY" => event.summary = unescape(&value),
+            "LOCATION" => event.location = Some(unescape(&value)),
+            "STATUS" => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            "DTSTART" => {
+                event.all_day = !value.contains('T');
+                event.start = parse_stamp(&value, tzid.as_deref());
+            }
+            "DTEND" => event.end = parse_stamp(&value, tzid.as_deref()),
+            "DURATION" => event.duration = parse_duration(&value),
+            "RRULE" => event.rrule = Some(value),
+            "EXDATE" => event.exdates.extend(
+                value
+                    .split(',')
+                    .filter_map(|v| parse_stamp(v, tzid.as_deref()).and_then(Stamp::unix)),
+            ),
+            "ATTENDEE" => event.attendees.push(person(&params, &value)),
+            "ORGANIZER" => event.organizer = Some(person(&params, &value)),
+            _ => {}
+        }
+    }
+    events
+}
+
+// Long lines continue on lines starting with a space or tab.
+fn unfold(raw: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in raw.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// `NAME;PARAM=x;PARAM="y:z":value` -> (NAME, [(PARAM, x), ...], value).
+fn split_property(line: &str) -> Option<(String, Params, String)> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        (c == ':' && !quoted).then_some(i)
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| {
+            (
+                k.trim().to_ascii_uppercase(),
+                v.trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    Some((name, params, value.trim().to_string()))
+}
+
+fn param(params: &Params, name: &str) -> Option<String> {
+    params
+        .iter()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.clone())
+}
+
+// An attendee's display name, else their address.
+fn person(params: &Params, value: &str) -> String {
+    param(params, "CN").unwrap_or_else(|| {
+        let lower = value.to_ascii_lowercase();
+        match lower.starts_with("mailto:") {
+            true => value["mailto:".len()..].to_string(),
+            false => value.to_string(),
+        }
+    })
+}
+
+fn unescape(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n' | 'N') => out.push(' '),
+                Some(other) => out.push(other),
+                None => {}
+            },
+            (c, false) => out.push(c),
+        }
+    }
+    out
+}
+
+/// `20261016T140000Z` (UTC), `20261016T140000` (local) or `20261016`.
+fn parse_stamp(raw: &str, tzid: Option<&str>) -> Option<Stamp> {
+    let raw = raw.trim();
+    let (date, time) = raw.split_once('T').unwrap_or((raw, "000000"));
+    if date.len() != 8 || time.len() < 6 {
+        return None;
+    }
+    let num = |s: &str| s.parse::<i64>().ok();
+    let days = output::days_from_civil(num(&date[..4])?, num(&date[4..6])?, num(&date[6..8])?);
+    let secs = (num(&time[..2])? * 3600 + num(&time[2..4])? * 60 + num(&time[4..6])?) as u64;
+    let utc = time.ends_with('Z')
+        || tzid.is_some_and(|tz| {
+            matches!(tz.to_ascii_uppercase().as_str(), "UTC" | "ETC/UTC" | "GMT")
+        });
+    Some(Stamp { days, secs, utc })
+}
+
+/// `PT1H30M`, `P1D`, `PT45M`, ... in seconds.
+fn parse_duration(raw: &str) -> Option<u64> {
+    let body = raw.trim().trim_start_matches(['+', 'P']);
+    let mut total = 0;
+    let mut number = String::new();
+    for c in body.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: u64 = number.parse().ok()?;
+                number.clear();
+                total += n * match unit {
+                    'W' => 7 * DAY_SEC,
+                    'D' => DAY_SEC,
+                    'H' => 3600,
+                    'M' => 60,
+                    'S' => 1,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(total)
+}
+
+#[cfg(unix)]
+fn local_to_unix(days: i64, secs: u64) -> Option<u64> {
+    let (y, m, d) = output::civil_from_days(days);
+    // SAFETY: an all-zero tm is valid; mktime only reads and normalizes it.
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    tm.tm_year = i32::try_from(y - 1900).ok()?;
+    tm.tm_mon = m as i32 - 1;
+    tm.tm_mday = d as i32;
+    tm.tm_hour = (secs / 3600) as i32;
+    tm.tm_min = (secs % 3600 / 60) as i32;
+    tm.tm_sec = (secs % 60) as i32;
+    // Let mktime work out whether DST applies.
+    tm.tm_isdst = -1;
+    // SAFETY: tm is initialized above.
+    let unix = unsafe { libc::mktime(&mut tm) };
+    u64::try_from(unix).ok()
+}
+
+#[cfg(not(unix))]
+fn local_to_unix(days: i64, secs: u64) -> Option<u64> {
+    u64::try_from(days).ok().map(|d| d * DAY_SEC + secs)
+}
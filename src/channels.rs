@@ -0,0 +1,174 @@
+//! Channel handling for multi-channel recordings:
+//!
+//! - `--channel left|right|N|mix` transcribes one channel instead of the
+//!   backend's downmix.
+//! - `--split-channels`: for stereo call recordings with one party per
+//!   channel, transcribes left and right on their own and interleaves the
+//!   segments by time, labelled per channel. Telephony audio separates
+//!   cleanly this way, where diarization of the mixed signal would guess.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use tokio::process::Command;
+
+use crate::progress::Progress;
+use crate::{
+    BackendRequest, BackendResponse, PostprocessStep, Segment, TranscribeCli, media, tools,
+};
+
+pub const DEFAULT_LABELS: [&str; 2] = ["Speaker A", "Speaker B"];
+
+/// A `--channel` choice.
+#[derive(Clone, Copy, Debug)]
+pub enum Channel {
+    /// All channels downmixed, as without the flag.
+    Mix,
+    /// Zero-based channel index.
+    Index(usize),
+}
+
+/// Parses `left`, `right`, `mix` or a channel number counting from 1.
+pub fn parse_channel(raw: &str) -> Result<Channel, String> {
+    match raw.to_ascii_lowercase().as_str() {
+        "mix" => Ok(Channel::Mix),
+        "left" => Ok(Channel::Index(0)),
+        "right" => Ok(Channel::Index(1)),
+        other => match other.parse::<usize>() {
+            Ok(n) if n >= 1 => Ok(Channel::Index(n - 1)),
+            _ => Err(format!(
+                "invalid channel: {raw} (expected left, right, mix or a number from 1)"
+            )),
+        },
+    }
+}
+
+/// Extracts channel `index` of `input` into `dir` for `--channel`.
+pub async fn select(input: &Path, index: usize, dir: &Path, verbose: bool) -> Result<PathBuf> {
+    let channels = media::probe_channels(input)?;
+    if index >= channels as usize {
+        bail!(
+            "--channel {}: {} has {channels} channel(s)",
+            index + 1,
+            input.display()
+        );
+    }
+    extract(input, index, dir, verbose).await
+}
+
+/// Transcribes each channel of `request.input` and merges the two.
+pub async fn transcribe(
+    cli: &TranscribeCli,
+    request: &BackendRequest<'_>,
+    labels: &[String],
+    progress: &Progress,
+) -> Result<BackendResponse> {
+    let channels = media::probe_channels(request.input)?;
+    if channels != 2 {
+        bail!(
+            "--split-channels needs a stereo recording; {} has {channels} channel(s)",
+            request.input.display()
+        );
+    }
+    let dir = request
+        .work_dir
+        .map_or_else(std::env::temp_dir, Path::to_path_buf);
+    let mut parts = Vec::with_capacity(2);
+    for (idx, label) in labels.iter().enumerate() {
+        let path = extract(request.input, idx, &dir, cli.verbose).await?;
+        progress.set_message(format!("channel {}/2 ({label})", idx + 1));
+        let channel = BackendRequest {
+            input: &path,
+            ..request.clone()
+        };
+        parts.push((
+            label.clone(),
+            crate::transcribe_pass(cli, &channel, progress).await?,
+        ));
+    }
+    let mut merged = merge(parts);
+    merged.source = request.input.display().to_string();
+    Ok(merged)
+}
+
+// One channel as 16 kHz mono WAV in the job workdir. Cuts (--start/--end)
+// stay with the request, so the backend applies them to each channel.
+async fn extract(input: &Path, channel: usize, dir: &Path, verbose: bool) -> Result<PathBuf> {
+    let out = dir.join(format!("channel-{channel}.wav"));
+    if verbose {
+        eprintln!("[parakeet] extracting channel {channel}: {}", out.display());
+    }
+    let output = Command::new(tools::require(&tools::FFMPEG)?)
+        .args(["-v", "error", "-y", "-i"])
+        .arg(input)
+        .arg("-af")
+        .arg(format!("pan=mono|c0=c{channel}"))
+        .args(["-ar", "16000"])
+        .arg(&out)
+        .output()
+        .await
+        .context("failed running ffmpeg")?;
+    if !output.status.success() {
+        bail!(
+            "ffmpeg failed extracting channel {channel} of {}: {}",
+            input.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(out)
+}
+
+/// Interleaves the channels' segments and words by start time. The
+/// transcript has one line per turn, `Label: text`.
+fn merge(mut parts: Vec<(String, BackendResponse)>) -> BackendResponse {
+    let labels: Vec<String> = parts.iter().map(|(label, _)| label.clone()).collect();
+    let (first_label, mut merged) = parts.remove(0);
+    let mut segments = label(&first_label, merged.segments.take());
+    let mut words = merged.words.take();
+    for (label_text, part) in parts {
+        segments.extend(label(&label_text, part.segments));
+        if let (Some(all), Some(more)) = (words.as_mut(), part.words) {
+            all.extend(more);
+        }
+        if let (Some(total), Some(m)) = (merged.metrics.as_mut(), part.metrics) {
+            total.add(&m);
+        }
+    }
+    segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+    if let Some(words) = words.as_mut() {
+        words.sort_by(|a, b| a.start.total_cmp(&b.start));
+    }
+
+    let mut turns: Vec<(Option<&str>, String)> = Vec::new();
+    for seg in &segments {
+        let text = seg.text.trim();
+        match turns.last_mut() {
+            Some((speaker, turn)) if *speaker == seg.speaker.as_deref() => {
+                turn.push(' ');
+                turn.push_str(text);
+            }
+            _ => turns.push((seg.speaker.as_deref(), text.to_string())),
+        }
+    }
+    merged.transcript = turns
+        .iter()
+        .map(|(speaker, text)| format!("{}: {text}", speaker.unwrap_or_default()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    merged.segments = Some(segments);
+    merged.words = words;
+    merged.postprocessing.push(PostprocessStep::new(
+        "split_channels",
+        1,
+        serde_json::json!({ "labels": labels }),
+    ));
+    merged
+}
+
+fn label(speaker: &str, segments: Option<Vec<Segment>>) -> Vec<Segment> {
+    let mut segments = segments.unwrap_or_default();
+    for seg in &mut segments {
+        seg.speaker = Some(speaker.to_string());
+    }
+    segments
+}
@@ -0,0 +1,127 @@
+//! `--by-chapter`: follows the chapter marks of audiobooks (m4b/m4a, and any
+//! other container ffprobe reads chapters from). Markdown gets a `## Title`
+//! section per chapter; text and subtitle formats get one output per
+//! chapter, `<name>-NN-<title>.<ext>`, instead of one monolithic file.
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+use crate::output::{self, RenderOptions};
+use crate::{BackendResponse, tools};
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Chapter {
+    pub start: f64,
+    pub end: f64,
+    pub title: String,
+}
+
+/// The container's chapters in order; untitled ones are `Chapter N`.
+pub fn probe(path: &Path) -> Result<Vec<Chapter>> {
+    let output = Command::new(tools::require(&tools::FFPROBE)?)
+        .args(["-v", "error", "-show_chapters", "-of", "json"])
+        .arg(path)
+        .output()
+        .context("failed running ffprobe")?;
+    if !output.status.success() {
+        bail!(
+            "ffprobe failed reading chapters of {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let raw: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .with_context(|| format!("invalid ffprobe chapter list for {}", path.display()))?;
+    let seconds = |value: &serde_json::Value| value.as_str().and_then(|s| s.parse::<f64>().ok());
+    let mut chapters: Vec<Chapter> = raw["chapters"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(idx, chapter)| {
+            let title = chapter["tags"]["title"]
+                .as_str()
+                .map(str::trim)
+                .filter(|title| !title.is_empty())
+                .map_or_else(|| format!("Chapter {}", idx + 1), str::to_string);
+            Some(Chapter {
+                start: seconds(&chapter["start_time"])?,
+                end: seconds(&chapter["end_time"])?,
+                title,
+            })
+        })
+        .collect();
+    chapters.sort_by(|a, b| a.start.total_cmp(&b.start));
+    Ok(chapters)
+}
+
+/// The part of `parsed` in chapter `idx`: segments and words that start in
+/// it, and the transcript they make up. Chapters run on to the next one's
+/// start, and the first and last take whatever lies before and after them.
+pub fn slice(parsed: &BackendResponse, idx: usize) -> BackendResponse {
+    let chapters = &parsed.chapters;
+    let from = if idx == 0 {
+        f64::NEG_INFINITY
+    } else {
+        chapters[idx].start
+    };
+    let to = chapters
+        .get(idx + 1)
+        .map_or(f64::INFINITY, |next| next.start);
+    let inside = |start: f64| start >= from && start < to;
+    let mut part = parsed.clone();
+    part.chapters = Vec::new();
+    part.segments = parsed.segments.as_ref().map(|segments| {
+        segments
+            .iter()
+            .filter(|seg| inside(seg.start))
+            .cloned()
+            .collect()
+    });
+    part.words = parsed.words.as_ref().map(|words| {
+        words
+            .iter()
+            .filter(|word| inside(word.start))
+            .cloned()
+            .collect()
+    });
+    part.transcript = part
+        .segments
+        .iter()
+        .flatten()
+        .map(|seg| seg.text.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+    part
+}
+
+/// Markdown body with a `## Title` section per chapter.
+pub fn markdown_body(parsed: &BackendResponse, opts: &RenderOptions) -> String {
+    parsed
+        .chapters
+        .iter()
+        .enumerate()
+        .map(|(idx, chapter)| {
+            let part = slice(parsed, idx);
+            let body = output::render_body(&part.transcript, part.segments.as_deref(), opts);
+            format!("## {}\n\n{}", chapter.title, body.trim())
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// `name` with the chapter number and title slug before the extension.
+pub fn file_name(name: &str, idx: usize, chapter: &Chapter) -> String {
+    let (base, ext) = match name.rsplit_once('.') {
+        Some((base, ext)) => (base, format!(".{ext}")),
+        None => (name, String::new()),
+    };
+    let slug = output::slugify(&chapter.title);
+    if slug.is_empty() {
+        format!("{base}-{:02}{ext}", idx + 1)
+    } else {
+        format!("{base}-{:02}-{slug}{ext}", idx + 1)
+    }
+}
@@ -0,0 +1,378 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::{Context, Result, bail};
+
+use crate::autosave::{Autosave, CaptionFile, Snapshot};
+use crate::progress::Progress;
+use crate::stitch::{self, Chunk, Stitcher};
+use crate::{
+    BackendMetrics, BackendRequest, BackendResponse, DaemonClient, LanguageMismatch,
+    PostprocessStep, STATUS_PING_TIMEOUT_SEC, TranscribeCli, Word, default_socket_path,
+    is_socket_reachable, media, output, request_backend,
+};
+
+/// Transcribes a long recording as overlapping windows and stitches the
+/// word-level results back into one response.
+pub async fn transcribe_chunked(
+    cli: &TranscribeCli,
+    request: &BackendRequest<'_>,
+    chunk_sec: f64,
+    overlap_sec: f64,
+    progress: &Progress,
+) -> Result<BackendResponse> {
+    check_windows(chunk_sec, overlap_sec)?;
+
+    let duration = media::probe_duration(request.input)?;
+    let range_start = request.start_sec.unwrap_or_default();
+    let range_end = request.end_sec.unwrap_or(duration).min(duration);
+    // One window needs no stitching, unless something is saved per chunk.
+    let saves = cli.autosave.is_some() || cli.caption_file.is_some();
+    if range_end - range_start <= chunk_sec && !saves {
+        let parsed = request_backend(cli, request, progress).await?;
+        progress.chunk_done(1, 1, 1.0);
+        return Ok(parsed);
+    }
+    if cli.no_daemon {
+        eprintln!("[parakeet] warning: chunking without the daemon reloads the model per chunk");
+    }
+
+    let windows = plan_windows(range_start, range_end, chunk_sec, overlap_sec)?;
+    let (mut combined, parallel) =
+        transcribe_windows(cli, request, &windows, (range_start, range_end), progress).await?;
+    // Per-chunk backend steps (pre- and postprocessing) are identical across
+    // chunks; the first chunk's lists stand for all.
+    combined.postprocessing.push(PostprocessStep::new(
+        "chunk_stitch",
+        1,
+        serde_json::json!({
+            "chunks": windows.len(),
+            "chunk_sec": chunk_sec,
+            "overlap_sec": overlap_sec,
+            "parallel": parallel,
+        }),
+    ));
+    Ok(combined)
+}
+
+/// Transcribes `windows` (absolute, in order, possibly overlapping) of
+/// `range` and stitches the words into one response. Also returns how many
+/// windows were in flight at once.
+pub async fn transcribe_windows(
+    cli: &TranscribeCli,
+    request: &BackendRequest<'_>,
+    windows: &[(f64, f64)],
+    (range_start, range_end): (f64, f64),
+    progress: &Progress,
+) -> Result<(BackendResponse, usize)> {
+    let parallel = parallelism(cli, windows.len()).await;
+    if cli.verbose && parallel > 1 {
+        eprintln!("[parakeet] sending up to {parallel} chunks to the daemon at once");
+    }
+    let started = Instant::now();
+    let autosave = cli
+        .autosave
+        .as_deref()
+        .map(|base| Autosave::new(base, &cli.redact));
+    let caption = cli
+        .caption_file
+        .as_deref()
+        .map(|path| CaptionFile::new(path, cli.caption_words as usize, &cli.redact));
+    let mut stitcher = Stitcher::default();
+    let mut first: Option<BackendResponse> = None;
+    let mut languages: BTreeMap<String, usize> = BTreeMap::new();
+    let mut metrics = BackendMetrics {
+        model_load_sec: 0.0,
+        inference_sec: 0.0,
+        total_sec: 0.0,
+        audio_sec: Some(range_end - range_start),
+        realtime_factor: None,
+        peak_gpu_mb: None,
+    };
+
+    // Called once per window, in window order.
+    let mut absorb = |idx: usize, mut parsed: BackendResponse| -> Result<()> {
+        let (start, end) = windows[idx];
+        // Partial results stay in the job workdir, so --keep-workdir can show
+        // what each window produced when a later chunk fails.
+        if let Some(dir) = request.work_dir {
+            let partial = dir.join(format!("chunk-{:03}.json", idx + 1));
+            fs::write(&partial, serde_json::to_vec(&parsed)?)
+                .with_context(|| format!("failed writing {}", partial.display()))?;
+        }
+        if let Some(m) = &parsed.metrics {
+            metrics.add(m);
+        }
+        if let Some(lang) = &parsed.language {
+            *languages.entry(lang.clone()).or_default() += 1;
+        }
+        let mut words = parsed.words.take().unwrap_or_default();
+        if let Some(segments) = &parsed.segments {
+            stitch::fill_confidence(&mut words, segments);
+        }
+        stitcher.push(Chunk {
+            window_start: start,
+            window_end: end,
+            words,
+        });
+        let source = first.get_or_insert(parsed).source.clone();
+        if autosave.is_some() || caption.is_some() {
+            let words = stitcher.words();
+            if let Some(caption) = &caption {
+                caption.save(words)?;
+            }
+            if let Some(autosave) = &autosave {
+                autosave.save(&Snapshot {
+                    source: &source,
+                    complete: idx + 1 == windows.len(),
+                    units_done: idx + 1,
+                    units_total: windows.len(),
+                    transcript: &join_words(words),
+                    words,
+                })?;
+            }
+        }
+        progress.chunk_done(
+            idx + 1,
+            windows.len(),
+            (end - range_start) / (range_end - range_start),
+        );
+        Ok(())
+    };
+
+    if parallel > 1 {
+        transcribe_parallel(cli, request, windows, parallel, progress, &mut absorb).await?;
+    } else {
+        for (idx, window) in windows.iter().copied().enumerate() {
+            log_chunk(cli, idx, windows);
+            progress.set_message(format!("chunk {}/{}", idx + 1, windows.len()));
+            let parsed = request_backend(cli, &chunk_request(request, window), progress).await?;
+            absorb(idx, parsed)?;
+        }
+    }
+    if parallel > 1 {
+        // Per-chunk times overlap; the job took as long as the wall clock says.
+        metrics.total_sec = started.elapsed().as_secs_f64();
+    }
+
+    let Some(mut combined) = first else {
+        bail!("no chunks transcribed");
+    };
+    let words = stitcher.into_words();
+    let segments = stitch::segments_from_words(&words);
+    combined.transcript = join_words(&words);
+    combined.segments = Some(segments);
+    combined.words = request.word_timestamps.then_some(words);
+    combined.metrics = Some(metrics);
+    combined.language = languages
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(lang, _)| lang);
+    // An undecided vote is unknown, not a mismatch.
+    combined.language_mismatch = cli
+        .only_language
+        .as_deref()
+        .zip(combined.language.as_deref())
+        .is_some_and(|(want, lang)| lang != want);
+    combined.skipped =
+        combined.language_mismatch && matches!(cli.language_mismatch, LanguageMismatch::Skip);
+    Ok((combined, parallel))
+}
+
+fn chunk_request<'a>(request: &BackendRequest<'a>, (start, end): (f64, f64)) -> BackendRequest<'a> {
+    BackendRequest {
+        start_sec: Some(start),
+        end_sec: Some(end),
+        timestamps: true,
+        word_timestamps: true,
+        only_language: None,
+        // Chunks are voted on as a whole; probing each one would only add work.
+        language: None,
+        language_models: None,
+        ..request.clone()
+    }
+}
+
+fn log_chunk(cli: &TranscribeCli, idx: usize, windows: &[(f64, f64)]) {
+    if cli.verbose {
+        let (start, end) = windows[idx];
+        eprintln!(
+            "[parakeet] chunk {}/{} {}-{}",
+            idx + 1,
+            windows.len(),
+            output::format_clock(start),
+            output::format_clock(end)
+        );
+    }
+}
+
+/// Chunks in flight at once: `--chunk-parallel`, else the daemon's worker
+/// count. Without a reachable daemon chunks run one by one.
+async fn parallelism(cli: &TranscribeCli, chunks: usize) -> usize {
+    if cli.no_daemon || chunks < 2 || cli.engine.is_some_and(|engine| engine.is_remote()) {
+        return 1;
+    }
+    let socket = daemon_socket(cli);
+    if !is_socket_reachable(&socket) {
+        if cli.chunk_parallel.is_some_and(|n| n > 1) {
+            eprintln!(
+                "[parakeet] warning: --chunk-parallel needs a running daemon; chunks run one by one"
+            );
+        }
+        return 1;
+    }
+    let workers = match cli.chunk_parallel {
+        Some(n) => n,
+        None => tokio::task::spawn_blocking(move || {
+            DaemonClient::without_retries(STATUS_PING_TIMEOUT_SEC)
+                .ping(&socket)
+                .map_or(1, |pong| pong.workers)
+        })
+        .await
+        .unwrap_or(1),
+    };
+    (workers as usize).clamp(1, chunks)
+}
+
+fn daemon_socket(cli: &TranscribeCli) -> PathBuf {
+    cli.daemon_socket
+        .clone()
+        .unwrap_or_else(default_socket_path)
+}
+
+/// Keeps up to `parallel` chunk requests open on the daemon, whose workers
+/// run them side by side, and hands results to `absorb` in window order
+/// whatever order they finish in.
+async fn transcribe_parallel(
+    cli: &TranscribeCli,
+    request: &BackendRequest<'_>,
+    windows: &[(f64, f64)],
+    parallel: usize,
+    progress: &Progress,
+    absorb: &mut impl FnMut(usize, BackendResponse) -> Result<()>,
+) -> Result<()> {
+    let socket = daemon_socket(cli);
+    let mut tasks = tokio::task::JoinSet::new();
+    let mut finished: BTreeMap<usize, BackendResponse> = BTreeMap::new();
+    let (mut submitted, mut absorbed) = (0, 0);
+    while absorbed < windows.len() {
+        while submitted < windows.len() && tasks.len() < parallel {
+            log_chunk(cli, submitted, windows);
+            let json = serde_json::to_string(&chunk_request(request, windows[submitted]))
+                .context("serialize backend request")?;
+            let client = DaemonClient::from_cli(cli);
+            let socket = socket.clone();
+            let idx = submitted;
+            // The daemon client is blocking; keep it off the runtime like request_backend does.
+            tasks.spawn_blocking(move || (idx, client.request(&socket, &json)));
+            submitted += 1;
+        }
+        progress.set_message(format!(
+            "chunks {}-{}/{}",
+            absorbed + 1,
+            submitted,
+            windows.len()
+        ));
+        let Some(joined) = tasks.join_next().await else {
+            bail!("chunk requests ended early");
+        };
+        let (idx, result) = joined.context("chunk request task join error")?;
+        let parsed = match result {
+            Ok(parsed) => parsed,
+            // Retried the sequential way: daemon again, then a one-off backend
+            // unless --require-daemon.
+            Err(err) => {
+                if cli.verbose {
+                    eprintln!("[parakeet] chunk {} failed on the daemon: {err:#}", idx + 1);
+                }
+                request_backend(cli, &chunk_request(request, windows[idx]), progress).await?
+            }
+        };
+        finished.insert(idx, parsed);
+        while let Some(parsed) = finished.remove(&absorbed) {
+            absorb(absorbed, parsed)?;
+            absorbed += 1;
+        }
+    }
+    Ok(())
+}
+
+fn join_words(words: &[Word]) -> String {
+    words
+        .iter()
+        .map(|w| w.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Otherwise the window cursor stalls or moves backwards and planning never
+// ends.
+fn check_windows(chunk_sec: f64, overlap_sec: f64) -> Result<()> {
+    if !chunk_sec.is_finite() || chunk_sec <= 0.0 {
+        bail!("--chunk-minutes must be positive");
+    }
+    if !(0.0..chunk_sec / 2.0).contains(&overlap_sec) {
+        bail!("--chunk-overlap must be less than half the chunk length");
+    }
+    Ok(())
+}
+
+/// Windows of `chunk_sec` covering `start..end`, each starting `overlap_sec`
+/// before the previous one ends.
+pub fn plan_windows(
+    start: f64,
+    end: f64,
+    chunk_sec: f64,
+    overlap_sec: f64,
+) -> Result<Vec<(f64, f64)>> {
+    check_windows(chunk_sec, overlap_sec)?;
+    let mut windows = Vec::new();
+    let mut cursor = start;
+    loop {
+        let window_end = (cursor + chunk_sec).min(end);
+        windows.push((cursor, window_end));
+        if window_end >= end {
+            break;
+        }
+        cursor = window_end - overlap_sec;
+    }
+    Ok(windows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_window_is_cut_at_the_range_end() {
+        assert_eq!(
+            plan_windows(10.0, 35.0, 10.0, 2.0).unwrap(),
+            [(10.0, 20.0), (18.0, 28.0), (26.0, 35.0)]
+        );
+    }
+
+    #[test]
+    fn range_shorter_than_a_chunk_is_one_window() {
+        assert_eq!(plan_windows(5.0, 8.0, 60.0, 5.0).unwrap(), [(5.0, 8.0)]);
+        assert_eq!(plan_windows(0.0, 60.0, 60.0, 5.0).unwrap(), [(0.0, 60.0)]);
+    }
+
+    #[test]
+    fn overlap_must_stay_under_half_a_chunk() {
+        assert!(check_windows(60.0, 0.0).is_ok());
+        assert!(check_windows(60.0, 29.9).is_ok());
+        // At exactly half, every inner window lies wholly inside its two overlaps.
+        assert!(check_windows(60.0, 30.0).is_err());
+        assert!(plan_windows(0.0, 100.0, 60.0, 30.0).is_err());
+        assert!(check_windows(60.0, -1.0).is_err());
+    }
+
+    #[test]
+    fn chunk_length_must_be_positive_and_finite() {
+        for chunk_sec in [0.0, -60.0, f64::NAN, f64::INFINITY] {
+            assert!(check_windows(chunk_sec, 0.0).is_err(), "{chunk_sec}");
+        }
+    }
+}
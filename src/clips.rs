@@ -0,0 +1,125 @@
+//! `--export-clips DIR`: cuts the input into one WAV per segment (or per
+//! speaker turn with `--clip-by speaker`), each next to a `.txt` with its
+//! final text, for training data or pulling quotes. Clips are named by
+//! index and time range, `0001_00-01-23.400_00-01-27.100.wav`, with the
+//! speaker appended for speaker turns.
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+
+use crate::{BackendResponse, PostprocessStep, output, tools};
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum ClipUnit {
+    Segment,
+    /// Consecutive segments of one `--split-channels` speaker.
+    Speaker,
+}
+
+struct Clip {
+    start: f64,
+    end: f64,
+    text: String,
+    speaker: Option<String>,
+}
+
+/// Writes the clips of `parsed` cut from `input` into `dir` and records an
+/// `export_clips` step.
+pub fn export(
+    parsed: &mut BackendResponse,
+    input: &Path,
+    dir: &Path,
+    unit: ClipUnit,
+    verbose: bool,
+) -> Result<()> {
+    let Some(segments) = parsed.segments.as_ref().filter(|segs| !segs.is_empty()) else {
+        eprintln!("[parakeet] warning: no segments; --export-clips wrote nothing");
+        return Ok(());
+    };
+    let by_speaker = match unit {
+        ClipUnit::Speaker if segments.iter().all(|seg| seg.speaker.is_none()) => {
+            eprintln!(
+                "[parakeet] warning: --clip-by speaker needs speaker labels (--split-channels); clipping per segment"
+            );
+            false
+        }
+        ClipUnit::Speaker => true,
+        ClipUnit::Segment => false,
+    };
+    let mut clips: Vec<Clip> = Vec::new();
+    for seg in segments {
+        match clips.last_mut() {
+            Some(clip) if by_speaker && clip.speaker == seg.speaker => {
+                clip.end = clip.end.max(seg.end);
+                clip.text = format!("{} {}", clip.text, seg.text.trim());
+            }
+            _ => clips.push(Clip {
+                start: seg.start,
+                end: seg.end,
+                text: seg.text.trim().to_string(),
+                speaker: seg.speaker.clone().filter(|_| by_speaker),
+            }),
+        }
+    }
+
+    fs::create_dir_all(dir).with_context(|| format!("failed creating {}", dir.display()))?;
+    let ffmpeg = tools::require(&tools::FFMPEG)?;
+    for (idx, clip) in clips.iter().enumerate() {
+        let mut name = format!("{:04}_{}_{}", idx + 1, stamp(clip.start), stamp(clip.end));
+        if let Some(speaker) = &clip.speaker {
+            name = format!("{name}_{}", output::slugify(speaker));
+        }
+        let audio = dir.join(format!("{name}.wav"));
+        if verbose {
+            eprintln!("[parakeet] clip: {}", audio.display());
+        }
+        let result = Command::new(&ffmpeg)
+            .args(["-v", "error", "-y", "-ss"])
+            .arg(format!("{:.3}", clip.start))
+            .arg("-i")
+            .arg(input)
+            .arg("-t")
+            .arg(format!("{:.3}", clip.end - clip.start))
+            .args(["-vn", "-c:a", "pcm_s16le"])
+            .arg(&audio)
+            .stdin(Stdio::null())
+            .output()
+            .context("failed running ffmpeg to cut clips")?;
+        if !result.status.success() {
+            bail!(
+                "ffmpeg failed cutting {}: {}",
+                audio.display(),
+                String::from_utf8_lossy(&result.stderr).trim()
+            );
+        }
+        let text = dir.join(format!("{name}.txt"));
+        fs::write(&text, format!("{}\n", clip.text))
+            .with_context(|| format!("failed writing {}", text.display()))?;
+    }
+    parsed.postprocessing.push(PostprocessStep::new(
+        "export_clips",
+        1,
+        serde_json::json!({
+            "dir": dir.display().to_string(),
+            "clips": clips.len(),
+            "by": if by_speaker { "speaker" } else { "segment" },
+        }),
+    ));
+    Ok(())
+}
+
+// `00-01-23.400`: a clock without colons, which some filesystems refuse.
+fn stamp(sec: f64) -> String {
+    let millis = (sec.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}-{:02}-{:02}.{:03}",
+        millis / 3_600_000,
+        millis % 3_600_000 / 60_000,
+        millis % 60_000 / 1000,
+        millis % 1000
+    )
+}
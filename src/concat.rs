@@ -0,0 +1,120 @@
+//! `--concat FILE` (repeatable): for recordings a recorder split into parts,
+//! transcribes `--input` and each FILE in turn and joins them into one
+//! transcript. Timestamps run on across parts, so the second part's first
+//! word is stamped after the first part's length, and each part starts with
+//! a `[name]` section line.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::progress::Progress;
+use crate::{BackendRequest, BackendResponse, PostprocessStep, TranscribeCli, media};
+
+/// Fails before transcribing when a part is missing or not audio.
+pub fn check(files: &[PathBuf]) -> Result<()> {
+    for file in files {
+        if !file.exists() {
+            bail!("--concat input does not exist: {}", file.display());
+        }
+        media::validate(file)?;
+    }
+    Ok(())
+}
+
+/// Transcribes `request.input` followed by the `--concat` files.
+pub async fn transcribe(
+    cli: &TranscribeCli,
+    request: &BackendRequest<'_>,
+    progress: &Progress,
+) -> Result<BackendResponse> {
+    // The daemon resolves paths from its own working directory (PARAKEET_HOME).
+    let parts = cli
+        .concat
+        .iter()
+        .map(|file| {
+            std::path::absolute(file)
+                .with_context(|| format!("invalid input path: {}", file.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let inputs: Vec<&Path> = std::iter::once(request.input)
+        .chain(parts.iter().map(PathBuf::as_path))
+        .collect();
+    let mut combined: Option<BackendResponse> = None;
+    let mut transcript = Vec::new();
+    let mut sections = Vec::new();
+    let mut offset = 0.0;
+    for (idx, input) in inputs.iter().enumerate() {
+        let name = section_name(input);
+        progress.set_message(format!("part {}/{} ({name})", idx + 1, inputs.len()));
+        let part_request = BackendRequest {
+            input,
+            ..request.clone()
+        };
+        let mut part = crate::transcribe_part(cli, &part_request, progress).await?;
+        let duration = media::audio_sec(input)
+            .or_else(|| part.metrics.as_ref().and_then(|m| m.audio_sec))
+            .with_context(|| format!("could not tell how long {} is", input.display()))?;
+        shift(&mut part, offset);
+        transcript.push(format!("[{name}]\n{}", part.transcript.trim()));
+        sections.push(serde_json::json!({
+            "source": input.display().to_string(),
+            "offset_sec": offset,
+            "duration_sec": duration,
+        }));
+        offset += duration;
+        match combined.as_mut() {
+            None => combined = Some(part),
+            Some(all) => absorb(all, part),
+        }
+    }
+    let mut combined = combined.context("no inputs transcribed")?;
+    combined.transcript = transcript.join("\n\n");
+    if let Some(metrics) = combined.metrics.as_mut() {
+        metrics.audio_sec = Some(offset);
+    }
+    combined.postprocessing.push(PostprocessStep::new(
+        "concat",
+        1,
+        serde_json::json!({ "parts": sections }),
+    ));
+    Ok(combined)
+}
+
+fn section_name(input: &Path) -> String {
+    input.file_name().map_or_else(
+        || input.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+fn shift(part: &mut BackendResponse, offset: f64) {
+    for seg in part.segments.iter_mut().flatten() {
+        seg.start += offset;
+        seg.end += offset;
+    }
+    for word in part.words.iter_mut().flatten() {
+        word.start += offset;
+        word.end += offset;
+    }
+    for warning in &mut part.warnings {
+        warning.start = warning.start.map(|start| start + offset);
+        warning.end = warning.end.map(|end| end + offset);
+    }
+}
+
+// Appends a later part; the first part's model, device and steps stand for all.
+fn absorb(all: &mut BackendResponse, part: BackendResponse) {
+    if let (Some(segments), Some(more)) = (all.segments.as_mut(), part.segments) {
+        segments.extend(more);
+    }
+    if let (Some(words), Some(more)) = (all.words.as_mut(), part.words) {
+        words.extend(more);
+    }
+    if let (Some(total), Some(m)) = (all.metrics.as_mut(), part.metrics) {
+        total.add(&m);
+    }
+    all.warnings.extend(part.warnings);
+    all.language_mismatch |= part.language_mismatch;
+    all.skipped &= part.skipped;
+}
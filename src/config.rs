@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::engine::Engine;
+use crate::parakeet_home;
+use crate::quality::Quality;
+use crate::summarize::SummarizeConfig;
+use crate::voicemail::VoicemailConfig;
+
+/// Persistent defaults from `$PARAKEET_HOME/config.json`. Every field is
+/// optional; command-line flags take precedence.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub auto_daemon: bool,
+    pub daemon_timeout_sec: Option<f64>,
+    pub daemon_write_timeout_sec: Option<f64>,
+    pub daemon_retries: Option<u32>,
+    /// Seconds without requests before the daemon unloads its models (`--idle-unload`).
+    pub daemon_idle_unload_sec: Option<u64>,
+    /// Parallel backend workers for `daemon serve` (`--workers`).
+    pub daemon_workers: Option<u32>,
+    /// Workers per visible GPU for `daemon serve` (`--workers-per-gpu`).
+    pub daemon_workers_per_gpu: Option<u32>,
+    /// Models `daemon serve` loads and warms up at startup (`--preload-model`).
+    pub daemon_preload_models: Vec<String>,
+    /// Models each daemon worker keeps loaded (`--max-models`).
+    pub daemon_max_models: Option<u32>,
+    /// Per-GPU memory budget in MiB for the daemon's models (`--vram-budget-mb`).
+    pub daemon_vram_budget_mb: Option<u64>,
+    /// Users and groups (names or ids) the daemon serves (`--allow-user`/`--allow-group`).
+    pub daemon_allowed_users: Vec<String>,
+    pub daemon_allowed_groups: Vec<String>,
+    /// Default `--quality` preset.
+    pub quality: Option<Quality>,
+    /// Default `--engine`.
+    pub engine: Option<Engine>,
+    pub engine_url: Option<String>,
+    /// Env var holding the engine's API key (default `OPENAI_API_KEY` for
+    /// `openai-api`, `PARAKEET_ENGINE_API_KEY` for `generic-http`).
+    pub engine_api_key_env: Option<String>,
+    /// Archive output files of runs older than this many days (see `history archive`).
+    pub archive_after_days: Option<u32>,
+    /// Default `--diff-guard` percentage for existing output files.
+    pub diff_guard_pct: Option<f64>,
+    /// Same as `--fast-start` on every transcription.
+    pub fast_start: bool,
+    /// Keep history, archive, downloads and workdirs in `users/<uid>` and have
+    /// the daemon check each caller's access (see `userhome`).
+    pub per_user_homes: bool,
+    /// Model per `--language` code (`*` for every other language), used when
+    /// no `--model` is given; see `languages::route`.
+    pub language_models: BTreeMap<String, String>,
+    /// Extra `--dictation` commands: spoken phrase -> text (`{scratch}` deletes
+    /// the last sentence), e.g. `{"smiley": ":)"}`.
+    pub dictation_commands: BTreeMap<String, String>,
+    /// Default `--rules` file (regex replacements for the final text).
+    pub rules: Option<PathBuf>,
+    /// Default `--pre-hook` command.
+    pub pre_hook: Option<String>,
+    /// Default `--post-hook` command.
+    pub post_hook: Option<String>,
+    /// Default `--redact` categories; compliance setups can enforce them here.
+    pub redact: Vec<crate::redact::Pii>,
+    /// Words `--mask-profanity` masks on top of its built-in list.
+    pub profanity_words: Vec<String>,
+    /// Default `--denoise-model` (RNNoise .rnnn file).
+    pub denoise_model: Option<PathBuf>,
+    /// Default `--calendar` (ICS file or URL) for transcriptions.
+    pub calendar: Option<String>,
+    /// Default `--obsidian-vault`, and the folder inside it for notes
+    /// (default `Transcripts`).
+    pub obsidian_vault: Option<PathBuf>,
+    pub obsidian_folder: Option<String>,
+    /// Default `--webhook`: every transcription (CLI, daemon jobs, voicemail)
+    /// reports completion and failure here.
+    pub webhook_url: Option<String>,
+    /// Backend for `--summarize`.
+    pub summarize: SummarizeConfig,
+    /// Mailbox, filing and reply settings for `parakeet voicemail`.
+    pub voicemail: VoicemailConfig,
+}
+
+pub fn config_path() -> PathBuf {
+    parakeet_home().join("config.json")
+}
+
+pub fn load() -> Result<Config> {
+    let path = config_path();
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let raw = fs::read_to_string(&path)
+        .with_context(|| format!("failed reading config: {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("invalid config: {}", path.display()))
+}
@@ -0,0 +1,164 @@
+//! `--dictation`: turns spoken commands into the edits they name, for
+//! dictated recordings ("Buy milk comma eggs period new line call Bob scratch
+//! that" becomes "Buy milk, eggs.\n"). The built-in commands cover
+//! punctuation, line breaks and "scratch that"; `dictation_commands` in
+//! config.json adds or overrides phrases.
+//!
+//! A command's text attaches to the previous word when it is only
+//! punctuation, starts a line when it holds a newline, and is inserted as a
+//! word otherwise. `{scratch}` deletes the last sentence or line.
+
+use std::collections::BTreeMap;
+
+use crate::{BackendResponse, PostprocessStep};
+
+const VERSION: u32 = 1;
+const SCRATCH: &str = "{scratch}";
+
+const BUILTIN: &[(&str, &str)] = &[
+    ("period", "."),
+    ("full stop", "."),
+    ("comma", ","),
+    ("question mark", "?"),
+    ("exclamation mark", "!"),
+    ("exclamation point", "!"),
+    ("colon", ":"),
+    ("semicolon", ";"),
+    ("dash", " -"),
+    ("new line", "\n"),
+    ("newline", "\n"),
+    ("new paragraph", "\n\n"),
+    ("scratch that", SCRATCH),
+    ("delete that", SCRATCH),
+];
+
+pub struct Commands {
+    // Phrase words (lowercase) and the text they stand for, longest first.
+    phrases: Vec<(Vec<String>, String)>,
+}
+
+impl Commands {
+    /// The built-in commands plus `extra` (phrase -> text), which wins on clashes.
+    pub fn new(extra: &BTreeMap<String, String>) -> Self {
+        let mut map: BTreeMap<String, String> = BUILTIN
+            .iter()
+            .map(|(phrase, text)| (phrase.to_string(), text.to_string()))
+            .collect();
+        for (phrase, text) in extra {
+            map.insert(phrase.to_lowercase(), text.clone());
+        }
+        let mut phrases: Vec<(Vec<String>, String)> = map
+            .into_iter()
+            .map(|(phrase, text)| {
+                let words: Vec<String> = phrase.split_whitespace().map(str::to_string).collect();
+                (words, text)
+            })
+            .filter(|(words, _)| !words.is_empty())
+            .collect();
+        phrases.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+        Self { phrases }
+    }
+
+    // The command starting at `tokens[0]`, as (words consumed, text).
+    fn matching(&self, tokens: &[&str]) -> Option<(usize, &str)> {
+        self.phrases.iter().find_map(|(words, text)| {
+            let hit = words.len() <= tokens.len()
+                && words
+                    .iter()
+                    .zip(tokens)
+                    .all(|(word, token)| *word == bare(token));
+            hit.then_some((words.len(), text.as_str()))
+        })
+    }
+}
+
+/// Applies the commands to the transcript and each segment, and records how
+/// many were applied. Word timings are dropped once anything changed.
+pub fn apply(parsed: &mut BackendResponse, commands: &Commands) {
+    let (transcript, applied) = rewrite(&parsed.transcript, commands);
+    parsed.transcript = transcript;
+    // Segments are edited on their own, so "scratch that" stays within one.
+    for seg in parsed.segments.iter_mut().flatten() {
+        seg.text = rewrite(&seg.text, commands).0;
+    }
+    if applied > 0 {
+        parsed.words = None;
+    }
+    parsed.postprocessing.push(PostprocessStep::new(
+        "dictation",
+        VERSION,
+        serde_json::json!({ "commands_applied": applied }),
+    ));
+}
+
+fn rewrite(text: &str, commands: &Commands) -> (String, usize) {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let mut out = String::new();
+    let mut applied = 0;
+    let mut capitalize = false;
+    let mut idx = 0;
+    while idx < tokens.len() {
+        let Some((len, action)) = commands.matching(&tokens[idx..]) else {
+            let token = tokens[idx];
+            if !out.is_empty() && !out.ends_with('\n') {
+                out.push(' ');
+            }
+            if capitalize {
+                out.extend(capitalized(token));
+            } else {
+                out.push_str(token);
+            }
+            capitalize = false;
+            idx += 1;
+            continue;
+        };
+        applied += 1;
+        idx += len;
+        if action == SCRATCH {
+            scratch(&mut out);
+            capitalize = out.is_empty() || out.ends_with(['.', '?', '!', '\n']);
+        } else if action.contains('\n') {
+            out.truncate(out.trim_end_matches(' ').len());
+            out.push_str(action);
+            capitalize = true;
+        } else if !action.chars().any(char::is_alphanumeric) {
+            // The model often punctuates around the spoken mark; the mark wins.
+            out.truncate(out.trim_end_matches([' ', ',', '.', ';', ':']).len());
+            out.push_str(action);
+            capitalize = action.ends_with(['.', '?', '!']);
+        } else {
+            if !out.is_empty() && !out.ends_with('\n') {
+                out.push(' ');
+            }
+            out.push_str(action);
+            capitalize = false;
+        }
+    }
+    (out.trim_end_matches(' ').to_string(), applied)
+}
+
+/// Drops the last sentence or line, keeping the break before it.
+fn scratch(out: &mut String) {
+    let kept = out.trim_end_matches(' ').len();
+    out.truncate(kept);
+    let body = out.trim_end_matches(['.', '?', '!', ',']);
+    let cut = body.rfind(['.', '?', '!', '\n']).map_or(0, |pos| pos + 1);
+    out.truncate(cut);
+}
+
+// The token as a command word: lowercase, without the punctuation the model
+// put around it ("Period." matches "period").
+fn bare(token: &str) -> String {
+    token
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+fn capitalized(token: &str) -> impl Iterator<Item = char> + '_ {
+    let mut chars = token.chars();
+    chars
+        .next()
+        .into_iter()
+        .flat_map(char::to_uppercase)
+        .chain(chars)
+}
@@ -0,0 +1,278 @@
+//! `parakeet diff A B`: aligns two transcripts of the same audio (two
+//! models, two vocab configs) word by word and prints each stretch where
+//! they differ with its timecode and a few words of context.
+//!
+//! Words are compared normalized like `eval` (case and punctuation ignored)
+//! but printed as written. Times come from word timestamps when present,
+//! else are spread evenly over each segment.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+
+use crate::eval::{self, Edit};
+use crate::{BackendResponse, export, output};
+
+#[derive(Debug, Parser)]
+pub struct DiffCli {
+    /// Transcripts to compare (`--emit json` output, .srt, .vtt).
+    a: PathBuf,
+    b: PathBuf,
+
+    /// Words of unchanged text shown around each difference.
+    #[arg(long, short = 'C', default_value_t = 3)]
+    context: usize,
+
+    /// Compare words as written, case and punctuation included.
+    #[arg(long, default_value_t = false)]
+    no_normalize: bool,
+
+    /// One JSON object per difference (`start`, `a`, `b`).
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
+
+struct TimedWord {
+    text: String,
+    start: Option<f64>,
+}
+
+/// A run of consecutive edits; the `a`/`b` ranges index into the word lists.
+struct Hunk {
+    a: (usize, usize),
+    b: (usize, usize),
+}
+
+pub fn run(cli: DiffCli) -> Result<()> {
+    let a = timed_words(&export::load_transcript(&cli.a)?);
+    let b = timed_words(&export::load_transcript(&cli.b)?);
+    let key = |word: &TimedWord| {
+        if cli.no_normalize {
+            word.text.clone()
+        } else {
+            eval::normalize(&word.text).split_whitespace().collect()
+        }
+    };
+    // Words that normalize away (dashes, stray punctuation) take no part.
+    let (a, a_keys) = keyed(a, key);
+    let (b, b_keys) = keyed(b, key);
+    let a_refs: Vec<&str> = a_keys.iter().map(String::as_str).collect();
+    let b_refs: Vec<&str> = b_keys.iter().map(String::as_str).collect();
+    let edits = eval::align(&a_refs, &b_refs);
+    let hunks = hunks(&edits);
+
+    let color = !cli.json && eval::use_color();
+    let join = |words: &[TimedWord]| {
+        words
+            .iter()
+            .map(|word| word.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    for hunk in &hunks {
+        let removed = &a[hunk.a.0..hunk.a.1];
+        let added = &b[hunk.b.0..hunk.b.1];
+        let start = removed
+            .first()
+            .or(added.first())
+            .and_then(|word| word.start)
+            .or_else(|| a.get(hunk.a.0).and_then(|word| word.start));
+        if cli.json {
+            println!(
+                "{}",
+                serde_json::json!({ "start": start, "a": join(removed), "b": join(added) })
+            );
+            continue;
+        }
+        let mut line = Vec::new();
+        let before = &a[hunk.a.0.saturating_sub(cli.context)..hunk.a.0];
+        if !before.is_empty() {
+            line.push(join(before));
+        }
+        // Markup as in `eval`: `[-a's words-]{+b's words+}`.
+        let mut change = String::new();
+        if !removed.is_empty() {
+            let text = format!("[-{}-]", join(removed));
+            change.push_str(&eval::paint(color, eval::DELETED, text));
+        }
+        if !added.is_empty() {
+            let text = format!("{{+{}+}}", join(added));
+            change.push_str(&eval::paint(color, eval::INSERTED, text));
+        }
+        line.push(change);
+        let after = &a[hunk.a.1..(hunk.a.1 + cli.context).min(a.len())];
+        if !after.is_empty() {
+            line.push(join(after));
+        }
+        let clock = start.map_or_else(|| "--:--:--".to_string(), output::format_clock);
+        println!("{clock}  {}", line.join(" "));
+    }
+    if !cli.json {
+        let changed = 100.0 * output::word_change(&a_keys.join(" "), &b_keys.join(" "));
+        println!(
+            "{} difference(s); {changed:.1}% of {}'s words changed",
+            hunks.len(),
+            cli.a.display()
+        );
+    }
+    Ok(())
+}
+
+fn keyed(
+    words: Vec<TimedWord>,
+    key: impl Fn(&TimedWord) -> String,
+) -> (Vec<TimedWord>, Vec<String>) {
+    words
+        .into_iter()
+        .map(|word| (key(&word), word))
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, word)| (word, key))
+        .unzip()
+}
+
+/// Word timestamps when the transcript has them, else segment words with
+/// times interpolated across the segment, else untimed transcript words.
+fn timed_words(parsed: &BackendResponse) -> Vec<TimedWord> {
+    if let Some(words) = parsed.words.as_ref().filter(|words| !words.is_empty()) {
+        return words
+            .iter()
+            .map(|word| TimedWord {
+                text: word.text.trim().to_string(),
+                start: Some(word.start),
+            })
+            .collect();
+    }
+    if let Some(segments) = parsed.segments.as_ref().filter(|segs| !segs.is_empty()) {
+        return segments
+            .iter()
+            .flat_map(|seg| {
+                let words: Vec<&str> = seg.text.split_whitespace().collect();
+                let step = (seg.end - seg.start) / words.len().max(1) as f64;
+                words
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(idx, text)| TimedWord {
+                        text: text.to_string(),
+                        start: Some(seg.start + idx as f64 * step),
+                    })
+            })
+            .collect();
+    }
+    parsed
+        .transcript
+        .split_whitespace()
+        .map(|text| TimedWord {
+            text: text.to_string(),
+            start: None,
+        })
+        .collect()
+}
+
+fn hunks(edits: &[Edit]) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut open = false;
+    for edit in edits {
+        let (di, dj) = match edit {
+            Edit::Same(_) => {
+                open = false;
+                i += 1;
+                j += 1;
+                continue;
+            }
+            Edit::Sub(..) => (1, 1),
+            Edit::Del(_) => (1, 0),
+            Edit::Ins(_) => (0, 1),
+        };
+        if !open {
+            hunks.push(Hunk {
+                a: (i, i),
+                b: (j, j),
+            });
+            open = true;
+        }
+        i += di;
+        j += dj;
+        if let Some(hunk) = hunks.last_mut() {
+            hunk.a.1 = i;
+            hunk.b.1 = j;
+        }
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(json: serde_json::Value) -> BackendResponse {
+        let mut base = serde_json::json!({
+            "transcript": "",
+            "source": "test",
+            "model": "",
+            "device": "",
+            "format": "json",
+        });
+        base.as_object_mut()
+            .unwrap()
+            .extend(json.as_object().unwrap().clone());
+        serde_json::from_value(base).unwrap()
+    }
+
+    fn spans(a: &str, b: &str) -> Vec<((usize, usize), (usize, usize))> {
+        let a: Vec<&str> = a.split_whitespace().collect();
+        let b: Vec<&str> = b.split_whitespace().collect();
+        hunks(&eval::align(&a, &b))
+            .into_iter()
+            .map(|hunk| (hunk.a, hunk.b))
+            .collect()
+    }
+
+    #[test]
+    fn merges_adjacent_edits_into_one_hunk() {
+        // Substitution, deletion and insertion next to each other.
+        assert_eq!(spans("a b c d e", "a x d y e"), [((1, 4), (1, 4))]);
+        assert_eq!(
+            spans("a b c d", "a x c y"),
+            [((1, 2), (1, 2)), ((3, 4), (3, 4))]
+        );
+    }
+
+    #[test]
+    fn closes_leading_and_trailing_hunks() {
+        assert_eq!(
+            spans("a b c", "b c d e"),
+            [((0, 1), (0, 0)), ((3, 3), (2, 4))]
+        );
+        assert_eq!(spans("a b", "a b"), []);
+        assert_eq!(spans("", "a"), [((0, 0), (0, 1))]);
+    }
+
+    #[test]
+    fn times_words_from_timestamps_segments_or_nothing() {
+        let timed = timed_words(&response(serde_json::json!({
+            "words": [{ "text": " Hello", "start": 0.5, "end": 0.9 }],
+            "segments": [{ "start": 0.0, "end": 4.0, "text": "ignored" }],
+        })));
+        assert_eq!(timed[0].text, "Hello");
+        assert_eq!(timed[0].start, Some(0.5));
+
+        // Spread evenly over each segment.
+        let timed = timed_words(&response(serde_json::json!({
+            "segments": [
+                { "start": 2.0, "end": 4.0, "text": "one two three four" },
+                { "start": 10.0, "end": 11.0, "text": "five" },
+            ],
+        })));
+        let starts: Vec<Option<f64>> = timed.iter().map(|word| word.start).collect();
+        assert_eq!(
+            starts,
+            [Some(2.0), Some(2.5), Some(3.0), Some(3.5), Some(10.0)]
+        );
+
+        let timed = timed_words(&response(serde_json::json!({ "transcript": "no times" })));
+        assert_eq!(timed.len(), 2);
+        assert!(timed.iter().all(|word| word.start.is_none()));
+    }
+}
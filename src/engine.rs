@@ -0,0 +1,467 @@
+//! `--engine openai-api|generic-http`: inference runs on an external HTTP
+//! transcription API instead of the local NeMo backend. The answer is adapted
+//! into a `BackendResponse`, so rendering, sinks, history and archiving work
+//! the same as for local runs.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Instant;
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::error::{ErrorKind, Failure};
+use crate::{
+    AudioFormat, BackendMetrics, BackendRequest, BackendResponse, PreprocessStep, Segment,
+    TranscribeCli, Word, media, tools, twopass,
+};
+
+const OPENAI_URL: &str = "https://api.openai.com/v1/audio/transcriptions";
+const OPENAI_MODEL: &str = "whisper-1";
+const CONNECT_TIMEOUT_SEC: &str = "30";
+// OpenAI reads only the last 224 tokens of the prompt; keep the vocab hint well under that.
+const MAX_PROMPT_CHARS: usize = 800;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Engine {
+    /// The NeMo backend, through the daemon or a one-off subprocess.
+    #[default]
+    Local,
+    /// OpenAI's `/v1/audio/transcriptions` (or a compatible server via `--engine-url`).
+    OpenaiApi,
+    /// Any endpoint taking a multipart `file` upload and answering JSON with `text`.
+    GenericHttp,
+}
+
+impl Engine {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Engine::Local => "local",
+            Engine::OpenaiApi => "openai-api",
+            Engine::GenericHttp => "generic-http",
+        }
+    }
+
+    pub fn is_remote(self) -> bool {
+        self != Engine::Local
+    }
+
+    /// Model sent when neither `--model` nor a `--quality` preset names one.
+    pub fn default_model(self) -> Option<&'static str> {
+        match self {
+            Engine::OpenaiApi => Some(OPENAI_MODEL),
+            Engine::Local | Engine::GenericHttp => None,
+        }
+    }
+
+    fn default_key_env(self) -> &'static str {
+        match self {
+            Engine::OpenaiApi => "OPENAI_API_KEY",
+            Engine::Local | Engine::GenericHttp => "PARAKEET_ENGINE_API_KEY",
+        }
+    }
+}
+
+/// Answer shape shared by both engines: OpenAI's `verbose_json`, which generic
+/// endpoints are expected to follow (only `text` is required).
+#[derive(serde::Deserialize)]
+struct ApiResponse {
+    #[serde(alias = "transcript")]
+    text: String,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    duration: Option<f64>,
+    #[serde(default)]
+    segments: Option<Vec<ApiSegment>>,
+    #[serde(default)]
+    words: Option<Vec<ApiWord>>,
+}
+
+#[derive(serde::Deserialize)]
+struct ApiSegment {
+    start: f64,
+    end: f64,
+    text: String,
+    #[serde(default)]
+    avg_logprob: Option<f64>,
+}
+
+#[derive(serde::Deserialize)]
+struct ApiWord {
+    #[serde(alias = "text")]
+    word: String,
+    start: f64,
+    end: f64,
+}
+
+/// Transcribes `request` on the engine configured in `cli`.
+pub async fn transcribe(
+    cli: &TranscribeCli,
+    engine: Engine,
+    request: &BackendRequest<'_>,
+) -> Result<BackendResponse> {
+    let started = Instant::now();
+    let url = match (&cli.engine_url, engine) {
+        (Some(url), _) => url.clone(),
+        (None, Engine::OpenaiApi) => OPENAI_URL.to_string(),
+        (None, _) => bail!(
+            "--engine {} needs --engine-url (or \"engine_url\" in config.json)",
+            engine.as_str()
+        ),
+    };
+    let url = match url.strip_suffix("/transcriptions") {
+        Some(base) if request.translate && engine == Engine::OpenaiApi => {
+            format!("{base}/translations")
+        }
+        _ => url,
+    };
+    let key_env = cli
+        .engine_api_key_env
+        .as_deref()
+        .unwrap_or(engine.default_key_env());
+    let api_key = std::env::var(key_env).ok().filter(|key| !key.is_empty());
+    if api_key.is_none() && engine == Engine::OpenaiApi {
+        bail!("--engine openai-api needs an API key in {key_env}");
+    }
+
+    let mut preprocessing = Vec::new();
+    let upload = match prepare_upload(request, cli.verbose).await? {
+        Some((path, step)) => {
+            preprocessing.push(step);
+            path
+        }
+        None => request.input.to_path_buf(),
+    };
+    let terms = twopass::read_terms(request.vocab)?;
+
+    let mut cmd = Command::new(tools::require(&tools::CURL)?);
+    cmd.kill_on_drop(true)
+        .args([
+            "-sS",
+            "--fail-with-body",
+            "--connect-timeout",
+            CONNECT_TIMEOUT_SEC,
+        ])
+        // Headers come from stdin so the key never shows up in `ps`.
+        .args(["--config", "-"])
+        .arg("-F")
+        .arg(format!("file=@\"{}\"", quote_form_value(&upload)))
+        .arg("-F")
+        .arg(format!("model={}", request.model));
+    match engine {
+        Engine::OpenaiApi => {
+            cmd.args(["-F", "response_format=verbose_json"]);
+            // The translations endpoint returns segments as is and has no word timing.
+            if (request.timestamps || request.word_timestamps) && !request.translate {
+                cmd.args(["-F", "timestamp_granularities[]=segment"]);
+            }
+            if request.word_timestamps && !request.translate {
+                cmd.args(["-F", "timestamp_granularities[]=word"]);
+            }
+            if !terms.is_empty() {
+                // The API has no vocabulary list; a prompt naming the terms steers spelling.
+                cmd.arg("--form-string")
+                    .arg(format!("prompt={}", vocab_prompt(&terms)));
+            }
+        }
+        Engine::GenericHttp | Engine::Local => {
+            cmd.arg("-F")
+                .arg(format!("timestamps={}", request.timestamps))
+                .arg("-F")
+                .arg(format!("word_timestamps={}", request.word_timestamps));
+            if request.translate {
+                cmd.args(["-F", "task=translate"]);
+            }
+            if !terms.is_empty() {
+                let vocab = terms.iter().cloned().collect::<Vec<_>>().join("\n");
+                cmd.arg("--form-string").arg(format!("vocab={vocab}"));
+            }
+        }
+    }
+    // With `auto` the API detects the language itself; OpenAI translations take none.
+    if let Some(language) = request
+        .language
+        .filter(|lang| *lang != "auto" && !(request.translate && engine == Engine::OpenaiApi))
+    {
+        cmd.arg("-F").arg(format!("language={language}"));
+    }
+    if let Some(temperature) = request.temperature {
+        cmd.arg("-F").arg(format!("temperature={temperature}"));
+    }
+    for (key, value) in &request.backend_args {
+        cmd.arg("--form-string").arg(format!("{key}={value}"));
+    }
+    cmd.arg(&url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if cli.verbose {
+        eprintln!(
+            "[parakeet] engine {}: POST {} ({})",
+            engine.as_str(),
+            url,
+            upload.display()
+        );
+    }
+
+    let mut child = cmd
+        .spawn()
+        .context("failed running curl for the transcription engine")?;
+    let mut config = String::new();
+    if let Some(key) = &api_key {
+        config.push_str(&format!(
+            "header = \"Authorization: Bearer {}\"\n",
+            key.replace('"', "")
+        ));
+    }
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes()).await?;
+    }
+    let output = child
+        .wait_with_output()
+        .await
+        .context("failed waiting for curl")?;
+    let body = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        let detail = match body.trim() {
+            "" => String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            body => body.to_string(),
+        };
+        bail!(Failure::new(
+            ErrorKind::BackendFailed,
+            format!(
+                "engine {} request to {url} failed: {detail}",
+                engine.as_str()
+            ),
+        ));
+    }
+    let api: ApiResponse = serde_json::from_str(body.trim()).map_err(|err| {
+        Failure::new(
+            ErrorKind::BackendFailed,
+            format!("engine {} returned unexpected JSON: {err}", engine.as_str()),
+        )
+    })?;
+    let elapsed = started.elapsed().as_secs_f64();
+    Ok(adapt(cli, engine, request, api, preprocessing, elapsed))
+}
+
+fn adapt(
+    cli: &TranscribeCli,
+    engine: Engine,
+    request: &BackendRequest<'_>,
+    api: ApiResponse,
+    preprocessing: Vec<PreprocessStep>,
+    elapsed: f64,
+) -> BackendResponse {
+    // The upload starts at --start; timestamps are reported on the input's clock.
+    let offset = request.start_sec.unwrap_or_default();
+    // Translation answers describe the English output; the source is what was asked for.
+    let language = match request.language.filter(|lang| *lang != "auto") {
+        Some(code) if request.translate => Some(code.to_string()),
+        _ if request.translate => None,
+        _ => api.language.as_deref().map(language_code),
+    };
+    let only_language = request.only_language.map(str::to_lowercase);
+    let language_mismatch = only_language
+        .as_deref()
+        .zip(language.as_deref())
+        .is_some_and(|(want, lang)| lang != want);
+    let segments = request.timestamps.then(|| {
+        api.segments
+            .unwrap_or_default()
+            .into_iter()
+            .map(|seg| Segment {
+                start: seg.start + offset,
+                end: seg.end + offset,
+                text: seg.text.trim().to_string(),
+                // Average token probability stands in for a confidence score.
+                confidence: seg
+                    .avg_logprob
+                    .filter(|_| request.confidence)
+                    .map(|logprob| logprob.exp().clamp(0.0, 1.0)),
+                speaker: None,
+            })
+            .collect()
+    });
+    let words = request.word_timestamps.then(|| {
+        api.words
+            .unwrap_or_default()
+            .into_iter()
+            .map(|word| Word {
+                text: word.word.trim().to_string(),
+                start: word.start + offset,
+                end: word.end + offset,
+                confidence: None,
+            })
+            .collect()
+    });
+    BackendResponse {
+        transcript: api.text.trim().to_string(),
+        segments,
+        words,
+        alternatives: None,
+        output_path: None,
+        source: cli.input.display().to_string(),
+        model: request.model.to_string(),
+        device: engine.as_str().to_string(),
+        precision: None,
+        format: "text".to_string(),
+        metrics: Some(BackendMetrics {
+            model_load_sec: 0.0,
+            inference_sec: elapsed,
+            total_sec: elapsed,
+            audio_sec: api.duration,
+            realtime_factor: None,
+            peak_gpu_mb: None,
+        }),
+        language,
+        task: Some(
+            if request.translate {
+                "translate"
+            } else {
+                "transcribe"
+            }
+            .to_string(),
+        ),
+        language_mismatch,
+        skipped: language_mismatch && request.language_mismatch == "skip",
+        history_id: None,
+        metadata: Default::default(),
+        media_tags: Default::default(),
+        tags: Default::default(),
+        preprocessing,
+        postprocessing: Vec::new(),
+        delivered_to: Vec::new(),
+        warnings: Vec::new(),
+        redactions: Default::default(),
+        summary: None,
+        chapters: Vec::new(),
+    }
+}
+
+/// Cuts `--start`/`--end` ranges and converts containers the backend would
+/// also convert, to 16 kHz mono WAV in the job workdir (mirrors `cut_audio`
+/// in parakeet_backend.py). Other input is uploaded as is.
+async fn prepare_upload(
+    request: &BackendRequest<'_>,
+    verbose: bool,
+) -> Result<Option<(PathBuf, PreprocessStep)>> {
+    let cut = request.start_sec.is_some() || request.end_sec.is_some();
+    if !cut && !media::needs_conversion(request.input) {
+        return Ok(None);
+    }
+    let dir = match request.work_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => std::env::temp_dir(),
+    };
+    let start = request.start_sec.unwrap_or_default();
+    let out = dir.join(format!(
+        "engine-{:.3}-{:.3}.wav",
+        start,
+        request.end_sec.unwrap_or(-1.0)
+    ));
+    let mut cmd = Command::new(tools::require(&tools::FFMPEG)?);
+    cmd.args(["-v", "error", "-y"]);
+    if start > 0.0 {
+        cmd.arg("-ss").arg(format!("{start:.3}"));
+    }
+    cmd.arg("-i").arg(request.input);
+    if let Some(end) = request.end_sec {
+        cmd.arg("-t").arg(format!("{:.3}", end - start));
+    }
+    cmd.args(["-ac", "1", "-ar", "16000"]).arg(&out);
+    if verbose {
+        eprintln!("[parakeet] preparing upload via ffmpeg: {}", out.display());
+    }
+    let output = cmd.output().await.context("failed running ffmpeg")?;
+    if !output.status.success() {
+        bail!(
+            "ffmpeg failed preparing {} for upload: {}",
+            request.input.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let mut changes = Vec::new();
+    if cut {
+        changes.push(match request.end_sec {
+            Some(end) => format!("cut {start:.3}-{end:.3}s"),
+            None => format!("cut from {start:.3}s"),
+        });
+    }
+    changes.push("convert to 16000 Hz mono WAV".to_string());
+    let step = PreprocessStep {
+        name: if cut { "cut" } else { "convert" }.to_string(),
+        tool: "ffmpeg".to_string(),
+        from: None,
+        to: Some(AudioFormat {
+            codec: Some("pcm_s16le".to_string()),
+            sample_rate: Some(16000),
+            channels: Some(1),
+            bit_depth: Some(16),
+        }),
+        changes,
+    };
+    Ok(Some((out, step)))
+}
+
+// curl's -F takes `"..."` quoting for names containing `;` or `,`.
+fn quote_form_value(path: &Path) -> String {
+    path.display()
+        .to_string()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+}
+
+fn vocab_prompt(terms: &std::collections::BTreeSet<String>) -> String {
+    let mut prompt = String::from("Vocabulary:");
+    for term in terms {
+        if prompt.len() + term.len() + 2 > MAX_PROMPT_CHARS {
+            break;
+        }
+        prompt.push(' ');
+        prompt.push_str(term);
+        prompt.push(',');
+    }
+    prompt.pop();
+    prompt
+}
+
+/// OpenAI reports languages by English name (`english`); `--only-language`
+/// and the local backend use ISO 639-1 codes. Covers the languages Parakeet v3
+/// supports; anything else passes through lowercased.
+fn language_code(raw: &str) -> String {
+    let lower = raw.trim().to_lowercase();
+    let code = match lower.as_str() {
+        "bulgarian" => "bg",
+        "croatian" => "hr",
+        "czech" => "cs",
+        "danish" => "da",
+        "dutch" => "nl",
+        "english" => "en",
+        "estonian" => "et",
+        "finnish" => "fi",
+        "french" => "fr",
+        "german" => "de",
+        "greek" => "el",
+        "hungarian" => "hu",
+        "italian" => "it",
+        "latvian" => "lv",
+        "lithuanian" => "lt",
+        "maltese" => "mt",
+        "polish" => "pl",
+        "portuguese" => "pt",
+        "romanian" => "ro",
+        "russian" => "ru",
+        "slovak" => "sk",
+        "slovenian" => "sl",
+        "spanish" => "es",
+        "swedish" => "sv",
+        "ukrainian" => "uk",
+        _ => return lower,
+    };
+    code.to_string()
+}
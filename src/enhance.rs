@@ -0,0 +1,116 @@
+//! Audio clean-up with ffmpeg before transcription, in one pass:
+//!
+//! - `--denoise`: for recordings with fan hum, keyboard clatter or street
+//!   noise. An 80 Hz high-pass and the FFT denoiser (`afftdn`), or with
+//!   `--denoise-model` an RNNoise model through ffmpeg's `arnndn` filter.
+//! - `--normalize-audio`: EBU R128 loudness normalization (`loudnorm`), since
+//!   very quiet recordings recognize noticeably worse.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use tokio::process::Command;
+
+use crate::{PreprocessStep, tools};
+
+const HIGHPASS: &str = "highpass=f=80";
+const FFT_FILTER: &str = "afftdn=nf=-25";
+// Integrated loudness, true peak and loudness range targets (EBU R128 style).
+const LOUDNORM_FILTER: &str = "loudnorm=I=-16:TP=-1.5:LRA=11";
+
+/// What to apply; nothing when all are off.
+pub struct Enhance<'a> {
+    pub denoise: bool,
+    pub denoise_model: Option<&'a Path>,
+    pub normalize: bool,
+}
+
+impl Enhance<'_> {
+    pub fn is_active(&self) -> bool {
+        self.denoise || self.normalize
+    }
+}
+
+/// Fails before transcribing when the RNNoise model cannot be used.
+pub fn check(model: &Path) -> Result<()> {
+    if !model.is_file() {
+        bail!("denoise model not found: {}", model.display());
+    }
+    // Filter arguments are single-quoted in the ffmpeg filtergraph.
+    if model.to_string_lossy().contains('\'') {
+        bail!(
+            "denoise model path must not contain a single quote: {}",
+            model.display()
+        );
+    }
+    Ok(())
+}
+
+/// Writes an enhanced 16 kHz WAV of `input` (channels kept) into `dir`, with
+/// one preprocessing step per stage.
+pub async fn apply(
+    input: &Path,
+    enhance: &Enhance<'_>,
+    dir: &Path,
+    verbose: bool,
+) -> Result<(PathBuf, Vec<PreprocessStep>)> {
+    let out = dir.join("enhanced.wav");
+    let mut filters = Vec::new();
+    let mut steps = Vec::new();
+    if enhance.denoise {
+        let change = match enhance.denoise_model {
+            Some(model) => {
+                filters.push(format!("{HIGHPASS},arnndn=m='{}'", model.display()));
+                format!("rnnoise ({})", model.display())
+            }
+            None => {
+                filters.push(format!("{HIGHPASS},{FFT_FILTER}"));
+                "fft denoise".to_string()
+            }
+        };
+        steps.push(step("denoise", vec!["high-pass 80 Hz".to_string(), change]));
+    }
+    // Loudness is measured after denoising, on what the model will hear.
+    if enhance.normalize {
+        filters.push(LOUDNORM_FILTER.to_string());
+        steps.push(step(
+            "normalize",
+            vec!["loudness -16 LUFS, true peak -1.5 dBTP".to_string()],
+        ));
+    }
+    let filter = filters.join(",");
+    if verbose {
+        eprintln!(
+            "[parakeet] enhancing audio via ffmpeg -af {filter}: {}",
+            out.display()
+        );
+    }
+    let output = Command::new(tools::require(&tools::FFMPEG)?)
+        .args(["-v", "error", "-y", "-i"])
+        .arg(input)
+        .arg("-af")
+        .arg(&filter)
+        .args(["-ar", "16000"])
+        .arg(&out)
+        .output()
+        .await
+        .context("failed running ffmpeg")?;
+    if !output.status.success() {
+        bail!(
+            "ffmpeg failed enhancing {}: {}",
+            input.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok((out, steps))
+}
+
+fn step(name: &str, changes: Vec<String>) -> PreprocessStep {
+    PreprocessStep {
+        name: name.to_string(),
+        tool: "ffmpeg".to_string(),
+        from: None,
+        to: None,
+        changes,
+    }
+}
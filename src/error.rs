@@ -0,0 +1,174 @@
+use std::fmt;
+
+use serde_json::json;
+
+// Lines of backend stderr kept in JSON error objects.
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Process exit codes. These are part of the CLI contract (see AGENTS.md):
+/// add new codes, never renumber existing ones.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ExitCode {
+    Success = 0,
+    Failure = 1,
+    // 2 is taken by clap for usage errors.
+    InputNotFound = 3,
+    EnvironmentMissing = 4,
+    BackendFailed = 5,
+    DaemonUnreachable = 6,
+    IncompatibleHost = 7,
+    InvalidInput = 8,
+    Timeout = 124,
+    Cancelled = 130,
+}
+
+impl From<ExitCode> for std::process::ExitCode {
+    fn from(code: ExitCode) -> Self {
+        Self::from(code as u8)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    InputNotFound,
+    InvalidInput,
+    VenvMissing,
+    BackendMissing,
+    ToolMissing,
+    BackendFailed,
+    DaemonUnreachable,
+    IncompatibleHost,
+    Cancelled,
+    Timeout,
+    Other,
+}
+
+impl ErrorKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::InputNotFound => "input_not_found",
+            Self::InvalidInput => "invalid_input",
+            Self::VenvMissing => "venv_missing",
+            Self::BackendMissing => "backend_missing",
+            Self::ToolMissing => "tool_missing",
+            Self::BackendFailed => "backend_failed",
+            Self::DaemonUnreachable => "daemon_unreachable",
+            Self::IncompatibleHost => "incompatible_host",
+            Self::Cancelled => "cancelled",
+            Self::Timeout => "timeout",
+            Self::Other => "error",
+        }
+    }
+
+    pub fn exit_code(self) -> ExitCode {
+        match self {
+            Self::InputNotFound => ExitCode::InputNotFound,
+            Self::InvalidInput => ExitCode::InvalidInput,
+            Self::VenvMissing | Self::BackendMissing | Self::ToolMissing => {
+                ExitCode::EnvironmentMissing
+            }
+            Self::BackendFailed => ExitCode::BackendFailed,
+            Self::DaemonUnreachable => ExitCode::DaemonUnreachable,
+            Self::IncompatibleHost => ExitCode::IncompatibleHost,
+            Self::Cancelled => ExitCode::Cancelled,
+            Self::Timeout => ExitCode::Timeout,
+            Self::Other => ExitCode::Failure,
+        }
+    }
+}
+
+/// A classified failure. Raised with `bail!(Failure::new(..))` so it travels
+/// through anyhow contexts and can be recovered for structured output.
+#[derive(Debug)]
+pub struct Failure {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub stderr: Vec<String>,
+    pub hint: Option<String>,
+}
+
+impl Failure {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            stderr: Vec::new(),
+            hint: None,
+        }
+    }
+
+    pub fn with_stderr(mut self, stderr: Vec<String>) -> Self {
+        self.hint = self.hint.or_else(|| hint_for_stderr(&stderr));
+        self.stderr = stderr;
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if !self.stderr.is_empty() {
+            write!(f, ":\n{}", self.stderr.join("\n").trim())?;
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, "\nhint: {hint}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Failure {}
+
+pub fn failure_of(err: &anyhow::Error) -> Option<&Failure> {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<Failure>())
+}
+
+pub fn exit_code_of(err: &anyhow::Error) -> ExitCode {
+    failure_of(err).map_or(ExitCode::Failure, |f| f.kind.exit_code())
+}
+
+/// `{"error": {...}}` object printed on stdout for `--emit json` failures.
+pub fn to_json(err: &anyhow::Error) -> serde_json::Value {
+    let failure = failure_of(err);
+    // The context chain, with the backend stderr moved to its own field.
+    let message = err
+        .chain()
+        .map(|cause| match cause.downcast_ref::<Failure>() {
+            Some(failure) => failure.message.clone(),
+            None => cause.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(": ");
+    let stderr_tail = failure.map_or(&[][..], |f| {
+        &f.stderr[f.stderr.len().saturating_sub(STDERR_TAIL_LINES)..]
+    });
+    json!({
+        "error": {
+            "code": failure.map_or(ErrorKind::Other, |f| f.kind).as_str(),
+            "message": message,
+            "stderr_tail": stderr_tail,
+            "hint": failure.and_then(|f| f.hint.as_deref()),
+        }
+    })
+}
+
+fn hint_for_stderr(stderr: &[String]) -> Option<String> {
+    let text = stderr.join("\n");
+    let hint = if text.contains("CUDA out of memory") || text.contains("OutOfMemoryError") {
+        "the GPU ran out of memory; retry with --device cpu or a shorter --chunk-minutes"
+    } else if text.contains("ModuleNotFoundError") || text.contains("No module named") {
+        "python dependencies are missing; re-run install.sh"
+    } else if text.contains("ffmpeg") && text.contains("not found") {
+        "ffmpeg is required; install it and make sure it is on PATH"
+    } else {
+        return None;
+    };
+    Some(hint.to_string())
+}
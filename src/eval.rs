@@ -0,0 +1,520 @@
+//! `parakeet eval`: scores a transcript against a reference with word and
+//! character error rates (WER/CER), and shows the word alignment as a diff,
+//! for measuring what a vocab or model change actually bought.
+//!
+//! Both texts are normalized first (lowercase, punctuation dropped, except
+//! apostrophes inside words) unless `--no-normalize`, so formatting choices
+//! do not count as errors.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+
+use crate::{TranscribeCli, export};
+
+#[derive(Debug, Parser)]
+pub struct EvalCli {
+    /// Reference (ground truth) transcript: plain text, .srt, .vtt or
+    /// `--emit json` output.
+    #[arg(long = "ref", value_name = "FILE")]
+    reference: PathBuf,
+
+    /// Transcript to score, in the same formats.
+    #[arg(long, value_name = "FILE", required_unless_present = "input")]
+    hyp: Option<PathBuf>,
+
+    /// Transcribe this recording and score the result instead; transcribe
+    /// flags go after `--`, e.g. `-- --model NAME --vocab terms.txt`.
+    #[arg(long, conflicts_with = "hyp")]
+    input: Option<PathBuf>,
+
+    /// Compare the texts as they are, case and punctuation included.
+    #[arg(long, default_value_t = false)]
+    no_normalize: bool,
+
+    /// Only print the scores.
+    #[arg(long, default_value_t = false)]
+    no_diff: bool,
+
+    /// Print the scores as JSON.
+    #[arg(long, default_value_t = false)]
+    json: bool,
+
+    #[arg(last = true, value_name = "TRANSCRIBE_ARGS", requires = "input")]
+    args: Vec<String>,
+}
+
+pub enum Edit<'a> {
+    Same(&'a str),
+    Sub(&'a str, &'a str),
+    Del(&'a str),
+    Ins(&'a str),
+}
+
+pub async fn run(cli: EvalCli) -> Result<()> {
+    let reference = read_text(&cli.reference)?;
+    let hypothesis = match (&cli.hyp, &cli.input) {
+        (Some(path), _) => read_text(path)?,
+        (None, Some(input)) => transcribe(input, &cli.args).await?,
+        (None, None) => bail!("eval needs --hyp or --input"),
+    };
+    let (reference, hypothesis) = if cli.no_normalize {
+        (reference, hypothesis)
+    } else {
+        (normalize(&reference), normalize(&hypothesis))
+    };
+    let ref_words: Vec<&str> = reference.split_whitespace().collect();
+    let hyp_words: Vec<&str> = hypothesis.split_whitespace().collect();
+    if ref_words.is_empty() {
+        bail!("the reference {} has no words", cli.reference.display());
+    }
+    let edits = align(&ref_words, &hyp_words);
+    let Score {
+        substitutions,
+        deletions,
+        insertions,
+        wer,
+        cer,
+        ref_chars,
+    } = score(&edits);
+
+    if cli.json {
+        let report = serde_json::json!({
+            "wer": wer,
+            "cer": cer,
+            "substitutions": substitutions,
+            "deletions": deletions,
+            "insertions": insertions,
+            "ref_words": ref_words.len(),
+            "hyp_words": hyp_words.len(),
+            "ref_chars": ref_chars,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).context("serialize eval report")?
+        );
+        return Ok(());
+    }
+    if !cli.no_diff {
+        println!("{}\n", render_diff(&edits, use_color()));
+    }
+    println!(
+        "WER {:.2}% ({substitutions} substituted, {deletions} deleted, {insertions} inserted of {} words)",
+        wer * 100.0,
+        ref_words.len()
+    );
+    println!("CER {:.2}% ({ref_chars} characters)", cer * 100.0);
+    Ok(())
+}
+
+struct Score {
+    substitutions: usize,
+    deletions: usize,
+    insertions: usize,
+    wer: f64,
+    cer: f64,
+    ref_chars: usize,
+}
+
+/// Error counts and rates of an alignment whose reference has words.
+fn score(edits: &[Edit]) -> Score {
+    let count = |pick: fn(&Edit) -> bool| edits.iter().filter(|edit| pick(edit)).count();
+    let substitutions = count(|edit| matches!(edit, Edit::Sub(..)));
+    let deletions = count(|edit| matches!(edit, Edit::Del(_)));
+    let insertions = count(|edit| matches!(edit, Edit::Ins(_)));
+    let ref_words: Vec<&str> = edits
+        .iter()
+        .filter_map(|edit| match edit {
+            Edit::Same(word) | Edit::Sub(word, _) | Edit::Del(word) => Some(*word),
+            Edit::Ins(_) => None,
+        })
+        .collect();
+    let ref_chars = ref_words.join(" ").chars().count();
+    Score {
+        substitutions,
+        deletions,
+        insertions,
+        wer: (substitutions + deletions + insertions) as f64 / ref_words.len() as f64,
+        cer: char_distance(edits) as f64 / ref_chars as f64,
+        ref_chars,
+    }
+}
+
+/// Plain text of a transcript file; subtitles and JSON give their segment
+/// text, since a JSON transcript may be rendered with timestamps.
+fn read_text(path: &Path) -> Result<String> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    if matches!(ext.as_str(), "json" | "srt" | "vtt") {
+        let parsed = export::load_transcript(path)?;
+        return Ok(match parsed.segments.filter(|segs| !segs.is_empty()) {
+            Some(segments) => segments
+                .iter()
+                .map(|seg| seg.text.trim())
+                .collect::<Vec<_>>()
+                .join(" "),
+            None => parsed.transcript,
+        });
+    }
+    fs::read_to_string(path).with_context(|| format!("failed reading {}", path.display()))
+}
+
+async fn transcribe(input: &Path, args: &[String]) -> Result<String> {
+    let argv = ["parakeet", "--progress", "none", "--no-history"]
+        .into_iter()
+        .map(str::to_string)
+        .chain(["--input".to_string(), input.display().to_string()])
+        .chain(args.iter().cloned());
+    let cli = TranscribeCli::try_parse_from(argv).context("invalid transcribe flags for eval")?;
+    if !matches!(cli.format, crate::OutputFormat::Text) {
+        bail!("eval scores plain text; drop --format");
+    }
+    let (_, response) = crate::transcribe_job(cli).await?;
+    Ok(response.transcript)
+}
+
+pub fn normalize(text: &str) -> String {
+    let chars: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    chars
+        .iter()
+        .enumerate()
+        .map(|(idx, &c)| {
+            // Keep "don't" and "o'clock" whole.
+            let inner_apostrophe = matches!(c, '\'' | '\u{2019}')
+                && idx > 0
+                && chars[idx - 1].is_alphanumeric()
+                && chars
+                    .get(idx + 1)
+                    .is_some_and(|next| next.is_alphanumeric());
+            if c.is_alphanumeric() || inner_apostrophe {
+                if c == '\u{2019}' { '\'' } else { c }
+            } else {
+                ' '
+            }
+        })
+        .collect()
+}
+
+/// Minimal word alignment (Levenshtein), in reference order.
+///
+/// Runs in memory linear in the input (Hirschberg's divide and conquer over
+/// a full matrix only for small pieces), so hour-long transcripts align in
+/// megabytes; time stays quadratic in the words that differ.
+pub fn align<'a>(reference: &[&'a str], hypothesis: &[&'a str]) -> Vec<Edit<'a>> {
+    // Words as ids, so the inner loop compares integers.
+    let mut ids = HashMap::new();
+    let mut intern = |words: &[&'a str]| -> Vec<u32> {
+        words
+            .iter()
+            .map(|word| {
+                let next = ids.len() as u32;
+                *ids.entry(*word).or_insert(next)
+            })
+            .collect()
+    };
+    let (ref_ids, hyp_ids) = (intern(reference), intern(hypothesis));
+    // Common ends align as they are, and are most of a near-identical pair.
+    let prefix = ref_ids
+        .iter()
+        .zip(&hyp_ids)
+        .take_while(|(r, h)| r == h)
+        .count();
+    let suffix = ref_ids[prefix..]
+        .iter()
+        .rev()
+        .zip(hyp_ids[prefix..].iter().rev())
+        .take_while(|(r, h)| r == h)
+        .count();
+    let mut ops = vec![Op::Same; prefix];
+    hirschberg(
+        &ref_ids[prefix..ref_ids.len() - suffix],
+        &hyp_ids[prefix..hyp_ids.len() - suffix],
+        &mut ops,
+    );
+    ops.extend(std::iter::repeat_n(Op::Same, suffix));
+
+    let (mut i, mut j) = (0, 0);
+    ops.into_iter()
+        .map(|op| {
+            let edit = match op {
+                Op::Same => Edit::Same(reference[i]),
+                Op::Sub => Edit::Sub(reference[i], hypothesis[j]),
+                Op::Del => Edit::Del(reference[i]),
+                Op::Ins => Edit::Ins(hypothesis[j]),
+            };
+            i += usize::from(!matches!(op, Op::Ins));
+            j += usize::from(!matches!(op, Op::Del));
+            edit
+        })
+        .collect()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Op {
+    Same,
+    Sub,
+    Del,
+    Ins,
+}
+
+// Pieces up to this many matrix cells are aligned with the full matrix.
+const SMALL_CELLS: usize = 1 << 16;
+
+/// Splits the reference in half and the hypothesis where the two halves'
+/// costs meet, until the pieces are small enough for the full matrix.
+fn hirschberg(reference: &[u32], hypothesis: &[u32], ops: &mut Vec<Op>) {
+    let (n, m) = (reference.len(), hypothesis.len());
+    if n < 2 || (n + 1) * (m + 1) <= SMALL_CELLS {
+        backtrace(reference, hypothesis, ops);
+        return;
+    }
+    let mid = n / 2;
+    let forward = last_row(reference[..mid].iter(), hypothesis.iter());
+    let backward = last_row(reference[mid..].iter().rev(), hypothesis.iter().rev());
+    let split = (0..=m)
+        .min_by_key(|&j| forward[j] + backward[m - j])
+        .unwrap_or_default();
+    hirschberg(&reference[..mid], &hypothesis[..split], ops);
+    hirschberg(&reference[mid..], &hypothesis[split..], ops);
+}
+
+/// Full cost matrix with backtrace, for small pieces.
+fn backtrace(reference: &[u32], hypothesis: &[u32], ops: &mut Vec<Op>) {
+    let width = hypothesis.len() + 1;
+    let mut cost = vec![0u32; (reference.len() + 1) * width];
+    for (j, cell) in cost.iter_mut().take(width).enumerate() {
+        *cell = j as u32;
+    }
+    for i in 1..=reference.len() {
+        cost[i * width] = i as u32;
+        for j in 1..width {
+            let same = reference[i - 1] == hypothesis[j - 1];
+            cost[i * width + j] = (cost[(i - 1) * width + j - 1] + u32::from(!same))
+                .min(cost[(i - 1) * width + j] + 1)
+                .min(cost[i * width + j - 1] + 1);
+        }
+    }
+    let start = ops.len();
+    let (mut i, mut j) = (reference.len(), hypothesis.len());
+    while i > 0 || j > 0 {
+        let here = cost[i * width + j];
+        if i > 0 && j > 0 {
+            let same = reference[i - 1] == hypothesis[j - 1];
+            if here == cost[(i - 1) * width + j - 1] + u32::from(!same) {
+                ops.push(if same { Op::Same } else { Op::Sub });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+        }
+        if i > 0 && here == cost[(i - 1) * width + j] + 1 {
+            ops.push(Op::Del);
+            i -= 1;
+        } else {
+            ops.push(Op::Ins);
+            j -= 1;
+        }
+    }
+    ops[start..].reverse();
+}
+
+/// Costs of turning all of `a` into each prefix of `b`, in two rows.
+fn last_row<'t, T: PartialEq + 't>(
+    a: impl Iterator<Item = &'t T>,
+    b: impl Iterator<Item = &'t T> + Clone,
+) -> Vec<u32> {
+    let mut prev: Vec<u32> = (0..=b.clone().count() as u32).collect();
+    let mut row = prev.clone();
+    for (i, x) in a.enumerate() {
+        row[0] = i as u32 + 1;
+        for (j, y) in b.clone().enumerate() {
+            row[j + 1] = (prev[j] + u32::from(x != y))
+                .min(prev[j + 1] + 1)
+                .min(row[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+    prev
+}
+
+/// Character edit distance between the two texts (words joined by spaces),
+/// taken stretch by stretch along the word alignment: the words it matches
+/// cost nothing, and each run between them costs the character distance of
+/// its two sides. That is the full character distance unless a cheaper
+/// character alignment crosses a matched word, and it stays fast on long
+/// texts.
+fn char_distance(edits: &[Edit]) -> usize {
+    let has_ref = edits.iter().any(|edit| !matches!(edit, Edit::Ins(_)));
+    let has_hyp = edits.iter().any(|edit| !matches!(edit, Edit::Del(_)));
+    let mut total = 0;
+    let (mut removed, mut added): (Vec<&str>, Vec<&str>) = (Vec::new(), Vec::new());
+    for edit in edits.iter().map(Some).chain([None]) {
+        match edit {
+            Some(Edit::Sub(old, new)) => {
+                removed.push(old);
+                added.push(new);
+                continue;
+            }
+            Some(Edit::Del(old)) => {
+                removed.push(old);
+                continue;
+            }
+            Some(Edit::Ins(new)) => {
+                added.push(new);
+                continue;
+            }
+            Some(Edit::Same(_)) | None => {}
+        }
+        let old: Vec<char> = removed.join(" ").chars().collect();
+        let new: Vec<char> = added.join(" ").chars().collect();
+        total += match (old.is_empty(), new.is_empty()) {
+            (true, true) => 0,
+            // The space that separated the run from its neighbours goes too,
+            // unless the other text has no words at all.
+            (false, true) => old.len() + usize::from(has_hyp),
+            (true, false) => new.len() + usize::from(has_ref),
+            (false, false) => last_row(old.iter(), new.iter())[new.len()] as usize,
+        };
+        removed.clear();
+        added.clear();
+    }
+    total
+}
+
+pub fn use_color() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+// Word-diff markup: `[-deleted-]`, `{+inserted+}`, `[-ref-]{+hyp+}` for a
+// substitution; colored on a terminal.
+fn render_diff(edits: &[Edit], color: bool) -> String {
+    edits
+        .iter()
+        .map(|edit| match edit {
+            Edit::Same(word) => word.to_string(),
+            Edit::Sub(old, new) => paint(color, SUBSTITUTED, format!("[-{old}-]{{+{new}+}}")),
+            Edit::Del(old) => paint(color, DELETED, format!("[-{old}-]")),
+            Edit::Ins(new) => paint(color, INSERTED, format!("{{+{new}+}}")),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// ANSI colors of the diff markup.
+pub const DELETED: &str = "31";
+pub const INSERTED: &str = "32";
+pub const SUBSTITUTED: &str = "33";
+
+pub fn paint(color: bool, code: &str, text: String) -> String {
+    if color {
+        format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<&str> {
+        text.split_whitespace().collect()
+    }
+
+    fn markup(reference: &str, hypothesis: &str) -> String {
+        render_diff(&align(&words(reference), &words(hypothesis)), false)
+    }
+
+    #[test]
+    fn normalizes_case_punctuation_and_apostrophes() {
+        assert_eq!(
+            normalize("Don't STOP, it's 5 o\u{2019}clock!")
+                .split_whitespace()
+                .collect::<Vec<_>>(),
+            ["don't", "stop", "it's", "5", "o'clock"]
+        );
+        // Quotes around a word are not inner apostrophes.
+        assert_eq!(normalize("'quoted'").trim(), "quoted");
+        assert_eq!(normalize("rock 'n' roll").split_whitespace().count(), 3);
+    }
+
+    #[test]
+    fn aligns_in_reference_order() {
+        assert_eq!(markup("a b c d", "a x c d e"), "a [-b-]{+x+} c d {+e+}");
+        assert_eq!(markup("a b c d", "a d"), "a [-b-] [-c-] d");
+        assert_eq!(markup("a b", ""), "[-a-] [-b-]");
+        assert_eq!(markup("", "a b"), "{+a+} {+b+}");
+        assert_eq!(
+            markup("same words here", "same words here"),
+            "same words here"
+        );
+    }
+
+    #[test]
+    fn aligns_long_inputs_like_the_full_matrix() {
+        // Past SMALL_CELLS, so Hirschberg splits; the edit count must still be
+        // minimal.
+        let reference: Vec<String> = (0..400).map(|n| format!("w{}", n % 37)).collect();
+        let hypothesis: Vec<String> = (0..400)
+            .filter(|n| n % 11 != 0)
+            .map(|n| {
+                if n % 13 == 0 {
+                    "other".to_string()
+                } else {
+                    format!("w{}", n % 37)
+                }
+            })
+            .collect();
+        let reference: Vec<&str> = reference.iter().map(String::as_str).collect();
+        let hypothesis: Vec<&str> = hypothesis.iter().map(String::as_str).collect();
+        let edits = align(&reference, &hypothesis);
+        let errors = edits
+            .iter()
+            .filter(|edit| !matches!(edit, Edit::Same(_)))
+            .count();
+        let full = last_row(reference.iter(), hypothesis.iter())[hypothesis.len()];
+        assert_eq!(errors, full as usize);
+        let kept: Vec<&str> = edits
+            .iter()
+            .filter_map(|edit| match edit {
+                Edit::Same(word) | Edit::Sub(word, _) | Edit::Del(word) => Some(*word),
+                Edit::Ins(_) => None,
+            })
+            .collect();
+        assert_eq!(kept, reference);
+    }
+
+    #[test]
+    fn scores_word_and_character_errors() {
+        let score = score_of("a cat sat on the mat", "cat sit on the mat today");
+        assert_eq!(
+            (score.substitutions, score.deletions, score.insertions),
+            (1, 1, 1)
+        );
+        assert!((score.wer - 0.5).abs() < 1e-9);
+        // "a " dropped (2), "sat" -> "sit" (1), " today" added (6).
+        assert_eq!(score.ref_chars, 20);
+        assert!((score.cer - 9.0 / 20.0).abs() < 1e-9);
+        let full = |a: &str, b: &str| {
+            let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+            last_row(a.iter(), b.iter())[b.len()] as usize
+        };
+        assert_eq!(full("a cat sat on the mat", "cat sit on the mat today"), 9);
+
+        let score = score_of("one two", "");
+        assert_eq!(score.ref_chars, 7);
+        assert!((score.cer - 1.0).abs() < 1e-9);
+        assert!((score.wer - 1.0).abs() < 1e-9);
+    }
+
+    fn score_of(reference: &str, hypothesis: &str) -> Score {
+        score(&align(&words(reference), &words(hypothesis)))
+    }
+}
@@ -0,0 +1,123 @@
+//! `parakeet export`: re-renders a transcript made elsewhere (SRT, VTT or
+//! `--emit json` output) in another format, with the render flags
+//! (`--timestamps`, `--paragraphs`, `--split`), `--mask-profanity` and
+//! `--redact`. Translation is not offered: `--translate` runs in the model on
+//! the audio, and there is no text-to-text path to reuse.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+
+use crate::{BackendResponse, EmitMode, ExportCli, config, output, porcelain, redact, subtitle};
+
+pub fn run_export(cli: ExportCli) -> Result<()> {
+    let mut parsed = load_transcript(&cli.input)?;
+    let redact = if cli.redact.is_empty() {
+        config::load()?.redact
+    } else {
+        cli.redact.clone()
+    };
+    redact::apply(&mut parsed, &redact);
+    cli.render.mask(&mut parsed)?;
+    parsed.transcript = output::render(cli.format, &parsed, &cli.render.options());
+    parsed.format = cli.format.as_str().to_string();
+    if let Some(min) = cli.render.min_confidence {
+        parsed.warnings = output::low_confidence(parsed.segments.as_deref(), min);
+    }
+    if let Some(out) = &cli.out {
+        output::write_output(out, &parsed.transcript)?;
+        parsed.output_path = Some(out.display().to_string());
+    }
+    if cli.porcelain {
+        porcelain::enable();
+        let duration = parsed
+            .metrics
+            .as_ref()
+            .and_then(|m| m.audio_sec)
+            .or_else(|| parsed.segments.as_ref()?.last().map(|seg| seg.end));
+        let out = parsed.output_path.as_deref().unwrap_or_default();
+        porcelain::record(
+            &cli.input.display().to_string(),
+            porcelain::Status::Ok,
+            duration,
+            out,
+        );
+        return Ok(());
+    }
+
+    match cli.emit {
+        EmitMode::Text => {
+            if cli.out.is_none() {
+                print!("{}", parsed.transcript);
+                if !parsed.transcript.ends_with('\n') {
+                    println!();
+                }
+            }
+        }
+        EmitMode::Json => {
+            let json = serde_json::to_string_pretty(&parsed).context("serialize output JSON")?;
+            println!("{json}");
+        }
+    }
+    Ok(())
+}
+
+/// Loads a transcript produced elsewhere (SRT, VTT, or `--emit json` output)
+/// into the response model used by the renderers.
+pub fn load_transcript(path: &Path) -> Result<BackendResponse> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("failed reading transcript: {}", path.display()))?;
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let segments = match ext.as_str() {
+        "json" => {
+            return serde_json::from_str(&raw)
+                .with_context(|| format!("invalid transcript JSON: {}", path.display()));
+        }
+        "srt" => subtitle::parse_srt(&raw),
+        "vtt" => subtitle::parse_vtt(&raw),
+        _ => bail!(
+            "unsupported transcript input (expected .srt, .vtt or .json): {}",
+            path.display()
+        ),
+    }
+    .with_context(|| format!("failed parsing {}", path.display()))?;
+
+    let transcript = segments
+        .iter()
+        .map(|seg| seg.text.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok(BackendResponse {
+        transcript,
+        segments: Some(segments),
+        words: None,
+        alternatives: None,
+        output_path: None,
+        source: path.display().to_string(),
+        model: String::new(),
+        device: String::new(),
+        precision: None,
+        format: ext,
+        metrics: None,
+        language: None,
+        task: None,
+        language_mismatch: false,
+        skipped: false,
+        history_id: None,
+        metadata: Default::default(),
+        media_tags: Default::default(),
+        tags: Default::default(),
+        preprocessing: Vec::new(),
+        postprocessing: Vec::new(),
+        delivered_to: Vec::new(),
+        warnings: Vec::new(),
+        redactions: Default::default(),
+        summary: None,
+        chapters: Vec::new(),
+    })
+}
@@ -0,0 +1,147 @@
+//! Golden-file tests for the renderers (`cargo test --features test-fixtures`).
+//!
+//! Every canned response in `tests/fixtures/render/*.json` is rendered in every
+//! output format under each layout option set, and compared against
+//! `tests/golden/render/<fixture>/<variant>.<ext>`. After an intended output
+//! change, regenerate with `UPDATE_GOLDEN=1` and review the diff.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+
+use crate::output::{self, RenderOptions};
+use crate::{BackendResponse, OutputFormat};
+
+// Layout options only affect the text-based formats; subtitles are rendered once.
+const LAYOUTS: &[(&str, RenderOptions)] = &[
+    (
+        "plain",
+        RenderOptions {
+            timestamps: false,
+            paragraph_gap: None,
+            sentences: false,
+            min_confidence: None,
+            frontmatter: None,
+        },
+    ),
+    (
+        "sentences",
+        RenderOptions {
+            timestamps: false,
+            paragraph_gap: None,
+            sentences: true,
+            min_confidence: None,
+            frontmatter: None,
+        },
+    ),
+    (
+        "timestamps",
+        RenderOptions {
+            timestamps: true,
+            paragraph_gap: None,
+            sentences: false,
+            min_confidence: None,
+            frontmatter: None,
+        },
+    ),
+    (
+        "paragraphs",
+        RenderOptions {
+            timestamps: false,
+            paragraph_gap: Some(2.0),
+            sentences: true,
+            min_confidence: None,
+            frontmatter: None,
+        },
+    ),
+    (
+        "timestamps-paragraphs",
+        RenderOptions {
+            timestamps: true,
+            paragraph_gap: Some(2.0),
+            sentences: false,
+            min_confidence: None,
+            frontmatter: None,
+        },
+    ),
+];
+
+fn manifest_path(rel: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(rel)
+}
+
+fn fixtures() -> Vec<(String, BackendResponse)> {
+    let dir = manifest_path("tests/fixtures/render");
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap_or_else(|err| panic!("reading {}: {err}", dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let raw = fs::read_to_string(&path).unwrap();
+            let parsed = serde_json::from_str(&raw)
+                .unwrap_or_else(|err| panic!("parsing {}: {err}", path.display()));
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            (name, parsed)
+        })
+        .collect()
+}
+
+/// `(variant, rendered)` for every format/layout combination of one response.
+fn variants(parsed: &BackendResponse) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    for format in OutputFormat::value_variants() {
+        let ext = output::extension_for(*format);
+        match format {
+            OutputFormat::Srt | OutputFormat::Vtt => {
+                let rendered = output::render(*format, parsed, &LAYOUTS[0].1);
+                out.push((format!("subtitle.{ext}"), rendered));
+            }
+            OutputFormat::Text | OutputFormat::Md => {
+                for (layout, opts) in LAYOUTS {
+                    let rendered = output::render(*format, parsed, opts);
+                    out.push((format!("{layout}.{ext}"), rendered));
+                }
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn renderers_match_golden_files() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let fixtures = fixtures();
+    assert!(!fixtures.is_empty(), "no render fixtures found");
+
+    let mut mismatches = Vec::new();
+    for (name, parsed) in &fixtures {
+        for (variant, rendered) in variants(parsed) {
+            let golden = manifest_path("tests/golden/render")
+                .join(name)
+                .join(&variant);
+            if update {
+                fs::create_dir_all(golden.parent().unwrap()).unwrap();
+                fs::write(&golden, &rendered).unwrap();
+                continue;
+            }
+            match fs::read_to_string(&golden) {
+                Ok(expected) if expected == rendered => {}
+                Ok(expected) => mismatches.push(format!(
+                    "{name}/{variant} differs\n--- expected\n{expected}\n--- actual\n{rendered}"
+                )),
+                Err(_) => mismatches.push(format!("{name}/{variant}: missing golden file")),
+            }
+        }
+    }
+    assert!(
+        mismatches.is_empty(),
+        "{} rendering(s) differ from golden files (rerun with UPDATE_GOLDEN=1 if intended):\n\n{}",
+        mismatches.len(),
+        mismatches.join("\n\n")
+    );
+}
@@ -0,0 +1,226 @@
+//! `parakeet grep`: searches stored transcripts for a term and prints each
+//! match with its timecode, so the history doubles as an index of what was
+//! said in which recording and when. `--play` plays the matched stretch of
+//! the recording with ffplay.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use regex::{Regex, RegexBuilder};
+
+use crate::{BackendResponse, export, history, output, tags, tools};
+
+// Characters kept on each side of a match in transcripts without segments.
+const SNIPPET_CHARS: usize = 60;
+// Played before and after a matched segment.
+const PLAY_PAD_SEC: f64 = 1.0;
+
+#[derive(Debug, Parser)]
+pub struct GrepCli {
+    /// Term to look for (case-insensitive, literal unless --regex).
+    pattern: String,
+
+    /// Transcripts to search (`--emit json` output, .srt, .vtt) instead of
+    /// the history.
+    files: Vec<PathBuf>,
+
+    /// Read the pattern as a regular expression.
+    #[arg(long, default_value_t = false)]
+    regex: bool,
+
+    #[arg(long, short = 's', default_value_t = false)]
+    case_sensitive: bool,
+
+    /// Also print this many segments before and after each match.
+    #[arg(long, short = 'C', default_value_t = 0)]
+    context: usize,
+
+    /// Only history runs carrying this tag (repeatable; all must match).
+    #[arg(long = "tag", value_name = "KEY=VALUE", value_parser = tags::parse, conflicts_with = "files")]
+    tags: Vec<(String, String)>,
+
+    /// Play each match's stretch of the recording (ffplay), in order.
+    #[arg(long, default_value_t = false, conflicts_with = "json")]
+    play: bool,
+
+    /// One JSON object per match (`source`, `input`, `id`, `start`, `end`, `text`).
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
+
+/// A transcript to search: a history run or a transcript file.
+struct Transcript {
+    /// History id, or the file searched.
+    label: String,
+    id: Option<String>,
+    /// The recording, for `--play`.
+    input: Option<String>,
+    response: BackendResponse,
+}
+
+struct Match {
+    start: Option<f64>,
+    end: Option<f64>,
+    text: String,
+}
+
+pub fn run(cli: GrepCli) -> Result<()> {
+    let source = if cli.regex {
+        cli.pattern.clone()
+    } else {
+        regex::escape(&cli.pattern)
+    };
+    let pattern = RegexBuilder::new(&source)
+        .case_insensitive(!cli.case_sensitive)
+        .build()
+        .with_context(|| format!("invalid pattern: {}", cli.pattern))?;
+    let ffplay = if cli.play {
+        Some(tools::require(&tools::FFPLAY)?)
+    } else {
+        None
+    };
+
+    let mut found = 0;
+    for transcript in transcripts(&cli)? {
+        let matches = search(&transcript.response, &pattern, cli.context);
+        found += matches.len();
+        for hit in &matches {
+            if cli.json {
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "source": transcript.label,
+                        "input": transcript.input,
+                        "id": transcript.id,
+                        "start": hit.start,
+                        "end": hit.end,
+                        "text": hit.text,
+                    })
+                );
+                continue;
+            }
+            let clock = hit
+                .start
+                .map_or_else(|| "--:--:--".to_string(), output::format_clock);
+            println!("{}\t{clock}\t{}", transcript.label, hit.text);
+            if let Some(ffplay) = &ffplay {
+                play(ffplay, transcript.input.as_deref(), hit)?;
+            }
+        }
+    }
+    if found == 0 {
+        bail!("no transcript matches {}", cli.pattern);
+    }
+    Ok(())
+}
+
+fn transcripts(cli: &GrepCli) -> Result<Vec<Transcript>> {
+    if !cli.files.is_empty() {
+        return cli
+            .files
+            .iter()
+            .map(|path| {
+                let response = export::load_transcript(path)?;
+                // --emit json output names its recording; subtitles do not.
+                let input = (path.extension().and_then(|e| e.to_str()) == Some("json"))
+                    .then(|| response.source.clone());
+                Ok(Transcript {
+                    label: path.display().to_string(),
+                    id: response.history_id.clone(),
+                    input,
+                    response,
+                })
+            })
+            .collect();
+    }
+    Ok(history::load_all()?
+        .into_iter()
+        .filter(|entry| tags::matches(&entry.response.tags, &cli.tags))
+        .map(|entry| Transcript {
+            label: entry.id.clone(),
+            id: Some(entry.id),
+            input: Some(entry.input),
+            response: entry.response,
+        })
+        .collect())
+}
+
+fn search(response: &BackendResponse, pattern: &Regex, context: usize) -> Vec<Match> {
+    let Some(segments) = response.segments.as_ref().filter(|segs| !segs.is_empty()) else {
+        return pattern
+            .find_iter(&response.transcript)
+            .map(|found| Match {
+                start: None,
+                end: None,
+                text: snippet(&response.transcript, found.start(), found.end()),
+            })
+            .collect();
+    };
+    segments
+        .iter()
+        .enumerate()
+        .filter(|(_, seg)| pattern.is_match(&seg.text))
+        .map(|(idx, seg)| {
+            let around =
+                &segments[idx.saturating_sub(context)..(idx + context + 1).min(segments.len())];
+            Match {
+                start: Some(seg.start),
+                end: Some(seg.end),
+                text: around
+                    .iter()
+                    .map(|seg| seg.text.trim())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            }
+        })
+        .collect()
+}
+
+fn snippet(text: &str, start: usize, end: usize) -> String {
+    let before: String = text[..start]
+        .chars()
+        .rev()
+        .take(SNIPPET_CHARS)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    let after: String = text[end..].chars().take(SNIPPET_CHARS).collect();
+    let snippet = format!("{before}{}{after}", &text[start..end]);
+    let snippet = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+    let lead = if before.len() < start { "..." } else { "" };
+    let tail = if after.len() < text.len() - end {
+        "..."
+    } else {
+        ""
+    };
+    format!("{lead}{snippet}{tail}")
+}
+
+fn play(ffplay: &Path, input: Option<&str>, hit: &Match) -> Result<()> {
+    let Some(input) = input.filter(|input| Path::new(input).is_file()) else {
+        eprintln!("[parakeet] warning: recording not found; nothing to play");
+        return Ok(());
+    };
+    let (Some(start), Some(end)) = (hit.start, hit.end) else {
+        eprintln!("[parakeet] warning: {input} has no timestamps; nothing to play");
+        return Ok(());
+    };
+    let from = (start - PLAY_PAD_SEC).max(0.0);
+    let status = Command::new(ffplay)
+        .args(["-v", "error", "-nodisp", "-autoexit"])
+        .arg("-ss")
+        .arg(format!("{from:.3}"))
+        .arg("-t")
+        .arg(format!("{:.3}", end + PLAY_PAD_SEC - from))
+        .arg(input)
+        .stdin(Stdio::null())
+        .status()
+        .context("failed running ffplay")?;
+    if !status.success() {
+        bail!("ffplay failed playing {input}");
+    }
+    Ok(())
+}
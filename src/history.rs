@@ -0,0 +1,334 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{
+    BackendResponse, EmitMode, HistoryCli, HistoryCommand, Segment, archive, config, export,
+    output, retranscribe, tags, userhome,
+};
+
+// Cue times may drift this far past the recorded audio length (rounding in editors).
+const DURATION_SLACK_SEC: f64 = 0.5;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub created_at: u64,
+    #[serde(default)]
+    pub edited_at: Option<u64>,
+    pub input: String,
+    pub output_path: Option<String>,
+    /// Set once the output file was moved into an archive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived: Option<archive::ArchivedOutput>,
+    /// The run this one re-transcribed (`history retranscribe`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retranscribed_from: Option<String>,
+    /// Backend response before client-side rendering (plain transcript + segments).
+    pub response: BackendResponse,
+}
+
+pub fn history_dir() -> PathBuf {
+    userhome::dir().join("history")
+}
+
+fn entry_path(id: &str) -> PathBuf {
+    history_dir().join(format!("{id}.json"))
+}
+
+fn new_id() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("{}-{:04x}", now.as_secs(), now.subsec_nanos() & 0xffff)
+}
+
+pub fn record(
+    input: &Path,
+    response: BackendResponse,
+    output_path: Option<String>,
+) -> Result<String> {
+    let entry = HistoryEntry {
+        id: new_id(),
+        created_at: output::unix_now(),
+        edited_at: None,
+        input: input.display().to_string(),
+        // Absolute, so archiving can find the file from any working directory.
+        output_path: output_path.map(|path| {
+            std::path::absolute(&path)
+                .map(|abs| abs.display().to_string())
+                .unwrap_or(path)
+        }),
+        archived: None,
+        retranscribed_from: None,
+        response,
+    };
+    save(&entry)?;
+    Ok(entry.id)
+}
+
+pub fn save(entry: &HistoryEntry) -> Result<()> {
+    let dir = history_dir();
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("failed creating history dir: {}", dir.display()))?;
+    let path = entry_path(&entry.id);
+    let json = serde_json::to_string_pretty(entry).context("serialize history entry")?;
+    fs::write(&path, json)
+        .with_context(|| format!("failed writing history entry: {}", path.display()))
+}
+
+pub fn load(id: &str) -> Result<HistoryEntry> {
+    let path = entry_path(id);
+    if !path.exists() {
+        bail!("history entry not found: {id}");
+    }
+    let raw = fs::read_to_string(&path)
+        .with_context(|| format!("failed reading history entry: {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("invalid history entry: {}", path.display()))
+}
+
+/// All entries, oldest first.
+pub fn load_all() -> Result<Vec<HistoryEntry>> {
+    let dir = history_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    for item in fs::read_dir(&dir)? {
+        let path = item?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let raw = fs::read_to_string(&path)
+            .with_context(|| format!("failed reading history entry: {}", path.display()))?;
+        match serde_json::from_str::<HistoryEntry>(&raw) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => eprintln!(
+                "[parakeet] skipping invalid history entry {}: {err}",
+                path.display()
+            ),
+        }
+    }
+    entries.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+    Ok(entries)
+}
+
+/// Median processing seconds per audio second over recent runs.
+pub fn recent_processing_ratio(window: usize) -> Option<f64> {
+    let entries = load_all().ok()?;
+    let mut ratios: Vec<f64> = entries
+        .iter()
+        .rev()
+        .filter_map(|e| e.response.metrics.as_ref())
+        .filter_map(|m| m.audio_sec.filter(|a| *a > 0.0).map(|a| m.total_sec / a))
+        .take(window)
+        .collect();
+    if ratios.is_empty() {
+        return None;
+    }
+    ratios.sort_by(f64::total_cmp);
+    Some(ratios[ratios.len() / 2])
+}
+
+pub async fn run_history(cli: HistoryCli) -> Result<()> {
+    match cli.command {
+        HistoryCommand::List { limit, tags } => {
+            let entries: Vec<HistoryEntry> = load_all()?
+                .into_iter()
+                .filter(|entry| tags::matches(&entry.response.tags, &tags))
+                .collect();
+            let start = entries.len().saturating_sub(limit);
+            for entry in &entries[start..] {
+                let preview: String = entry.response.transcript.chars().take(60).collect();
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    entry.id,
+                    output::format_datetime(entry.created_at),
+                    entry.input,
+                    preview.replace('\n', " ")
+                );
+            }
+            Ok(())
+        }
+        HistoryCommand::Show {
+            id,
+            format,
+            render,
+            emit,
+            original,
+        } => {
+            let mut entry = load(&id)?;
+            render.mask(&mut entry.response)?;
+            if original {
+                std::io::Write::write_all(&mut std::io::stdout(), &archive::read_output(&entry)?)?;
+                return Ok(());
+            }
+            match emit {
+                EmitMode::Text => {
+                    let opts = output::RenderOptions {
+                        // Dated when transcribed, not when shown.
+                        frontmatter: render.frontmatter.then_some(entry.created_at),
+                        ..render.options()
+                    };
+                    let rendered = output::render(format, &entry.response, &opts);
+                    print!("{rendered}");
+                    if !rendered.ends_with('\n') {
+                        println!();
+                    }
+                }
+                EmitMode::Json => {
+                    let json =
+                        serde_json::to_string_pretty(&entry).context("serialize history entry")?;
+                    println!("{json}");
+                }
+            }
+            Ok(())
+        }
+        HistoryCommand::Archive {
+            older_than_days,
+            dry_run,
+            porcelain,
+        } => {
+            let Some(days) = older_than_days.or(config::load()?.archive_after_days) else {
+                bail!("pass --older-than-days or set archive_after_days in config.json");
+            };
+            if porcelain {
+                crate::porcelain::enable();
+            }
+            let count = archive::archive_older_than(days, dry_run, porcelain)?;
+            match (count, dry_run) {
+                _ if porcelain => {}
+                (0, _) => println!("no outputs older than {days} days to archive"),
+                (n, true) => println!("would archive {n} output(s)"),
+                (n, false) => println!(
+                    "archived {n} output(s) into {}",
+                    archive::archive_dir().display()
+                ),
+            }
+            Ok(())
+        }
+        HistoryCommand::Update { id, from } => {
+            let mut entry = load(&id)?;
+            let edited = export::load_transcript(&from)?;
+            let segments = edited.segments.unwrap_or_default();
+            let audio_sec = entry.response.metrics.as_ref().and_then(|m| m.audio_sec);
+            validate_timing(&segments, audio_sec)
+                .with_context(|| format!("rejected {}", from.display()))?;
+
+            entry.response.transcript = edited.transcript;
+            entry.response.segments = Some(segments);
+            // Word timings, n-best candidates and low-confidence warnings describe
+            // the replaced cues; keeping them would contradict the edit.
+            entry.response.words = None;
+            entry.response.alternatives = None;
+            entry.response.warnings.clear();
+            entry.edited_at = Some(output::unix_now());
+            save(&entry)?;
+            println!("updated history entry {id} from {}", from.display());
+            Ok(())
+        }
+        HistoryCommand::Retranscribe(cli) => retranscribe::run(cli).await,
+    }
+}
+
+/// Edited subtitles must stay monotonic, non-overlapping, and inside the recording.
+pub fn validate_timing(segments: &[Segment], audio_sec: Option<f64>) -> Result<()> {
+    if segments.is_empty() {
+        bail!("no segments");
+    }
+    for (idx, seg) in segments.iter().enumerate() {
+        if seg.start < 0.0 || seg.end < seg.start {
+            bail!(
+                "cue {} has invalid timing {:.3}-{:.3}",
+                idx + 1,
+                seg.start,
+                seg.end
+            );
+        }
+        if idx > 0 && seg.start < segments[idx - 1].end {
+            bail!(
+                "cue {} starts at {:.3} before cue {} ends at {:.3}",
+                idx + 1,
+                seg.start,
+                idx,
+                segments[idx - 1].end
+            );
+        }
+        if let Some(limit) = audio_sec
+            && seg.end > limit + DURATION_SLACK_SEC
+        {
+            bail!(
+                "cue {} ends at {:.3} past the recording length {:.3}",
+                idx + 1,
+                seg.end,
+                limit
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cues(spec: &[(f64, f64)]) -> Vec<Segment> {
+        spec.iter()
+            .map(|&(start, end)| Segment {
+                start,
+                end,
+                text: "cue".to_string(),
+                confidence: None,
+                speaker: None,
+            })
+            .collect()
+    }
+
+    fn rejection(spec: &[(f64, f64)], audio_sec: Option<f64>) -> String {
+        validate_timing(&cues(spec), audio_sec)
+            .expect_err("timing should be rejected")
+            .to_string()
+    }
+
+    #[test]
+    fn accepts_ordered_cues_within_the_recording() {
+        // Touching cues are fine; so is ending inside the slack.
+        assert!(validate_timing(&cues(&[(0.0, 2.0), (2.0, 4.0), (5.0, 10.4)]), Some(10.0)).is_ok());
+        assert!(validate_timing(&cues(&[(0.0, 2.0)]), None).is_ok());
+    }
+
+    #[test]
+    fn rejects_overlapping_cues() {
+        assert_eq!(
+            rejection(&[(0.0, 2.5), (2.0, 4.0)], None),
+            "cue 2 starts at 2.000 before cue 1 ends at 2.500"
+        );
+    }
+
+    #[test]
+    fn rejects_non_monotonic_cues() {
+        assert_eq!(
+            rejection(&[(5.0, 6.0), (1.0, 2.0)], None),
+            "cue 2 starts at 1.000 before cue 1 ends at 6.000"
+        );
+        assert_eq!(
+            rejection(&[(3.0, 1.0)], None),
+            "cue 1 has invalid timing 3.000-1.000"
+        );
+        assert_eq!(
+            rejection(&[(-1.0, 1.0)], None),
+            "cue 1 has invalid timing -1.000-1.000"
+        );
+    }
+
+    #[test]
+    fn rejects_cues_past_the_duration_slack() {
+        assert_eq!(
+            rejection(&[(0.0, 2.0), (8.0, 10.6)], Some(10.0)),
+            "cue 2 ends at 10.600 past the recording length 10.000"
+        );
+        assert_eq!(rejection(&[], Some(10.0)), "no segments");
+    }
+}
@@ -0,0 +1,109 @@
+//! External commands around a transcription, run by `sh -c`, for steps that
+//! do not belong in the crate:
+//!
+//! - `--pre-hook "cmd"` gets the input path as `$1` and may print a
+//!   replacement path (denoised, trimmed, ...), e.g. a file it wrote under
+//!   `$PARAKEET_WORKDIR`, which is removed with the job.
+//! - `--post-hook "cmd"` gets the response JSON on stdin and prints the final
+//!   transcript (LLM polishing, house formatting, ...). Plain stdout replaces
+//!   the transcript. A JSON object with `transcript` (and optionally
+//!   `segments`) replaces those fields instead, so a hook that keeps the
+//!   timings can keep subtitles in step with the text.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{BackendResponse, PostprocessStep, Segment};
+
+const VERSION: u32 = 1;
+
+#[derive(serde::Deserialize)]
+struct HookOutput {
+    transcript: String,
+    segments: Option<Vec<Segment>>,
+}
+
+/// Runs the pre-hook on `input`; returns the path it printed, or None when it
+/// printed nothing (keep the input as is).
+pub fn pre(command: &str, input: &Path, workdir: &Path) -> Result<Option<PathBuf>> {
+    let output = Command::new("sh")
+        .args(["-c", command, "sh"])
+        .arg(input)
+        .env("PARAKEET_WORKDIR", workdir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .with_context(|| format!("failed running pre-hook: {command}"))?;
+    if !output.status.success() {
+        bail!("pre-hook {command:?} failed ({})", output.status);
+    }
+    let stdout = String::from_utf8(output.stdout).context("pre-hook printed invalid UTF-8")?;
+    let Some(line) = stdout.lines().map(str::trim).rfind(|line| !line.is_empty()) else {
+        return Ok(None);
+    };
+    let path = PathBuf::from(line);
+    if !path.is_file() {
+        bail!("pre-hook {command:?} printed {line:?}, which is not a file");
+    }
+    Ok(Some(path))
+}
+
+pub fn post(parsed: &mut BackendResponse, command: &str) -> Result<()> {
+    let input = serde_json::to_vec(&*parsed)?;
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed starting post-hook: {command}"))?;
+    // A hook that exits without reading stdin is fine; its output decides.
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(&input);
+    }
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("failed running post-hook: {command}"))?;
+    if !output.status.success() {
+        bail!(
+            "post-hook {command:?} failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let stdout = String::from_utf8(output.stdout).context("post-hook printed invalid UTF-8")?;
+    let text = stdout.trim();
+    if text.is_empty() {
+        bail!("post-hook {command:?} printed nothing");
+    }
+    let before = parsed.transcript.clone();
+    let mut segments_replaced = false;
+    match serde_json::from_str::<HookOutput>(text) {
+        Ok(hook) => {
+            parsed.transcript = hook.transcript.trim().to_string();
+            if let Some(segments) = hook.segments {
+                parsed.segments = Some(segments);
+                segments_replaced = true;
+            }
+        }
+        Err(_) => parsed.transcript = text.to_string(),
+    }
+    let changed = parsed.transcript != before;
+    // Word timings no longer line up with rewritten text.
+    if changed {
+        parsed.words = None;
+    }
+    parsed.postprocessing.push(PostprocessStep::new(
+        "post_hook",
+        VERSION,
+        serde_json::json!({
+            "command": command,
+            "changed": changed,
+            "segments_replaced": segments_replaced,
+        }),
+    ));
+    Ok(())
+}
@@ -0,0 +1,953 @@
+//! Number formatting of the final text (`--numbers`, `--itn`), done here
+//! rather than in the backend so the result doesn't depend on the NeMo
+//! version or external engine that produced the transcript.
+//!
+//! `digits` turns spoken numbers into numerals ("twenty five percent" ->
+//! "25%", "nineteen eighty four" -> "1984"), leaving single words below ten
+//! alone ("one of them"). `words` spells numerals out ("$5" -> "five
+//! dollars"), and `--itn off` also spells the symbols `&`, `+`, `=` and `@`,
+//! giving the spoken form.
+//!
+//! Dates follow the same direction: "March fifteenth twenty twenty four" ->
+//! "March 15, 2024" as digits, and "March 15" or "2024-03-15" -> "March
+//! fifteenth[, twenty twenty-four]" as words.
+
+use clap::ValueEnum;
+
+use crate::{BackendResponse, PostprocessStep};
+
+const VERSION: u32 = 1;
+
+#[derive(Copy, Clone, Debug, Default, ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Numbers {
+    /// Leave numbers as the model wrote them.
+    #[default]
+    Keep,
+    Digits,
+    Words,
+}
+
+#[derive(Copy, Clone, Debug, Default, ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Itn {
+    /// Written form, as the model produces it.
+    #[default]
+    On,
+    /// Spoken form: numbers and symbols spelled out.
+    Off,
+}
+
+#[derive(Copy, Clone)]
+enum Mode {
+    Digits,
+    Words,
+    Spoken,
+}
+
+impl Numbers {
+    fn as_str(self) -> &'static str {
+        match self {
+            Numbers::Keep => "keep",
+            Numbers::Digits => "digits",
+            Numbers::Words => "words",
+        }
+    }
+}
+
+impl Itn {
+    fn as_str(self) -> &'static str {
+        match self {
+            Itn::On => "on",
+            Itn::Off => "off",
+        }
+    }
+}
+
+/// Rewrites the transcript, segments and alternatives; word timings keep the
+/// model's tokens.
+pub fn apply(parsed: &mut BackendResponse, numbers: Numbers, itn: Itn) {
+    let mode = match (itn, numbers) {
+        (Itn::Off, _) => Mode::Spoken,
+        (Itn::On, Numbers::Keep) => return,
+        (Itn::On, Numbers::Digits) => Mode::Digits,
+        (Itn::On, Numbers::Words) => Mode::Words,
+    };
+    parsed.transcript = normalize(&parsed.transcript, mode);
+    for seg in parsed.segments.iter_mut().flatten() {
+        seg.text = normalize(&seg.text, mode);
+    }
+    for alt in parsed.alternatives.iter_mut().flatten() {
+        alt.text = normalize(&alt.text, mode);
+    }
+    parsed.postprocessing.push(PostprocessStep::new(
+        "itn",
+        VERSION,
+        serde_json::json!({ "numbers": numbers.as_str(), "itn": itn.as_str() }),
+    ));
+}
+
+fn normalize(text: &str, mode: Mode) -> String {
+    text.split('\n')
+        .map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match mode {
+                Mode::Digits => to_digits(&tokens),
+                Mode::Words | Mode::Spoken => to_words(&tokens, matches!(mode, Mode::Spoken)),
+            }
+            .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits `"(twenty,"` into `("(", "twenty", ",")`.
+fn split_punct(token: &str) -> (&str, &str, &str) {
+    let keep =
+        |c: char| c.is_alphanumeric() || matches!(c, '$' | '€' | '£' | '%' | '&' | '+' | '=' | '@');
+    let start = token.find(keep).unwrap_or(token.len());
+    let end = token.rfind(keep).map_or(start, |idx| {
+        idx + token[idx..].chars().next().map_or(0, char::len_utf8)
+    });
+    (&token[..start], &token[start..end], &token[end..])
+}
+
+// ---- words -> digits ----
+
+#[derive(Copy, Clone, PartialEq)]
+enum Kind {
+    Unit(u64),
+    Teen(u64),
+    Tens(u64),
+    Hundred,
+    Scale(u64),
+}
+
+const UNITS: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const TEENS: &[&str] = &[
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: &[&str] = &[
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const SCALES: &[(&str, u64)] = &[
+    ("thousand", 1_000),
+    ("million", 1_000_000),
+    ("billion", 1_000_000_000),
+    ("trillion", 1_000_000_000_000),
+];
+// `cardinal` names three digits per scale, so this is the first number it
+// cannot say; longer numerals (card and account numbers) stay digits.
+const MAX_SPELLED: u64 = 1_000_000_000_000_000;
+const MONTHS: &[&str] = &[
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const UNIT_ORDINALS: &[&str] = &[
+    "zeroth", "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth",
+];
+const TEEN_ORDINALS: &[&str] = &[
+    "tenth",
+    "eleventh",
+    "twelfth",
+    "thirteenth",
+    "fourteenth",
+    "fifteenth",
+    "sixteenth",
+    "seventeenth",
+    "eighteenth",
+    "nineteenth",
+];
+const TENS_ORDINALS: &[&str] = &[
+    "",
+    "",
+    "twentieth",
+    "thirtieth",
+    "fortieth",
+    "fiftieth",
+    "sixtieth",
+    "seventieth",
+    "eightieth",
+    "ninetieth",
+];
+
+fn position(list: &[&str], word: &str) -> Option<u64> {
+    list.iter()
+        .position(|w| !w.is_empty() && *w == word)
+        .map(|idx| idx as u64)
+}
+
+/// Kind of a number word and whether it is an ordinal.
+fn kind(word: &str) -> Option<(Kind, bool)> {
+    if let Some(n) = position(UNITS, word) {
+        return Some((Kind::Unit(n), false));
+    }
+    if let Some(n) = position(TEENS, word) {
+        return Some((Kind::Teen(10 + n), false));
+    }
+    if let Some(n) = position(TENS, word) {
+        return Some((Kind::Tens(n * 10), false));
+    }
+    if word == "hundred" {
+        return Some((Kind::Hundred, false));
+    }
+    if let Some((_, scale)) = SCALES.iter().find(|(name, _)| *name == word) {
+        return Some((Kind::Scale(*scale), false));
+    }
+    if let Some(n) = position(UNIT_ORDINALS, word) {
+        return Some((Kind::Unit(n), true));
+    }
+    if let Some(n) = position(TEEN_ORDINALS, word) {
+        return Some((Kind::Teen(10 + n), true));
+    }
+    if let Some(n) = position(TENS_ORDINALS, word) {
+        return Some((Kind::Tens(n * 10), true));
+    }
+    if word == "hundredth" {
+        return Some((Kind::Hundred, true));
+    }
+    SCALES
+        .iter()
+        .find(|(name, _)| word.strip_suffix("th") == Some(name))
+        .map(|(_, scale)| (Kind::Scale(*scale), true))
+}
+
+/// One number being read from consecutive words.
+#[derive(Clone, Default)]
+struct Reading {
+    total: u64,
+    current: u64,
+    last: Option<Kind>,
+    // Smallest scale used so far; a following scale must be smaller.
+    scale: Option<u64>,
+    words: usize,
+    ordinal: bool,
+    decimals: String,
+    // Two-digit pair ("nineteen", "eighty four") when the number may be a year.
+    simple: bool,
+}
+
+impl Reading {
+    fn accepts(&self, kind: Kind) -> bool {
+        if self.ordinal || !self.decimals.is_empty() {
+            return false;
+        }
+        match (self.last, kind) {
+            (None, Kind::Hundred | Kind::Scale(_)) => false,
+            (None, _) => true,
+            (Some(Kind::Tens(_)), Kind::Unit(n)) => n > 0,
+            (
+                Some(Kind::Hundred | Kind::Scale(_)),
+                Kind::Unit(_) | Kind::Teen(_) | Kind::Tens(_),
+            ) => true,
+            (Some(Kind::Unit(_) | Kind::Teen(_) | Kind::Tens(_)), Kind::Hundred) => {
+                self.current < 100
+            }
+            (Some(_), Kind::Scale(scale)) => {
+                self.current > 0 && self.scale.is_none_or(|prev| scale < prev)
+            }
+            _ => false,
+        }
+    }
+
+    fn push(&mut self, kind: Kind, ordinal: bool) {
+        match kind {
+            Kind::Unit(n) | Kind::Teen(n) | Kind::Tens(n) => self.current += n,
+            Kind::Hundred => self.current *= 100,
+            Kind::Scale(scale) => {
+                self.total += self.current * scale;
+                self.current = 0;
+                self.scale = Some(scale);
+            }
+        }
+        self.simple =
+            self.words == 0 || matches!((self.last, kind), (Some(Kind::Tens(_)), Kind::Unit(_)));
+        self.simple &= !matches!(kind, Kind::Hundred | Kind::Scale(_));
+        self.last = Some(kind);
+        self.words += 1;
+        self.ordinal = ordinal;
+    }
+
+    fn value(&self) -> u64 {
+        self.total + self.current
+    }
+
+    /// A lone word below ten ("one of them") reads better as a word.
+    fn is_small_word(&self) -> bool {
+        self.words == 1 && self.decimals.is_empty() && self.value() < 10
+    }
+
+    fn render(&self) -> String {
+        let value = self.value();
+        let mut out = group_thousands(value);
+        if !self.decimals.is_empty() {
+            out = format!("{value}.{}", self.decimals);
+        }
+        if self.ordinal {
+            out.push_str(ordinal_suffix(value));
+        }
+        out
+    }
+}
+
+fn group_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    // Four-digit numbers stay ungrouped, so years and "1500" read naturally.
+    if digits.len() <= 4 {
+        return digits;
+    }
+    let mut out = String::new();
+    for (idx, ch) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+fn ordinal_suffix(value: u64) -> &'static str {
+    match (value % 10, value % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+/// Number words of a token ("twenty-five" -> ["twenty", "five"]), if all are.
+fn number_parts(core: &str) -> Option<Vec<(Kind, bool)>> {
+    if core.is_empty() {
+        return None;
+    }
+    let lower = core.to_lowercase();
+    lower.split('-').map(kind).collect()
+}
+
+fn to_digits(tokens: &[&str]) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut idx = 0;
+    while idx < tokens.len() {
+        match read_date(tokens, idx).or_else(|| read_number(tokens, idx)) {
+            Some((text, used)) => {
+                out.push(text);
+                idx += used;
+            }
+            None => {
+                out.push(tokens[idx].to_string());
+                idx += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Reads the number starting at `tokens[start]`, with a following year half,
+/// decimal part and unit; returns its rendering and the tokens consumed.
+fn read_number(tokens: &[&str], start: usize) -> Option<(String, usize)> {
+    let (lead, _, _) = split_punct(tokens[start]);
+    let (mut reading, mut end, mut trail) = read_cardinal(tokens, start)?;
+
+    if let Some((year, year_end, year_trail)) = year_pair(&reading, tokens, end, trail) {
+        return Some((format!("{lead}{year}{year_trail}"), year_end - start));
+    }
+
+    // "three point one four"
+    if trail.is_empty()
+        && !reading.ordinal
+        && tokens
+            .get(end)
+            .is_some_and(|t| t.eq_ignore_ascii_case("point"))
+    {
+        let mut digits = String::new();
+        let mut cursor = end + 1;
+        let mut digits_trail = "";
+        while let Some(token) = tokens.get(cursor) {
+            let (pre, core, post) = split_punct(token);
+            let Some(n) = pre
+                .is_empty()
+                .then(|| position(UNITS, &core.to_lowercase()))
+                .flatten()
+            else {
+                break;
+            };
+            digits.push_str(&n.to_string());
+            cursor += 1;
+            digits_trail = post;
+            if !post.is_empty() {
+                break;
+            }
+        }
+        if !digits.is_empty() {
+            reading.decimals = digits;
+            end = cursor;
+            trail = digits_trail;
+        }
+    }
+
+    let unit = trail
+        .is_empty()
+        .then(|| with_unit(&reading, tokens, end, lead))
+        .flatten();
+    if let Some((text, used)) = unit {
+        return Some((text, used - start));
+    }
+    // Without a unit ("five percent"), lone words below ten stay words.
+    if reading.is_small_word() {
+        return None;
+    }
+    Some((format!("{lead}{}{trail}", reading.render()), end - start))
+}
+
+/// "nineteen eighty four", "twenty twenty four": a first two-digit half
+/// followed by a second; the year, the index after it and its punctuation.
+fn year_pair<'a>(
+    first: &Reading,
+    tokens: &[&'a str],
+    end: usize,
+    trail: &str,
+) -> Option<(u64, usize, &'a str)> {
+    if !trail.is_empty() || !first.simple || first.ordinal || !(11..=20).contains(&first.value()) {
+        return None;
+    }
+    let (second, second_end, second_trail) = read_cardinal(tokens, end)?;
+    (second.simple && !second.ordinal && (10..=99).contains(&second.value())).then_some((
+        first.value() * 100 + second.value(),
+        second_end,
+        second_trail,
+    ))
+}
+
+/// A year from `start`, as two halves or as a cardinal ("two thousand five").
+fn read_year<'a>(tokens: &[&'a str], start: usize) -> Option<(u64, usize, &'a str)> {
+    if !split_punct(tokens.get(start)?).0.is_empty() {
+        return None;
+    }
+    let (reading, end, trail) = read_cardinal(tokens, start)?;
+    if let Some(year) = year_pair(&reading, tokens, end, trail) {
+        return Some(year);
+    }
+    (!reading.ordinal && (1000..=2099).contains(&reading.value())).then_some((
+        reading.value(),
+        end,
+        trail,
+    ))
+}
+
+/// "March fifteenth [twenty twenty four]" -> "March 15[, 2024]": the day
+/// drops its ordinal suffix and a following year is set off by a comma. A
+/// cardinal day ("May one") only counts with a year after it.
+fn read_date(tokens: &[&str], start: usize) -> Option<(String, usize)> {
+    let (_, month, month_post) = split_punct(tokens[start]);
+    if !month_post.is_empty() || !is_month(month) {
+        return None;
+    }
+    if !split_punct(tokens.get(start + 1)?).0.is_empty() {
+        return None;
+    }
+    let (day, day_end, day_trail) = read_cardinal(tokens, start + 1)?;
+    if !(1..=31).contains(&day.value()) {
+        return None;
+    }
+    let year = day_trail
+        .is_empty()
+        .then(|| read_year(tokens, day_end))
+        .flatten();
+    let date = format!("{} {}", tokens[start], day.value());
+    match year {
+        Some((year, end, trail)) => Some((format!("{date}, {year}{trail}"), end - start)),
+        None if day.ordinal => Some((format!("{date}{day_trail}"), day_end - start)),
+        None => None,
+    }
+}
+
+/// Cardinal/ordinal words from `start`: the reading, the index after it and
+/// the punctuation that ended it (a comma or period closes the number).
+fn read_cardinal<'a>(tokens: &[&'a str], start: usize) -> Option<(Reading, usize, &'a str)> {
+    let mut reading = Reading::default();
+    let mut idx = start;
+    let mut trail = "";
+    while let Some(token) = tokens.get(idx) {
+        let (pre, core, post) = split_punct(token);
+        if idx > start && !pre.is_empty() {
+            break;
+        }
+        let lower = core.to_lowercase();
+        // "one hundred and five"
+        if lower == "and"
+            && post.is_empty()
+            && matches!(reading.last, Some(Kind::Hundred | Kind::Scale(_)))
+            && !reading.ordinal
+        {
+            let next_fits = tokens
+                .get(idx + 1)
+                .and_then(|next| number_parts(split_punct(next).1))
+                .is_some_and(|parts| {
+                    matches!(parts[0].0, Kind::Unit(_) | Kind::Teen(_) | Kind::Tens(_))
+                });
+            if next_fits {
+                idx += 1;
+                continue;
+            }
+            break;
+        }
+        let Some(parts) = number_parts(core) else {
+            break;
+        };
+        // All parts of a hyphenated token go in, or none.
+        let mut trial = reading.clone();
+        let mut fits = true;
+        for &(kind, ordinal) in &parts {
+            if !trial.accepts(kind) {
+                fits = false;
+                break;
+            }
+            trial.push(kind, ordinal);
+        }
+        if !fits {
+            break;
+        }
+        reading = trial;
+        idx += 1;
+        if !post.is_empty() {
+            trail = post;
+            break;
+        }
+    }
+    (reading.words > 0).then_some((reading, idx, trail))
+}
+
+/// "N percent", "N dollars [and M cents]", "N euros".
+fn with_unit(
+    reading: &Reading,
+    tokens: &[&str],
+    end: usize,
+    lead: &str,
+) -> Option<(String, usize)> {
+    if reading.ordinal {
+        return None;
+    }
+    let (pre, core, post) = split_punct(tokens.get(end)?);
+    if !pre.is_empty() {
+        return None;
+    }
+    let amount = reading.render();
+    let symbol = match core.to_lowercase().as_str() {
+        "percent" => return Some((format!("{lead}{amount}%{post}"), end + 1)),
+        "dollar" | "dollars" => "$",
+        "euro" | "euros" => "€",
+        _ => return None,
+    };
+    // "five dollars and fifty cents"
+    if post.is_empty()
+        && reading.decimals.is_empty()
+        && tokens
+            .get(end + 1)
+            .is_some_and(|t| t.eq_ignore_ascii_case("and"))
+        && let Some((cents, cents_end, _)) = read_cardinal(tokens, end + 2)
+        && cents.value() < 100
+        && !cents.ordinal
+        && let Some(unit) = tokens.get(cents_end)
+        && let (unit_pre, unit_core, unit_post) = split_punct(unit)
+        && unit_pre.is_empty()
+        && matches!(unit_core.to_lowercase().as_str(), "cent" | "cents")
+    {
+        return Some((
+            format!("{lead}{symbol}{amount}.{:02}{unit_post}", cents.value()),
+            cents_end + 1,
+        ));
+    }
+    Some((format!("{lead}{symbol}{amount}{post}"), end + 1))
+}
+
+// ---- digits -> words ----
+
+fn to_words(tokens: &[&str], spoken: bool) -> Vec<String> {
+    tokens
+        .iter()
+        .enumerate()
+        .map(|(idx, token)| {
+            let (pre, core, post) = split_punct(token);
+            if spoken && let Some(word) = symbol_word(core) {
+                return format!("{pre}{word}{post}");
+            }
+            // "March 15" -> "March fifteenth"
+            let after_month = idx > 0 && {
+                let (_, prev, prev_post) = split_punct(tokens[idx - 1]);
+                prev_post.is_empty() && is_month(prev)
+            };
+            if after_month
+                && pre.is_empty()
+                && let Some(day) = day_words(core)
+            {
+                return format!("{day}{post}");
+            }
+            let Some(words) = spell(core) else {
+                return token.to_string();
+            };
+            match pre.strip_suffix('-') {
+                Some(pre) => format!("{pre}minus {words}{post}"),
+                None => format!("{pre}{words}{post}"),
+            }
+        })
+        .collect()
+}
+
+fn symbol_word(core: &str) -> Option<&'static str> {
+    Some(match core {
+        "&" => "and",
+        "+" => "plus",
+        "=" => "equals",
+        "@" => "at",
+        "%" => "percent",
+        _ => return None,
+    })
+}
+
+/// Spoken form of a numeric token: "$5.50", "25%", "21st", "1984", "10:30",
+/// "3.14", "1,000", "2024-03-15". Anything else (e.g. "mp3", "COVID-19") and
+/// numbers past the largest scale are None.
+fn spell(core: &str) -> Option<String> {
+    if let Some((hours, minutes)) = core.split_once(':') {
+        return spell_time(hours, minutes);
+    }
+    if let Some(date) = spell_date(core) {
+        return Some(date);
+    }
+    let (currency, core) = match core.chars().next()? {
+        '$' => (Some(("dollar", "dollars")), &core[1..]),
+        '€' => (Some(("euro", "euros")), &core['€'.len_utf8()..]),
+        '£' => (Some(("pound", "pounds")), &core['£'.len_utf8()..]),
+        _ => (None, core),
+    };
+    let (core, suffix) = ["%", "st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| core.strip_suffix(suffix).map(|rest| (rest, *suffix)))
+        .unwrap_or((core, ""));
+    let (int_part, frac_part) = match core.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (core, None),
+    };
+    let grouped = int_part.contains(',');
+    let digits: String = int_part.chars().filter(|c| *c != ',').collect();
+    if digits.is_empty()
+        || !digits.chars().all(|c| c.is_ascii_digit())
+        || (grouped && !valid_grouping(int_part))
+        || frac_part.is_some_and(|f| f.is_empty() || !f.chars().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+    let value: u64 = digits.parse().ok().filter(|value| *value < MAX_SPELLED)?;
+    let ordinal = matches!(suffix, "st" | "nd" | "rd" | "th");
+    if ordinal {
+        if frac_part.is_some() || currency.is_some() || ordinal_suffix(value) != suffix {
+            return None;
+        }
+        return Some(ordinal_words(value));
+    }
+    let mut words = if currency.is_none() && suffix.is_empty() && frac_part.is_none() && !grouped {
+        year_words(&digits).unwrap_or_else(|| cardinal(value))
+    } else {
+        cardinal(value)
+    };
+    match (currency, frac_part) {
+        (Some((one, many)), frac) => {
+            words.push(' ');
+            words.push_str(if value == 1 { one } else { many });
+            if let Some(frac) = frac {
+                let cents: u64 = format!("{frac:0<2}")[..2].parse().ok()?;
+                if cents > 0 {
+                    words.push_str(&format!(
+                        " and {} cent{}",
+                        cardinal(cents),
+                        if cents == 1 { "" } else { "s" }
+                    ));
+                }
+            }
+        }
+        (None, Some(frac)) => {
+            words.push_str(" point");
+            for digit in frac.chars() {
+                words.push(' ');
+                words.push_str(UNITS[digit.to_digit(10)? as usize]);
+            }
+        }
+        (None, None) => {}
+    }
+    if suffix == "%" {
+        words.push_str(" percent");
+    }
+    Some(words)
+}
+
+fn is_month(word: &str) -> bool {
+    word.starts_with(char::is_uppercase) && MONTHS.iter().any(|m| m.eq_ignore_ascii_case(word))
+}
+
+/// A day of the month after a month name: "15" or "15th" -> "fifteenth".
+fn day_words(core: &str) -> Option<String> {
+    let (digits, suffix) = ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| core.strip_suffix(suffix).map(|rest| (rest, *suffix)))
+        .unwrap_or((core, ""));
+    if digits.is_empty() || digits.len() > 2 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let day: u64 = digits.parse().ok().filter(|day| (1..=31).contains(day))?;
+    if !suffix.is_empty() && ordinal_suffix(day) != suffix {
+        return None;
+    }
+    Some(ordinal_words(day))
+}
+
+/// "2024-03-15" -> "March fifteenth, twenty twenty-four".
+fn spell_date(core: &str) -> Option<String> {
+    let mut parts = core.split('-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some()
+        || (year.len(), month.len(), day.len()) != (4, 2, 2)
+        || !core.chars().all(|c| c.is_ascii_digit() || c == '-')
+    {
+        return None;
+    }
+    let month: usize = month.parse().ok().filter(|m| (1..=12).contains(m))?;
+    let day: u64 = day.parse().ok().filter(|d| (1..=31).contains(d))?;
+    let year = match year_words(year) {
+        Some(words) => words,
+        None => cardinal(year.parse().ok()?),
+    };
+    Some(format!(
+        "{} {}, {year}",
+        MONTHS[month - 1],
+        ordinal_words(day)
+    ))
+}
+
+fn valid_grouping(int_part: &str) -> bool {
+    let mut groups = int_part.split(',');
+    let first = groups.next().unwrap_or_default();
+    (1..=3).contains(&first.len()) && groups.all(|group| group.len() == 3)
+}
+
+fn spell_time(hours: &str, minutes: &str) -> Option<String> {
+    let h: u64 = hours.parse().ok().filter(|h| *h <= 24)?;
+    if minutes.len() != 2 || !hours.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let m: u64 = minutes.parse().ok().filter(|m| *m < 60)?;
+    Some(match m {
+        0 => format!("{} o'clock", cardinal(h)),
+        1..=9 => format!("{} oh {}", cardinal(h), cardinal(m)),
+        _ => format!("{} {}", cardinal(h), cardinal(m)),
+    })
+}
+
+/// "1984" -> "nineteen eighty-four", "2024" -> "twenty twenty-four",
+/// "1905" -> "nineteen oh five", "1900" -> "nineteen hundred". Other values
+/// (including 2000-2009, said as "two thousand five") are None.
+fn year_words(digits: &str) -> Option<String> {
+    if digits.len() != 4 || digits.starts_with('0') {
+        return None;
+    }
+    let value: u64 = digits.parse().ok()?;
+    if !(1100..=2099).contains(&value) || (2000..2010).contains(&value) {
+        return None;
+    }
+    let (high, low) = (value / 100, value % 100);
+    Some(match low {
+        0 => format!("{} hundred", cardinal(high)),
+        1..=9 => format!("{} oh {}", cardinal(high), cardinal(low)),
+        _ => format!("{} {}", cardinal(high), cardinal(low)),
+    })
+}
+
+fn cardinal(value: u64) -> String {
+    if value == 0 {
+        return "zero".to_string();
+    }
+    let mut parts = Vec::new();
+    let mut rest = value;
+    for (name, scale) in SCALES.iter().rev() {
+        if rest >= *scale {
+            parts.push(format!("{} {name}", below_thousand(rest / scale)));
+            rest %= scale;
+        }
+    }
+    if rest > 0 {
+        parts.push(below_thousand(rest));
+    }
+    parts.join(" ")
+}
+
+fn below_thousand(value: u64) -> String {
+    let (hundreds, rest) = (value / 100, value % 100);
+    let mut out = Vec::new();
+    if hundreds > 0 {
+        out.push(format!("{} hundred", UNITS[hundreds as usize]));
+    }
+    if rest > 0 {
+        out.push(below_hundred(rest));
+    }
+    out.join(" ")
+}
+
+fn below_hundred(value: u64) -> String {
+    match value {
+        0..=9 => UNITS[value as usize].to_string(),
+        10..=19 => TEENS[value as usize - 10].to_string(),
+        _ if value.is_multiple_of(10) => TENS[value as usize / 10].to_string(),
+        _ => format!(
+            "{}-{}",
+            TENS[value as usize / 10],
+            UNITS[value as usize % 10]
+        ),
+    }
+}
+
+fn ordinal_words(value: u64) -> String {
+    let words = cardinal(value);
+    // Only the last word takes the ordinal form: "twenty-first", "one hundredth".
+    let split = words.rfind([' ', '-']).map_or(0, |idx| idx + 1);
+    let (head, last) = words.split_at(split);
+    let last = position(UNITS, last)
+        .map(|n| UNIT_ORDINALS[n as usize].to_string())
+        .or_else(|| position(TEENS, last).map(|n| TEEN_ORDINALS[n as usize].to_string()))
+        .or_else(|| position(TENS, last).map(|n| TENS_ORDINALS[n as usize].to_string()))
+        .unwrap_or_else(|| format!("{last}th"));
+    format!("{head}{last}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(text: &str) -> String {
+        normalize(text, Mode::Digits)
+    }
+
+    fn words(text: &str) -> String {
+        normalize(text, Mode::Words)
+    }
+
+    #[test]
+    fn spells_numerals() {
+        assert_eq!(spell("25%").as_deref(), Some("twenty-five percent"));
+        assert_eq!(
+            spell("$5.50").as_deref(),
+            Some("five dollars and fifty cents")
+        );
+        assert_eq!(spell("$1").as_deref(), Some("one dollar"));
+        assert_eq!(spell("3.14").as_deref(), Some("three point one four"));
+        assert_eq!(spell("1,000").as_deref(), Some("one thousand"));
+        assert_eq!(spell("10:05").as_deref(), Some("ten oh five"));
+        assert_eq!(
+            spell("999999999999999").as_deref(),
+            Some(
+                "nine hundred ninety-nine trillion nine hundred ninety-nine billion \
+                 nine hundred ninety-nine million nine hundred ninety-nine thousand \
+                 nine hundred ninety-nine"
+            )
+        );
+        assert_eq!(spell("mp3"), None);
+        assert_eq!(spell("1,00"), None);
+    }
+
+    #[test]
+    fn long_numerals_stay_digits() {
+        assert_eq!(spell("1000000000000000"), None);
+        assert_eq!(spell("4111111111111111"), None);
+        assert_eq!(spell("4111111111111111th"), None);
+        assert_eq!(
+            words("card 4111111111111111 please"),
+            "card 4111111111111111 please"
+        );
+    }
+
+    #[test]
+    fn spells_ordinals() {
+        assert_eq!(spell("21st").as_deref(), Some("twenty-first"));
+        assert_eq!(spell("12th").as_deref(), Some("twelfth"));
+        assert_eq!(spell("100th").as_deref(), Some("one hundredth"));
+        assert_eq!(spell("11st"), None);
+        assert_eq!(digits("the twenty first floor"), "the 21st floor");
+        assert_eq!(digits("his one hundredth run"), "his 100th run");
+    }
+
+    #[test]
+    fn spells_years() {
+        assert_eq!(year_words("1984").as_deref(), Some("nineteen eighty-four"));
+        assert_eq!(year_words("2024").as_deref(), Some("twenty twenty-four"));
+        assert_eq!(year_words("1905").as_deref(), Some("nineteen oh five"));
+        assert_eq!(year_words("1900").as_deref(), Some("nineteen hundred"));
+        assert_eq!(year_words("2005"), None);
+        assert_eq!(year_words("0984"), None);
+        assert_eq!(spell("2005").as_deref(), Some("two thousand five"));
+    }
+
+    #[test]
+    fn reads_numbers() {
+        let read = |text: &str| {
+            let tokens: Vec<&str> = text.split_whitespace().collect();
+            read_number(&tokens, 0)
+        };
+        assert_eq!(read("twenty five percent"), Some(("25%".to_string(), 3)));
+        assert_eq!(read("nineteen eighty four"), Some(("1984".to_string(), 3)));
+        assert_eq!(
+            read("one hundred and five people"),
+            Some(("105".to_string(), 4))
+        );
+        assert_eq!(
+            read("five dollars and fifty cents."),
+            Some(("$5.50.".to_string(), 5))
+        );
+        assert_eq!(read("three point one four"), Some(("3.14".to_string(), 4)));
+        assert_eq!(
+            read("two million three hundred thousand"),
+            Some(("2,300,000".to_string(), 5))
+        );
+        assert_eq!(read("one of them"), None);
+        assert_eq!(read("hello"), None);
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(digits("on March fifteenth."), "on March 15.");
+        assert_eq!(
+            digits("on March fifteenth twenty twenty four"),
+            "on March 15, 2024"
+        );
+        assert_eq!(digits("May one twenty twenty"), "May 1, 2020");
+        assert_eq!(digits("May one of you"), "May one of you");
+        assert_eq!(
+            words("on March 15, 2024"),
+            "on March fifteenth, twenty twenty-four"
+        );
+        assert_eq!(
+            words("due 2024-03-05"),
+            "due March fifth, twenty twenty-four"
+        );
+        assert_eq!(words("due 2024-13-05"), "due 2024-13-05");
+    }
+}
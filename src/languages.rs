@@ -0,0 +1,54 @@
+//! Languages of the known NeMo checkpoints, for validating `--language`, and
+//! the `language_models` routing table from config.json.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Result, bail};
+
+// Parakeet TDT 0.6B v3 and Canary 1B v2: 25 European languages.
+const EUROPEAN: &[&str] = &[
+    "bg", "cs", "da", "de", "el", "en", "es", "et", "fi", "fr", "hr", "hu", "it", "lt", "lv", "mt",
+    "nl", "pl", "pt", "ro", "ru", "sk", "sl", "sv", "uk",
+];
+const CANARY_V1: &[&str] = &["de", "en", "es", "fr"];
+const ENGLISH: &[&str] = &["en"];
+
+/// Languages `model` transcribes, or None when the model is not known
+/// (local .nemo files, other checkpoints), which then goes unchecked.
+pub fn supported(model: &str) -> Option<&'static [&'static str]> {
+    let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    match name.as_str() {
+        // A local checkpoint's name says nothing reliable about its training data.
+        _ if name.ends_with(".nemo") => None,
+        "parakeet-tdt-0.6b-v3" | "canary-1b-v2" => Some(EUROPEAN),
+        "canary-1b" | "canary-1b-flash" | "canary-180m-flash" => Some(CANARY_V1),
+        _ if name.starts_with("parakeet-") && name.ends_with("-ja") => Some(&["ja"]),
+        _ if name.starts_with("parakeet-") => Some(ENGLISH),
+        _ => None,
+    }
+}
+
+/// Whether `model` can translate speech to English (`--translate`): the
+/// Canary multitask models can; unknown models are left to the backend.
+pub fn translates(model: &str) -> bool {
+    let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    supported(model).is_none() || name.starts_with("canary-")
+}
+
+/// Model configured for `language` in `language_models`; `*` catches the
+/// languages without their own entry.
+pub fn route(routes: &BTreeMap<String, String>, language: &str) -> Option<String> {
+    routes.get(language).or_else(|| routes.get("*")).cloned()
+}
+
+/// Fails when `model` is known not to transcribe `language`.
+pub fn check(model: &str, language: &str) -> Result<()> {
+    match supported(model) {
+        Some(langs) if !langs.contains(&language) => bail!(
+            "{model} does not transcribe language {language} (supported: {}); pick another --model or \
+             route the language in language_models (config.json)",
+            langs.join(", ")
+        ),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,175 @@
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use regex::Regex;
+
+use crate::output;
+
+const FOLLOW_POLL: Duration = Duration::from_millis(250);
+// The daemon prefixes every line it writes with `YYYY-MM-DD HH:MM:SS ` (UTC).
+const STAMP_LEN: usize = 19;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+/// Line filters for `daemon logs`. Continuation lines (indented traceback
+/// frames, wrapped output) take the timestamp and level of the line above.
+pub struct Filter {
+    since: Option<u64>,
+    pattern: Option<Regex>,
+    level: Option<Level>,
+    // Carried across lines (and polls, when following).
+    last_stamp: Option<u64>,
+    last_level: Level,
+}
+
+impl Filter {
+    pub fn new(since_sec: Option<u64>, grep: Option<&str>, level: Option<Level>) -> Result<Self> {
+        let pattern = grep
+            .map(|raw| Regex::new(raw).with_context(|| format!("invalid --grep pattern: {raw}")))
+            .transpose()?;
+        Ok(Self {
+            since: since_sec.map(|sec| output::unix_now().saturating_sub(sec)),
+            pattern,
+            level,
+            last_stamp: None,
+            last_level: Level::Info,
+        })
+    }
+
+    fn matches(&mut self, line: &str) -> bool {
+        let (stamp, body) = split_stamp(line);
+        let continuation = body.starts_with([' ', '\t']);
+        if stamp.is_some() {
+            self.last_stamp = stamp;
+        }
+        if !continuation {
+            self.last_level = classify(body);
+        }
+        // Lines from before timestamping have no known age; --since drops them.
+        if let Some(since) = self.since
+            && self.last_stamp.is_none_or(|stamp| stamp < since)
+        {
+            return false;
+        }
+        if let Some(level) = self.level
+            && self.last_level < level
+        {
+            return false;
+        }
+        self.pattern.as_ref().is_none_or(|re| re.is_match(line))
+    }
+}
+
+fn split_stamp(line: &str) -> (Option<u64>, &str) {
+    match line.get(..STAMP_LEN).and_then(output::parse_datetime) {
+        Some(stamp) => (
+            Some(stamp),
+            line[STAMP_LEN..].strip_prefix(' ').unwrap_or(""),
+        ),
+        None => (None, line),
+    }
+}
+
+fn classify(body: &str) -> Level {
+    let lower = body.to_ascii_lowercase();
+    if body.starts_with("[NeMo E")
+        || ["error", "traceback", "exception", "out of memory", "failed"]
+            .iter()
+            .any(|needle| lower.contains(needle))
+    {
+        Level::Error
+    } else if body.starts_with("[NeMo W") || lower.contains("warn") {
+        Level::Warn
+    } else {
+        Level::Info
+    }
+}
+
+/// `30s`, `10m`, `2h`, `1d` or plain seconds.
+pub fn parse_since(raw: &str) -> std::result::Result<u64, String> {
+    let (digits, unit) = match raw.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => raw.split_at(idx),
+        None => (raw, "s"),
+    };
+    let value: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid duration: {raw} (expected e.g. 30s, 10m, 2h, 1d)"))?;
+    let scale = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        _ => return Err(format!("invalid duration unit in {raw} (use s, m, h or d)")),
+    };
+    Ok(value * scale)
+}
+
+/// Prints the last `lines` matching lines, then with `follow` keeps printing
+/// matching lines as they are appended, until killed.
+pub fn show(logfile: &Path, lines: usize, follow: bool, mut filter: Filter) -> Result<()> {
+    if follow && !logfile.exists() {
+        eprintln!("[parakeet] waiting for {}", logfile.display());
+        while !logfile.exists() {
+            std::thread::sleep(FOLLOW_POLL);
+        }
+    }
+    let content = fs::read(logfile)
+        .with_context(|| format!("failed reading logfile: {}", logfile.display()))?;
+    let text = String::from_utf8_lossy(&content);
+    let matching: Vec<&str> = text.lines().filter(|line| filter.matches(line)).collect();
+    let start = matching.len().saturating_sub(lines);
+    for line in &matching[start..] {
+        println!("{line}");
+    }
+    if follow {
+        follow_log(logfile, content.len() as u64, &mut filter)?;
+    }
+    Ok(())
+}
+
+/// Polls the logfile and prints matching lines appended past `pos`. A file
+/// that shrinks (truncated or replaced by a new daemon) is re-read from the
+/// start.
+fn follow_log(logfile: &Path, mut pos: u64, filter: &mut Filter) -> Result<()> {
+    let mut out = std::io::stdout();
+    let mut partial = Vec::new();
+    loop {
+        std::thread::sleep(FOLLOW_POLL);
+        let Ok(mut file) = fs::File::open(logfile) else {
+            continue;
+        };
+        let len = file.metadata()?.len();
+        if len < pos {
+            pos = 0;
+            partial.clear();
+        }
+        if len == pos {
+            continue;
+        }
+        file.seek(SeekFrom::Start(pos))?;
+        let mut chunk = Vec::new();
+        file.read_to_end(&mut chunk)?;
+        pos += chunk.len() as u64;
+        partial.extend_from_slice(&chunk);
+        // Only complete lines can be filtered; keep the tail for the next poll.
+        let Some(end) = partial.iter().rposition(|b| *b == b'\n') else {
+            continue;
+        };
+        let complete: Vec<u8> = partial.drain(..=end).collect();
+        for line in String::from_utf8_lossy(&complete).lines() {
+            if filter.matches(line) {
+                writeln!(out, "{line}")?;
+            }
+        }
+        out.flush()?;
+    }
+}
@@ -1,16 +1,81 @@
-use std::io::{BufRead, BufReader as StdBufReader, Write};
-use std::os::unix::net::UnixStream;
-use std::os::unix::process::CommandExt;
+use std::io::{BufRead, BufReader as StdBufReader, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use std::time::Duration;
-use std::{collections::BTreeSet, fs};
+use std::time::{Duration, Instant};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+};
 
 use anyhow::{Context, Result, anyhow, bail};
 use clap::{Parser, Subcommand, ValueEnum};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 
+use crate::error::{ErrorKind, ExitCode, Failure};
+use crate::protocol::MessageType;
+use crate::sink::Sink;
+
+mod archive;
+mod autosave;
+mod bench;
+mod calendar;
+mod channels;
+mod chapters;
+mod chunk;
+mod clips;
+mod concat;
+mod config;
+mod dictation;
+mod diff;
+mod engine;
+mod enhance;
+mod error;
+mod eval;
+mod export;
+#[cfg(all(test, feature = "test-fixtures"))]
+mod golden;
+mod grep;
+mod history;
+mod hooks;
+mod itn;
+mod languages;
+mod logs;
+mod media;
+mod metadata;
+mod music;
+mod notify;
+mod obsidian;
+mod output;
+mod porcelain;
+mod preflight;
+mod profanity;
+mod progress;
+mod protocol;
+mod quality;
+mod redact;
+mod refine;
+mod remote;
+mod resample;
+mod retranscribe;
+mod rules;
+mod service;
+mod sink;
+mod stdio;
+mod stitch;
+mod subtitle;
+mod summarize;
+#[cfg(unix)]
+mod supervisor;
+mod tags;
+mod tools;
+mod transport;
+mod twopass;
+mod userhome;
+mod vad;
+mod voicemail;
+mod workdir;
+
 #[derive(Debug, Parser)]
 #[command(name = "parakeet")]
 #[command(about = "Fast local transcription CLI using NVIDIA Parakeet")]
@@ -24,9 +89,60 @@ struct TranscribeCli {
     #[arg(long)]
     model: Option<String>,
 
-    #[arg(long, default_value = "auto")]
+    /// Preset for model, chunking, decoding and vocab postprocessing;
+    /// explicit flags override it. Without it (or a config default) the
+    /// `balanced` settings apply.
+    #[arg(long, value_enum)]
+    quality: Option<quality::Quality>,
+
+    /// Where inference runs: the local NeMo backend (default) or an external
+    /// HTTP transcription API. Defaults to `engine` from config.json.
+    #[arg(long, value_enum)]
+    engine: Option<engine::Engine>,
+
+    /// Endpoint for `--engine openai-api|generic-http` (OpenAI's by default).
+    #[arg(long)]
+    engine_url: Option<String>,
+
+    /// Beam width for decoding; 1 is greedy.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    beam_size: Option<u32>,
+
+    /// Return the N best candidate transcripts with their scores as
+    /// `alternatives` (needs --emit json; raises --beam-size to at least N).
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    nbest: Option<u32>,
+
+    /// Length penalty for beam search (needs --beam-size 2 or more).
+    #[arg(long, allow_negative_numbers = true)]
+    len_penalty: Option<f64>,
+
+    /// Softmax temperature for decoding (1.0 leaves the scores unchanged).
+    #[arg(long)]
+    temperature: Option<f64>,
+
+    /// Sets any option of the model's decoding config by dotted path, e.g.
+    /// `--backend-arg greedy.max_symbols=10` (repeatable). With an external
+    /// `--engine` these become extra form fields of the upload.
+    #[arg(long = "backend-arg", value_name = "KEY=VALUE", value_parser = parse_backend_arg)]
+    backend_args: Vec<(String, String)>,
+
+    /// `auto`, `cpu`, `cuda` or a specific GPU as `cuda:N` (see `doctor` for
+    /// the indices visible here).
+    #[arg(long, default_value = "auto", value_parser = parse_device)]
     device: String,
 
+    /// Weight precision: `auto` keeps the model's fp32 weights; fp16 and bf16
+    /// (Ampere or newer) halve VRAM on a GPU, int8 quantizes for CPU.
+    #[arg(long, value_enum, default_value_t = Precision::Auto)]
+    precision: Precision,
+
+    /// Load the model from a pickled snapshot of its initialized state when
+    /// one matches the checkpoint, torch and NeMo versions; otherwise load it
+    /// normally and write the snapshot for next time.
+    #[arg(long)]
+    fast_start: bool,
+
     #[arg(long)]
     vocab: Option<PathBuf>,
 
@@ -36,23 +152,381 @@ struct TranscribeCli {
     #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
     format: OutputFormat,
 
-    #[arg(long, default_value_t = false)]
-    timestamps: bool,
+    #[command(flatten)]
+    render: RenderArgs,
 
     #[arg(long, default_value_t = false)]
     no_fuzzy_vocab: bool,
 
+    #[arg(long, default_value_t = false, conflicts_with = "no_fuzzy_vocab")]
+    fuzzy_vocab: bool,
+
     #[arg(long, default_value_t = false)]
     verbose: bool,
 
     #[arg(long, value_enum, default_value_t = EmitMode::Text)]
     emit: EmitMode,
 
+    /// Print one tab-separated status line per file instead (path, status,
+    /// duration, output); see `porcelain`.
+    #[arg(long, conflicts_with = "emit")]
+    porcelain: bool,
+
     #[arg(long)]
     daemon_socket: Option<PathBuf>,
 
     #[arg(long, default_value_t = false)]
     no_daemon: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with = "no_daemon")]
+    require_daemon: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with = "no_daemon")]
+    auto_daemon: bool,
+
+    #[arg(long, value_parser = parse_time_arg)]
+    daemon_timeout: Option<f64>,
+
+    #[arg(long, value_parser = parse_time_arg)]
+    daemon_write_timeout: Option<f64>,
+
+    #[arg(long)]
+    daemon_retries: Option<u32>,
+
+    #[arg(long, value_parser = parse_time_arg)]
+    timeout: Option<f64>,
+
+    /// TOML file of ordered regex replacements for the final text (see
+    /// `rules`), e.g. to fix a term the model keeps mishearing
+    #[arg(long, value_name = "FILE")]
+    rules: Option<PathBuf>,
+
+    /// Shell command that gets the input path as `$1` and may print a
+    /// replacement audio file to transcribe instead (denoised, trimmed, ...)
+    #[arg(long, value_name = "CMD")]
+    pre_hook: Option<String>,
+
+    /// Shell command that gets the response JSON on stdin and prints the
+    /// final transcript (or a JSON object with `transcript`/`segments`)
+    #[arg(long, value_name = "CMD")]
+    post_hook: Option<String>,
+
+    /// Replace personal data with placeholders in every output and in
+    /// history, e.g. `--redact emails,phones` or `--redact all`
+    #[arg(long, value_enum, value_delimiter = ',')]
+    redact: Vec<redact::Pii>,
+
+    /// Ask an LLM (`summarize` in config.json) for a summary and action
+    /// items, appended to md output and returned as `summary` in JSON
+    #[arg(long)]
+    summarize: bool,
+
+    /// Apply spoken commands in dictated audio ("comma", "new line",
+    /// "scratch that"; more in `dictation_commands`)
+    #[arg(long, default_value_t = false)]
+    dictation: bool,
+
+    /// Number style of the final text: as the model wrote it, numerals, or
+    /// spelled out
+    #[arg(long, value_enum, default_value_t = itn::Numbers::Keep)]
+    numbers: itn::Numbers,
+
+    /// `off` gives the spoken form: numbers, currency and symbols in words
+    #[arg(long, value_enum, default_value_t = itn::Itn::On)]
+    itn: itn::Itn,
+
+    /// Translate the speech to English (Canary models, `--engine openai-api`
+    /// or a generic engine that handles `task=translate`)
+    #[arg(long, default_value_t = false)]
+    translate: bool,
+
+    /// `auto` detects the spoken language on the first 20 seconds; a code
+    /// (e.g. `de`) is the expected language, warned about when detection disagrees
+    #[arg(long, value_parser = parse_language)]
+    language: Option<String>,
+
+    /// Skip (or flag) the input unless it is in this language. The local
+    /// backend can tell apart en, de, fr, es, it, pt, nl, pl, uk and ru
+    #[arg(long, value_parser = parse_language)]
+    only_language: Option<String>,
+
+    #[arg(long, value_enum, default_value_t = LanguageMismatch::Skip)]
+    language_mismatch: LanguageMismatch,
+
+    #[arg(long, default_value_t = false)]
+    auto_name: bool,
+
+    #[arg(long)]
+    name_template: Option<String>,
+
+    /// When the output file already exists, replace it only if more than PCT
+    /// percent of its words change; otherwise keep it and write `<file>.new`.
+    /// Name templates then target the existing name instead of `-2` variants.
+    #[arg(long, value_name = "PCT", value_parser = parse_percent)]
+    diff_guard: Option<f64>,
+
+    /// Extra destination for the rendered output (repeatable): stdout,
+    /// file:PATH, s3://..., gs://..., webhook:URL. A trailing `/` on an
+    /// object-store URL appends the output name.
+    #[arg(long = "sink")]
+    sinks: Vec<String>,
+
+    #[arg(long, default_value_t = false)]
+    no_history: bool,
+
+    /// Keep the job's working directory under `$PARAKEET_HOME/tmp` if the job fails.
+    #[arg(long, default_value_t = false)]
+    keep_workdir: bool,
+
+    #[arg(long, value_parser = parse_time_arg)]
+    start: Option<f64>,
+
+    #[arg(long, value_parser = parse_time_arg, conflicts_with = "duration")]
+    end: Option<f64>,
+
+    #[arg(long, value_parser = parse_time_arg)]
+    duration: Option<f64>,
+
+    #[arg(long)]
+    chunk_minutes: Option<f64>,
+
+    /// Seconds shared by neighbouring chunks [default: 10].
+    #[arg(long)]
+    chunk_overlap: Option<f64>,
+
+    /// Chunks sent to the daemon at once [default: its --workers count].
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    chunk_parallel: Option<u32>,
+
+    /// With --chunk-minutes, keep PATH.json and PATH.txt updated with the
+    /// transcript so far after every finished chunk; a crash loses at most
+    /// the chunks in flight, so shorter chunks mean finer saves.
+    #[arg(long)]
+    autosave: Option<PathBuf>,
+
+    /// With --chunk-minutes, rewrite PATH with the last --caption-words words
+    /// after every chunk, as a caption source for OBS (`.vtt`: one cue)
+    #[arg(long, value_name = "PATH")]
+    caption_file: Option<PathBuf>,
+
+    #[arg(long, default_value_t = 12, value_parser = clap::value_parser!(u64).range(1..))]
+    caption_words: u64,
+
+    /// Detect speech first and transcribe only those regions, skipping long
+    /// silences; timestamps keep their original offsets. Detection is an
+    /// energy threshold (12 dB over the recording's noise floor), not a
+    /// trained model: steady loud noise or music counts as speech, so pair
+    /// it with --skip-music for such recordings
+    #[arg(long, default_value_t = false)]
+    vad: bool,
+
+    /// Silences shorter than this many seconds stay in a speech region [default: 2]
+    #[arg(long, requires = "vad")]
+    vad_min_silence: Option<f64>,
+
+    /// Leave music-only regions (intros, jingles, hold music) out of the
+    /// transcription and mark them `[music]`
+    #[arg(long, default_value_t = false)]
+    skip_music: bool,
+
+    /// Transcribe one channel of a multi-channel recording: left, right, a
+    /// channel number (from 1), or mix (the default downmix)
+    #[arg(long, value_parser = channels::parse_channel, conflicts_with = "split_channels")]
+    channel: Option<channels::Channel>,
+
+    /// Clean up background noise (hum, fans, keyboards) with ffmpeg before
+    /// transcribing
+    #[arg(long, default_value_t = false)]
+    denoise: bool,
+
+    /// RNNoise model (.rnnn) for --denoise instead of the FFT denoiser
+    #[arg(long, value_name = "PATH", requires = "denoise")]
+    denoise_model: Option<PathBuf>,
+
+    /// Normalize loudness (EBU R128) before transcribing; helps very quiet
+    /// recordings
+    #[arg(long, default_value_t = false)]
+    normalize_audio: bool,
+
+    /// Decode and resample the input to 16 kHz mono WAV in-process
+    /// (symphonia + rubato) instead of leaving it to the backend and ffmpeg
+    #[arg(long, default_value_t = false, conflicts_with = "split_channels")]
+    resample: bool,
+
+    /// Transcribe FILE after --input as one continuous transcript (repeatable),
+    /// for recordings split into parts
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "start", "end", "duration", "channel", "split_channels", "denoise",
+            "normalize_audio", "resample", "refine", "nbest",
+        ]
+    )]
+    concat: Vec<PathBuf>,
+
+    /// Follow the input's chapter marks (m4b/m4a audiobooks): a `## Title`
+    /// section per chapter in md, one output file per chapter otherwise
+    #[arg(long, default_value_t = false, conflicts_with = "concat")]
+    by_chapter: bool,
+
+    /// Cut the input into one WAV per segment in DIR, named by index and
+    /// time range, each with a .txt of its text
+    #[arg(long, value_name = "DIR", conflicts_with = "concat")]
+    export_clips: Option<PathBuf>,
+
+    /// Clip per segment or per speaker turn (--split-channels)
+    #[arg(long, value_enum, default_value_t = clips::ClipUnit::Segment, requires = "export_clips")]
+    clip_by: clips::ClipUnit,
+
+    /// Stereo call recordings with one party per channel: transcribe each
+    /// channel on its own and merge the turns by time, labelled per channel
+    #[arg(long, default_value_t = false)]
+    split_channels: bool,
+
+    /// Labels for the left and right channel [default: Speaker A,Speaker B]
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "LEFT,RIGHT",
+        requires = "split_channels"
+    )]
+    channel_labels: Vec<String>,
+
+    #[arg(long, default_value_t = false)]
+    two_pass: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with = "two_pass")]
+    no_two_pass: bool,
+
+    /// Re-transcribe the segments scoring below --refine-below with MODEL and
+    /// splice the result into the first pass
+    #[arg(long, value_name = "MODEL")]
+    refine: Option<String>,
+
+    /// Confidence (0-1) below which --refine re-transcribes a segment
+    #[arg(long, value_parser = parse_confidence, default_value_t = 0.6, requires = "refine")]
+    refine_below: f64,
+
+    #[arg(long, value_enum, default_value_t = ProgressMode::Auto)]
+    progress: ProgressMode,
+
+    #[arg(long)]
+    meta: Option<PathBuf>,
+
+    /// ICS file or URL; label the transcript with the meeting overlapping the
+    /// recording (`{meta.meeting}`, `{meta.meeting_attendees}`, ...).
+    #[arg(long, value_name = "FILE|URL")]
+    calendar: Option<String>,
+
+    /// Local start time of the recording (`YYYY-MM-DD HH:MM[:SS]`) for
+    /// --calendar, when the file's own timestamps are wrong.
+    #[arg(long, value_parser = calendar::parse_local_time)]
+    recorded_at: Option<u64>,
+
+    /// Label the run, e.g. `--tag project=alpha --tag speaker=ceo` (repeatable).
+    /// Stored in history and counted per tag by the daemon.
+    #[arg(long = "tag", value_name = "KEY=VALUE", value_parser = tags::parse)]
+    tags: Vec<(String, String)>,
+
+    // Original s3:// or gs:// URL when `input` points at the downloaded copy.
+    #[arg(skip)]
+    remote_source: Option<String>,
+
+    /// Show a desktop notification with the outcome and run time when done
+    #[arg(long, default_value_t = false)]
+    notify: bool,
+
+    /// POST a completion event (JSON result, or the error) to this URL when
+    /// the transcription finishes or fails
+    #[arg(long, value_name = "URL")]
+    webhook: Option<String>,
+
+    /// File the md transcript as a note in this Obsidian vault, with
+    /// frontmatter, a daily-note backlink and timestamped headings
+    #[arg(long, value_name = "PATH")]
+    obsidian_vault: Option<PathBuf>,
+
+    // Vault folder for --obsidian-vault notes (`obsidian_folder` in config.json).
+    #[arg(skip)]
+    obsidian_folder: String,
+
+    // Env var holding the engine's API key (`engine_api_key_env` in config.json).
+    #[arg(skip)]
+    engine_api_key_env: Option<String>,
+
+    #[arg(skip)]
+    metadata: metadata::Metadata,
+
+    // Set for jobs submitted over `serve-stdio`: progress goes to stdout as
+    // JSON-RPC notifications tagged with this job id.
+    #[arg(skip)]
+    rpc_job: Option<u64>,
+}
+
+#[derive(Debug, clap::Args)]
+struct RenderArgs {
+    #[arg(long, default_value_t = false)]
+    timestamps: bool,
+
+    #[arg(long, default_value_t = false)]
+    paragraphs: bool,
+
+    #[arg(long, default_value_t = 1.5)]
+    paragraph_gap: f64,
+
+    #[arg(long, value_enum, default_value_t = SplitMode::None)]
+    split: SplitMode,
+
+    /// Mark segments whose confidence is below this (0-1): highlighted in md
+    /// output, listed under `warnings` in JSON
+    #[arg(long, value_parser = parse_confidence)]
+    min_confidence: Option<f64>,
+
+    /// Replace profanity with asterisks (built-in list plus `profanity_words`
+    /// in config.json)
+    #[arg(long, default_value_t = false)]
+    mask_profanity: bool,
+
+    /// More words to mask, one per line; implies --mask-profanity
+    #[arg(long, value_name = "FILE")]
+    profanity_list: Option<PathBuf>,
+
+    /// Start md output with YAML frontmatter (source, date, duration, model,
+    /// language, tags) for notes tools such as Obsidian
+    #[arg(long, default_value_t = false)]
+    frontmatter: bool,
+}
+
+impl RenderArgs {
+    fn options(&self) -> output::RenderOptions {
+        output::RenderOptions {
+            timestamps: self.timestamps,
+            paragraph_gap: self.paragraphs.then_some(self.paragraph_gap),
+            sentences: matches!(self.split, SplitMode::Sentences),
+            min_confidence: self.min_confidence,
+            frontmatter: self.frontmatter.then(output::unix_now),
+        }
+    }
+
+    fn needs_segments(&self) -> bool {
+        self.timestamps || self.paragraphs || self.min_confidence.is_some()
+    }
+
+    fn masker(&self) -> Result<Option<profanity::Masker>> {
+        if !self.mask_profanity && self.profanity_list.is_none() {
+            return Ok(None);
+        }
+        profanity::Masker::new(self.profanity_list.as_deref()).map(Some)
+    }
+
+    /// Masks profanity in what is about to be emitted (`--mask-profanity`).
+    fn mask(&self, parsed: &mut BackendResponse) -> Result<()> {
+        if let Some(masker) = self.masker()? {
+            masker.apply(parsed);
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Parser)]
@@ -64,8 +538,154 @@ struct RootCli {
 
 #[derive(Debug, Subcommand)]
 enum RootCommand {
-    Transcribe(TranscribeCli),
+    Transcribe(Box<TranscribeCli>),
     Daemon(DaemonCli),
+    Export(ExportCli),
+    History(HistoryCli),
+    Doctor(DoctorCli),
+    /// Load models into the daemon (starting it if needed) and run a warm-up
+    /// inference, so the next transcription skips the model-load wait.
+    Warm(WarmCli),
+    /// Speak line-delimited JSON-RPC on stdin/stdout, for GUI frontends.
+    ServeStdio,
+    /// Poll an IMAP mailbox for voicemail audio attachments, transcribe them
+    /// and file the transcripts (or reply with them); see `voicemail` in config.json.
+    Voicemail(VoicemailCli),
+    /// Search stored transcripts (or the given ones) for a term and print the
+    /// matches with their timecodes.
+    Grep(grep::GrepCli),
+    /// Score a transcript against a reference: word and character error
+    /// rates, with the word alignment as a diff.
+    Eval(eval::EvalCli),
+    /// Align two transcripts of the same audio and show where their words
+    /// differ, with timecodes.
+    Diff(diff::DiffCli),
+    /// Time models and precisions on a sample file (cold and warm): load,
+    /// inference, real-time factor and peak GPU memory.
+    Bench(bench::BenchCli),
+}
+
+#[derive(Debug, Parser)]
+struct VoicemailCli {
+    /// Check the mailbox once and exit instead of polling.
+    #[arg(long)]
+    once: bool,
+
+    /// Time between checks, e.g. `5m` [default: `voicemail.interval_sec`, else 5m].
+    #[arg(long, value_parser = logs::parse_since)]
+    interval: Option<u64>,
+
+    /// Mailbox URL such as `imaps://mail.example.com/INBOX`, overriding
+    /// `voicemail.imap_url`.
+    #[arg(long)]
+    imap_url: Option<String>,
+
+    /// Print one tab-separated status line per file instead (path, status,
+    /// duration, output); see `porcelain`.
+    #[arg(long)]
+    porcelain: bool,
+}
+
+#[derive(Debug, Parser)]
+struct WarmCli {
+    /// Model to load and warm up (repeatable) [default: the daemon's model].
+    #[arg(long = "model", value_name = "NAME")]
+    models: Vec<String>,
+
+    #[arg(long)]
+    socket: Option<PathBuf>,
+
+    /// Seconds to wait for loading and the warm-up inference.
+    #[arg(long, default_value_t = 300.0)]
+    timeout: f64,
+}
+
+#[derive(Debug, Parser)]
+struct DoctorCli {
+    #[arg(long, default_value = "auto", value_parser = parse_device)]
+    device: String,
+
+    /// Print the report as JSON (host, optional tools, verdict).
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
+
+#[derive(Debug, Parser)]
+struct HistoryCli {
+    #[command(subcommand)]
+    command: HistoryCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum HistoryCommand {
+    List {
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Only runs carrying this tag (repeatable; all must match).
+        #[arg(long = "tag", value_name = "KEY=VALUE", value_parser = tags::parse)]
+        tags: Vec<(String, String)>,
+    },
+    Show {
+        id: String,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+        #[command(flatten)]
+        render: RenderArgs,
+        #[arg(long, value_enum, default_value_t = EmitMode::Text)]
+        emit: EmitMode,
+        /// Print the output file exactly as written, extracting it from its
+        /// archive if it was archived.
+        #[arg(long, default_value_t = false, conflicts_with_all = ["format", "emit"])]
+        original: bool,
+    },
+    /// Moves output files of runs older than N days into a tar.zst under
+    /// `$PARAKEET_HOME/archive`; `history show --original` still reads them.
+    Archive {
+        /// Defaults to `archive_after_days` from config.json.
+        #[arg(long)]
+        older_than_days: Option<u32>,
+        /// List what would be archived without touching anything.
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// One status line per output file (see `porcelain`).
+        #[arg(long)]
+        porcelain: bool,
+    },
+    Update {
+        id: String,
+        #[arg(long)]
+        from: PathBuf,
+    },
+    /// Re-runs past inputs with another model, e.g. after a better checkpoint
+    /// ships, keeping the old runs for comparison.
+    Retranscribe(retranscribe::RetranscribeCli),
+}
+
+#[derive(Debug, Parser)]
+struct ExportCli {
+    #[arg(long, short = 'i')]
+    input: PathBuf,
+
+    #[arg(long, short = 'o')]
+    out: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    #[command(flatten)]
+    render: RenderArgs,
+
+    /// Replace personal data with placeholders, as for transcription
+    /// (default: `redact` in config.json)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    redact: Vec<redact::Pii>,
+
+    #[arg(long, value_enum, default_value_t = EmitMode::Text)]
+    emit: EmitMode,
+
+    /// One status line for the written file (needs --out); see `porcelain`.
+    #[arg(long, requires = "out", conflicts_with = "emit")]
+    porcelain: bool,
 }
 
 #[derive(Debug, Parser)]
@@ -74,6 +694,117 @@ struct DaemonCli {
     command: DaemonCommand,
 }
 
+/// How `daemon serve` runs the backend; `start` passes these on and records
+/// them for `restart`. Unset ones fall back to config.json in `daemon serve`.
+#[derive(Clone, Debug, Default, clap::Args, serde::Deserialize, serde::Serialize)]
+struct BackendArgs {
+    /// Unload the model after this long without requests, e.g. `15m`, freeing
+    /// its (GPU) memory; the next request loads it again.
+    #[arg(long, value_parser = logs::parse_since)]
+    #[serde(default, rename = "idle_unload_sec")]
+    idle_unload: Option<u64>,
+    /// Requests run in parallel, each worker with its own copy of the model,
+    /// spread round-robin over the visible GPUs (default 1).
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    #[serde(default)]
+    workers: Option<u32>,
+    /// Run this many workers on every visible GPU (1 = one per GPU) instead
+    /// of a fixed `--workers` count; queued jobs go to whichever is idle.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "workers")]
+    #[serde(default)]
+    workers_per_gpu: Option<u32>,
+    /// Load this model (repeatable) before accepting requests and run a
+    /// warm-up inference, so the first request after boot is fast.
+    #[arg(long = "preload-model", value_name = "NAME")]
+    #[serde(default, rename = "preload_models")]
+    preload_model: Vec<String>,
+    /// Models each worker keeps loaded at once, selected per request by
+    /// `--model`; the least recently used one is unloaded first (default 2).
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    #[serde(default)]
+    max_models: Option<u32>,
+    /// Unload least recently used models to keep the daemon's allocated GPU
+    /// memory under this many MiB per GPU.
+    #[arg(long, value_name = "MIB", value_parser = clap::value_parser!(u64).range(1..))]
+    #[serde(default)]
+    vram_budget_mb: Option<u64>,
+    /// Serve only these users (name or uid, repeatable) plus members of
+    /// `--allow-group`; others are refused by their socket credentials.
+    #[arg(long = "allow-user", value_name = "USER")]
+    #[serde(default, rename = "allowed_users")]
+    allow_user: Vec<String>,
+    /// Serve members of this group (name or gid, repeatable).
+    #[arg(long = "allow-group", value_name = "GROUP")]
+    #[serde(default, rename = "allowed_groups")]
+    allow_group: Vec<String>,
+}
+
+impl BackendArgs {
+    fn or(self, other: Self) -> Self {
+        // The lists go together: either both come from the flags or both from config.
+        let (allow_user, allow_group) = if self.restricts_access() {
+            (self.allow_user, self.allow_group)
+        } else {
+            (other.allow_user, other.allow_group)
+        };
+        // Likewise for the two ways of sizing the worker pool.
+        let (workers, workers_per_gpu) = if self.workers.is_some() || self.workers_per_gpu.is_some()
+        {
+            (self.workers, self.workers_per_gpu)
+        } else {
+            (other.workers, other.workers_per_gpu)
+        };
+        Self {
+            idle_unload: self.idle_unload.or(other.idle_unload),
+            workers,
+            workers_per_gpu,
+            preload_model: if self.preload_model.is_empty() {
+                other.preload_model
+            } else {
+                self.preload_model
+            },
+            max_models: self.max_models.or(other.max_models),
+            vram_budget_mb: self.vram_budget_mb.or(other.vram_budget_mb),
+            allow_user,
+            allow_group,
+        }
+    }
+
+    fn restricts_access(&self) -> bool {
+        !self.allow_user.is_empty() || !self.allow_group.is_empty()
+    }
+
+    /// Flags for `daemon serve`.
+    fn serve_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(secs) = self.idle_unload {
+            args.push(format!("--idle-unload={secs}s"));
+        }
+        if let Some(workers) = self.workers {
+            args.push(format!("--workers={workers}"));
+        }
+        if let Some(per_gpu) = self.workers_per_gpu {
+            args.push(format!("--workers-per-gpu={per_gpu}"));
+        }
+        for model in &self.preload_model {
+            args.push(format!("--preload-model={model}"));
+        }
+        if let Some(max) = self.max_models {
+            args.push(format!("--max-models={max}"));
+        }
+        if let Some(budget) = self.vram_budget_mb {
+            args.push(format!("--vram-budget-mb={budget}"));
+        }
+        for user in &self.allow_user {
+            args.push(format!("--allow-user={user}"));
+        }
+        for group in &self.allow_group {
+            args.push(format!("--allow-group={group}"));
+        }
+        args
+    }
+}
+
 #[derive(Debug, Subcommand)]
 enum DaemonCommand {
     Start {
@@ -83,12 +814,40 @@ enum DaemonCommand {
         pidfile: Option<PathBuf>,
         #[arg(long)]
         logfile: Option<PathBuf>,
+        #[command(flatten)]
+        backend: BackendArgs,
+        /// `json` emits startup stages as NDJSON events on stderr.
+        #[arg(long, value_enum, default_value_t = ProgressMode::Auto)]
+        progress: ProgressMode,
     },
     Stop {
         #[arg(long)]
         pidfile: Option<PathBuf>,
         #[arg(long)]
         socket: Option<PathBuf>,
+        /// Seconds to wait for in-flight requests before killing the daemon.
+        #[arg(long, visible_alias = "drain-timeout", default_value_t = DEFAULT_STOP_TIMEOUT_SEC)]
+        timeout: f64,
+        /// Kill the daemon at once instead of letting in-flight requests finish.
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    /// Stops the daemon gracefully and starts it again with the same socket and logfile.
+    Restart {
+        #[arg(long)]
+        socket: Option<PathBuf>,
+        #[arg(long)]
+        pidfile: Option<PathBuf>,
+        #[arg(long)]
+        logfile: Option<PathBuf>,
+        #[command(flatten)]
+        backend: BackendArgs,
+        /// Seconds to wait for in-flight requests before killing the daemon.
+        #[arg(long, visible_alias = "drain-timeout", default_value_t = DEFAULT_STOP_TIMEOUT_SEC)]
+        timeout: f64,
+        /// Kill the daemon at once instead of letting in-flight requests finish.
+        #[arg(long, default_value_t = false)]
+        force: bool,
     },
     Status {
         #[arg(long)]
@@ -97,16 +856,75 @@ enum DaemonCommand {
         socket: Option<PathBuf>,
         #[arg(long)]
         logfile: Option<PathBuf>,
+        /// Print status and daemon stats as JSON (for monitoring scripts).
+        #[arg(long, default_value_t = false)]
+        json: bool,
+        /// Only per-tag stats for this tag (repeatable), e.g. `--tag project=alpha`.
+        #[arg(long = "tag", value_name = "KEY=VALUE", value_parser = tags::parse)]
+        tags: Vec<(String, String)>,
     },
     Logs {
         #[arg(long)]
         logfile: Option<PathBuf>,
         #[arg(long, default_value_t = 80)]
         lines: usize,
+        /// Keep printing lines as the daemon writes them, like `tail -f`.
+        #[arg(long, short = 'f', default_value_t = false)]
+        follow: bool,
+        /// Only lines logged within this window, e.g. `30s`, `10m`, `2h`, `1d`.
+        #[arg(long, value_parser = logs::parse_since)]
+        since: Option<u64>,
+        /// Only lines matching this regular expression.
+        #[arg(long)]
+        grep: Option<String>,
+        /// Only lines at or above this severity.
+        #[arg(long, value_enum)]
+        level: Option<logs::Level>,
     },
+    /// Runs the backend in the foreground and restarts it if it crashes. Under
+    /// systemd socket activation (`LISTEN_FDS`) it serves the inherited socket
+    /// instead of binding one.
     Serve {
         #[arg(long)]
         socket: Option<PathBuf>,
+        #[command(flatten)]
+        backend: BackendArgs,
+    },
+    /// Writes a systemd unit running `daemon serve` with the current binary and paths.
+    InstallSystemd {
+        /// Install a user unit (`~/.config/systemd/user`) instead of a system one.
+        #[arg(long, default_value_t = false)]
+        user: bool,
+        /// Also write a `.socket` unit so systemd owns the socket and starts the
+        /// daemon on the first connection.
+        #[arg(long, default_value_t = false)]
+        socket_activation: bool,
+        #[arg(long)]
+        socket: Option<PathBuf>,
+        #[arg(long)]
+        logfile: Option<PathBuf>,
+        /// Write units here instead of the standard systemd directory.
+        #[arg(long)]
+        unit_dir: Option<PathBuf>,
+    },
+    /// Writes a macOS LaunchAgent running `daemon serve` and loads it with launchctl.
+    InstallLaunchd {
+        #[arg(long)]
+        socket: Option<PathBuf>,
+        #[arg(long)]
+        logfile: Option<PathBuf>,
+        /// Write the plist here instead of `~/Library/LaunchAgents`.
+        #[arg(long)]
+        agent_dir: Option<PathBuf>,
+        /// Only write the plist; do not load it.
+        #[arg(long, default_value_t = false)]
+        no_load: bool,
+    },
+    Ping {
+        #[arg(long)]
+        socket: Option<PathBuf>,
+        #[arg(long, default_value_t = 5.0)]
+        timeout: f64,
     },
 }
 
@@ -114,6 +932,23 @@ enum DaemonCommand {
 enum OutputFormat {
     Text,
     Md,
+    Srt,
+    Vtt,
+}
+
+impl OutputFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Md => "md",
+            OutputFormat::Srt => "srt",
+            OutputFormat::Vtt => "vtt",
+        }
+    }
+
+    fn needs_segments(self) -> bool {
+        matches!(self, OutputFormat::Srt | OutputFormat::Vtt)
+    }
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
@@ -122,10 +957,69 @@ enum EmitMode {
     Json,
 }
 
-#[derive(serde::Serialize)]
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum ProgressMode {
+    Auto,
+    Bar,
+    Json,
+    None,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum SplitMode {
+    None,
+    Sentences,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum LanguageMismatch {
+    Skip,
+    Flag,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Precision {
+    Auto,
+    Fp32,
+    Fp16,
+    Bf16,
+    Int8,
+}
+
+impl Precision {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Fp32 => "fp32",
+            Self::Fp16 => "fp16",
+            Self::Bf16 => "bf16",
+            Self::Int8 => "int8",
+        }
+    }
+
+    /// Rejects combinations that cannot work on any host; whether a GPU
+    /// supports bf16 is left to the backend, which can ask torch.
+    fn check(self, device: &str, engine: engine::Engine) -> Result<()> {
+        let on_cuda = device.starts_with("cuda");
+        if engine.is_remote() && self != Self::Auto {
+            bail!(
+                "--precision only applies to the local backend, not --engine {}",
+                engine.as_str()
+            );
+        }
+        if self == Self::Fp16 && device == "cpu" {
+            bail!("--precision fp16 needs a CUDA device; use bf16 or int8 with --device cpu");
+        }
+        if self == Self::Int8 && on_cuda {
+            bail!("--precision int8 runs on CPU only; use fp16 or bf16 with --device {device}");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
 struct BackendRequest<'a> {
     input: &'a Path,
-    output: Option<&'a Path>,
     model: &'a str,
     device: &'a str,
     vocab: Option<&'a Path>,
@@ -133,58 +1027,316 @@ struct BackendRequest<'a> {
     timestamps: bool,
     fuzzy_vocab: bool,
     verbose: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<&'a str>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    translate: bool,
+    // With `language: auto`: the backend switches to the model routed for the
+    // detected language before the full pass.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language_models: Option<&'a BTreeMap<String, String>>,
+    only_language: Option<&'a str>,
+    language_mismatch: &'a str,
+    start_sec: Option<f64>,
+    end_sec: Option<f64>,
+    word_timestamps: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    work_dir: Option<&'a Path>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    beam_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nbest: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    len_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    backend_args: BTreeMap<String, String>,
+    precision: &'a str,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    fast_start: bool,
+    // Per-segment confidence scores (`--min-confidence`).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    confidence: bool,
+    #[serde(skip_serializing_if = "tags::Tags::is_empty")]
+    tags: &'a tags::Tags,
 }
 
-#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
 struct BackendResponse {
     transcript: String,
+    #[serde(default)]
+    segments: Option<Vec<Segment>>,
+    #[serde(default)]
+    words: Option<Vec<Word>>,
+    /// `--nbest` candidates, best first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alternatives: Option<Vec<Alternative>>,
     output_path: Option<String>,
     source: String,
     model: String,
     device: String,
+    // Precision the model ran at; absent for external engines.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    precision: Option<String>,
     format: String,
     metrics: Option<BackendMetrics>,
+    /// Spoken (source) language, also when translating.
+    #[serde(default)]
+    language: Option<String>,
+    /// `transcribe` or `translate` (to English).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task: Option<String>,
+    #[serde(default)]
+    language_mismatch: bool,
+    #[serde(default)]
+    skipped: bool,
+    #[serde(default)]
+    history_id: Option<String>,
+    #[serde(default, skip_serializing_if = "metadata::Metadata::is_empty")]
+    metadata: metadata::Metadata,
+    /// Title, artist, album and date tagged in the input file.
+    #[serde(default, skip_serializing_if = "media::MediaTags::is_empty")]
+    media_tags: media::MediaTags,
+    #[serde(default, skip_serializing_if = "tags::Tags::is_empty")]
+    tags: tags::Tags,
+    #[serde(default)]
+    preprocessing: Vec<PreprocessStep>,
+    #[serde(default)]
+    postprocessing: Vec<PostprocessStep>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    delivered_to: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<Warning>,
+    /// `--redact` replacements per category (`email`, `phone`, `card`, `ssn`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    redactions: BTreeMap<String, usize>,
+    /// `--summarize` result (Markdown).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    /// `--by-chapter` chapter marks of the input.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    chapters: Vec<chapters::Chapter>,
 }
 
-#[derive(serde::Deserialize, serde::Serialize)]
-struct BackendMetrics {
-    model_load_sec: f64,
-    inference_sec: f64,
-    total_sec: f64,
-    audio_sec: Option<f64>,
+/// Something a reviewer should look at, e.g. a segment the model was unsure of.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+struct Warning {
+    code: String,
+    message: String,
+    #[serde(default)]
+    start: Option<f64>,
+    #[serde(default)]
+    end: Option<f64>,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let mut args: Vec<std::ffi::OsString> = std::env::args_os().collect();
-    if let Some(argv0) = args.first()
-        && Path::new(argv0)
-            .file_name()
+/// One conversion applied to the input audio before the model saw it, e.g. an
+/// ffmpeg resample/downmix, or NeMo's own resampling of audio passed as-is.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+struct PreprocessStep {
+    name: String,
+    tool: String,
+    #[serde(default)]
+    from: Option<AudioFormat>,
+    #[serde(default)]
+    to: Option<AudioFormat>,
+    /// Human-readable summary, e.g. "resample 44100 -> 16000 Hz".
+    #[serde(default)]
+    changes: Vec<String>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+struct AudioFormat {
+    codec: Option<String>,
+    sample_rate: Option<u32>,
+    channels: Option<u32>,
+    bit_depth: Option<u32>,
+}
+
+/// One transformation applied to the raw ASR text, in the order applied.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+struct PostprocessStep {
+    name: String,
+    version: u32,
+    #[serde(default)]
+    params: serde_json::Value,
+}
+
+impl PostprocessStep {
+    fn new(name: &str, version: u32, params: serde_json::Value) -> Self {
+        Self {
+            name: name.to_string(),
+            version,
+            params,
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+struct Segment {
+    start: f64,
+    end: f64,
+    text: String,
+    // Lowest word confidence in the segment (0-1), when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    confidence: Option<f64>,
+    // Channel label with --split-channels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speaker: Option<String>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+struct Word {
+    text: String,
+    start: f64,
+    end: f64,
+    // Word confidence (0-1), when requested; kept so chunked runs can still
+    // score their rebuilt segments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    confidence: Option<f64>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+struct Alternative {
+    text: String,
+    // Log-probability from the decoder; higher is better.
+    score: Option<f64>,
+}
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+struct BackendMetrics {
+    model_load_sec: f64,
+    inference_sec: f64,
+    total_sec: f64,
+    audio_sec: Option<f64>,
+    /// Seconds of audio per second of inference (`audio_sec / inference_sec`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    realtime_factor: Option<f64>,
+    /// Peak GPU memory allocated during inference, model weights included [MiB].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    peak_gpu_mb: Option<f64>,
+}
+
+impl BackendMetrics {
+    /// Folds in another backend call of the same run: times add up, the
+    /// memory peak is the higher one.
+    fn add(&mut self, other: &BackendMetrics) {
+        self.model_load_sec += other.model_load_sec;
+        self.inference_sec += other.inference_sec;
+        self.total_sec += other.total_sec;
+        self.peak_gpu_mb = match (self.peak_gpu_mb, other.peak_gpu_mb) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::Success.into(),
+        Err(err) => {
+            eprintln!("Error: {err:?}");
+            error::exit_code_of(&err).into()
+        }
+    }
+}
+
+async fn run() -> Result<()> {
+    let mut args: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    if let Some(argv0) = args.first()
+        && Path::new(argv0)
+            .file_name()
             .and_then(|s| s.to_str())
             .is_some_and(|name| matches!(name, "parakeetd" | "paraketd"))
     {
         args.insert(1, "daemon".into());
     }
+    userhome::prepare()?;
     if args.len() > 1 {
         if args[1] == "transcribe" {
             args.remove(1);
             let cli = TranscribeCli::parse_from(args);
-            return run_transcribe(cli).await;
+            return transcribe_main(cli).await;
         }
-        if args[1] == "daemon" {
+        if matches!(
+            args[1].to_str(),
+            Some(
+                "daemon"
+                    | "export"
+                    | "history"
+                    | "doctor"
+                    | "warm"
+                    | "serve-stdio"
+                    | "voicemail"
+                    | "grep"
+                    | "eval"
+                    | "diff"
+                    | "bench"
+            )
+        ) {
             let root = RootCli::parse_from(args);
             return run_root(root).await;
         }
     }
 
     let cli = TranscribeCli::parse_from(args);
-    run_transcribe(cli).await
+    transcribe_main(cli).await
+}
+
+/// Runs a transcription under `--timeout` and Ctrl-C; with `--emit json`,
+/// failures become a JSON error object on stdout.
+async fn transcribe_main(mut cli: TranscribeCli) -> Result<()> {
+    let emit = cli.emit;
+    let timeout = cli.timeout;
+    let input = cli.input.display().to_string();
+    if cli.porcelain {
+        porcelain::enable();
+        cli.progress = ProgressMode::None;
+    }
+    // Dropping the transcription future kills the backend subprocess (kill_on_drop).
+    let result = tokio::select! {
+        result = run_transcribe(cli) => result,
+        _ = tokio::signal::ctrl_c() => Err(Failure::new(ErrorKind::Cancelled, "cancelled").into()),
+        _ = sleep_until_timeout(timeout) => Err(Failure::new(
+            ErrorKind::Timeout,
+            format!("timed out after {}s", timeout.unwrap_or_default()),
+        )
+        .into()),
+    };
+    if let (Err(err), EmitMode::Json) = (&result, emit) {
+        println!("{}", error::to_json(err));
+        std::process::exit(error::exit_code_of(err) as i32);
+    }
+    if let Err(err) = &result {
+        porcelain::failed(&input, err);
+    }
+    result
+}
+
+async fn sleep_until_timeout(timeout: Option<f64>) {
+    match timeout {
+        Some(sec) => tokio::time::sleep(Duration::from_secs_f64(sec)).await,
+        None => std::future::pending().await,
+    }
 }
 
 async fn run_root(root: RootCli) -> Result<()> {
     match root.command {
-        RootCommand::Transcribe(cli) => run_transcribe(cli).await,
+        RootCommand::Transcribe(cli) => transcribe_main(*cli).await,
         RootCommand::Daemon(daemon) => run_daemon(daemon).await,
+        RootCommand::Export(cli) => export::run_export(cli),
+        RootCommand::History(cli) => history::run_history(cli).await,
+        RootCommand::Doctor(cli) => preflight::run_doctor(cli),
+        RootCommand::Warm(cli) => run_warm(cli),
+        RootCommand::ServeStdio => stdio::serve().await,
+        RootCommand::Voicemail(cli) => voicemail::run(cli).await,
+        RootCommand::Grep(cli) => grep::run(cli),
+        RootCommand::Eval(cli) => eval::run(cli).await,
+        RootCommand::Diff(cli) => diff::run(cli),
+        RootCommand::Bench(cli) => bench::run(cli).await,
     }
 }
 
@@ -194,74 +1346,343 @@ async fn run_daemon(daemon: DaemonCli) -> Result<()> {
             socket,
             pidfile,
             logfile,
+            backend,
+            progress,
         } => daemon_start(
             &socket.unwrap_or_else(default_socket_path),
             &pidfile.unwrap_or_else(default_pid_path),
             &logfile.unwrap_or_else(default_log_path),
+            backend,
+            matches!(progress, ProgressMode::Json).then(|| progress::Events::new("daemon_start")),
         ),
-        DaemonCommand::Stop { pidfile, socket } => daemon_stop(
+        DaemonCommand::Stop {
+            pidfile,
+            socket,
+            timeout,
+            force,
+        } => daemon_stop(
             &pidfile.unwrap_or_else(default_pid_path),
             &socket.unwrap_or_else(default_socket_path),
+            timeout,
+            force,
+        ),
+        DaemonCommand::Restart {
+            socket,
+            pidfile,
+            logfile,
+            backend,
+            timeout,
+            force,
+        } => daemon_restart(
+            socket,
+            &pidfile.unwrap_or_else(default_pid_path),
+            logfile,
+            backend,
+            timeout,
+            force,
         ),
         DaemonCommand::Status {
             pidfile,
             socket,
             logfile,
+            json,
+            tags,
         } => daemon_status(
             &pidfile.unwrap_or_else(default_pid_path),
             &socket.unwrap_or_else(default_socket_path),
             &logfile.unwrap_or_else(default_log_path),
+            json,
+            &tags,
+        ),
+        DaemonCommand::Logs {
+            logfile,
+            lines,
+            follow,
+            since,
+            grep,
+            level,
+        } => logs::show(
+            &logfile.unwrap_or_else(default_log_path),
+            lines,
+            follow,
+            logs::Filter::new(since, grep.as_deref(), level)?,
         ),
-        DaemonCommand::Logs { logfile, lines } => {
-            daemon_logs(&logfile.unwrap_or_else(default_log_path), lines)
+        DaemonCommand::Serve { socket, backend } => {
+            let config = config::load()?;
+            let backend = backend.or(BackendArgs {
+                idle_unload: config.daemon_idle_unload_sec,
+                workers: config.daemon_workers,
+                workers_per_gpu: config.daemon_workers_per_gpu,
+                preload_model: config.daemon_preload_models,
+                max_models: config.daemon_max_models,
+                vram_budget_mb: config.daemon_vram_budget_mb,
+                allow_user: config.daemon_allowed_users,
+                allow_group: config.daemon_allowed_groups,
+            });
+            daemon_serve(&socket.unwrap_or_else(default_socket_path), backend).await
         }
-        DaemonCommand::Serve { socket } => {
-            daemon_serve(&socket.unwrap_or_else(default_socket_path)).await
+        DaemonCommand::InstallSystemd {
+            user,
+            socket_activation,
+            socket,
+            logfile,
+            unit_dir,
+        } => {
+            let paths = service_paths(socket, logfile)?;
+            service::install_systemd(&paths, user, socket_activation, unit_dir)
+        }
+        DaemonCommand::InstallLaunchd {
+            socket,
+            logfile,
+            agent_dir,
+            no_load,
+        } => {
+            let paths = service_paths(socket, logfile)?;
+            service::install_launchd(&paths, agent_dir, no_load)
+        }
+        DaemonCommand::Ping { socket, timeout } => {
+            daemon_ping(&socket.unwrap_or_else(default_socket_path), timeout)
         }
     }
 }
 
-async fn daemon_serve(socket: &Path) -> Result<()> {
+fn service_paths(
+    socket: Option<PathBuf>,
+    logfile: Option<PathBuf>,
+) -> Result<service::ServicePaths> {
+    Ok(service::ServicePaths {
+        exe: std::env::current_exe().context("failed locating the parakeet binary")?,
+        home: parakeet_home(),
+        socket: socket.unwrap_or_else(default_socket_path),
+        logfile: logfile.unwrap_or_else(default_log_path),
+    })
+}
+
+async fn daemon_serve(socket: &Path, options: BackendArgs) -> Result<()> {
     let root_dir = parakeet_home();
-    let venv_python = root_dir.join(".venv/bin/python");
+    let venv_python = venv_python(&root_dir);
     let backend = root_dir.join("python/parakeet_backend.py");
 
     fs::create_dir_all(root_dir.join("tmp"))?;
     fs::create_dir_all(root_dir.join("output"))?;
 
-    let err = std::process::Command::new(&venv_python)
-        .arg(&backend)
-        .arg("--serve")
-        .arg("--socket-path")
-        .arg(socket)
-        .env("PARAKEET_HOME", &root_dir)
-        .env("HF_HOME", root_dir.join(".cache/hf"))
-        .env("TRANSFORMERS_CACHE", root_dir.join(".cache/hf"))
-        .env("TORCH_HOME", root_dir.join(".cache/torch"))
-        .env("NEMO_HOME", root_dir.join(".cache/nemo"))
-        .env("PIP_CACHE_DIR", root_dir.join(".cache/pip"))
-        .exec();
+    let backend_cmd = |listen_fd: i32| {
+        let mut cmd = std::process::Command::new(&venv_python);
+        cmd.arg(&backend)
+            .arg("--serve")
+            .arg("--socket-path")
+            .arg(socket)
+            // The supervisor owns the socket (bound here or inherited from systemd).
+            .arg("--listen-fd")
+            .arg(listen_fd.to_string())
+            .arg("--idle-unload-sec")
+            .arg(options.idle_unload.unwrap_or(0).to_string())
+            .arg("--workers")
+            .arg(options.workers.unwrap_or(1).to_string())
+            .arg("--workers-per-gpu")
+            .arg(options.workers_per_gpu.unwrap_or(0).to_string())
+            .arg("--max-models")
+            .arg(options.max_models.unwrap_or(2).to_string())
+            .arg("--vram-budget-mb")
+            .arg(options.vram_budget_mb.unwrap_or(0).to_string())
+            .args(userhome::enabled().then_some("--per-user-homes"))
+            .args(
+                options
+                    .allow_user
+                    .iter()
+                    .flat_map(|user| ["--allow-user", user]),
+            )
+            .args(
+                options
+                    .allow_group
+                    .iter()
+                    .flat_map(|group| ["--allow-group", group]),
+            )
+            .args(
+                options
+                    .preload_model
+                    .iter()
+                    .flat_map(|model| ["--preload-model", model]),
+            )
+            .env_remove("LISTEN_PID")
+            .env_remove("LISTEN_FDS")
+            .env_remove("LISTEN_FDNAMES")
+            .env("PARAKEET_HOME", &root_dir)
+            .env("PARAKEET_VERSION", env!("CARGO_PKG_VERSION"))
+            .env("HF_HOME", root_dir.join(".cache/hf"))
+            .env("TRANSFORMERS_CACHE", root_dir.join(".cache/hf"))
+            .env("TORCH_HOME", root_dir.join(".cache/torch"))
+            .env("NEMO_HOME", root_dir.join(".cache/nemo"))
+            .env("PIP_CACHE_DIR", root_dir.join(".cache/pip"));
+        cmd
+    };
+    // Other users need to connect to be served, or refused by name.
+    let shared = userhome::enabled() || options.restricts_access();
+    supervise_backend(socket, shared, backend_cmd).await
+}
 
-    Err(anyhow!(err)).context("failed launching daemon backend")
+#[cfg(unix)]
+async fn supervise_backend(
+    socket: &Path,
+    shared: bool,
+    backend: impl Fn(i32) -> std::process::Command,
+) -> Result<()> {
+    supervisor::run(socket, shared, backend).await
 }
 
-fn daemon_start(socket: &Path, pidfile: &Path, logfile: &Path) -> Result<()> {
-    if is_pidfile_running(pidfile)? {
-        println!("parakeet daemon already running");
-        return Ok(());
+// The Python daemon listens on a unix domain socket, which CPython does not
+// offer on Windows; transcriptions there run a one-off backend instead.
+#[cfg(not(unix))]
+async fn supervise_backend(
+    _socket: &Path,
+    _shared: bool,
+    _backend: impl Fn(i32) -> std::process::Command,
+) -> Result<()> {
+    bail!("the daemon is not supported on this platform yet; transcribe with --no-daemon")
+}
+
+/// Makes the spawned daemon independent of the launching terminal: it gets its
+/// own session (closing the terminal no longer SIGHUPs it) and keeps no fds
+/// besides stdio, which already point at the log.
+///
+/// It stays our direct child rather than double-forking, so the pidfile holds
+/// its real pid and a crash while loading is noticed; as a session leader it
+/// could only regain a controlling terminal by opening a tty, which neither
+/// `daemon serve` nor the backend does.
+#[cfg(unix)]
+fn detach_daemon(cmd: &mut std::process::Command) {
+    use std::os::unix::process::CommandExt;
+    // SAFETY: the hook runs between fork and exec and only makes async-signal-safe calls.
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            // fds the launcher itself inherited without close-on-exec (shell
+            // redirections, an editor's pipes) would otherwise stay open for
+            // the daemon's lifetime. Closed by the exec, like our own.
+            let max_fd = match libc::sysconf(libc::_SC_OPEN_MAX) {
+                n if n > 0 => n.min(65536) as libc::c_int,
+                _ => 1024,
+            };
+            for fd in 3..max_fd {
+                let flags = libc::fcntl(fd, libc::F_GETFD);
+                if flags != -1 {
+                    libc::fcntl(fd, libc::F_SETFD, flags | libc::FD_CLOEXEC);
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn detach_daemon(_cmd: &mut std::process::Command) {}
+
+fn daemon_start(
+    socket: &Path,
+    pidfile: &Path,
+    logfile: &Path,
+    backend: BackendArgs,
+    events: Option<progress::Events>,
+) -> Result<()> {
+    let launch = launch_daemon(socket, pidfile, logfile, backend, events)?;
+    if let Some(events) = events {
+        events.emit(
+            "finished",
+            "done",
+            Some(100.0),
+            Some("daemon ready"),
+            serde_json::json!({}),
+        );
+    }
+    match launch {
+        DaemonLaunch::AlreadyRunning { pid, waited } => {
+            let pid = pid.map_or_else(|| "unknown".to_string(), |p| p.to_string());
+            if waited {
+                println!("parakeet daemon started by another process (pid={pid}); attached");
+            } else {
+                println!("parakeet daemon already running (pid={pid})");
+            }
+        }
+        DaemonLaunch::Started { pid } => {
+            println!("parakeet daemon started (pid={pid})");
+            println!("socket: {}", socket.display());
+            println!("log: {}", logfile.display());
+        }
     }
+    Ok(())
+}
 
-    if let Some(parent) = socket.parent() {
-        fs::create_dir_all(parent)?;
+enum DaemonLaunch {
+    // `waited`: another starter held the launch lock and this call attached to its daemon.
+    AlreadyRunning { pid: Option<u32>, waited: bool },
+    Started { pid: u32 },
+}
+
+/// Spawns `daemon serve` in the background and waits until its socket appears.
+///
+/// Concurrent starters serialize on `<pidfile>.lock`; whoever gets it second
+/// finds the first one's daemon (pidfile or live socket) and attaches to it
+/// instead of spawning another backend.
+fn launch_daemon(
+    socket: &Path,
+    pidfile: &Path,
+    logfile: &Path,
+    backend: BackendArgs,
+    events: Option<progress::Events>,
+) -> Result<DaemonLaunch> {
+    for path in [socket, pidfile, logfile] {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
     }
-    if let Some(parent) = logfile.parent() {
-        fs::create_dir_all(parent)?;
+    let lock_path = pidfile.with_extension("lock");
+    let lock = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("failed opening lockfile: {}", lock_path.display()))?;
+    let waited = match lock.try_lock() {
+        Ok(()) => false,
+        Err(fs::TryLockError::WouldBlock) => {
+            eprintln!("[parakeet] another daemon start is in progress; waiting for it");
+            if let Some(events) = events {
+                events.emit(
+                    "daemon_waiting",
+                    "starting_daemon",
+                    None,
+                    Some("another daemon start is in progress"),
+                    serde_json::json!({}),
+                );
+            }
+            lock.lock()
+                .with_context(|| format!("failed locking {}", lock_path.display()))?;
+            true
+        }
+        Err(fs::TryLockError::Error(err)) => {
+            return Err(err).with_context(|| format!("failed locking {}", lock_path.display()));
+        }
+    };
+
+    if is_pidfile_running(pidfile)? {
+        let pid = read_pid(pidfile)?;
+        // A starter without the lock may still be loading the model.
+        wait_for_ready(socket, None, events)?;
+        return Ok(DaemonLaunch::AlreadyRunning { pid, waited });
+    }
+    if is_socket_reachable(socket) {
+        return Ok(DaemonLaunch::AlreadyRunning { pid: None, waited });
     }
     if socket.exists() {
         let _ = fs::remove_file(socket);
     }
 
     let exe = std::env::current_exe()?;
+    // The daemon runs from PARAKEET_HOME, so a relative --socket would move.
+    let socket = &std::path::absolute(socket)
+        .with_context(|| format!("invalid socket path: {}", socket.display()))?;
     let log = fs::OpenOptions::new()
         .create(true)
         .append(true)
@@ -269,46 +1690,196 @@ fn daemon_start(socket: &Path, pidfile: &Path, logfile: &Path) -> Result<()> {
         .with_context(|| format!("failed opening logfile: {}", logfile.display()))?;
     let log_err = log.try_clone()?;
 
-    let child = std::process::Command::new(exe)
-        .arg("daemon")
+    let mut cmd = std::process::Command::new(exe);
+    cmd.arg("daemon")
         .arg("serve")
         .arg("--socket")
         .arg(socket)
+        .args(backend.serve_args())
+        .current_dir(parakeet_home())
         .stdin(Stdio::null())
         .stdout(Stdio::from(log))
-        .stderr(Stdio::from(log_err))
-        .spawn()
-        .context("failed spawning daemon")?;
+        .stderr(Stdio::from(log_err));
+    detach_daemon(&mut cmd);
+    let mut child = cmd.spawn().context("failed spawning daemon")?;
 
     fs::write(pidfile, child.id().to_string())
         .with_context(|| format!("failed writing pidfile: {}", pidfile.display()))?;
+    if let Some(events) = events {
+        events.emit(
+            "daemon_spawned",
+            "starting_daemon",
+            None,
+            Some("daemon spawned; loading model"),
+            serde_json::json!({ "pid": child.id() }),
+        );
+    }
 
-    for _ in 0..240 {
-        if socket.exists() {
-            println!("parakeet daemon started");
-            println!("socket: {}", socket.display());
-            println!("log: {}", logfile.display());
-            return Ok(());
+    if let Err(err) = wait_for_ready(socket, Some(&mut child), events) {
+        // A supervisor retrying a backend that cannot start would linger; stop it.
+        if matches!(child.try_wait(), Ok(None)) {
+            let _ = send_signal(child.id(), "TERM");
+            let _ = child.wait();
+        }
+        let _ = fs::remove_file(pidfile);
+        return Err(err).with_context(|| format!("see daemon log: {}", logfile.display()));
+    }
+    let options = LaunchOptions {
+        socket: socket.to_path_buf(),
+        logfile: logfile.to_path_buf(),
+        backend,
+    };
+    let options_path = launch_options_path(pidfile);
+    if let Err(err) = fs::write(&options_path, serde_json::to_string(&options)?) {
+        eprintln!(
+            "[parakeet] failed recording daemon options in {}: {err}",
+            options_path.display()
+        );
+    }
+    Ok(DaemonLaunch::Started { pid: child.id() })
+}
+
+/// Waits until the daemon answers a ping. Its socket is listening from the
+/// start, but the backend only accepts once the model is loaded, so a single
+/// ping connection is held open and re-read every second.
+fn wait_for_ready(
+    socket: &Path,
+    mut child: Option<&mut std::process::Child>,
+    events: Option<progress::Events>,
+) -> Result<()> {
+    let mut probe: Option<transport::Stream> = None;
+    for waited in 0..240 {
+        if probe.is_none() && socket.exists() {
+            probe = start_probe(socket).ok();
+        }
+        match probe.as_mut().map(read_probe) {
+            Some(ProbeState::Ready) => return Ok(()),
+            Some(ProbeState::Failed(message)) => {
+                bail!("daemon backend failed while starting: {message}")
+            }
+            Some(ProbeState::Waiting) => {}
+            Some(ProbeState::Lost) => {
+                probe = None;
+                std::thread::sleep(Duration::from_secs(1));
+            }
+            None => std::thread::sleep(Duration::from_secs(1)),
+        }
+        if let Some(events) = events
+            && waited > 0
+            && waited % 5 == 0
+        {
+            events.emit(
+                "daemon_loading",
+                "loading_model",
+                None,
+                Some("waiting for the daemon to load the model"),
+                serde_json::json!({ "waited_sec": waited }),
+            );
+        }
+        if let Some(child) = child.as_deref_mut()
+            && let Some(status) = child.try_wait()?
+        {
+            bail!("daemon exited during startup ({status})");
         }
-        std::thread::sleep(Duration::from_secs(1));
     }
 
     bail!("daemon start timed out")
 }
 
-fn daemon_stop(pidfile: &Path, socket: &Path) -> Result<()> {
+enum ProbeState {
+    Ready,
+    Waiting,
+    // The daemon answered with an error: its backend is down and restarting.
+    Failed(String),
+    // Connection dropped or garbled; open a new one.
+    Lost,
+}
+
+fn start_probe(socket: &Path) -> Result<transport::Stream> {
+    let mut stream = transport::connect(socket)?;
+    stream.set_timeouts(Duration::from_secs(1), Duration::from_secs(1))?;
+    send_frame(&mut stream, MessageType::Ping, br#"{"type":"ping"}"#)?;
+    Ok(stream)
+}
+
+fn read_probe(stream: &mut transport::Stream) -> ProbeState {
+    let mut magic = [0u8; 2];
+    match stream.read_exact(&mut magic) {
+        Ok(()) if magic == protocol::MAGIC => {}
+        Ok(()) => return ProbeState::Lost,
+        Err(err)
+            if matches!(
+                err.kind(),
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+            ) =>
+        {
+            return ProbeState::Waiting;
+        }
+        Err(_) => return ProbeState::Lost,
+    }
+    match protocol::read_frame_after_magic(stream) {
+        Ok(frame) if frame.kind == MessageType::Pong => ProbeState::Ready,
+        Ok(frame) if frame.kind == MessageType::Error => {
+            ProbeState::Failed(daemon_error_message(&frame.payload))
+        }
+        _ => ProbeState::Lost,
+    }
+}
+
+fn daemon_error_message(payload: &[u8]) -> String {
+    let err: serde_json::Value = serde_json::from_slice(payload).unwrap_or_default();
+    err["error"].as_str().unwrap_or("unknown error").to_string()
+}
+
+const DEFAULT_STOP_TIMEOUT_SEC: f64 = 300.0;
+
+/// Sends SIGTERM and waits for the daemon to exit. The backend stops accepting
+/// connections at once but finishes in-flight requests; after `timeout` it is
+/// killed. `force` kills it straight away.
+///
+/// Requests a kill cut short are listed in the queue state file the backend
+/// keeps while draining (see [`queue_state_path`]).
+fn daemon_stop(pidfile: &Path, socket: &Path, timeout: f64, force: bool) -> Result<()> {
+    if timeout < 0.0 {
+        bail!("--timeout must not be negative");
+    }
     let pid = read_pid(pidfile)?;
-    if let Some(pid) = pid {
-        let status = std::process::Command::new("kill")
-            .arg(pid.to_string())
-            .status()
-            .context("failed to send kill")?;
-        if !status.success() {
-            bail!("failed stopping daemon pid={pid}");
-        }
-        println!("parakeet daemon stopped");
-    } else {
-        println!("parakeet daemon not running");
+    match pid {
+        Some(pid) if force && is_pidfile_running(pidfile)? => {
+            kill_daemon(pid)?;
+            while is_pidfile_running(pidfile)? {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            println!("parakeet daemon killed");
+        }
+        Some(pid) if is_pidfile_running(pidfile)? => {
+            send_signal(pid, "TERM")?;
+            let deadline = std::time::Instant::now() + Duration::from_secs_f64(timeout);
+            let mut announced = false;
+            while is_pidfile_running(pidfile)? {
+                if std::time::Instant::now() >= deadline {
+                    eprintln!(
+                        "[parakeet] daemon pid={pid} did not stop within {timeout}s; killing it"
+                    );
+                    kill_daemon(pid)?;
+                    break;
+                }
+                if !announced {
+                    eprintln!("[parakeet] waiting for daemon pid={pid} to finish in-flight work");
+                    announced = true;
+                }
+                std::thread::sleep(Duration::from_millis(200));
+            }
+            println!("parakeet daemon stopped");
+            if let Some(queue) = read_queue_state(socket) {
+                eprintln!(
+                    "[parakeet] {} request(s) did not finish; recorded in {}",
+                    queue.requests.len(),
+                    queue_state_path(socket).display()
+                );
+            }
+        }
+        _ => println!("parakeet daemon not running"),
     }
 
     let _ = fs::remove_file(pidfile);
@@ -316,138 +1887,1176 @@ fn daemon_stop(pidfile: &Path, socket: &Path) -> Result<()> {
     Ok(())
 }
 
-fn daemon_status(pidfile: &Path, socket: &Path, logfile: &Path) -> Result<()> {
-    if is_pidfile_running(pidfile)? || is_socket_reachable(socket) {
-        println!("parakeet daemon running");
-        println!("socket: {}", socket.display());
-        println!("log: {}", logfile.display());
-        Ok(())
-    } else {
+/// SIGKILL for the daemon and its backend. `daemon start` makes the daemon a
+/// process group leader; killing only the supervisor would orphan the backend.
+fn kill_daemon(pid: u32) -> Result<()> {
+    let group = std::process::Command::new("kill")
+        .args(["-KILL", "--", &format!("-{pid}")])
+        .stderr(Stdio::null())
+        .status();
+    match group {
+        Ok(status) if status.success() => Ok(()),
+        // Not a group leader (started by other means): the pid alone.
+        _ => send_signal(pid, "KILL"),
+    }
+}
+
+fn send_signal(pid: u32, signal: &str) -> Result<()> {
+    let status = std::process::Command::new("kill")
+        .arg(format!("-{signal}"))
+        .arg(pid.to_string())
+        .status()
+        .context("failed to send kill")?;
+    if !status.success() {
+        bail!("failed stopping daemon pid={pid}");
+    }
+    Ok(())
+}
+
+/// Socket, logfile and backend options a daemon was started with, kept next
+/// to its pidfile so `restart` brings it back the same way.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct LaunchOptions {
+    socket: PathBuf,
+    logfile: PathBuf,
+    #[serde(flatten)]
+    backend: BackendArgs,
+}
+
+/// Where the backend records requests still pending while it shuts down
+/// (`<socket stem>.queue.json`); removed once every one of them finished.
+fn queue_state_path(socket: &Path) -> PathBuf {
+    socket.with_extension("queue.json")
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct QueueState {
+    shutdown_at: f64,
+    requests: Vec<PendingRequest>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct PendingRequest {
+    input: Option<String>,
+    model: String,
+    /// `queued` (waiting for the model) or `running`.
+    state: String,
+    accepted_at: f64,
+    #[serde(default)]
+    tags: tags::Tags,
+}
+
+fn read_queue_state(socket: &Path) -> Option<QueueState> {
+    let raw = fs::read_to_string(queue_state_path(socket)).ok()?;
+    serde_json::from_str(&raw)
+        .ok()
+        .filter(|queue: &QueueState| !queue.requests.is_empty())
+}
+
+fn launch_options_path(pidfile: &Path) -> PathBuf {
+    pidfile.with_extension("json")
+}
+
+fn daemon_restart(
+    socket: Option<PathBuf>,
+    pidfile: &Path,
+    logfile: Option<PathBuf>,
+    backend: BackendArgs,
+    timeout: f64,
+    force: bool,
+) -> Result<()> {
+    let recorded: Option<LaunchOptions> = fs::read_to_string(launch_options_path(pidfile))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok());
+    let (recorded_socket, recorded_log, recorded_backend) = recorded
+        .map_or((None, None, BackendArgs::default()), |opts| {
+            (Some(opts.socket), Some(opts.logfile), opts.backend)
+        });
+    let socket = socket
+        .or(recorded_socket)
+        .unwrap_or_else(default_socket_path);
+    let logfile = logfile.or(recorded_log).unwrap_or_else(default_log_path);
+
+    daemon_stop(pidfile, &socket, timeout, force)?;
+    daemon_start(
+        &socket,
+        pidfile,
+        &logfile,
+        backend.or(recorded_backend),
+        None,
+    )
+}
+
+fn daemon_status(
+    pidfile: &Path,
+    socket: &Path,
+    logfile: &Path,
+    json: bool,
+    tag_filter: &[(String, String)],
+) -> Result<()> {
+    if !(is_pidfile_running(pidfile)? || is_socket_reachable(socket)) {
         let _ = fs::remove_file(pidfile);
-        println!("parakeet daemon not running");
+        let interrupted = read_queue_state(socket);
+        if json {
+            println!(
+                "{}",
+                serde_json::json!({ "running": false, "interrupted": interrupted })
+            );
+        } else {
+            println!("parakeet daemon not running");
+            if let Some(queue) = interrupted {
+                println!(
+                    "interrupted at last shutdown: {} request(s), see {}",
+                    queue.requests.len(),
+                    queue_state_path(socket).display()
+                );
+            }
+        }
         bail!("not running")
     }
-}
-
-fn daemon_logs(logfile: &Path, lines: usize) -> Result<()> {
-    let content = fs::read_to_string(logfile)
-        .with_context(|| format!("failed reading logfile: {}", logfile.display()))?;
-    let all: Vec<&str> = content.lines().collect();
-    let start = all.len().saturating_sub(lines);
-    for line in &all[start..] {
-        println!("{line}");
+    // A live pid can still be a wedged backend; only an answered ping proves health.
+    let health = DaemonClient::without_retries(STATUS_PING_TIMEOUT_SEC)
+        .ping(socket)
+        .map(|mut pong| {
+            if !tag_filter.is_empty() {
+                pong.by_tag.retain(|label, _| {
+                    tag_filter
+                        .iter()
+                        .any(|(key, value)| *label == tags::label(key, value))
+                });
+            }
+            pong
+        });
+    if json {
+        let (daemon, error) = match &health {
+            Ok(pong) => (serde_json::to_value(pong)?, None),
+            Err(err) => (serde_json::Value::Null, Some(format!("{err:#}"))),
+        };
+        let status = serde_json::json!({
+            "running": true,
+            "healthy": health.is_ok(),
+            "pid": read_pid(pidfile)?,
+            "socket": socket,
+            "log": logfile,
+            "error": error,
+            "daemon": daemon,
+        });
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+    println!("parakeet daemon running");
+    println!("socket: {}", socket.display());
+    println!("log: {}", logfile.display());
+    match health {
+        Ok(pong) => {
+            println!("health: ok ({})", pong.summary());
+            let running = match (pong.busy, pong.workers_busy) {
+                (false, _) => String::new(),
+                // Daemons before worker pools ran one request at a time.
+                (true, 0) => ", 1 running".to_string(),
+                (true, busy) => format!(", {busy} running"),
+            };
+            println!(
+                "requests: {} served, {} failed, {} queued{running}",
+                pong.requests_served, pong.requests_failed, pong.queue_depth,
+            );
+            if pong.workers > 1 {
+                let mut devices = pong.worker_devices.clone();
+                devices.sort();
+                devices.dedup();
+                let on = if devices.is_empty() {
+                    String::new()
+                } else {
+                    format!(" on {}", devices.join(", "))
+                };
+                println!("workers: {} ({} busy){on}", pong.workers, pong.workers_busy);
+            }
+            if !pong.models.is_empty() {
+                let mut limits = Vec::new();
+                if let Some(max) = pong.max_models {
+                    limits.push(format!("max {max}"));
+                }
+                if let Some(budget) = pong.vram_budget_mb {
+                    limits.push(format!("VRAM budget {budget:.0} MiB"));
+                }
+                if pong.evictions > 0 {
+                    limits.push(format!("{} evicted", pong.evictions));
+                }
+                if let Some(idle) = pong.idle_unload_sec {
+                    limits.push(format!("unloaded after {idle:.0}s idle"));
+                }
+                if limits.is_empty() {
+                    println!("models: {}", pong.models.join(", "));
+                } else {
+                    println!("models: {} ({})", pong.models.join(", "), limits.join(", "));
+                }
+            } else if pong.idle_unload_sec.is_some() {
+                println!("models: none loaded (unloaded while idle; the next request reloads)");
+            }
+            if !pong.allowed_users.is_empty() || !pong.allowed_groups.is_empty() {
+                let mut allowed = Vec::new();
+                if !pong.allowed_users.is_empty() {
+                    allowed.push(format!("users {}", pong.allowed_users.join(", ")));
+                }
+                if !pong.allowed_groups.is_empty() {
+                    allowed.push(format!("groups {}", pong.allowed_groups.join(", ")));
+                }
+                println!("access: {}", allowed.join("; "));
+            }
+            if pong.per_user_homes {
+                println!("per-user homes: on (requests checked against the caller's permissions)");
+            }
+            if let Some(mem) = &pong.gpu_memory {
+                println!(
+                    "gpu memory: {:.0} MiB allocated, {:.0} MiB reserved of {:.0} MiB",
+                    mem.allocated_mb, mem.reserved_mb, mem.total_mb
+                );
+            }
+            for (label, counts) in &pong.by_tag {
+                println!(
+                    "tag {label}: {} served, {} failed, {:.1}s audio in {:.1}s",
+                    counts.served, counts.failed, counts.audio_sec, counts.inference_sec
+                );
+            }
+            if let Some(last) = &pong.last_error {
+                println!("last error: {}", last.message);
+            }
+        }
+        Err(err) => println!("health: not responding ({err:#})"),
+    }
+    Ok(())
+}
+
+const STATUS_PING_TIMEOUT_SEC: f64 = 2.0;
+
+fn run_warm(cli: WarmCli) -> Result<()> {
+    if cli.timeout <= 0.0 {
+        bail!("--timeout must be positive");
+    }
+    let socket = cli.socket.unwrap_or_else(default_socket_path);
+    if !is_socket_reachable(&socket) {
+        daemon_start(
+            &socket,
+            &default_pid_path(),
+            &default_log_path(),
+            BackendArgs::default(),
+            None,
+        )?;
+    }
+    let report = DaemonClient::without_retries(cli.timeout)
+        .warm(&socket, &cli.models)
+        .map_err(|err| Failure::new(ErrorKind::DaemonUnreachable, format!("{err:#}")))?;
+    for warmed in &report.models {
+        println!(
+            "warmed {} on {} (worker {}): load {:.1}s, warm-up {:.2}s",
+            warmed.model, warmed.device, warmed.worker, warmed.load_sec, warmed.warmup_sec
+        );
+    }
+    Ok(())
+}
+
+#[derive(serde::Deserialize)]
+struct WarmReport {
+    models: Vec<WarmedModel>,
+}
+
+#[derive(serde::Deserialize)]
+struct WarmedModel {
+    model: String,
+    device: String,
+    worker: u32,
+    /// 0 when the model was already loaded.
+    load_sec: f64,
+    warmup_sec: f64,
+}
+
+fn daemon_ping(socket: &Path, timeout: f64) -> Result<()> {
+    if timeout <= 0.0 {
+        bail!("--timeout must be positive");
+    }
+    let started = std::time::Instant::now();
+    let pong = DaemonClient::without_retries(timeout)
+        .ping(socket)
+        .map_err(|err| Failure::new(ErrorKind::DaemonUnreachable, format!("{err:#}")))?;
+    println!(
+        "parakeet daemon ok ({}, {} ms)",
+        pong.summary(),
+        started.elapsed().as_millis()
+    );
+    Ok(())
+}
+
+// Fields after `uptime_sec` are absent from older daemons.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Pong {
+    version: String,
+    model: String,
+    device: String,
+    pid: u32,
+    uptime_sec: f64,
+    #[serde(default)]
+    models: Vec<String>,
+    #[serde(default)]
+    gpu_memory: Option<GpuMemory>,
+    #[serde(default)]
+    requests_served: u64,
+    #[serde(default)]
+    requests_failed: u64,
+    #[serde(default)]
+    queue_depth: u32,
+    #[serde(default)]
+    busy: bool,
+    #[serde(default)]
+    last_error: Option<DaemonError>,
+    /// Counters per `key=value` tag of the requests served.
+    #[serde(default)]
+    by_tag: BTreeMap<String, TagStats>,
+    /// Set when the daemon unloads its models after this many idle seconds.
+    #[serde(default)]
+    idle_unload_sec: Option<f64>,
+    /// Requests the daemon runs in parallel (`--workers`) and how many run now.
+    #[serde(default = "one_worker")]
+    workers: u32,
+    #[serde(default)]
+    workers_busy: u32,
+    /// Device of each worker, e.g. `cuda:0`, `cuda:1`.
+    #[serde(default)]
+    worker_devices: Vec<String>,
+    /// Model pool limits (`--max-models`, `--vram-budget-mb`) and how many
+    /// models they have unloaded so far.
+    #[serde(default)]
+    max_models: Option<u32>,
+    #[serde(default)]
+    vram_budget_mb: Option<f64>,
+    #[serde(default)]
+    evictions: u64,
+    /// Requests are checked against the caller's permissions (`per_user_homes`).
+    #[serde(default)]
+    per_user_homes: bool,
+    /// Who may connect (`--allow-user`/`--allow-group`); empty means anyone
+    /// who can open the socket.
+    #[serde(default)]
+    allowed_users: Vec<String>,
+    #[serde(default)]
+    allowed_groups: Vec<String>,
+}
+
+fn one_worker() -> u32 {
+    1
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct TagStats {
+    served: u64,
+    failed: u64,
+    audio_sec: f64,
+    inference_sec: f64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct GpuMemory {
+    allocated_mb: f64,
+    reserved_mb: f64,
+    total_mb: f64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct DaemonError {
+    message: String,
+    // Unix timestamp (seconds).
+    at: f64,
+}
+
+impl Pong {
+    fn summary(&self) -> String {
+        let uptime = self.uptime_sec as u64;
+        format!(
+            "version {}, pid {}, model {} on {}, up {}",
+            self.version,
+            self.pid,
+            self.model,
+            self.device,
+            if uptime >= 3600 {
+                format!("{}h{:02}m", uptime / 3600, uptime % 3600 / 60)
+            } else {
+                format!("{}m{:02}s", uptime / 60, uptime % 60)
+            }
+        )
+    }
+}
+
+fn is_pidfile_running(pidfile: &Path) -> Result<bool> {
+    let Some(pid) = read_pid(pidfile)? else {
+        return Ok(false);
+    };
+
+    if is_zombie_pid(pid)? {
+        return Ok(false);
+    }
+
+    let status = std::process::Command::new("kill")
+        .arg("-0")
+        .arg(pid.to_string())
+        .status()
+        .context("failed to probe pid")?;
+    Ok(status.success())
+}
+
+fn is_zombie_pid(pid: u32) -> Result<bool> {
+    // No procfs on macOS; ask ps for the process state instead.
+    if !Path::new("/proc/self").exists() {
+        let output = std::process::Command::new("ps")
+            .args(["-o", "stat=", "-p", &pid.to_string()])
+            .output()
+            .context("failed to probe pid state")?;
+        return Ok(String::from_utf8_lossy(&output.stdout)
+            .trim_start()
+            .starts_with('Z'));
+    }
+    let proc_stat = format!("/proc/{pid}/stat");
+    let raw = match fs::read_to_string(&proc_stat) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err).with_context(|| format!("failed reading {proc_stat}")),
+    };
+
+    let Some(close_paren) = raw.rfind(')') else {
+        return Ok(false);
+    };
+    let rest = raw[close_paren + 1..].trim_start();
+    let mut parts = rest.split_whitespace();
+    let state = parts.next().unwrap_or_default();
+    Ok(state == "Z")
+}
+
+fn is_socket_reachable(socket: &Path) -> bool {
+    transport::is_reachable(socket)
+}
+
+fn read_pid(pidfile: &Path) -> Result<Option<u32>> {
+    if !pidfile.exists() {
+        return Ok(None);
+    }
+    let raw = fs::read_to_string(pidfile)
+        .with_context(|| format!("failed reading pidfile: {}", pidfile.display()))?;
+    let pid = raw.trim().parse::<u32>().ok();
+    Ok(pid)
+}
+
+/// Fills in whatever the preset covers that was not given explicitly.
+fn apply_quality(cli: &mut TranscribeCli, quality: quality::Quality) {
+    let preset = quality.preset();
+    cli.model.get_or_insert_with(|| preset.model.to_string());
+    cli.beam_size.get_or_insert(preset.beam_size);
+    if cli.chunk_minutes.is_none() {
+        cli.chunk_minutes = preset.chunk_minutes;
+        cli.chunk_overlap.get_or_insert(preset.chunk_overlap_sec);
+    }
+    if !(cli.fuzzy_vocab || cli.no_fuzzy_vocab) {
+        cli.no_fuzzy_vocab = !preset.fuzzy_vocab;
+    }
+    if !cli.no_two_pass {
+        cli.two_pass |= preset.two_pass;
+    }
+}
+
+async fn run_transcribe(cli: TranscribeCli) -> Result<()> {
+    let input = cli.input.display().to_string();
+    if cli.notify {
+        tools::require(&tools::NOTIFIER)?;
+    }
+    let notify = cli.notify;
+    let started = Instant::now();
+    let result = transcribe_job(cli).await;
+    if notify {
+        let took = output::format_clock(started.elapsed().as_secs_f64());
+        let (title, body) = match &result {
+            Ok((_, parsed)) => (
+                "Transcription finished",
+                match &parsed.output_path {
+                    Some(path) => format!("{input} in {took}: {path}"),
+                    None => format!("{input} in {took}"),
+                },
+            ),
+            Err(err) => (
+                "Transcription failed",
+                format!("{input} after {took}: {err}"),
+            ),
+        };
+        if let Err(err) = notify::desktop(title, &body) {
+            eprintln!("[parakeet] {err:#}");
+        }
+    }
+    let (cli, parsed) = result?;
+    if cli.porcelain {
+        porcelain::transcribed(&input, &parsed);
+        return Ok(());
+    }
+    emit_response(&cli, &parsed)
+}
+
+/// Runs one transcription to completion (outputs written, history recorded)
+/// without printing the result. Returns the effective options alongside the
+/// response, since remote inputs and config defaults adjust them.
+async fn transcribe_job(cli: TranscribeCli) -> Result<(TranscribeCli, BackendResponse)> {
+    let webhook = cli
+        .webhook
+        .clone()
+        .or_else(|| config::load().ok().and_then(|config| config.webhook_url));
+    let input = cli.input.display().to_string();
+    let result = run_transcription(cli).await;
+    if let Some(url) = webhook {
+        let event = notify::event(&input, result.as_ref().map(|(_, parsed)| parsed));
+        // The transcript is on disk either way; a dead endpoint only costs the event.
+        if let Err(err) = notify::post(&url, &event) {
+            eprintln!("[parakeet] {err:#}");
+        }
+    }
+    result
+}
+
+async fn run_transcription(mut cli: TranscribeCli) -> Result<(TranscribeCli, BackendResponse)> {
+    let root_dir = parakeet_home();
+    let venv_python = venv_python(&root_dir);
+    let backend = root_dir.join("python/parakeet_backend.py");
+
+    let remote_url = remote::remote_url(&cli.input).map(str::to_string);
+    if remote_url.is_none() && !cli.input.exists() {
+        bail!(Failure::new(
+            ErrorKind::InputNotFound,
+            format!("input does not exist: {}", cli.input.display()),
+        ));
+    }
+    let config = config::load()?;
+    let engine = cli.engine.or(config.engine).unwrap_or_default();
+    cli.engine = Some(engine);
+    if engine.is_remote() {
+        cli.engine_url = cli.engine_url.take().or(config.engine_url.clone());
+        cli.engine_api_key_env = config.engine_api_key_env.clone();
+        if let Some(model) = engine.default_model() {
+            // Keeps a --quality preset from naming a local model.
+            cli.model.get_or_insert_with(|| model.to_string());
+        }
+    } else if !venv_python.exists() {
+        bail!(
+            Failure::new(
+                ErrorKind::VenvMissing,
+                format!("python environment missing at {}", venv_python.display()),
+            )
+            .with_hint(format!(
+                "bootstrap env/tools via: {}/install.sh",
+                root_dir.display()
+            ))
+        );
+    }
+    if !engine.is_remote() && !backend.exists() {
+        bail!(Failure::new(
+            ErrorKind::BackendMissing,
+            format!("backend script not found: {}", backend.display()),
+        ));
+    }
+    if !engine.is_remote()
+        && let Some(lang) = cli.only_language.as_deref()
+        && !DETECTED_LANGUAGES.contains(&lang)
+    {
+        bail!(
+            "--only-language {lang}: the local backend only detects {}",
+            DETECTED_LANGUAGES.join(", ")
+        );
+    }
+    if cli.precision == Precision::Int8 && cli.device == "auto" {
+        // Quantized weights only run on CPU.
+        cli.device = "cpu".to_string();
+    }
+    cli.precision.check(&cli.device, engine)?;
+    cli.auto_daemon |= config.auto_daemon && !cli.no_daemon && !engine.is_remote();
+    // Only a cold backend start pays torch's import cost; catch hopeless hosts first.
+    let socket = cli
+        .daemon_socket
+        .clone()
+        .unwrap_or_else(default_socket_path);
+    if !engine.is_remote() && (cli.no_daemon || !is_socket_reachable(&socket)) {
+        preflight::check(&root_dir, &cli.device, cli.verbose)?;
+    }
+    cli.daemon_timeout = cli.daemon_timeout.or(config.daemon_timeout_sec);
+    cli.daemon_write_timeout = cli.daemon_write_timeout.or(config.daemon_write_timeout_sec);
+    cli.daemon_retries = cli.daemon_retries.or(config.daemon_retries);
+    cli.quality = cli.quality.or(config.quality);
+    cli.diff_guard = cli.diff_guard.or(config.diff_guard_pct);
+    cli.fast_start |= config.fast_start;
+    cli.rules = cli.rules.take().or_else(|| config.rules.clone());
+    cli.pre_hook = cli.pre_hook.take().or_else(|| config.pre_hook.clone());
+    if cli.pre_hook.is_some() && !cli.concat.is_empty() {
+        bail!(
+            "--concat cannot be combined with a pre-hook (--pre-hook or pre_hook in config.json)"
+        );
+    }
+    cli.post_hook = cli.post_hook.take().or_else(|| config.post_hook.clone());
+    if cli.redact.is_empty() {
+        cli.redact = config.redact.clone();
+    }
+    // A broken rules file or word list should fail before the transcription, not after.
+    if let Some(path) = &cli.rules {
+        rules::load(path)?;
+    }
+    cli.render.masker()?;
+    if cli.denoise {
+        cli.denoise_model = cli
+            .denoise_model
+            .take()
+            .or_else(|| config.denoise_model.clone());
+        if let Some(model) = &cli.denoise_model {
+            enhance::check(model)?;
+        }
+    }
+    if cli.summarize {
+        config.summarize.check()?;
+    }
+    cli.obsidian_vault = cli
+        .obsidian_vault
+        .take()
+        .or_else(|| config.obsidian_vault.clone());
+    if let Some(vault) = &cli.obsidian_vault {
+        obsidian::check(vault)?;
+        cli.obsidian_folder = config
+            .obsidian_folder
+            .clone()
+            .unwrap_or_else(|| obsidian::DEFAULT_FOLDER.to_string());
+    }
+    // Routing only picks a default: an explicit --model always wins.
+    let routes = cli
+        .model
+        .is_none()
+        .then_some(&config.language_models)
+        .filter(|routes| !routes.is_empty() && !engine.is_remote());
+    if let Some(lang) = cli.language.as_deref().filter(|lang| *lang != "auto")
+        && let Some(model) = routes.and_then(|routes| languages::route(routes, lang))
+    {
+        if cli.verbose {
+            eprintln!("[parakeet] language {lang}: using {model} (language_models)");
+        }
+        cli.model = Some(model);
+    }
+    if cli
+        .diff_guard
+        .is_some_and(|pct| !(0.0..=100.0).contains(&pct))
+    {
+        bail!("diff_guard_pct in config.json must be between 0 and 100");
+    }
+    if let Some(quality) = cli.quality {
+        apply_quality(&mut cli, quality);
+        if cli.verbose {
+            eprintln!(
+                "[parakeet] quality={}: model={} beam_size={} chunk_minutes={} fuzzy_vocab={} two_pass={}",
+                quality.as_str(),
+                cli.model.as_deref().unwrap_or(quality::DEFAULT_MODEL),
+                cli.beam_size.unwrap_or(1),
+                cli.chunk_minutes
+                    .map_or_else(|| "off".to_string(), |m| m.to_string()),
+                !cli.no_fuzzy_vocab,
+                cli.two_pass,
+            );
+        }
+    }
+    if let Some(nbest) = cli.nbest {
+        if !matches!(cli.emit, EmitMode::Json) {
+            bail!("--nbest returns its candidates in --emit json output; add --emit json");
+        }
+        if engine.is_remote() {
+            bail!("--nbest needs the local backend's beam search");
+        }
+        if cli.chunk_minutes.is_some() {
+            bail!("--nbest does not combine with --chunk-minutes (also set by --quality best)");
+        }
+        if nbest > 1 && cli.beam_size.unwrap_or(1) < nbest {
+            cli.beam_size = Some(nbest);
+        }
+    }
+    if cli.translate {
+        let model = cli.model.as_deref().unwrap_or(quality::DEFAULT_MODEL);
+        if !engine.is_remote() && !languages::translates(model) {
+            bail!(
+                "{model} cannot translate; --translate needs a multitask model such as \
+                 nvidia/canary-1b-flash (or --engine openai-api)"
+            );
+        }
+        if cli.refine.is_some() {
+            bail!("--refine does not combine with --translate");
+        }
+        if cli.chunk_minutes.is_some() {
+            bail!(
+                "--translate does not combine with --chunk-minutes (also set by --quality best): \
+                 translated chunks have no word timing to stitch on"
+            );
+        }
+    }
+    if matches!(cli.itn, itn::Itn::Off) && matches!(cli.numbers, itn::Numbers::Digits) {
+        bail!("--numbers digits conflicts with --itn off, which spells numbers out");
+    }
+    if cli.refine.is_some() && cli.nbest.is_some() {
+        bail!("--refine rewrites the transcript, which --nbest alternatives would not match");
+    }
+    if !cli.channel_labels.is_empty() && cli.channel_labels.len() != 2 {
+        bail!("--channel-labels takes two names, left then right (e.g. Agent,Customer)");
+    }
+    if cli.split_channels {
+        // Each of these re-runs the backend on the mixed input or compares
+        // against it, which would lose the channel split.
+        for (flag, set) in [
+            ("--two-pass", cli.two_pass),
+            ("--refine", cli.refine.is_some()),
+            ("--nbest", cli.nbest.is_some()),
+        ] {
+            if set {
+                bail!("{flag} does not combine with --split-channels");
+            }
+        }
+    }
+    if cli.caption_file.is_some() && cli.chunk_minutes.is_none() {
+        bail!(
+            "--caption-file is updated per chunk; add --chunk-minutes (e.g. 1 for near-live captions)"
+        );
+    }
+    if cli.autosave.is_some() && cli.chunk_minutes.is_none() {
+        bail!(
+            "--autosave saves after every chunk; add --chunk-minutes (a run loses at most one chunk)"
+        );
+    }
+    if cli.temperature.is_some_and(|t| t <= 0.0) {
+        bail!("--temperature must be greater than 0");
+    }
+    if cli.len_penalty.is_some() && (engine.is_remote() || cli.beam_size.unwrap_or(1) < 2) {
+        bail!("--len-penalty only applies to beam search; add --beam-size 2 or more");
+    }
+    if [cli.daemon_timeout, cli.daemon_write_timeout]
+        .into_iter()
+        .flatten()
+        .any(|sec| sec <= 0.0)
+    {
+        bail!("daemon timeouts must be positive");
     }
-    Ok(())
-}
-
-fn is_pidfile_running(pidfile: &Path) -> Result<bool> {
-    let Some(pid) = read_pid(pidfile)? else {
-        return Ok(false);
-    };
-
-    if is_zombie_pid(pid)? {
-        return Ok(false);
+    for spec in &cli.sinks {
+        if sink::is_stdout(spec) && matches!(cli.emit, EmitMode::Json) {
+            bail!("--sink stdout conflicts with --emit json");
+        }
+        sink::parse(spec)?;
     }
 
-    let status = std::process::Command::new("kill")
-        .arg("-0")
-        .arg(pid.to_string())
-        .status()
-        .context("failed to probe pid")?;
-    Ok(status.success())
-}
-
-fn is_zombie_pid(pid: u32) -> Result<bool> {
-    let proc_stat = format!("/proc/{pid}/stat");
-    let raw = match fs::read_to_string(&proc_stat) {
-        Ok(raw) => raw,
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
-        Err(err) => return Err(err).with_context(|| format!("failed reading {proc_stat}")),
-    };
+    let model_name = cli.model.as_deref().unwrap_or(quality::DEFAULT_MODEL);
+    if let Some(lang) = cli.language.as_deref().filter(|lang| *lang != "auto")
+        && !engine.is_remote()
+    {
+        languages::check(model_name, lang)?;
+    }
 
-    let Some(close_paren) = raw.rfind(')') else {
-        return Ok(false);
+    let end_sec = match (cli.end, cli.duration) {
+        (Some(end), _) => Some(end),
+        (None, Some(duration)) => Some(cli.start.unwrap_or_default() + duration),
+        (None, None) => None,
     };
-    let rest = raw[close_paren + 1..].trim_start();
-    let mut parts = rest.split_whitespace();
-    let state = parts.next().unwrap_or_default();
-    Ok(state == "Z")
-}
-
-fn is_socket_reachable(socket: &Path) -> bool {
-    if !socket.exists() {
-        return false;
+    if let Some(end) = end_sec
+        && end <= cli.start.unwrap_or_default()
+    {
+        bail!("--end/--duration must be after --start");
     }
-    UnixStream::connect(socket).is_ok()
-}
 
-fn read_pid(pidfile: &Path) -> Result<Option<u32>> {
-    if !pidfile.exists() {
-        return Ok(None);
+    // Only download once every flag has been checked.
+    if let Some(url) = remote_url {
+        cli.input = remote::fetch(&url, cli.verbose)?;
+        // Named outputs would otherwise land next to the cached download.
+        if cli.out.is_none() && (cli.auto_name || cli.name_template.is_some()) {
+            cli.out = Some(PathBuf::from("."));
+        }
+        cli.remote_source = Some(url);
     }
-    let raw = fs::read_to_string(pidfile)
-        .with_context(|| format!("failed reading pidfile: {}", pidfile.display()))?;
-    let pid = raw.trim().parse::<u32>().ok();
-    Ok(pid)
-}
-
-async fn run_transcribe(cli: TranscribeCli) -> Result<()> {
-    let root_dir = parakeet_home();
-    let venv_python = root_dir.join(".venv/bin/python");
-    let backend = root_dir.join("python/parakeet_backend.py");
-
-    if !cli.input.exists() {
-        bail!("input does not exist: {}", cli.input.display());
+    if let Some(meta) = &cli.meta {
+        cli.metadata = metadata::load(meta, &cli.input)?;
     }
-    if !venv_python.exists() {
-        bail!(
-            "python environment missing at {}. Bootstrap env/tools via: {}/install.sh",
-            venv_python.display(),
-            root_dir.display(),
-        );
+    if let Some(source) = cli.calendar.clone().or_else(|| config.calendar.clone()) {
+        // A calendar that cannot be read should not cost the transcript.
+        match calendar::lookup(&source, &cli.input, cli.recorded_at) {
+            Ok(Some(meeting)) => {
+                eprintln!("[parakeet] calendar: {}", meeting.title);
+                meeting.label(&mut cli.metadata);
+            }
+            Ok(None) => eprintln!("[parakeet] calendar: no meeting at the recording time"),
+            Err(err) => eprintln!("[parakeet] calendar lookup failed: {err:#}"),
+        }
     }
-    if !backend.exists() {
-        bail!("backend script not found: {}", backend.display());
+    // Catch unknown {meta.*} keys before spending time on transcription; calendar
+    // labels count as metadata, so this waits for the lookup.
+    resolve_output_path(&cli, None)?;
+    if cli.start.is_some()
+        || end_sec.is_some()
+        || media::needs_conversion(&cli.input)
+        || cli.export_clips.is_some()
+    {
+        tools::require(&tools::FFMPEG)?;
+    }
+    // The rest of the range check needs the (downloaded) input's length.
+    if let Some(start) = cli.start
+        && let Some(total) = media::audio_sec(&cli.input)
+        && start >= total
+    {
+        bail!("--start {start}s is at or past the end of the input ({total:.1}s)");
     }
 
-    let output_format = match cli.format {
-        OutputFormat::Text => "text",
-        OutputFormat::Md => "md",
+    let mut workdir = workdir::Workdir::create(&userhome::dir(), cli.keep_workdir)?;
+    let merged_vocab_path = prepare_vocab_file(
+        &root_dir,
+        workdir.path(),
+        cli.vocab.as_deref(),
+        !cli.no_library,
+    )
+    .context("failed preparing vocabulary file")?;
+
+    let hooked = match cli.pre_hook.as_deref() {
+        Some(command) => hooks::pre(command, &cli.input, workdir.path())?,
+        None => None,
     };
-    let model_name = cli
-        .model
-        .as_deref()
-        .unwrap_or("nvidia/parakeet-tdt-0.6b-v3");
-
-    let merged_vocab_path = prepare_vocab_file(&root_dir, cli.vocab.as_deref(), !cli.no_library)
-        .context("failed preparing vocabulary file")?;
-
+    if cli.verbose
+        && let Some(path) = &hooked
+    {
+        eprintln!("[parakeet] pre-hook: transcribing {}", path.display());
+    }
+    media::validate(hooked.as_deref().unwrap_or(&cli.input))?;
+    concat::check(&cli.concat)?;
+    // Read from the container itself; derived WAVs carry no chapters.
+    let chapter_marks = if cli.by_chapter {
+        let marks = chapters::probe(hooked.as_deref().unwrap_or(&cli.input))?;
+        if marks.is_empty() {
+            eprintln!("[parakeet] warning: --by-chapter found no chapters; writing one transcript");
+        }
+        marks
+    } else {
+        Vec::new()
+    };
+    let selected = match cli.channel {
+        Some(channels::Channel::Index(index)) => Some(
+            channels::select(
+                hooked.as_deref().unwrap_or(&cli.input),
+                index,
+                workdir.path(),
+                cli.verbose,
+            )
+            .await?,
+        ),
+        Some(channels::Channel::Mix) | None => None,
+    };
+    let enhance = enhance::Enhance {
+        denoise: cli.denoise,
+        denoise_model: cli.denoise_model.as_deref(),
+        normalize: cli.normalize_audio,
+    };
+    let enhanced = if enhance.is_active() {
+        Some(
+            enhance::apply(
+                selected
+                    .as_deref()
+                    .or(hooked.as_deref())
+                    .unwrap_or(&cli.input),
+                &enhance,
+                workdir.path(),
+                cli.verbose,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+    let prepared = enhanced
+        .as_ref()
+        .map(|(path, _)| path.as_path())
+        .or(selected.as_deref())
+        .or(hooked.as_deref())
+        .unwrap_or(&cli.input);
+    let resampled = if cli.resample {
+        resample::to_model_format(prepared, workdir.path(), cli.verbose)?
+    } else {
+        None
+    };
+    // The daemon resolves paths from its own working directory (PARAKEET_HOME).
+    let audio = resampled
+        .as_ref()
+        .map_or(prepared, |(path, _)| path.as_path());
+    let input = std::path::absolute(audio)
+        .with_context(|| format!("invalid input path: {}", audio.display()))?;
+    let run_tags = tags::collect(&cli.tags);
     let request = BackendRequest {
-        input: &cli.input,
-        output: cli.out.as_deref(),
+        input: &input,
         model: model_name,
         device: &cli.device,
         vocab: merged_vocab_path.as_deref(),
-        format: output_format,
-        timestamps: cli.timestamps,
+        // Rendering and output writing happen client-side; the backend returns plain text.
+        format: "text",
+        timestamps: cli.render.needs_segments()
+            || cli.format.needs_segments()
+            || cli.refine.is_some()
+            || cli.obsidian_vault.is_some()
+            || cli.split_channels
+            || cli.by_chapter
+            || cli.export_clips.is_some(),
         fuzzy_vocab: !cli.no_fuzzy_vocab,
         verbose: cli.verbose,
+        language: cli.language.as_deref(),
+        translate: cli.translate,
+        language_models: routes.filter(|_| cli.language.as_deref() == Some("auto")),
+        only_language: cli.only_language.as_deref(),
+        language_mismatch: match cli.language_mismatch {
+            LanguageMismatch::Skip => "skip",
+            LanguageMismatch::Flag => "flag",
+        },
+        start_sec: cli.start,
+        end_sec,
+        word_timestamps: false,
+        work_dir: Some(workdir.path()),
+        beam_size: cli.beam_size,
+        nbest: cli.nbest,
+        len_penalty: cli.len_penalty,
+        temperature: cli.temperature,
+        backend_args: cli.backend_args.iter().cloned().collect(),
+        precision: cli.precision.as_str(),
+        fast_start: cli.fast_start,
+        confidence: cli.render.min_confidence.is_some() || cli.refine.is_some(),
+        tags: &run_tags,
+    };
+    let progress_mode = match cli.progress {
+        _ if cli.rpc_job.is_some() => ProgressMode::Json,
+        ProgressMode::Auto if cli.verbose => ProgressMode::None,
+        mode => mode,
+    };
+    let events = matches!(progress_mode, ProgressMode::Json).then(|| match cli.rpc_job {
+        Some(job) => progress::Events::for_rpc_job("transcribe", job),
+        None => progress::Events::new("transcribe"),
+    });
+    // Cuts conflict with --concat, whose run covers every part.
+    let audio_sec = std::iter::once(input.as_path())
+        .chain(cli.concat.iter().map(PathBuf::as_path))
+        .map(media::audio_sec)
+        .sum::<Option<f64>>()
+        .map(|total| end_sec.unwrap_or(total).min(total) - cli.start.unwrap_or_default());
+    let progress = progress::Progress::start(
+        progress_mode,
+        events,
+        audio_sec,
+        history::recent_processing_ratio(20).unwrap_or(progress::DEFAULT_PROCESSING_RATIO),
+    );
+
+    let mut parsed = if cli.split_channels {
+        let labels = match cli.channel_labels.as_slice() {
+            [] => channels::DEFAULT_LABELS.map(str::to_string).to_vec(),
+            labels => labels.to_vec(),
+        };
+        channels::transcribe(&cli, &request, &labels, &progress).await?
+    } else {
+        transcribe_pass(&cli, &request, &progress).await?
+    };
+    if cli.two_pass {
+        let known = twopass::read_terms(request.vocab)?;
+        let learned = twopass::learned_terms(&parsed.transcript, &known);
+        if cli.verbose {
+            eprintln!("[parakeet] two-pass learned terms: {}", learned.join(", "));
+        }
+        if !learned.is_empty() {
+            let vocab = twopass::write_vocab(workdir.path(), request.vocab, &learned)?;
+            let second = BackendRequest {
+                vocab: Some(&vocab),
+                fuzzy_vocab: true,
+                ..request.clone()
+            };
+            progress.set_message("second pass".to_string());
+            let first_metrics = parsed.metrics.take();
+            parsed = transcribe_pass(&cli, &second, &progress).await?;
+            // Learning the terms precedes the vocab pass that applies them.
+            parsed.postprocessing.insert(
+                0,
+                PostprocessStep::new(
+                    "two_pass_vocab",
+                    1,
+                    serde_json::json!({ "learned_terms": learned }),
+                ),
+            );
+            if let (Some(total), Some(first)) = (parsed.metrics.as_mut(), first_metrics) {
+                total.add(&first);
+            }
+        }
+    }
+    if let Some(model) = cli.refine.as_deref() {
+        parsed = refine::refine(&cli, &request, model, cli.refine_below, parsed, &progress).await?;
+    }
+    progress.finish();
+    if let Some(metrics) = parsed.metrics.as_mut() {
+        metrics.audio_sec = metrics.audio_sec.or(audio_sec);
+        metrics.realtime_factor = metrics
+            .audio_sec
+            .filter(|_| metrics.inference_sec > 0.0)
+            .map(|audio| audio / metrics.inference_sec);
+    }
+    warn_slow(&cli, &parsed);
+    if let Some((_, step)) = resampled.as_ref() {
+        parsed.preprocessing.insert(0, step.clone());
+    }
+    if let Some((_, steps)) = enhanced {
+        parsed.preprocessing.splice(0..0, steps);
+    }
+    if hooked.is_some() || selected.is_some() || enhance.is_active() || resampled.is_some() {
+        // Outputs and history name the recording, not a derived temp file.
+        parsed.source = std::path::absolute(&cli.input)
+            .unwrap_or_else(|_| cli.input.clone())
+            .display()
+            .to_string();
+    }
+    parsed.chapters = chapter_marks;
+    parsed.media_tags = media::tags(hooked.as_deref().unwrap_or(&cli.input));
+    let parsed = complete_transcription(&cli, parsed)?;
+    workdir.mark_succeeded();
+    Ok((cli, parsed))
+}
+
+// Below these speeds (x real time) the run was probably not on the hardware
+// the user expected. Short clips are dominated by overhead and are not judged.
+const MIN_CUDA_REALTIME_FACTOR: f64 = 20.0;
+const MIN_CPU_REALTIME_FACTOR: f64 = 2.0;
+const MIN_JUDGED_AUDIO_SEC: f64 = 30.0;
+
+fn warn_slow(cli: &TranscribeCli, parsed: &BackendResponse) {
+    let Some(metrics) = &parsed.metrics else {
+        return;
     };
-    let json = serde_json::to_string(&request).context("serialize backend request")?;
+    let (Some(audio), Some(factor)) = (metrics.audio_sec, metrics.realtime_factor) else {
+        return;
+    };
+    if audio < MIN_JUDGED_AUDIO_SEC || cli.engine.is_some_and(|engine| engine.is_remote()) {
+        return;
+    }
+    if parsed.device.starts_with("cuda") && factor < MIN_CUDA_REALTIME_FACTOR {
+        eprintln!(
+            "[parakeet] warning: inference ran at only {factor:.1}x real time on {}; the GPU may be busy or throttled",
+            parsed.device
+        );
+    } else if parsed.device == "cpu" && factor < MIN_CPU_REALTIME_FACTOR {
+        let hint = if cli.device == "auto" {
+            "; --device auto found no usable GPU (see `parakeet doctor`)"
+        } else {
+            ""
+        };
+        eprintln!("[parakeet] warning: inference ran at only {factor:.1}x real time on cpu{hint}");
+    }
+}
+
+async fn transcribe_pass(
+    cli: &TranscribeCli,
+    request: &BackendRequest<'_>,
+    progress: &progress::Progress,
+) -> Result<BackendResponse> {
+    if cli.concat.is_empty() {
+        transcribe_part(cli, request, progress).await
+    } else {
+        concat::transcribe(cli, request, progress).await
+    }
+}
+
+/// One input: region, chunked or whole-file transcription.
+async fn transcribe_part(
+    cli: &TranscribeCli,
+    request: &BackendRequest<'_>,
+    progress: &progress::Progress,
+) -> Result<BackendResponse> {
+    if (cli.vad || cli.skip_music)
+        && let Some(parsed) = vad::transcribe(cli, request, progress).await?
+    {
+        return Ok(parsed);
+    }
+    match cli.chunk_minutes {
+        Some(minutes) => {
+            chunk::transcribe_chunked(
+                cli,
+                request,
+                minutes * 60.0,
+                cli.chunk_overlap
+                    .unwrap_or(quality::DEFAULT_CHUNK_OVERLAP_SEC),
+                progress,
+            )
+            .await
+        }
+        None => {
+            let parsed = request_backend(cli, request, progress).await?;
+            progress.chunk_done(1, 1, 1.0);
+            Ok(parsed)
+        }
+    }
+}
+
+async fn request_backend(
+    cli: &TranscribeCli,
+    request: &BackendRequest<'_>,
+    progress: &progress::Progress,
+) -> Result<BackendResponse> {
+    if let Some(engine) = cli.engine.filter(|engine| engine.is_remote()) {
+        return engine::transcribe(cli, engine, request).await;
+    }
+    let json = serde_json::to_string(request).context("serialize backend request")?;
 
     let daemon_socket = cli
         .daemon_socket
         .as_deref()
         .map_or_else(default_socket_path, PathBuf::from);
-    if !cli.no_daemon
-        && let Ok(parsed) = try_daemon_request(&daemon_socket, &json)
-    {
-        emit_response(&cli, &parsed)?;
-        return Ok(());
+    if cli.auto_daemon && !is_socket_reachable(&daemon_socket) {
+        eprintln!("[parakeet] starting daemon (auto-daemon)...");
+        let socket = daemon_socket.clone();
+        let events = progress.events();
+        let launched = tokio::task::spawn_blocking(move || {
+            // `daemon serve` applies the daemon_* options from config.json itself.
+            launch_daemon(
+                &socket,
+                &default_pid_path(),
+                &default_log_path(),
+                BackendArgs::default(),
+                events,
+            )
+        })
+        .await
+        .context("daemon start task join error")?;
+        if let Err(err) = launched {
+            eprintln!("[parakeet] auto-daemon start failed, using a one-off backend: {err:#}");
+        }
+    }
+    if !cli.no_daemon {
+        // The daemon client is blocking; keep it off the runtime so Ctrl-C and --timeout still fire.
+        let attempt = {
+            let json = json.clone();
+            let client = DaemonClient::from_cli(cli);
+            tokio::task::spawn_blocking(move || client.request(&daemon_socket, &json))
+                .await
+                .context("daemon request task join error")?
+        };
+        match attempt {
+            Ok(parsed) => return Ok(parsed),
+            Err(err) if cli.require_daemon => {
+                bail!(
+                    Failure::new(
+                        ErrorKind::DaemonUnreachable,
+                        format!("daemon request failed and --require-daemon is set: {err:#}"),
+                    )
+                    .with_hint("start it with: parakeet daemon start")
+                );
+            }
+            Err(_) => {}
+        }
     }
 
+    progress.model_loading();
+    run_backend_subprocess(&json, cli.verbose).await
+}
+
+async fn run_backend_subprocess(json: &str, verbose: bool) -> Result<BackendResponse> {
+    let root_dir = parakeet_home();
+    let venv_python = venv_python(&root_dir);
+    let backend = root_dir.join("python/parakeet_backend.py");
+
     let mut cmd = Command::new(&venv_python);
-    cmd.arg(&backend)
+    cmd.kill_on_drop(true)
+        .arg(&backend)
         .arg("--json")
         .arg(json)
         .env("PARAKEET_HOME", &root_dir)
@@ -492,11 +3101,13 @@ async fn run_transcribe(cli: TranscribeCli) -> Result<()> {
     let stderr_lines = stderr_task.await.context("stderr task join error")??;
 
     if !status.success() {
-        let stderr_text = stderr_lines.join("\n");
-        bail!("transcription failed:\n{}", stderr_text.trim());
+        bail!(
+            Failure::new(ErrorKind::BackendFailed, "transcription failed")
+                .with_stderr(stderr_lines)
+        );
     }
 
-    if cli.verbose {
+    if verbose {
         for line in &stderr_lines {
             eprintln!("{line}");
         }
@@ -507,13 +3118,153 @@ async fn run_transcribe(cli: TranscribeCli) -> Result<()> {
         .lines()
         .rev()
         .find(|line| line.trim_start().starts_with('{'))
-        .ok_or_else(|| anyhow!("backend did not return JSON output"))?;
-    let parsed: BackendResponse =
-        serde_json::from_str(json_line.trim()).context("failed to parse backend response JSON")?;
+        .ok_or_else(|| {
+            Failure::new(
+                ErrorKind::BackendFailed,
+                "backend did not return JSON output",
+            )
+        })?;
+    serde_json::from_str(json_line.trim()).map_err(|err| {
+        Failure::new(
+            ErrorKind::BackendFailed,
+            format!("failed to parse backend response JSON: {err}"),
+        )
+        .into()
+    })
+}
 
-    emit_response(&cli, &parsed)?;
+fn complete_transcription(
+    cli: &TranscribeCli,
+    mut parsed: BackendResponse,
+) -> Result<BackendResponse> {
+    if let Some(url) = &cli.remote_source {
+        parsed.source = url.clone();
+    }
+    parsed.metadata = cli.metadata.clone();
+    parsed.tags = tags::collect(&cli.tags);
+    if cli.dictation {
+        let commands = dictation::Commands::new(&config::load()?.dictation_commands);
+        dictation::apply(&mut parsed, &commands);
+    }
+    itn::apply(&mut parsed, cli.numbers, cli.itn);
+    if let Some(path) = &cli.rules {
+        rules::apply(&mut parsed, path, &rules::load(path)?);
+    }
+    if let Some(command) = cli.post_hook.as_deref().filter(|_| !parsed.skipped) {
+        hooks::post(&mut parsed, command)?;
+    }
+    redact::apply(&mut parsed, &cli.redact);
+    // Summarized after redaction, so personal data never reaches the LLM.
+    if cli.summarize && !parsed.skipped {
+        match summarize::summarize(&config::load()?.summarize, &parsed.transcript) {
+            Ok(summary) => parsed.summary = Some(summary),
+            Err(err) => eprintln!("[parakeet] summarize failed: {err:#}"),
+        }
+    }
+    if let Some(dir) = cli.export_clips.as_deref().filter(|_| !parsed.skipped) {
+        clips::export(&mut parsed, &cli.input, dir, cli.clip_by, cli.verbose)?;
+    }
+    let raw = parsed.clone();
+    finalize_response(cli, &mut parsed)?;
+    if !cli.no_history && !parsed.skipped {
+        let input = cli
+            .remote_source
+            .as_deref()
+            .map_or(cli.input.as_path(), Path::new);
+        match history::record(input, raw, parsed.output_path.clone()) {
+            Ok(id) => parsed.history_id = Some(id),
+            Err(err) => eprintln!("[parakeet] failed recording history: {err:#}"),
+        }
+        archive::run_policy();
+    }
+    Ok(parsed)
+}
 
-    Ok(())
+fn parse_device(raw: &str) -> std::result::Result<String, String> {
+    let device = raw.to_ascii_lowercase();
+    if matches!(device.as_str(), "auto" | "cpu" | "cuda")
+        || preflight::cuda_index(&device).is_some()
+    {
+        return Ok(device);
+    }
+    Err(format!(
+        "invalid device: {raw} (expected auto, cpu, cuda or cuda:N)"
+    ))
+}
+
+fn parse_percent(raw: &str) -> std::result::Result<f64, String> {
+    match raw.trim_end_matches('%').parse::<f64>() {
+        Ok(pct) if (0.0..=100.0).contains(&pct) => Ok(pct),
+        _ => Err(format!("invalid percentage: {raw} (expected 0-100)")),
+    }
+}
+
+/// Languages the local backend's stopword vote (`detect_language`) can
+/// name; `--only-language` with any other code would skip every input.
+const DETECTED_LANGUAGES: [&str; 10] = ["en", "de", "fr", "es", "it", "pt", "nl", "pl", "uk", "ru"];
+
+fn parse_language(raw: &str) -> std::result::Result<String, String> {
+    let lang = raw.to_ascii_lowercase();
+    if lang == "auto"
+        || (2..=3).contains(&lang.len()) && lang.chars().all(|c| c.is_ascii_lowercase())
+    {
+        Ok(lang)
+    } else {
+        Err(format!(
+            "invalid language: {raw} (expected auto or a code such as en, de)"
+        ))
+    }
+}
+
+fn parse_confidence(raw: &str) -> std::result::Result<f64, String> {
+    match raw.parse::<f64>() {
+        Ok(value) if (0.0..=1.0).contains(&value) => Ok(value),
+        _ => Err(format!("invalid confidence: {raw} (expected 0-1)")),
+    }
+}
+
+fn parse_backend_arg(raw: &str) -> std::result::Result<(String, String), String> {
+    match raw.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("invalid backend arg: {raw} (expected KEY=VALUE)")),
+    }
+}
+
+/// Accepts `750`, `12:30`, `00:12:30` or `00:12:30.5` and returns seconds.
+/// Only the last part may have a fraction, and minutes and seconds below a
+/// higher unit must be under 60.
+fn parse_time_arg(raw: &str) -> std::result::Result<f64, String> {
+    let invalid = || format!("invalid time: {raw} (expected SECONDS, MM:SS or HH:MM:SS)");
+    let parts: Vec<&str> = raw.split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+    let mut total = 0.0;
+    for (idx, part) in parts.iter().enumerate() {
+        let last = idx + 1 == parts.len();
+        // Plain digits only: no sign, exponent, `inf` or `nan`.
+        let (whole, fraction) = part.split_once('.').unwrap_or((part, ""));
+        if whole.is_empty()
+            || !whole.chars().all(|c| c.is_ascii_digit())
+            || !fraction.chars().all(|c| c.is_ascii_digit())
+            || (part.contains('.') && (!last || fraction.is_empty()))
+        {
+            return Err(invalid());
+        }
+        let value: f64 = part.parse().map_err(|_| invalid())?;
+        if idx > 0 && value >= 60.0 {
+            return Err(format!(
+                "invalid time: {raw} (minutes and seconds must be below 60)"
+            ));
+        }
+        total = total * 60.0 + value;
+    }
+    if !total.is_finite() {
+        return Err(invalid());
+    }
+    Ok(total)
 }
 
 fn parakeet_home() -> PathBuf {
@@ -526,11 +3277,29 @@ fn default_parakeet_home() -> PathBuf {
     std::env::current_exe()
         .ok()
         .and_then(|path| path.parent().map(PathBuf::from))
-        .unwrap_or_else(|| PathBuf::from("/root/TAO/Tools/parakeet"))
+        .unwrap_or_else(fallback_parakeet_home)
+}
+
+// Only used when the binary cannot locate itself.
+fn fallback_parakeet_home() -> PathBuf {
+    let home = std::env::var_os("HOME").map_or_else(std::env::temp_dir, PathBuf::from);
+    if cfg!(target_os = "macos") {
+        home.join("Library/Application Support/parakeet")
+    } else {
+        home.join(".local/share/parakeet")
+    }
+}
+
+fn venv_python(root_dir: &Path) -> PathBuf {
+    if cfg!(windows) {
+        root_dir.join(".venv/Scripts/python.exe")
+    } else {
+        root_dir.join(".venv/bin/python")
+    }
 }
 
 fn default_socket_path() -> PathBuf {
-    parakeet_home().join("tmp/parakeet.sock")
+    transport::default_endpoint(&parakeet_home())
 }
 
 fn default_pid_path() -> PathBuf {
@@ -541,39 +3310,378 @@ fn default_log_path() -> PathBuf {
     parakeet_home().join("output/parakeetd.log")
 }
 
-fn try_daemon_request(socket_path: &Path, request_json: &str) -> Result<BackendResponse> {
-    let mut stream = UnixStream::connect(socket_path)
-        .with_context(|| format!("daemon socket not reachable: {}", socket_path.display()))?;
-    stream.set_read_timeout(Some(Duration::from_secs(180)))?;
-    stream.set_write_timeout(Some(Duration::from_secs(30)))?;
-    stream.write_all(request_json.as_bytes())?;
-    stream.write_all(b"\n")?;
+/// Writes a frame plus a trailing newline in one write. Pre-framing daemons read
+/// until a newline, so they answer (with a parse error) instead of waiting for
+/// more input; framed daemons may reply and close before reading it, which is
+/// why it must not be a separate write.
+fn send_frame(stream: &mut transport::Stream, kind: MessageType, payload: &[u8]) -> Result<()> {
+    let mut buf = Vec::with_capacity(payload.len() + 16);
+    protocol::write_frame(&mut buf, kind, payload)?;
+    buf.push(b'\n');
+    stream.write_all(&buf)?;
+    Ok(())
+}
+
+struct DaemonClient {
+    read_timeout: Duration,
+    write_timeout: Duration,
+    retries: u32,
+}
 
-    let mut reader = StdBufReader::new(stream);
-    let mut line = String::new();
-    reader.read_line(&mut line)?;
-    if line.trim().is_empty() {
-        bail!("empty daemon response");
+impl DaemonClient {
+    const DEFAULT_READ_TIMEOUT_SEC: f64 = 180.0;
+    const DEFAULT_WRITE_TIMEOUT_SEC: f64 = 30.0;
+    const DEFAULT_RETRIES: u32 = 3;
+    const MAX_BACKOFF: Duration = Duration::from_secs(4);
+
+    fn from_cli(cli: &TranscribeCli) -> Self {
+        Self {
+            read_timeout: Duration::from_secs_f64(
+                cli.daemon_timeout.unwrap_or(Self::DEFAULT_READ_TIMEOUT_SEC),
+            ),
+            write_timeout: Duration::from_secs_f64(
+                cli.daemon_write_timeout
+                    .unwrap_or(Self::DEFAULT_WRITE_TIMEOUT_SEC),
+            ),
+            retries: cli.daemon_retries.unwrap_or(Self::DEFAULT_RETRIES),
+        }
     }
-    let parsed: BackendResponse =
-        serde_json::from_str(line.trim()).context("invalid daemon JSON response")?;
-    Ok(parsed)
+
+    fn request(&self, socket_path: &Path, request_json: &str) -> Result<BackendResponse> {
+        let mut stream = self.connect(socket_path)?;
+        stream.set_timeouts(self.read_timeout, self.write_timeout)?;
+        send_frame(&mut stream, MessageType::Request, request_json.as_bytes())?;
+
+        let mut magic = [0u8; 2];
+        stream
+            .read_exact(&mut magic)
+            .context("daemon closed the connection")?;
+        if magic != protocol::MAGIC {
+            return self.request_legacy(socket_path, request_json);
+        }
+        let frame = protocol::read_frame_after_magic(&mut stream)?;
+        match frame.kind {
+            MessageType::Response => {
+                serde_json::from_slice(&frame.payload).context("invalid daemon JSON response")
+            }
+            MessageType::Error => {
+                bail!("daemon error: {}", daemon_error_message(&frame.payload));
+            }
+            other => bail!("unexpected daemon message type {other:?}"),
+        }
+    }
+
+    fn without_retries(timeout_sec: f64) -> Self {
+        Self {
+            read_timeout: Duration::from_secs_f64(timeout_sec),
+            write_timeout: Duration::from_secs_f64(timeout_sec),
+            retries: 0,
+        }
+    }
+
+    fn ping(&self, socket_path: &Path) -> Result<Pong> {
+        let mut stream = self.connect(socket_path)?;
+        stream.set_timeouts(self.read_timeout, self.write_timeout)?;
+        send_frame(&mut stream, MessageType::Ping, br#"{"type":"ping"}"#)?;
+        let mut magic = [0u8; 2];
+        stream
+            .read_exact(&mut magic)
+            .context("daemon closed the connection")?;
+        if magic != protocol::MAGIC {
+            bail!("daemon predates ping support (restart it to upgrade)");
+        }
+        let frame = protocol::read_frame_after_magic(&mut stream)?;
+        match frame.kind {
+            MessageType::Pong => {}
+            MessageType::Error => bail!("daemon error: {}", daemon_error_message(&frame.payload)),
+            other => bail!("unexpected daemon message type {other:?}"),
+        }
+        serde_json::from_slice(&frame.payload).context("invalid daemon pong")
+    }
+
+    /// Loads `models` (the daemon's own when empty) on every worker and runs a
+    /// warm-up inference with each.
+    fn warm(&self, socket_path: &Path, models: &[String]) -> Result<WarmReport> {
+        let mut stream = self.connect(socket_path)?;
+        stream.set_timeouts(self.read_timeout, self.write_timeout)?;
+        let payload = serde_json::json!({ "models": models }).to_string();
+        send_frame(&mut stream, MessageType::Warm, payload.as_bytes())?;
+        let mut magic = [0u8; 2];
+        stream
+            .read_exact(&mut magic)
+            .context("daemon closed the connection")?;
+        if magic != protocol::MAGIC {
+            bail!("daemon predates warm-up support (restart it to upgrade)");
+        }
+        let frame = protocol::read_frame_after_magic(&mut stream)?;
+        match frame.kind {
+            MessageType::Response => {
+                serde_json::from_slice(&frame.payload).context("invalid daemon warm-up report")
+            }
+            MessageType::Error => bail!("daemon error: {}", daemon_error_message(&frame.payload)),
+            other => bail!("unexpected daemon message type {other:?}"),
+        }
+    }
+
+    /// Newline-delimited JSON, for daemons started before the framed protocol.
+    fn request_legacy(&self, socket_path: &Path, request_json: &str) -> Result<BackendResponse> {
+        let mut stream = self.connect(socket_path)?;
+        stream.set_timeouts(self.read_timeout, self.write_timeout)?;
+        stream.write_all(request_json.as_bytes())?;
+        stream.write_all(b"\n")?;
+
+        let mut reader = StdBufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        if line.trim().is_empty() {
+            bail!("empty daemon response");
+        }
+        let parsed: BackendResponse =
+            serde_json::from_str(line.trim()).context("invalid daemon JSON response")?;
+        Ok(parsed)
+    }
+
+    /// Connects with bounded exponential backoff on transient failures, e.g. a
+    /// daemon whose pidfile is live but whose socket is not listening yet.
+    fn connect(&self, socket_path: &Path) -> Result<transport::Stream> {
+        let mut backoff = Duration::from_millis(250);
+        let mut attempt = 0;
+        loop {
+            let err = match transport::connect(socket_path) {
+                Ok(stream) => return Ok(stream),
+                Err(err) => err,
+            };
+            if attempt >= self.retries || !is_transient_connect_error(&err) {
+                return Err(err).with_context(|| {
+                    format!("daemon socket not reachable: {}", socket_path.display())
+                });
+            }
+            attempt += 1;
+            std::thread::sleep(backoff);
+            backoff = (backoff * 2).min(Self::MAX_BACKOFF);
+        }
+    }
+}
+
+fn is_transient_connect_error(err: &std::io::Error) -> bool {
+    use std::io::ErrorKind as IoErrorKind;
+    match err.kind() {
+        IoErrorKind::ConnectionRefused | IoErrorKind::WouldBlock | IoErrorKind::Interrupted => true,
+        // No socket yet: only worth waiting for if a daemon is starting up.
+        IoErrorKind::NotFound => is_pidfile_running(&default_pid_path()).unwrap_or(false),
+        _ => false,
+    }
+}
+
+fn finalize_response(cli: &TranscribeCli, parsed: &mut BackendResponse) -> Result<()> {
+    cli.render.mask(parsed)?;
+    let title = output::derive_title(&parsed.transcript);
+    // Built from the plain transcript and segments, before rendering replaces them.
+    let note = cli.obsidian_vault.as_ref().map(|_| {
+        let date = output::unix_now();
+        obsidian::note(parsed, &cli.render.options(), date)
+    });
+    parsed.transcript = output::render(cli.format, parsed, &cli.render.options());
+    parsed.format = cli.format.as_str().to_string();
+    if let Some(min) = cli.render.min_confidence {
+        parsed.warnings = output::low_confidence(parsed.segments.as_deref(), min);
+    }
+    if let Some(want) = cli.only_language.as_deref()
+        && parsed.language.is_none()
+    {
+        eprintln!(
+            "[parakeet] warning: --only-language {want}: could not detect the language of {}; keeping it",
+            parsed.source
+        );
+    }
+
+    if parsed.skipped {
+        return Ok(());
+    }
+    let mut sinks: Vec<Box<dyn Sink>> = Vec::new();
+    let mut file = resolve_output_path(cli, title.as_deref())?;
+    let mut unchanged = false;
+    // Markdown keeps chapters as sections of one document.
+    let by_chapter = !parsed.chapters.is_empty() && !matches!(cli.format, OutputFormat::Md);
+    if let (Some(path), Some(pct)) = (&file, cli.diff_guard)
+        && path.exists()
+        && !by_chapter
+    {
+        match output::guard_existing(path, &parsed.transcript, pct)? {
+            output::Guarded::Replace => {}
+            output::Guarded::Unchanged => unchanged = true,
+            output::Guarded::Kept(new) => file = Some(new),
+        }
+    }
+    if let Some(path) = file.as_ref().filter(|_| !unchanged && !by_chapter) {
+        sinks.push(Box::new(sink::FileSink(path.clone())));
+    }
+    // Text mode always prints the transcript (see emit_response).
+    for spec in cli.sinks.iter().filter(|spec| !sink::is_stdout(spec)) {
+        sinks.push(sink::parse(spec)?);
+    }
+    let name = output_name(cli, title.as_deref())?;
+    if by_chapter {
+        let delivered = deliver_chapters(cli, parsed, file.as_deref(), &name, &sinks)?;
+        parsed.delivered_to.extend(delivered);
+        file = None;
+    } else {
+        let doc = sink::Document {
+            content: &parsed.transcript,
+            name: &name,
+            format: cli.format,
+            source: &parsed.source,
+        };
+        for target in &sinks {
+            parsed.delivered_to.push(target.deliver(&doc)?);
+        }
+    }
+    if let (Some(vault), Some(note)) = (&cli.obsidian_vault, note) {
+        let name = output::expand_name_template(
+            obsidian::NAME_TEMPLATE,
+            &output::NameContext {
+                input: &cli.input,
+                title: title.as_deref(),
+                ext: "md",
+                meta: &cli.metadata,
+            },
+        )?;
+        parsed
+            .delivered_to
+            .push(obsidian::write(vault, &cli.obsidian_folder, &name, &note)?);
+    }
+    parsed.output_path = file.map(|path| path.display().to_string());
+    Ok(())
+}
+
+/// `--by-chapter` outside md: one document per chapter, numbered and titled.
+fn deliver_chapters(
+    cli: &TranscribeCli,
+    parsed: &BackendResponse,
+    file: Option<&Path>,
+    name: &str,
+    sinks: &[Box<dyn Sink>],
+) -> Result<Vec<String>> {
+    let mut delivered = Vec::new();
+    for (idx, chapter) in parsed.chapters.iter().enumerate() {
+        let part = chapters::slice(parsed, idx);
+        if part.segments.as_ref().is_none_or(Vec::is_empty) {
+            continue;
+        }
+        let content = output::render(cli.format, &part, &cli.render.options());
+        let doc = sink::Document {
+            content: &content,
+            name: &chapters::file_name(name, idx, chapter),
+            format: cli.format,
+            source: &parsed.source,
+        };
+        if let Some(path) = file {
+            let file_name = path.file_name().map_or_else(
+                || name.to_string(),
+                |file_name| file_name.to_string_lossy().into_owned(),
+            );
+            let path = path.with_file_name(chapters::file_name(&file_name, idx, chapter));
+            delivered.push(sink::FileSink(path).deliver(&doc)?);
+        }
+        for target in sinks {
+            delivered.push(target.deliver(&doc)?);
+        }
+    }
+    Ok(delivered)
+}
+
+/// File/object name for the output: the name template if any, else `{stem}.{ext}`.
+fn output_name(cli: &TranscribeCli, title: Option<&str>) -> Result<String> {
+    let template = match (&cli.name_template, cli.auto_name) {
+        (Some(template), _) => template.as_str(),
+        (None, true) => "{title}.{ext}",
+        (None, false) => "{stem}.{ext}",
+    };
+    output::expand_name_template(
+        template,
+        &output::NameContext {
+            input: &cli.input,
+            title: if cli.auto_name { title } else { None },
+            ext: output::extension_for(cli.format),
+            meta: &cli.metadata,
+        },
+    )
+}
+
+fn resolve_output_path(cli: &TranscribeCli, title: Option<&str>) -> Result<Option<PathBuf>> {
+    if cli.name_template.is_none() && !cli.auto_name {
+        return Ok(cli.out.clone());
+    }
+    let name = output_name(cli, title)?;
+    // With a name template, --out names the target directory.
+    let dir = cli.out.clone().unwrap_or_else(|| {
+        cli.input
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."))
+    });
+    if cli.diff_guard.is_some() {
+        return Ok(Some(dir.join(name)));
+    }
+    Ok(Some(output::unique_path(&dir, &name)))
 }
 
 fn emit_response(cli: &TranscribeCli, parsed: &BackendResponse) -> Result<()> {
     match cli.emit {
         EmitMode::Text => {
-            println!("{}", parsed.transcript);
+            if parsed.language_mismatch {
+                eprintln!(
+                    "[parakeet] language mismatch: detected={} expected={} ({})",
+                    parsed.language.as_deref().unwrap_or("unknown"),
+                    cli.only_language.as_deref().unwrap_or_default(),
+                    parsed.source,
+                );
+            }
+            if let Some(expected) = cli.language.as_deref().filter(|lang| *lang != "auto")
+                && let Some(detected) = parsed.language.as_deref()
+                && detected != expected
+            {
+                eprintln!(
+                    "[parakeet] warning: detected language {detected}, expected {expected} ({})",
+                    parsed.source,
+                );
+            }
+            if parsed.skipped {
+                return Ok(());
+            }
+            sink::StdoutSink.deliver(&sink::Document {
+                content: &parsed.transcript,
+                name: "",
+                format: cli.format,
+                source: &parsed.source,
+            })?;
+            if cli.verbose {
+                // Printed client-side: in daemon mode the backend's stderr goes to the daemon log.
+                for step in &parsed.preprocessing {
+                    let changes = if step.changes.is_empty() {
+                        "format unchanged".to_string()
+                    } else {
+                        step.changes.join(", ")
+                    };
+                    eprintln!(
+                        "[parakeet preprocessing] {} ({}): {changes}",
+                        step.name, step.tool
+                    );
+                }
+            }
             if cli.verbose
                 && let Some(m) = &parsed.metrics
             {
                 eprintln!(
-                    "[parakeet metrics] load={:.2}s infer={:.2}s total={:.2}s audio={}",
+                    "[parakeet metrics] load={:.2}s infer={:.2}s total={:.2}s audio={} speed={}",
                     m.model_load_sec,
                     m.inference_sec,
                     m.total_sec,
                     m.audio_sec
                         .map(|x| format!("{x:.2}s"))
+                        .unwrap_or_else(|| "n/a".to_string()),
+                    m.realtime_factor
+                        .map(|x| format!("{x:.1}x"))
                         .unwrap_or_else(|| "n/a".to_string())
                 );
             }
@@ -588,6 +3696,7 @@ fn emit_response(cli: &TranscribeCli, parsed: &BackendResponse) -> Result<()> {
 
 fn prepare_vocab_file(
     root_dir: &Path,
+    work_dir: &Path,
     user_vocab: Option<&Path>,
     use_library: bool,
 ) -> Result<Option<PathBuf>> {
@@ -621,10 +3730,7 @@ fn prepare_vocab_file(
         }
     }
 
-    let tmp_dir = root_dir.join("tmp");
-    fs::create_dir_all(&tmp_dir)
-        .with_context(|| format!("failed creating tmp dir: {}", tmp_dir.display()))?;
-    let merged_path = tmp_dir.join("merged_vocab.txt");
+    let merged_path = work_dir.join("merged_vocab.txt");
     let mut out = String::new();
     for term in merged {
         out.push_str(&term);
@@ -634,3 +3740,36 @@ fn prepare_vocab_file(
         .with_context(|| format!("failed writing merged vocab: {}", merged_path.display()))?;
     Ok(Some(merged_path))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_time_args() {
+        let cases = [
+            ("750", Some(750.0)),
+            ("0.5", Some(0.5)),
+            ("12:30", Some(750.0)),
+            ("00:12:30", Some(750.0)),
+            ("01:00:00.25", Some(3600.25)),
+            ("90:00", Some(5400.0)),
+            ("1:-5", None),
+            ("-5", None),
+            ("+5", None),
+            ("nan", None),
+            ("inf", None),
+            ("1e3", None),
+            ("1:60", None),
+            ("1:75:00", None),
+            ("1.5:00", None),
+            ("5.", None),
+            ("", None),
+            ("1::2", None),
+            ("1:2:3:4", None),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(parse_time_arg(raw).ok(), expected, "{raw}");
+        }
+    }
+}
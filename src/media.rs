@@ -0,0 +1,336 @@
+use std::fs::File;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use symphonia::core::codecs::{CODEC_TYPE_NULL, CODEC_TYPE_OPUS, DecoderOptions};
+use symphonia::core::errors::Error as DecodeError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey};
+use symphonia::core::probe::Hint;
+
+use crate::error::{ErrorKind, Failure};
+use crate::{output, tools};
+
+// Extensions the backend decodes directly; anything else goes through ffmpeg
+// (mirrors `normalize_audio` in parakeet_backend.py).
+const NATIVE_AUDIO: &[&str] = &["wav", "flac", "mp3", "m4a", "ogg"];
+
+pub fn needs_conversion(path: &Path) -> bool {
+    !path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| NATIVE_AUDIO.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+// Packets read while looking for decodable audio; some formats lead with
+// empty or priming packets.
+const PROBE_PACKETS: usize = 64;
+
+/// Fails fast on inputs the backend would only reject after loading the
+/// model: empty files, files that are not audio, and codecs it cannot
+/// decode. Natively decoded formats are probed with symphonia; others go
+/// through ffmpeg anyway, so ffprobe only checks for an audio stream.
+pub fn validate(path: &Path) -> Result<()> {
+    let invalid = |reason: String| {
+        Failure::new(
+            ErrorKind::InvalidInput,
+            format!("invalid input {}: {reason}", path.display()),
+        )
+    };
+    let size = std::fs::metadata(path)
+        .with_context(|| format!("failed reading {}", path.display()))?
+        .len();
+    if size == 0 {
+        bail!(invalid("the file is empty".to_string()));
+    }
+    if needs_conversion(path) {
+        if tools::find(&tools::FFPROBE).is_some() && probe_channels(path).is_err() {
+            bail!(invalid("no audio stream found".to_string()));
+        }
+        return Ok(());
+    }
+
+    let file = File::open(path).with_context(|| format!("failed opening {}", path.display()))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+    let Ok(probed) = symphonia::default::get_probe().format(
+        &hint,
+        stream,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    ) else {
+        bail!(invalid("not a recognized audio file".to_string()));
+    };
+    let mut format = probed.format;
+    let Some(track) = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+    else {
+        bail!(invalid("no audio track".to_string()));
+    };
+    if track.codec_params.n_frames == Some(0) {
+        bail!(invalid("the audio is zero-length".to_string()));
+    }
+    // Opus decodes in the backend (libsndfile) but not in symphonia.
+    if track.codec_params.codec == CODEC_TYPE_OPUS {
+        return Ok(());
+    }
+    let track_id = track.id;
+    let codecs = symphonia::default::get_codecs();
+    let Ok(mut decoder) = codecs.make(&track.codec_params, &DecoderOptions::default()) else {
+        let name = codecs
+            .get_codec(track.codec_params.codec)
+            .map_or("unknown", |codec| codec.short_name);
+        bail!(
+            invalid(format!("unsupported codec {name}"))
+                .with_hint("convert it to WAV or FLAC with ffmpeg first")
+        );
+    };
+    for _ in 0..PROBE_PACKETS {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                bail!(invalid("the audio is zero-length".to_string()));
+            }
+            Err(err) => bail!(invalid(format!("unreadable audio ({err})"))),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        match decoder.decode(&packet) {
+            Ok(decoded) if decoded.frames() > 0 => return Ok(()),
+            Ok(_) | Err(DecodeError::DecodeError(_)) => {}
+            Err(err) => bail!(invalid(format!("undecodable audio ({err})"))),
+        }
+    }
+    Ok(())
+}
+
+/// Duration in seconds, from the stream header for natively decoded formats
+/// (no external tool needed) and via ffprobe otherwise.
+pub fn audio_sec(path: &Path) -> Option<f64> {
+    let native = (!needs_conversion(path))
+        .then(|| header_duration(path))
+        .flatten();
+    native.or_else(|| probe_duration(path).ok())
+}
+
+fn header_duration(path: &Path) -> Option<f64> {
+    let stream = MediaSourceStream::new(Box::new(File::open(path).ok()?), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .ok()?;
+    let params = &probed.format.default_track()?.codec_params;
+    Some(params.n_frames? as f64 / f64::from(params.sample_rate?))
+}
+
+/// Container duration in seconds via `ffprobe`.
+pub fn probe_duration(path: &Path) -> Result<f64> {
+    let ffprobe = tools::require(&tools::FFPROBE)?;
+    let output = Command::new(ffprobe)
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+        ])
+        .arg(path)
+        .output()
+        .context("failed running ffprobe")?;
+    if !output.status.success() {
+        bail!(
+            "ffprobe failed for {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let raw = String::from_utf8_lossy(&output.stdout);
+    raw.trim()
+        .parse::<f64>()
+        .with_context(|| format!("ffprobe returned no duration for {}", path.display()))
+}
+
+/// Channel count of the first audio stream via `ffprobe`.
+pub fn probe_channels(path: &Path) -> Result<u32> {
+    let ffprobe = tools::require(&tools::FFPROBE)?;
+    let output = Command::new(ffprobe)
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "a:0",
+            "-show_entries",
+            "stream=channels",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+        ])
+        .arg(path)
+        .output()
+        .context("failed running ffprobe")?;
+    if !output.status.success() {
+        bail!(
+            "ffprobe failed for {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let raw = String::from_utf8_lossy(&output.stdout);
+    raw.trim()
+        .parse::<u32>()
+        .with_context(|| format!("ffprobe found no audio stream in {}", path.display()))
+}
+
+/// The container's `creation_time` tag (unix seconds), which phones and
+/// recorders set to when recording started.
+pub fn probe_creation_time(path: &Path) -> Option<u64> {
+    let ffprobe = tools::require(&tools::FFPROBE).ok()?;
+    let output = Command::new(ffprobe)
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format_tags=creation_time",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+        ])
+        .arg(path)
+        .output()
+        .ok()?;
+    let raw = String::from_utf8_lossy(&output.stdout);
+    // `2026-10-16T14:03:11.000000Z`; drop the fraction.
+    let stamp = raw.trim().split('.').next()?.trim_end_matches('Z');
+    output::parse_datetime(&stamp.replacen('T', " ", 1))
+}
+
+/// Descriptive tags of the recording itself (ID3, MP4 `ilst`, Vorbis
+/// comments, RIFF INFO), so a transcript says what it is a transcript of.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct MediaTags {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artist: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+
+impl MediaTags {
+    pub fn is_empty(&self) -> bool {
+        self.fields().iter().all(|(_, value)| value.is_none())
+    }
+
+    /// `(label, value)` in display order.
+    pub fn fields(&self) -> [(&'static str, Option<&str>); 4] {
+        [
+            ("Title", self.title.as_deref()),
+            ("Artist", self.artist.as_deref()),
+            ("Album", self.album.as_deref()),
+            ("Date", self.date.as_deref()),
+        ]
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        // RIFF INFO strings keep their NUL padding.
+        let value = value.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+        let slot = match key.to_ascii_lowercase().as_str() {
+            "title" => &mut self.title,
+            "artist" | "album_artist" => &mut self.artist,
+            "album" => &mut self.album,
+            "date" | "year" => &mut self.date,
+            _ => return,
+        };
+        if slot.is_none() && !value.is_empty() {
+            *slot = Some(value.to_string());
+        }
+    }
+}
+
+/// The input's tags, read in-process for natively decoded formats and via
+/// ffprobe (when installed) otherwise. Untagged or unreadable files give
+/// empty tags.
+pub fn tags(path: &Path) -> MediaTags {
+    let native = (!needs_conversion(path))
+        .then(|| header_tags(path))
+        .flatten()
+        .filter(|tags| !tags.is_empty());
+    native.or_else(|| probe_tags(path)).unwrap_or_default()
+}
+
+fn header_tags(path: &Path) -> Option<MediaTags> {
+    let stream = MediaSourceStream::new(Box::new(File::open(path).ok()?), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+    let mut probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .ok()?;
+    let mut tags = MediaTags::default();
+    let mut read = |revision: &MetadataRevision| {
+        for tag in revision.tags() {
+            let key = match tag.std_key {
+                Some(StandardTagKey::TrackTitle) => "title",
+                Some(StandardTagKey::Artist) => "artist",
+                Some(StandardTagKey::AlbumArtist) => "album_artist",
+                Some(StandardTagKey::Album) => "album",
+                Some(StandardTagKey::Date | StandardTagKey::ReleaseDate) => "date",
+                _ => continue,
+            };
+            tags.set(key, &tag.value.to_string());
+        }
+    };
+    // Container tags first; an ID3 block ahead of the stream fills the gaps.
+    if let Some(revision) = probed.format.metadata().current() {
+        read(revision);
+    }
+    if let Some(revision) = probed
+        .metadata
+        .get()
+        .as_ref()
+        .and_then(|meta| meta.current())
+    {
+        read(revision);
+    }
+    Some(tags)
+}
+
+fn probe_tags(path: &Path) -> Option<MediaTags> {
+    let ffprobe = tools::find(&tools::FFPROBE)?;
+    let output = Command::new(ffprobe)
+        .args(["-v", "error", "-show_entries", "format_tags", "-of", "json"])
+        .arg(path)
+        .output()
+        .ok()?;
+    let raw: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    let mut tags = MediaTags::default();
+    for (key, value) in raw["format"]["tags"].as_object()? {
+        if let Some(value) = value.as_str() {
+            tags.set(key, value);
+        }
+    }
+    Some(tags)
+}
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+
+pub type Metadata = BTreeMap<String, String>;
+
+// CSV columns that identify which recording a manifest row describes.
+const FILE_COLUMNS: &[&str] = &["file", "filename", "input", "path"];
+
+/// Loads `{meta.*}` template values for `input` from a sidecar JSON object or
+/// a CSV manifest.
+///
+/// A JSON file is either the metadata object itself or an object keyed by
+/// input file name. A CSV manifest picks the row whose `file` (or `filename`,
+/// `input`, `path`) column names the input; a single-row CSV applies as is.
+pub fn load(path: &Path, input: &Path) -> Result<Metadata> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("failed reading metadata: {}", path.display()))?;
+    let is_csv = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+    if is_csv {
+        from_csv(&raw, input)
+    } else {
+        from_json(&raw, input)
+    }
+    .with_context(|| format!("failed loading metadata from {}", path.display()))
+}
+
+fn from_json(raw: &str, input: &Path) -> Result<Metadata> {
+    let value: Value = serde_json::from_str(raw).context("invalid JSON")?;
+    let Value::Object(object) = value else {
+        bail!("expected a JSON object");
+    };
+    let keyed = input_names(input)
+        .iter()
+        .find_map(|name| object.get(name.as_str()).and_then(Value::as_object));
+    Ok(keyed
+        .unwrap_or(&object)
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            (key.clone(), value)
+        })
+        .collect())
+}
+
+fn from_csv(raw: &str, input: &Path) -> Result<Metadata> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(raw.as_bytes());
+    let headers = reader.headers().context("invalid CSV header")?.clone();
+    let rows = reader
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .context("invalid CSV row")?;
+    let file_column = headers
+        .iter()
+        .position(|h| FILE_COLUMNS.contains(&h.to_lowercase().as_str()));
+    let row = match file_column {
+        Some(col) => {
+            let names = input_names(input);
+            rows.iter()
+                .find(|row| row.get(col).is_some_and(|v| names.iter().any(|n| n == v)))
+        }
+        None if rows.len() == 1 => rows.first(),
+        None => bail!(
+            "manifest has several rows but no file column ({})",
+            FILE_COLUMNS.join(", ")
+        ),
+    };
+    let Some(row) = row else {
+        bail!("no manifest row for {}", input.display());
+    };
+    Ok(headers
+        .iter()
+        .zip(row.iter())
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect())
+}
+
+// Names a manifest may use for the input: full path, file name, or stem.
+fn input_names(input: &Path) -> Vec<String> {
+    let mut names = vec![input.display().to_string()];
+    names.extend(input.file_name().map(|n| n.to_string_lossy().into_owned()));
+    names.extend(input.file_stem().map(|n| n.to_string_lossy().into_owned()));
+    names
+}
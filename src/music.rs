@@ -0,0 +1,231 @@
+//! `--skip-music`: a lightweight music/speech classifier, so podcast intros,
+//! jingles and hold music are neither transcribed nor hallucinated over.
+//!
+//! Each second of audio is judged on two classic cues over its 30 ms frames:
+//! speech keeps dipping between syllables (many frames well below the
+//! second's mean energy) and alternates voiced and unvoiced sounds (a
+//! zero-crossing rate that varies a lot), where music is sustained and
+//! steady. Seconds are smoothed, and only long music runs count, so a
+//! sung phrase or a beat under speech stays in.
+
+use crate::vad::{FRAME_SEC, Frame};
+use crate::{BackendResponse, Segment};
+
+/// Text of the segment standing in for a skipped music region.
+pub const MARKER: &str = "[music]";
+
+const WINDOW_SEC: f64 = 1.0;
+// Below this share of low-energy frames and crossing-rate variation, a
+// second sounds like music.
+const MAX_LOW_ENERGY_SHARE: f64 = 0.2;
+const MAX_CROSSING_VARIATION: f64 = 0.5;
+// Quieter seconds are silence, not music.
+const MIN_LEVEL_DB: f64 = -50.0;
+// Seconds on each side voting in the smoothing.
+const SMOOTHING: usize = 2;
+const MIN_MUSIC_SEC: f64 = 8.0;
+// Trimmed off both ends so speech over a fade is kept.
+const EDGE_SEC: f64 = 0.5;
+
+/// Absolute `(start, end)` of the music regions within `range`.
+pub fn regions(frames: &[Frame], (range_start, range_end): (f64, f64)) -> Vec<(f64, f64)> {
+    let per_window = (WINDOW_SEC / FRAME_SEC).round() as usize;
+    let votes: Vec<bool> = frames.chunks(per_window).map(is_music).collect();
+    let smoothed: Vec<bool> = (0..votes.len())
+        .map(|idx| {
+            let around =
+                &votes[idx.saturating_sub(SMOOTHING)..(idx + SMOOTHING + 1).min(votes.len())];
+            around.iter().filter(|vote| **vote).count() * 2 > around.len()
+        })
+        .collect();
+
+    let window_sec = per_window as f64 * FRAME_SEC;
+    let mut found = Vec::new();
+    let mut run_start = None;
+    for (idx, music) in smoothed.iter().chain([&false]).enumerate() {
+        match (music, run_start) {
+            (true, None) => run_start = Some(idx),
+            (false, Some(first)) => {
+                run_start = None;
+                let start = range_start + first as f64 * window_sec;
+                let end = (range_start + idx as f64 * window_sec).min(range_end);
+                if end - start >= MIN_MUSIC_SEC {
+                    // The recording's own edges need no margin.
+                    let start = if first == 0 { start } else { start + EDGE_SEC };
+                    let end = if end >= range_end {
+                        end
+                    } else {
+                        end - EDGE_SEC
+                    };
+                    found.push((start, end));
+                }
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+/// Adds a `[music]` segment per region and rebuilds the transcript in time
+/// order.
+pub fn mark(parsed: &mut BackendResponse, music: &[(f64, f64)]) {
+    if music.is_empty() {
+        return;
+    }
+    let segments = parsed.segments.get_or_insert_with(Vec::new);
+    segments.extend(music.iter().map(|&(start, end)| Segment {
+        start,
+        end,
+        text: MARKER.to_string(),
+        confidence: None,
+        speaker: None,
+    }));
+    segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+    parsed.transcript = segments
+        .iter()
+        .map(|seg| seg.text.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+}
+
+fn is_music(window: &[Frame]) -> bool {
+    if window.len() < 2 {
+        return false;
+    }
+    let count = window.len() as f64;
+    let energies: Vec<f64> = window
+        .iter()
+        .map(|frame| 10f64.powf(frame.level_db / 10.0))
+        .collect();
+    let mean_energy = energies.iter().sum::<f64>() / count;
+    if 10.0 * mean_energy.max(1e-10).log10() < MIN_LEVEL_DB {
+        return false;
+    }
+    let low_energy_share =
+        energies.iter().filter(|e| **e < mean_energy * 0.5).count() as f64 / count;
+
+    let mean_rate = window.iter().map(|frame| frame.crossing_rate).sum::<f64>() / count;
+    let variance = window
+        .iter()
+        .map(|frame| (frame.crossing_rate - mean_rate).powi(2))
+        .sum::<f64>()
+        / count;
+    let variation = if mean_rate > 0.0 {
+        variance.sqrt() / mean_rate
+    } else {
+        0.0
+    };
+    low_energy_share < MAX_LOW_ENERGY_SHARE && variation < MAX_CROSSING_VARIATION
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Frames per classified second, and that second's length.
+    const PER_WINDOW: usize = 33;
+    const WINDOW: f64 = PER_WINDOW as f64 * FRAME_SEC;
+
+    /// Sustained level and steady crossing rate.
+    fn music(secs: usize) -> Vec<Frame> {
+        (0..secs * PER_WINDOW)
+            .map(|_| Frame {
+                level_db: -20.0,
+                crossing_rate: 0.1,
+            })
+            .collect()
+    }
+
+    /// Dips between syllables and alternating voiced/unvoiced frames.
+    fn speech(secs: usize) -> Vec<Frame> {
+        (0..secs * PER_WINDOW)
+            .map(|idx| Frame {
+                level_db: if idx % 3 == 0 { -45.0 } else { -20.0 },
+                crossing_rate: if idx % 2 == 0 { 0.05 } else { 0.4 },
+            })
+            .collect()
+    }
+
+    fn concat<const N: usize>(parts: [Vec<Frame>; N]) -> Vec<Frame> {
+        parts.into_iter().flatten().collect()
+    }
+
+    fn assert_regions(actual: &[(f64, f64)], expected: &[(f64, f64)]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a.0 - e.0).abs() < 1e-6 && (a.1 - e.1).abs() < 1e-6,
+                "{actual:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn classifies_single_seconds() {
+        assert!(is_music(&music(1)));
+        assert!(!is_music(&speech(1)));
+        let quiet: Vec<Frame> = music(1)
+            .into_iter()
+            .map(|frame| Frame {
+                level_db: -70.0,
+                ..frame
+            })
+            .collect();
+        assert!(!is_music(&quiet));
+    }
+
+    #[test]
+    fn short_music_run_stays_speech() {
+        let frames = concat([speech(5), music(6), speech(5)]);
+        assert!(regions(&frames, (0.0, 16.0 * WINDOW)).is_empty());
+    }
+
+    #[test]
+    fn long_music_run_is_one_region_trimmed_at_inner_edges() {
+        let frames = concat([speech(5), music(12), speech(5)]);
+        assert_regions(
+            &regions(&frames, (10.0, 10.0 + 22.0 * WINDOW)),
+            &[(
+                10.0 + 5.0 * WINDOW + EDGE_SEC,
+                10.0 + 17.0 * WINDOW - EDGE_SEC,
+            )],
+        );
+        // An intro at the start of the range keeps its outer edge.
+        let frames = concat([music(10), speech(5)]);
+        assert_regions(
+            &regions(&frames, (0.0, 15.0 * WINDOW)),
+            &[(0.0, 10.0 * WINDOW - EDGE_SEC)],
+        );
+    }
+
+    #[test]
+    fn markers_are_placed_in_time_order() {
+        let mut parsed: BackendResponse = serde_json::from_value(serde_json::json!({
+            "transcript": "Welcome back. Thanks for listening.",
+            "source": "test",
+            "model": "",
+            "device": "",
+            "format": "json",
+            "segments": [
+                {"start": 12.0, "end": 14.0, "text": "Welcome back."},
+                {"start": 40.0, "end": 42.0, "text": "Thanks for listening."},
+            ],
+        }))
+        .unwrap();
+        mark(&mut parsed, &[(0.0, 11.5), (20.0, 38.0)]);
+        let texts: Vec<&str> = parsed
+            .segments
+            .iter()
+            .flatten()
+            .map(|seg| seg.text.as_str())
+            .collect();
+        assert_eq!(
+            texts,
+            [MARKER, "Welcome back.", MARKER, "Thanks for listening."]
+        );
+        assert_eq!(
+            parsed.transcript,
+            "[music] Welcome back. [music] Thanks for listening."
+        );
+    }
+}
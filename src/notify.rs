@@ -0,0 +1,90 @@
+//! Telling someone a transcription is done:
+//!
+//! - `--webhook URL` (or `webhook_url` in config.json): POSTs a completion
+//!   event when a transcription finishes or fails, so automations (n8n,
+//!   Zapier, scripts) need not poll. Unlike a `webhook:` sink, which delivers
+//!   the rendered document, this fires on failures too and carries the JSON
+//!   result.
+//! - `--notify`: a desktop notification through notify-send (libnotify) or
+//!   osascript (macOS), for long interactive runs.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{BackendResponse, error, tools};
+
+/// The event body: `transcription.completed` with the output path and the
+/// full result, or `transcription.failed` with the `--emit json` error object.
+pub fn event(input: &str, result: Result<&BackendResponse, &anyhow::Error>) -> serde_json::Value {
+    match result {
+        Ok(parsed) => serde_json::json!({
+            "event": "transcription.completed",
+            "input": input,
+            "output_path": parsed.output_path,
+            "history_id": parsed.history_id,
+            "result": parsed,
+        }),
+        Err(err) => serde_json::json!({
+            "event": "transcription.failed",
+            "input": input,
+            "error": error::to_json(err)["error"],
+        }),
+    }
+}
+
+/// Shows a desktop notification.
+pub fn desktop(title: &str, body: &str) -> Result<()> {
+    let notifier = tools::require(&tools::NOTIFIER)?;
+    let mut cmd = Command::new(&notifier);
+    if notifier.file_stem().is_some_and(|stem| stem == "osascript") {
+        let quote = |raw: &str| raw.replace('\\', "\\\\").replace('"', "\\\"");
+        cmd.arg("-e").arg(format!(
+            "display notification \"{}\" with title \"{}\"",
+            quote(body),
+            quote(title)
+        ));
+    } else {
+        cmd.args(["--app-name", "parakeet", title, body]);
+    }
+    let output = cmd
+        .stdin(Stdio::null())
+        .output()
+        .context("failed running the desktop notifier")?;
+    if !output.status.success() {
+        bail!(
+            "desktop notification failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+pub fn post(url: &str, event: &serde_json::Value) -> Result<()> {
+    let mut child = Command::new(tools::require(&tools::CURL)?)
+        .args(["-sS", "-f", "--max-time", "30", "-X", "POST"])
+        .args([
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+        ])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed running curl for --webhook")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(event.to_string().as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "webhook {url} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! `--obsidian-vault PATH`: files the transcript as a note in an Obsidian
+//! vault, with frontmatter, a backlink to the daily note and one heading per
+//! paragraph carrying its start time, so voice memos land as notes directly.
+
+use std::path::Path;
+
+use anyhow::{Result, bail};
+
+use crate::BackendResponse;
+use crate::output::{self, RenderOptions};
+
+/// Folder inside the vault when config.json names none.
+pub const DEFAULT_FOLDER: &str = "Transcripts";
+// Paragraphs break at pauses this long unless --paragraph-gap says otherwise.
+const DEFAULT_GAP_SEC: f64 = 1.5;
+
+/// Fails before transcribing when the vault is not a directory.
+pub fn check(vault: &Path) -> Result<()> {
+    if !vault.is_dir() {
+        bail!("obsidian vault not found: {}", vault.display());
+    }
+    Ok(())
+}
+
+/// File name of a note: `<YYYY-MM-DD> <title>.md`, the title falling back to
+/// the input's stem.
+pub const NAME_TEMPLATE: &str = "{date} {title}.md";
+
+/// Writes `note` as `<vault>/<folder>/<name>`, never overwriting; returns the path.
+pub fn write(vault: &Path, folder: &str, name: &str, note: &str) -> Result<String> {
+    let path = output::unique_path(&vault.join(folder), name);
+    output::write_output(&path, note)?;
+    Ok(path.display().to_string())
+}
+
+/// The note for `parsed` (plain transcript and segments, before rendering).
+pub fn note(parsed: &BackendResponse, opts: &RenderOptions, date: u64) -> String {
+    let mut out = output::frontmatter(parsed, date);
+    let day = output::format_date(date);
+    out.push_str(&format!("Daily note: [[{day}]]\n"));
+    let gap = opts.paragraph_gap.unwrap_or(DEFAULT_GAP_SEC);
+    let mut last_end = None;
+    match parsed.segments.as_deref().filter(|s| !s.is_empty()) {
+        Some(segments) => {
+            for seg in segments {
+                if last_end.is_none_or(|end| seg.start - end >= gap) {
+                    out.push_str(&format!("\n## {}\n\n", output::format_clock(seg.start)));
+                } else {
+                    out.push(' ');
+                }
+                out.push_str(seg.text.trim());
+                last_end = Some(seg.end);
+            }
+            out.push('\n');
+        }
+        None => out.push_str(&format!("\n{}\n", parsed.transcript.trim())),
+    }
+    if let Some(summary) = &parsed.summary {
+        out.push_str(&format!("\n## Summary\n\n{}\n", summary.trim()));
+    }
+    out
+}
@@ -0,0 +1,681 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::media::MediaTags;
+use crate::metadata::Metadata;
+use crate::{BackendResponse, OutputFormat, Segment, Warning, chapters, subtitle};
+
+const TITLE_MAX_WORDS: usize = 8;
+const TITLE_MAX_CHARS: usize = 60;
+
+#[derive(Clone, Copy)]
+pub struct RenderOptions {
+    pub timestamps: bool,
+    pub paragraph_gap: Option<f64>,
+    pub sentences: bool,
+    /// Segments scoring below this are wrapped in `<mark>` (md only).
+    pub min_confidence: Option<f64>,
+    /// Start md output with YAML frontmatter dated at these Unix seconds.
+    pub frontmatter: Option<u64>,
+}
+
+/// Lays out the transcript body from segments when timing-dependent options are on,
+/// otherwise returns the plain transcript (optionally one sentence per line).
+pub fn render_body(text: &str, segments: Option<&[Segment]>, opts: &RenderOptions) -> String {
+    let timed = opts.timestamps || opts.paragraph_gap.is_some() || opts.min_confidence.is_some();
+    let Some(segments) = segments.filter(|s| timed && !s.is_empty()) else {
+        return if opts.sentences {
+            split_sentences(text).join("\n")
+        } else {
+            text.to_string()
+        };
+    };
+
+    let mut paragraphs: Vec<Vec<&Segment>> = Vec::new();
+    for (idx, seg) in segments.iter().enumerate() {
+        // A change of speaker (--split-channels) always starts a paragraph.
+        let breaks = idx == 0
+            || opts
+                .paragraph_gap
+                .is_some_and(|gap| seg.start - segments[idx - 1].end >= gap)
+            || seg.speaker != segments[idx - 1].speaker;
+        if breaks {
+            paragraphs.push(Vec::new());
+        }
+        if let Some(current) = paragraphs.last_mut() {
+            current.push(seg);
+        }
+    }
+
+    let rendered: Vec<String> = paragraphs
+        .iter()
+        .map(|para| {
+            if opts.timestamps {
+                para.iter()
+                    .map(|seg| {
+                        format!(
+                            "[{}] {}{}",
+                            format_clock(seg.start),
+                            speaker_prefix(seg),
+                            segment_text(seg, opts)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            } else {
+                let joined = para
+                    .iter()
+                    .map(|seg| segment_text(seg, opts))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let body = if opts.sentences {
+                    split_sentences(&joined).join("\n")
+                } else {
+                    joined
+                };
+                format!("{}{body}", speaker_prefix(para[0]))
+            }
+        })
+        .collect();
+    let separator = if opts.paragraph_gap.is_some() {
+        "\n\n"
+    } else {
+        "\n"
+    };
+    rendered.join(separator)
+}
+
+fn segment_text(seg: &Segment, opts: &RenderOptions) -> String {
+    let text = seg.text.trim();
+    if is_low_confidence(seg, opts.min_confidence) {
+        format!("<mark>{text}</mark>")
+    } else {
+        text.to_string()
+    }
+}
+
+fn speaker_prefix(seg: &Segment) -> String {
+    seg.speaker
+        .as_deref()
+        .map_or_else(String::new, |speaker| format!("{speaker}: "))
+}
+
+fn is_low_confidence(seg: &Segment, min: Option<f64>) -> bool {
+    matches!((seg.confidence, min), (Some(score), Some(min)) if score < min)
+}
+
+/// `low_confidence` warnings for the segments scoring below `min`. Says so
+/// on stderr when no segment has a score (an engine or input without
+/// confidences), rather than reporting nothing as if all were fine.
+pub fn low_confidence(segments: Option<&[Segment]>, min: f64) -> Vec<Warning> {
+    let segments = segments.unwrap_or_default();
+    if !segments.is_empty() && segments.iter().all(|seg| seg.confidence.is_none()) {
+        eprintln!(
+            "[parakeet] warning: --min-confidence: the transcript has no confidence scores; nothing marked"
+        );
+    }
+    segments
+        .iter()
+        .filter(|seg| is_low_confidence(seg, Some(min)))
+        .map(|seg| Warning {
+            code: "low_confidence".to_string(),
+            message: format!(
+                "[{}] {} (confidence {:.2})",
+                format_clock(seg.start),
+                seg.text.trim(),
+                seg.confidence.unwrap_or_default()
+            ),
+            start: Some(seg.start),
+            end: Some(seg.end),
+            confidence: seg.confidence,
+        })
+        .collect()
+}
+
+/// Renders a response in the requested format from its plain transcript and segments.
+pub fn render(format: OutputFormat, parsed: &BackendResponse, opts: &RenderOptions) -> String {
+    match format {
+        OutputFormat::Text => {
+            let plain = RenderOptions {
+                min_confidence: None,
+                ..*opts
+            };
+            render_body(&parsed.transcript, parsed.segments.as_deref(), &plain)
+        }
+        OutputFormat::Md => {
+            let body = if parsed.chapters.is_empty() {
+                render_body(&parsed.transcript, parsed.segments.as_deref(), opts)
+            } else {
+                chapters::markdown_body(parsed, opts)
+            };
+            let mut out = to_markdown(
+                &body,
+                &parsed.source,
+                &parsed.media_tags,
+                &parsed.model,
+                &parsed.device,
+                &parsed.metadata,
+            );
+            if let Some(summary) = &parsed.summary {
+                out.push_str(&format!("\n## Summary\n\n{}\n", summary.trim()));
+            }
+            match opts.frontmatter {
+                Some(date) => frontmatter(parsed, date) + &out,
+                None => out,
+            }
+        }
+        OutputFormat::Srt => subtitle::to_srt(&cues(parsed)),
+        OutputFormat::Vtt => subtitle::to_vtt(&cues(parsed)),
+    }
+}
+
+// Subtitle formats need timing; without segments the whole transcript becomes one cue.
+fn cues(parsed: &BackendResponse) -> Vec<Segment> {
+    match &parsed.segments {
+        Some(segments) if !segments.is_empty() => segments
+            .iter()
+            .map(|seg| Segment {
+                text: format!("{}{}", speaker_prefix(seg), seg.text.trim()),
+                ..seg.clone()
+            })
+            .collect(),
+        _ => vec![Segment {
+            start: 0.0,
+            end: parsed
+                .metrics
+                .as_ref()
+                .and_then(|m| m.audio_sec)
+                .unwrap_or_default(),
+            text: parsed.transcript.trim().to_string(),
+            confidence: None,
+            speaker: None,
+        }],
+    }
+}
+
+const ABBREVIATIONS: &[&str] = &[
+    "mr.", "mrs.", "ms.", "dr.", "prof.", "st.", "vs.", "etc.", "e.g.", "i.e.", "approx.", "no.",
+];
+
+/// Splits text on Unicode (UAX #29) sentence boundaries, keeping common
+/// abbreviations ("Dr.", "e.g.") attached to the sentence they start.
+pub fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences: Vec<String> = Vec::new();
+    let mut carry = String::new();
+    for raw in text.unicode_sentences() {
+        let sentence = raw.trim();
+        if sentence.is_empty() {
+            continue;
+        }
+        carry.push_str(sentence);
+        let last_word = sentence.rsplit(' ').next().unwrap_or_default();
+        if ABBREVIATIONS.contains(&last_word.to_lowercase().as_str()) {
+            carry.push(' ');
+            continue;
+        }
+        sentences.push(std::mem::take(&mut carry));
+    }
+    if !carry.trim().is_empty() {
+        sentences.push(carry.trim().to_string());
+    }
+    sentences
+}
+
+pub fn format_clock(sec: f64) -> String {
+    let total = sec.max(0.0) as u64;
+    format!(
+        "{:02}:{:02}:{:02}",
+        total / 3600,
+        total % 3600 / 60,
+        total % 60
+    )
+}
+
+pub fn to_markdown(
+    text: &str,
+    source: &str,
+    media: &MediaTags,
+    model: &str,
+    device: &str,
+    meta: &Metadata,
+) -> String {
+    let mut out = String::from("# Transcript\n\n");
+    if !source.is_empty() {
+        out.push_str(&format!("- Source: `{source}`\n"));
+    }
+    for (label, value) in media.fields() {
+        if let Some(value) = value {
+            out.push_str(&format!("- {label}: {value}\n"));
+        }
+    }
+    for (label, value) in [("Model", model), ("Device", device)] {
+        if !value.is_empty() {
+            out.push_str(&format!("- {label}: `{value}`\n"));
+        }
+    }
+    for (key, value) in meta {
+        if !value.is_empty() {
+            out.push_str(&format!("- {key}: {value}\n"));
+        }
+    }
+    out.push_str(&format!("\n{}\n", text.trim()));
+    out
+}
+
+/// YAML frontmatter for notes tools (Obsidian, Zettlr). Tags become nested
+/// `key/value` tags, the form those tools index.
+pub fn frontmatter(parsed: &BackendResponse, date: u64) -> String {
+    // JSON strings are valid YAML scalars and need no quoting rules of their own.
+    let quote = |value: &str| serde_json::to_string(value).unwrap_or_default();
+    let day_secs = date % 86_400;
+    let mut out = String::from("---\n");
+    out.push_str(&format!("source: {}\n", quote(&parsed.source)));
+    out.push_str(&format!(
+        "date: {}T{:02}:{:02}:{:02}Z\n",
+        format_date(date),
+        day_secs / 3600,
+        day_secs % 3600 / 60,
+        day_secs % 60
+    ));
+    if let Some(sec) = parsed.metrics.as_ref().and_then(|m| m.audio_sec) {
+        out.push_str(&format!("duration: {}\n", format_clock(sec)));
+    }
+    // `date` above is when the transcript was made; the tagged date is `recorded`.
+    let media = &parsed.media_tags;
+    for (key, value) in [
+        ("title", &media.title),
+        ("artist", &media.artist),
+        ("album", &media.album),
+        ("recorded", &media.date),
+    ] {
+        if let Some(value) = value {
+            out.push_str(&format!("{key}: {}\n", quote(value)));
+        }
+    }
+    if !parsed.model.is_empty() {
+        out.push_str(&format!("model: {}\n", quote(&parsed.model)));
+    }
+    if let Some(language) = &parsed.language {
+        out.push_str(&format!("language: {}\n", quote(language)));
+    }
+    if !parsed.tags.is_empty() {
+        out.push_str("tags:\n");
+        for (key, value) in &parsed.tags {
+            out.push_str(&format!(
+                "  - {}\n",
+                quote(&format!("{key}/{}", slugify(value)))
+            ));
+        }
+    }
+    out.push_str("---\n\n");
+    out
+}
+
+pub fn extension_for(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Text => "txt",
+        OutputFormat::Md => "md",
+        OutputFormat::Srt => "srt",
+        OutputFormat::Vtt => "vtt",
+    }
+}
+
+/// Builds a short title from the opening sentence(s) of a transcript.
+pub fn derive_title(transcript: &str) -> Option<String> {
+    let mut words = Vec::new();
+    for word in transcript.split_whitespace() {
+        words.push(word);
+        let ends_sentence = word.ends_with(['.', '!', '?']);
+        if words.len() >= TITLE_MAX_WORDS || (ends_sentence && words.len() >= 3) {
+            break;
+        }
+    }
+    let slug = slugify(&words.join(" "));
+    (!slug.is_empty()).then_some(slug)
+}
+
+pub fn slugify(raw: &str) -> String {
+    let mut slug = String::new();
+    for c in raw.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let mut slug = slug.trim_end_matches('-').to_string();
+    if slug.chars().count() > TITLE_MAX_CHARS {
+        slug = slug.chars().take(TITLE_MAX_CHARS).collect();
+        slug = slug.trim_end_matches('-').to_string();
+    }
+    slug
+}
+
+pub struct NameContext<'a> {
+    pub input: &'a Path,
+    pub title: Option<&'a str>,
+    pub ext: &'a str,
+    pub meta: &'a Metadata,
+}
+
+/// Expands `{stem}`, `{title}`, `{date}`, `{ext}` and `{meta.KEY}` in a filename template.
+/// One left-to-right pass: substituted values are never expanded again, and
+/// unknown braces are kept as written.
+pub fn expand_name_template(template: &str, ctx: &NameContext<'_>) -> Result<String> {
+    let stem = ctx
+        .input
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "transcript".to_string());
+    let mut name = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        name.push_str(&rest[..open]);
+        rest = &rest[open..];
+        let Some(close) = rest.find('}') else {
+            if rest.starts_with("{meta.") {
+                bail!("unterminated {{meta.}} placeholder in name template: {template}");
+            }
+            break;
+        };
+        match &rest[1..close] {
+            "title" => name.push_str(ctx.title.unwrap_or(&stem)),
+            "stem" => name.push_str(&stem),
+            "date" => name.push_str(&today()),
+            "ext" => name.push_str(ctx.ext),
+            placeholder if placeholder.starts_with("meta.") => {
+                let key = &placeholder["meta.".len()..];
+                let Some(value) = ctx.meta.get(key) else {
+                    bail!(
+                        "name template uses {{meta.{key}}} but the metadata has no `{key}` field"
+                    );
+                };
+                // Metadata values are free text; keep them from introducing directories.
+                name.push_str(value.replace(['/', '\\'], "-").trim());
+            }
+            _ => {
+                // Not a placeholder; a later brace may still open one.
+                name.push('{');
+                rest = &rest[1..];
+                continue;
+            }
+        }
+        rest = &rest[close + 1..];
+    }
+    name.push_str(rest);
+    Ok(name)
+}
+
+/// Picks a path in `dir` for `name`, appending `-2`, `-3`, ... when it already exists.
+pub fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let (base, ext) = match name.rsplit_once('.') {
+        Some((base, ext)) => (base, format!(".{ext}")),
+        None => (name, String::new()),
+    };
+    (2..)
+        .map(|n| dir.join(format!("{base}-{n}{ext}")))
+        .find(|path| !path.exists())
+        .unwrap_or(candidate)
+}
+
+pub fn write_output(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed creating output dir: {}", parent.display()))?;
+    }
+    fs::write(path, content).with_context(|| format!("failed writing output: {}", path.display()))
+}
+
+/// What `--diff-guard` decided for an output file that already exists.
+pub enum Guarded {
+    Replace,
+    Unchanged,
+    /// The existing file stays; the new transcript went to this `.new` path.
+    Kept(PathBuf),
+}
+
+/// Replaces `path` only when more than `threshold_pct` percent of its words
+/// change, so a hand-corrected transcript survives a re-run that would mostly
+/// undo the corrections. Subtitles are compared by cue text, not timing.
+pub fn guard_existing(path: &Path, content: &str, threshold_pct: f64) -> Result<Guarded> {
+    let existing = fs::read_to_string(path)
+        .with_context(|| format!("failed reading existing output: {}", path.display()))?;
+    let changed = 100.0
+        * word_change(
+            &comparable_text(path, &existing),
+            &comparable_text(path, content),
+        );
+    if changed == 0.0 {
+        eprintln!("[parakeet] {} is unchanged", path.display());
+        return Ok(Guarded::Unchanged);
+    }
+    if changed > threshold_pct {
+        eprintln!(
+            "[parakeet] replacing {} ({changed:.1}% of words changed)",
+            path.display()
+        );
+        return Ok(Guarded::Replace);
+    }
+    let mut new = path.as_os_str().to_owned();
+    new.push(".new");
+    let new = PathBuf::from(new);
+    eprintln!(
+        "[parakeet] kept {} ({changed:.1}% of words changed, --diff-guard {threshold_pct}%); wrote {}",
+        path.display(),
+        new.display()
+    );
+    Ok(Guarded::Kept(new))
+}
+
+fn comparable_text(path: &Path, content: &str) -> String {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    let cues = match ext.to_ascii_lowercase().as_str() {
+        "srt" => subtitle::parse_srt(content).ok(),
+        "vtt" => subtitle::parse_vtt(content).ok(),
+        _ => None,
+    };
+    match cues {
+        Some(cues) => cues
+            .iter()
+            .map(|cue| cue.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" "),
+        None => content.to_string(),
+    }
+}
+
+/// Word-level edit distance between two transcripts relative to the old one's
+/// length, capped at 1.
+pub fn word_change(old: &str, new: &str) -> f64 {
+    let old: Vec<&str> = old.split_whitespace().collect();
+    let new: Vec<&str> = new.split_whitespace().collect();
+    if old.is_empty() {
+        return if new.is_empty() { 0.0 } else { 1.0 };
+    }
+    let mut prev: Vec<usize> = (0..=new.len()).collect();
+    for (i, a) in old.iter().enumerate() {
+        let mut row = vec![i + 1; new.len() + 1];
+        for (j, b) in new.iter().enumerate() {
+            let substitute = prev[j] + usize::from(!a.eq_ignore_ascii_case(b));
+            row[j + 1] = substitute.min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
+    }
+    (prev[new.len()] as f64 / old.len() as f64).min(1.0)
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Current UTC date as `YYYY-MM-DD`.
+pub fn today() -> String {
+    format_date(unix_now())
+}
+
+pub fn format_date(secs: u64) -> String {
+    let (y, m, d) = civil_from_days((secs / 86_400) as i64);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+/// UTC `YYYY-MM-DD HH:MM` for unix seconds.
+pub fn format_datetime(secs: u64) -> String {
+    let day_secs = secs % 86_400;
+    format!(
+        "{} {:02}:{:02}",
+        format_date(secs),
+        day_secs / 3600,
+        day_secs % 3600 / 60
+    )
+}
+
+/// Unix seconds for a UTC `YYYY-MM-DD HH:MM:SS`.
+pub fn parse_datetime(raw: &str) -> Option<u64> {
+    let (date, time) = raw.split_once(' ')?;
+    let mut date = date.split('-').map(|p| p.parse::<i64>().ok());
+    let (y, m, d) = (date.next()??, date.next()??, date.next()??);
+    let mut time = time.split(':').map(|p| p.parse::<u64>().ok());
+    let (hh, mm, ss) = (time.next()??, time.next()??, time.next()??);
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) || hh > 23 || mm > 59 || ss > 60 {
+        return None;
+    }
+    let days = u64::try_from(days_from_civil(y, m, d)).ok()?;
+    Some(days * 86_400 + hh * 3600 + mm * 60 + ss)
+}
+
+// Inverse of civil_from_days.
+pub fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = if m > 2 { m - 3 } else { m + 9 };
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// Howard Hinnant's days-to-civil conversion.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(template: &str, title: Option<&str>, meta: &[(&str, &str)]) -> Result<String> {
+        let meta: Metadata = meta
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        expand_name_template(
+            template,
+            &NameContext {
+                input: Path::new("/recordings/standup.m4a"),
+                title,
+                ext: "md",
+                meta: &meta,
+            },
+        )
+    }
+
+    #[test]
+    fn slugify_collapses_separators() {
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(slugify("  --Q3 review:  budget -- "), "q3-review-budget");
+        assert_eq!(slugify("Übergröße café"), "übergröße-café");
+        assert_eq!(slugify("?!"), "");
+    }
+
+    #[test]
+    fn slugify_truncates_without_trailing_dash() {
+        let slug = slugify(&"word ".repeat(20));
+        assert!(slug.chars().count() <= TITLE_MAX_CHARS);
+        assert!(slug.starts_with("word-word") && !slug.ends_with('-'));
+    }
+
+    #[test]
+    fn derive_title_takes_the_opening_sentence() {
+        assert_eq!(
+            derive_title("Okay, let's start. Budget first.").as_deref(),
+            Some("okay-let-s-start")
+        );
+        // A short first sentence runs on into the next.
+        assert_eq!(
+            derive_title("Hi. So the plan is set.").as_deref(),
+            Some("hi-so-the-plan-is-set")
+        );
+        assert_eq!(
+            derive_title("one two three four five six seven eight nine ten").as_deref(),
+            Some("one-two-three-four-five-six-seven-eight")
+        );
+        assert_eq!(derive_title("  ... "), None);
+    }
+
+    #[test]
+    fn expands_placeholders() {
+        assert_eq!(
+            expand("{stem}-{title}.{ext}", Some("weekly-sync"), &[]).unwrap(),
+            "standup-weekly-sync.md"
+        );
+        assert_eq!(expand("{title}.{ext}", None, &[]).unwrap(), "standup.md");
+        assert_eq!(
+            expand(
+                "{date}_{meta.client}.{ext}",
+                None,
+                &[("client", " Acme/EU ")]
+            )
+            .unwrap(),
+            format!("{}_Acme-EU.md", today())
+        );
+    }
+
+    #[test]
+    fn substituted_values_are_not_expanded_again() {
+        // Used to rescan from the start and loop forever on a self-reference.
+        assert_eq!(
+            expand("{meta.a}.{ext}", None, &[("a", "{meta.a}")]).unwrap(),
+            "{meta.a}.md"
+        );
+        assert_eq!(expand("{title}", Some("{stem}"), &[]).unwrap(), "{stem}");
+    }
+
+    #[test]
+    fn keeps_unknown_braces_and_rejects_bad_meta() {
+        assert_eq!(
+            expand("{x{stem}}.{ext", None, &[]).unwrap(),
+            "{xstandup}.{ext"
+        );
+        let err = expand("{meta.client}", None, &[]).unwrap_err().to_string();
+        assert!(err.contains("no `client` field"), "{err}");
+        let err = expand("{stem}{meta.client", None, &[])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unterminated"), "{err}");
+    }
+}
@@ -0,0 +1,96 @@
+//! `--porcelain`: one tab-separated status line per processed file on stdout,
+//! `<path>\t<status>\t<duration>\t<output>`, in place of the human-readable
+//! output. Like git's porcelain formats, the columns are stable: new ones are
+//! only ever appended. Tabs, newlines and backslashes inside a field are
+//! written as `\t`, `\n` and `\\`; an empty field is `-`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::BackendResponse;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy)]
+pub enum Status {
+    Ok,
+    /// Not transcribed on purpose, e.g. `--language-mismatch skip`.
+    Skipped,
+    /// The output column holds the error message.
+    Failed,
+    Archived,
+    /// `--dry-run`: would have been processed.
+    Planned,
+}
+
+impl Status {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Skipped => "skipped",
+            Self::Failed => "failed",
+            Self::Archived => "archived",
+            Self::Planned => "planned",
+        }
+    }
+}
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Prints one record when `--porcelain` is on. `duration` is the audio length
+/// in seconds.
+pub fn record(path: &str, status: Status, duration: Option<f64>, output: &str) {
+    if !enabled() {
+        return;
+    }
+    let duration = duration.map_or_else(String::new, |sec| format!("{sec:.3}"));
+    println!(
+        "{}\t{}\t{}\t{}",
+        field(path),
+        status.as_str(),
+        field(&duration),
+        field(output)
+    );
+}
+
+/// Record for a finished transcription: its output file, else the history
+/// entry (`history:<id>`).
+pub fn transcribed(path: &str, response: &BackendResponse) {
+    let status = if response.skipped {
+        Status::Skipped
+    } else {
+        Status::Ok
+    };
+    let output = match (&response.output_path, &response.history_id) {
+        (Some(path), _) => path.clone(),
+        (None, Some(id)) => format!("history:{id}"),
+        (None, None) => String::new(),
+    };
+    let duration = response.metrics.as_ref().and_then(|m| m.audio_sec);
+    record(path, status, duration, &output);
+}
+
+/// Failure record; only the first line of the error is kept.
+pub fn failed(path: &str, err: &anyhow::Error) {
+    let message = format!("{err:#}");
+    record(
+        path,
+        Status::Failed,
+        None,
+        message.lines().next().unwrap_or_default(),
+    );
+}
+
+fn field(raw: &str) -> String {
+    if raw.is_empty() {
+        return "-".to_string();
+    }
+    raw.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
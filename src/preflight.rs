@@ -0,0 +1,317 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Result, bail};
+use serde_json::json;
+
+use crate::error::{ErrorKind, Failure};
+use crate::tools;
+use crate::{DoctorCli, parakeet_home};
+
+/// What the host offers the backend, gathered without importing torch.
+#[derive(serde::Serialize)]
+pub struct Report {
+    pub cpu_missing: Vec<&'static str>,
+    pub cpu_recommended_missing: Vec<&'static str>,
+    pub gpu: Option<Gpu>,
+    /// GPUs torch will see (`cuda:0` up to `cuda:N-1`), after CUDA_VISIBLE_DEVICES.
+    pub visible_gpus: usize,
+    pub torch: Option<TorchBuild>,
+}
+
+#[derive(serde::Serialize)]
+pub struct Gpu {
+    pub name: String,
+    pub driver_version: String,
+    // Highest CUDA runtime the installed driver supports.
+    pub driver_cuda: Option<String>,
+    #[serde(skip)]
+    count: usize,
+}
+
+#[derive(serde::Serialize)]
+pub struct TorchBuild {
+    pub version: String,
+    // CUDA runtime torch was built against; None for CPU-only wheels.
+    pub cuda: Option<String>,
+}
+
+pub enum Verdict {
+    Ok,
+    Warn(String),
+    Fail(String),
+}
+
+// Torch x86_64 wheels will not run without these; the rest only cost speed.
+#[cfg(target_arch = "x86_64")]
+const REQUIRED_CPU: &[&str] = &["sse4.2", "avx"];
+#[cfg(target_arch = "x86_64")]
+const RECOMMENDED_CPU: &[&str] = &["avx2", "fma"];
+
+pub fn inspect(root_dir: &Path, with_gpu: bool) -> Report {
+    let (cpu_missing, cpu_recommended_missing) = cpu_features();
+    let gpu = with_gpu.then(detect_gpu).flatten();
+    let visible_gpus = gpu.as_ref().map_or(0, |gpu| visible_count(gpu.count));
+    Report {
+        cpu_missing,
+        cpu_recommended_missing,
+        gpu,
+        visible_gpus,
+        torch: torch_build(root_dir),
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn cpu_features() -> (Vec<&'static str>, Vec<&'static str>) {
+    let has = |feature: &str| match feature {
+        "sse4.2" => std::arch::is_x86_feature_detected!("sse4.2"),
+        "avx" => std::arch::is_x86_feature_detected!("avx"),
+        "avx2" => std::arch::is_x86_feature_detected!("avx2"),
+        "fma" => std::arch::is_x86_feature_detected!("fma"),
+        _ => true,
+    };
+    let missing = |list: &[&'static str]| list.iter().copied().filter(|f| !has(f)).collect();
+    (missing(REQUIRED_CPU), missing(RECOMMENDED_CPU))
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn cpu_features() -> (Vec<&'static str>, Vec<&'static str>) {
+    (Vec::new(), Vec::new())
+}
+
+fn detect_gpu() -> Option<Gpu> {
+    let output = Command::new("nvidia-smi")
+        .args(["--query-gpu=name,driver_version", "--format=csv,noheader"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let raw = String::from_utf8_lossy(&output.stdout);
+    let (name, driver_version) = raw.lines().next()?.split_once(',')?;
+    let count = raw.lines().filter(|line| !line.trim().is_empty()).count();
+    // The supported CUDA version only appears in the banner of plain `nvidia-smi`.
+    let driver_cuda = Command::new("nvidia-smi").output().ok().and_then(|o| {
+        let banner = String::from_utf8_lossy(&o.stdout).into_owned();
+        let rest = banner.split("CUDA Version:").nth(1)?;
+        rest.split_whitespace().next().map(str::to_string)
+    });
+    Some(Gpu {
+        name: name.trim().to_string(),
+        driver_version: driver_version.trim().to_string(),
+        driver_cuda,
+        count,
+    })
+}
+
+// nvidia-smi lists every GPU; CUDA_VISIBLE_DEVICES hides some from torch.
+fn visible_count(detected: usize) -> usize {
+    match std::env::var("CUDA_VISIBLE_DEVICES") {
+        Ok(list) => list
+            .split(',')
+            .take_while(|entry| !entry.trim().is_empty() && !entry.trim().starts_with('-'))
+            .count()
+            .min(detected),
+        Err(_) => detected,
+    }
+}
+
+/// The GPU index of `cuda:N`.
+pub fn cuda_index(device: &str) -> Option<usize> {
+    let (kind, index) = device.split_once(':')?;
+    kind.eq_ignore_ascii_case("cuda")
+        .then(|| index.parse().ok())
+        .flatten()
+}
+
+/// Reads torch's generated `version.py` from the venv instead of importing torch.
+fn torch_build(root_dir: &Path) -> Option<TorchBuild> {
+    let lib = root_dir.join(".venv/lib");
+    let version_py = fs::read_dir(lib)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path().join("site-packages/torch/version.py"))
+        .find(|path| path.exists())?;
+    let raw = fs::read_to_string(version_py).ok()?;
+    let value = |name: &str| {
+        raw.lines()
+            .find(|line| line.split([':', '=']).next().map(str::trim) == Some(name))
+            .and_then(|line| line.rsplit_once('='))
+            .map(|(_, v)| v.trim().trim_matches(['\'', '"']).to_string())
+            .filter(|v| v != "None")
+    };
+    Some(TorchBuild {
+        version: value("__version__")?,
+        cuda: value("cuda"),
+    })
+}
+
+impl Report {
+    /// Compatibility of this host with the requested `--device`.
+    pub fn verdict(&self, device: &str) -> Verdict {
+        if !self.cpu_missing.is_empty() {
+            return Verdict::Fail(format!(
+                "CPU lacks required instruction sets: {}",
+                self.cpu_missing.join(", ")
+            ));
+        }
+        let index = cuda_index(device);
+        let wants_cuda = device.eq_ignore_ascii_case("cuda") || index.is_some();
+        let gpu_problem = self.gpu_problem().or_else(|| {
+            let index = index.filter(|&index| index >= self.visible_gpus)?;
+            Some(format!(
+                "--device cuda:{index} requested but {} GPU(s) are visible{}",
+                self.visible_gpus,
+                match self.visible_gpus {
+                    0 => String::new(),
+                    1 => " (cuda:0)".to_string(),
+                    n => format!(" (cuda:0 to cuda:{})", n - 1),
+                }
+            ))
+        });
+        match (wants_cuda, gpu_problem) {
+            (true, Some(problem)) => Verdict::Fail(problem),
+            (false, Some(problem)) if device.eq_ignore_ascii_case("auto") => {
+                Verdict::Warn(format!("{problem}; --device auto will run on CPU"))
+            }
+            _ if !self.cpu_recommended_missing.is_empty() => Verdict::Warn(format!(
+                "CPU lacks {}; CPU inference will be slow",
+                self.cpu_recommended_missing.join(", ")
+            )),
+            _ => Verdict::Ok,
+        }
+    }
+
+    fn gpu_problem(&self) -> Option<String> {
+        let Some(gpu) = &self.gpu else {
+            return Some("no NVIDIA GPU/driver detected (nvidia-smi unavailable)".to_string());
+        };
+        let torch = self.torch.as_ref()?;
+        let Some(torch_cuda) = &torch.cuda else {
+            return Some(format!("torch {} is a CPU-only build", torch.version));
+        };
+        let driver_cuda = gpu.driver_cuda.as_deref()?;
+        if version_tuple(torch_cuda) > version_tuple(driver_cuda) {
+            return Some(format!(
+                "torch {} needs CUDA {torch_cuda} but driver {} supports up to CUDA {driver_cuda}",
+                torch.version, gpu.driver_version
+            ));
+        }
+        None
+    }
+}
+
+fn version_tuple(raw: &str) -> Vec<u32> {
+    raw.split('.').map(|p| p.parse().unwrap_or(0)).collect()
+}
+
+/// Startup check before launching a backend: fails fast on a hard
+/// incompatibility, warns on stderr otherwise.
+pub fn check(root_dir: &Path, device: &str, verbose: bool) -> Result<()> {
+    let with_gpu = !device.eq_ignore_ascii_case("cpu");
+    match inspect(root_dir, with_gpu).verdict(device) {
+        Verdict::Fail(reason) => bail!(incompatible(reason)),
+        Verdict::Warn(reason) if verbose => eprintln!("[parakeet] preflight: {reason}"),
+        Verdict::Warn(_) | Verdict::Ok => {}
+    }
+    Ok(())
+}
+
+pub fn run_doctor(cli: DoctorCli) -> Result<()> {
+    let root_dir = parakeet_home();
+    let report = inspect(&root_dir, true);
+    let verdict = report.verdict(&cli.device);
+    if cli.json {
+        print_doctor_json(&report, &verdict, &cli.device)?;
+    } else {
+        print_doctor_text(&report, &verdict, &cli.device, &root_dir);
+    }
+    match verdict {
+        Verdict::Fail(reason) => bail!(incompatible(reason)),
+        Verdict::Warn(_) | Verdict::Ok => Ok(()),
+    }
+}
+
+fn print_doctor_text(report: &Report, verdict: &Verdict, device: &str, root_dir: &Path) {
+    match (
+        report.cpu_missing.is_empty(),
+        report.cpu_recommended_missing.is_empty(),
+    ) {
+        (false, _) => println!("cpu: MISSING {}", report.cpu_missing.join(", ")),
+        (true, false) => println!("cpu: ok (no {})", report.cpu_recommended_missing.join(", ")),
+        (true, true) => println!("cpu: ok"),
+    }
+    match &report.gpu {
+        Some(gpu) => println!(
+            "gpu: {} (driver {}, CUDA {}){}",
+            gpu.name,
+            gpu.driver_version,
+            gpu.driver_cuda.as_deref().unwrap_or("unknown"),
+            match report.visible_gpus {
+                0 => " but hidden by CUDA_VISIBLE_DEVICES".to_string(),
+                1 => String::new(),
+                n => format!("; {n} visible as --device cuda:0 to cuda:{}", n - 1),
+            }
+        ),
+        None => println!("gpu: none detected"),
+    }
+    match &report.torch {
+        Some(torch) => println!(
+            "torch: {} ({})",
+            torch.version,
+            torch
+                .cuda
+                .as_deref()
+                .map_or_else(|| "CPU-only".to_string(), |c| format!("CUDA {c}"))
+        ),
+        None => println!("torch: not found in {}", root_dir.join(".venv").display()),
+    }
+    for tool in tools::OPTIONAL {
+        match tools::find(tool) {
+            Some(path) => println!("{}: ok ({})", tool.name, path.display()),
+            None => println!(
+                "{}: missing, needed for {}; {}",
+                tool.name, tool.needed_for, tool.install_hint
+            ),
+        }
+    }
+    match verdict {
+        Verdict::Ok => println!("verdict: compatible (device={device})"),
+        Verdict::Warn(reason) => println!("verdict: compatible with warnings: {reason}"),
+        Verdict::Fail(reason) => println!("verdict: incompatible: {reason}"),
+    }
+}
+
+fn print_doctor_json(report: &Report, verdict: &Verdict, device: &str) -> Result<()> {
+    let tools: Vec<_> = tools::OPTIONAL
+        .iter()
+        .map(|tool| {
+            let path = tools::find(tool);
+            json!({
+                "name": tool.name,
+                "available": path.is_some(),
+                "path": path,
+                "needed_for": tool.needed_for,
+                "install_hint": tool.install_hint,
+            })
+        })
+        .collect();
+    let (status, reason) = match verdict {
+        Verdict::Ok => ("ok", None),
+        Verdict::Warn(reason) => ("warn", Some(reason)),
+        Verdict::Fail(reason) => ("fail", Some(reason)),
+    };
+    let doc = json!({
+        "host": report,
+        "tools": tools,
+        "verdict": {"device": device, "status": status, "reason": reason},
+    });
+    println!("{}", serde_json::to_string_pretty(&doc)?);
+    Ok(())
+}
+
+fn incompatible(reason: String) -> Failure {
+    Failure::new(
+        ErrorKind::IncompatibleHost,
+        format!("incompatible host: {reason}"),
+    )
+}
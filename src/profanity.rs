@@ -0,0 +1,100 @@
+//! `--mask-profanity`: replaces profane words with asterisks in what gets
+//! emitted (transcript, subtitles, JSON words), for transcripts published as
+//! they are. History keeps the unmasked text.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+
+use crate::{BackendResponse, config};
+
+// Stems; common inflections ("-s", "-ed", "-ing", "-er", "-y", ...) match too.
+const BUILTIN: &[&str] = &[
+    "arsehole",
+    "asshole",
+    "bastard",
+    "bitch",
+    "bollocks",
+    "bullshit",
+    "cock",
+    "cocksucker",
+    "cunt",
+    "dickhead",
+    "fuck",
+    "goddamn",
+    "horseshit",
+    "jackass",
+    "motherfuck",
+    "motherfucker",
+    "piss",
+    "prick",
+    "shit",
+    "slut",
+    "twat",
+    "wanker",
+    "whore",
+];
+
+pub struct Masker {
+    regex: Regex,
+}
+
+impl Masker {
+    /// Built-in list plus `profanity_words` from config.json and the words in
+    /// `extra` (one per line, `#` comments).
+    pub fn new(extra: Option<&Path>) -> Result<Self> {
+        let mut words: Vec<String> = BUILTIN.iter().map(|w| w.to_string()).collect();
+        words.extend(config::load()?.profanity_words);
+        if let Some(path) = extra {
+            let raw = fs::read_to_string(path)
+                .with_context(|| format!("failed reading profanity list: {}", path.display()))?;
+            words.extend(
+                raw.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string),
+            );
+        }
+        // The last letter may double before a suffix ("shitty", "frakking").
+        let alternation = words
+            .iter()
+            .map(|w| w.trim())
+            .filter(|w| !w.is_empty())
+            .map(|w| {
+                let last = w.chars().last().map(String::from).unwrap_or_default();
+                format!("{}(?:{})?", regex::escape(w), regex::escape(&last))
+            })
+            .collect::<Vec<_>>()
+            .join("|");
+        let regex = RegexBuilder::new(&format!(
+            r"\b(?:{alternation})(?:s|es|ed|er|ers|ing|in|y)?\b"
+        ))
+        .case_insensitive(true)
+        .build()
+        .context("invalid profanity list")?;
+        Ok(Self { regex })
+    }
+
+    pub fn apply(&self, parsed: &mut BackendResponse) {
+        parsed.transcript = self.mask(&parsed.transcript);
+        for seg in parsed.segments.iter_mut().flatten() {
+            seg.text = self.mask(&seg.text);
+        }
+        for word in parsed.words.iter_mut().flatten() {
+            word.text = self.mask(&word.text);
+        }
+        for alt in parsed.alternatives.iter_mut().flatten() {
+            alt.text = self.mask(&alt.text);
+        }
+    }
+
+    fn mask(&self, text: &str) -> String {
+        self.regex
+            .replace_all(text, |caps: &regex::Captures| {
+                "*".repeat(caps[0].chars().count())
+            })
+            .into_owned()
+    }
+}
@@ -0,0 +1,235 @@
+use std::io::IsTerminal;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use indicatif::{ProgressBar, ProgressStyle};
+use serde_json::{Value, json};
+
+use crate::{ProgressMode, stdio};
+
+const STEPS: u64 = 1000;
+// Time-based estimates never claim completion; only a real finish does.
+const ESTIMATE_CAP: u64 = 990;
+const TICK: Duration = Duration::from_millis(200);
+// Used when no recent runs exist to learn from (processing seconds per audio second).
+pub const DEFAULT_PROCESSING_RATIO: f64 = 0.1;
+
+/// Newline-delimited JSON progress events on stderr (`--progress json`).
+///
+/// Every event has the same shape so wrappers can drive a progress bar
+/// without knowing the command: `event`, `command`, `stage`, `percent`
+/// (null when unknown), `message` (null when none) and `elapsed_sec`, plus
+/// event-specific fields.
+#[derive(Copy, Clone)]
+pub struct Events {
+    command: &'static str,
+    started: Instant,
+    // Under `serve-stdio`, events become `progress` notifications on stdout.
+    rpc_job: Option<u64>,
+}
+
+impl Events {
+    pub fn new(command: &'static str) -> Self {
+        Self {
+            command,
+            started: Instant::now(),
+            rpc_job: None,
+        }
+    }
+
+    pub fn for_rpc_job(command: &'static str, job: u64) -> Self {
+        Self {
+            rpc_job: Some(job),
+            ..Self::new(command)
+        }
+    }
+
+    pub fn emit(
+        &self,
+        event: &str,
+        stage: &str,
+        percent: Option<f64>,
+        message: Option<&str>,
+        mut fields: Value,
+    ) {
+        fields["event"] = json!(event);
+        fields["command"] = json!(self.command);
+        fields["stage"] = json!(stage);
+        fields["percent"] = json!(percent.map(|p| (p * 10.0).round() / 10.0));
+        fields["message"] = json!(message);
+        fields["elapsed_sec"] =
+            json!((self.started.elapsed().as_secs_f64() * 1000.0).round() / 1000.0);
+        match self.rpc_job {
+            Some(job) => {
+                fields["job"] = json!(job);
+                stdio::notify("progress", fields);
+            }
+            None => eprintln!("{fields}"),
+        }
+    }
+}
+
+/// Transcription progress on stderr. Without backend events the bar advances on
+/// an estimate (audio duration x recent processing ratio); chunk completions
+/// move it forward for real. In JSON mode the same milestones are written as
+/// newline-delimited events instead.
+pub struct Progress {
+    bar: Option<ProgressBar>,
+    events: Option<Events>,
+    floor: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    ticker: Option<JoinHandle<()>>,
+}
+
+impl Progress {
+    /// `events` carries the JSON event stream and must be set for `ProgressMode::Json`.
+    pub fn start(
+        mode: ProgressMode,
+        events: Option<Events>,
+        audio_sec: Option<f64>,
+        processing_ratio: f64,
+    ) -> Self {
+        let enabled = match mode {
+            ProgressMode::Bar => true,
+            ProgressMode::Auto => std::io::stderr().is_terminal(),
+            ProgressMode::None | ProgressMode::Json => false,
+        };
+        let floor = Arc::new(AtomicU64::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        if !enabled {
+            let progress = Self {
+                bar: None,
+                events,
+                floor,
+                stop,
+                ticker: None,
+            };
+            progress.emit(
+                "queued",
+                "queued",
+                Some(0.0),
+                None,
+                json!({ "audio_sec": audio_sec }),
+            );
+            return progress;
+        }
+
+        let Some(estimate) = audio_sec.map(|sec| (sec * processing_ratio).max(1.0)) else {
+            let spinner = ProgressBar::new_spinner();
+            spinner.set_message("transcribing");
+            spinner.enable_steady_tick(TICK);
+            return Self {
+                bar: Some(spinner),
+                events: None,
+                floor,
+                stop,
+                ticker: None,
+            };
+        };
+
+        let bar = ProgressBar::new(STEPS);
+        bar.set_style(
+            ProgressStyle::with_template(
+                "{spinner} [{elapsed_precise}] [{bar:40}] {percent:>3}% ETA {eta} {msg}",
+            )
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> "),
+        );
+        let started = Instant::now();
+        let ticker = {
+            let bar = bar.clone();
+            let floor = Arc::clone(&floor);
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let estimated =
+                        (started.elapsed().as_secs_f64() / estimate * STEPS as f64) as u64;
+                    let pos = estimated
+                        .min(ESTIMATE_CAP)
+                        .max(floor.load(Ordering::Relaxed));
+                    bar.set_position(pos);
+                    std::thread::sleep(TICK);
+                }
+            })
+        };
+        Self {
+            bar: Some(bar),
+            events: None,
+            floor,
+            stop,
+            ticker: Some(ticker),
+        }
+    }
+
+    /// The backend is starting cold (no daemon), so the model has to load first.
+    pub fn model_loading(&self) {
+        self.emit(
+            "model_loading",
+            "loading_model",
+            None,
+            Some("loading model"),
+            json!({}),
+        );
+    }
+
+    /// The JSON event stream, for steps (like auto-starting the daemon) that
+    /// report their own stages.
+    pub fn events(&self) -> Option<Events> {
+        self.events
+    }
+
+    /// Records real progress (0.0..=1.0) once chunk `index` of `total` is transcribed.
+    pub fn chunk_done(&self, index: usize, total: usize, fraction: f64) {
+        let fraction = fraction.clamp(0.0, 1.0);
+        self.floor
+            .fetch_max((fraction * STEPS as f64) as u64, Ordering::Relaxed);
+        self.emit(
+            "chunk_done",
+            "transcribing",
+            Some(fraction * 100.0),
+            Some(&format!("chunk {index}/{total}")),
+            json!({ "chunk": index, "chunks": total }),
+        );
+    }
+
+    pub fn set_message(&self, msg: String) {
+        self.emit("status", "transcribing", None, Some(&msg), json!({}));
+        if let Some(bar) = &self.bar {
+            bar.set_message(msg);
+        }
+    }
+
+    pub fn finish(mut self) {
+        self.emit("finished", "done", Some(100.0), None, json!({}));
+        self.events = None;
+    }
+
+    fn emit(
+        &self,
+        event: &str,
+        stage: &str,
+        percent: Option<f64>,
+        message: Option<&str>,
+        fields: Value,
+    ) {
+        if let Some(events) = &self.events {
+            events.emit(event, stage, percent, message, fields);
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        // Dropped without finish(): the run failed, timed out or was cancelled.
+        self.emit("aborted", "failed", None, None, json!({}));
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(ticker) = self.ticker.take() {
+            let _ = ticker.join();
+        }
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
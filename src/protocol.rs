@@ -0,0 +1,84 @@
+use std::io::{Read, Write};
+
+use anyhow::{Context, Result, bail};
+
+/// Daemon wire format: `b"PK"`, protocol version (u8), message type (u8),
+/// payload length (u32 big-endian), then a UTF-8 JSON payload.
+///
+/// Daemons still accept the legacy newline-delimited JSON protocol from
+/// clients whose first bytes are not the magic.
+pub const MAGIC: [u8; 2] = *b"PK";
+pub const VERSION: u8 = 1;
+const HEADER_LEN: usize = 8;
+const MAX_PAYLOAD: u32 = 64 * 1024 * 1024;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MessageType {
+    Request = 1,
+    Response = 2,
+    Error = 3,
+    Ping = 4,
+    Pong = 5,
+    /// Load models and run a warm-up inference; answered with a `Response`.
+    Warm = 6,
+}
+
+impl MessageType {
+    fn from_u8(raw: u8) -> Option<Self> {
+        match raw {
+            1 => Some(Self::Request),
+            2 => Some(Self::Response),
+            3 => Some(Self::Error),
+            4 => Some(Self::Ping),
+            5 => Some(Self::Pong),
+            6 => Some(Self::Warm),
+            _ => None,
+        }
+    }
+}
+
+pub struct Frame {
+    pub kind: MessageType,
+    pub payload: Vec<u8>,
+}
+
+pub fn write_frame(writer: &mut impl Write, kind: MessageType, payload: &[u8]) -> Result<()> {
+    let len = u32::try_from(payload.len())
+        .ok()
+        .filter(|len| *len <= MAX_PAYLOAD)
+        .context("daemon message too large")?;
+    let mut header = [0u8; HEADER_LEN];
+    header[..2].copy_from_slice(&MAGIC);
+    header[2] = VERSION;
+    header[3] = kind as u8;
+    header[4..].copy_from_slice(&len.to_be_bytes());
+    writer.write_all(&header)?;
+    writer.write_all(payload)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the rest of a frame whose two magic bytes were already consumed.
+pub fn read_frame_after_magic(reader: &mut impl Read) -> Result<Frame> {
+    let mut header = [0u8; HEADER_LEN - 2];
+    reader
+        .read_exact(&mut header)
+        .context("truncated daemon frame header")?;
+    let [version, kind, len @ ..] = header;
+    if version > VERSION {
+        bail!("daemon speaks protocol version {version}, this client supports {VERSION}");
+    }
+    let Some(kind) = MessageType::from_u8(kind) else {
+        bail!("unknown daemon message type {kind}");
+    };
+    let len = u32::from_be_bytes(len);
+    if len > MAX_PAYLOAD {
+        bail!("daemon frame too large: {len} bytes");
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader
+        .read_exact(&mut payload)
+        .context("truncated daemon frame payload")?;
+    Ok(Frame { kind, payload })
+}
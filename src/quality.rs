@@ -0,0 +1,66 @@
+use clap::ValueEnum;
+
+pub const DEFAULT_MODEL: &str = "nvidia/parakeet-tdt-0.6b-v3";
+pub const DEFAULT_CHUNK_OVERLAP_SEC: f64 = 10.0;
+
+/// `--quality` presets: one flag for model, chunking, decoding and
+/// postprocessing. Flags given explicitly always win over the preset.
+#[derive(Copy, Clone, Debug, ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Quality {
+    /// Small English-only model, greedy decoding, exact vocab matching.
+    Fast,
+    /// The default model and settings.
+    Balanced,
+    /// Beam search, long overlapping chunks and a second vocab-guided pass.
+    Best,
+}
+
+pub struct Preset {
+    pub model: &'static str,
+    pub chunk_minutes: Option<f64>,
+    pub chunk_overlap_sec: f64,
+    // 1 means greedy decoding.
+    pub beam_size: u32,
+    pub fuzzy_vocab: bool,
+    pub two_pass: bool,
+}
+
+impl Quality {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Quality::Fast => "fast",
+            Quality::Balanced => "balanced",
+            Quality::Best => "best",
+        }
+    }
+
+    pub fn preset(self) -> Preset {
+        match self {
+            Quality::Fast => Preset {
+                model: "nvidia/parakeet-tdt_ctc-110m",
+                chunk_minutes: Some(10.0),
+                chunk_overlap_sec: 5.0,
+                beam_size: 1,
+                fuzzy_vocab: false,
+                two_pass: false,
+            },
+            Quality::Balanced => Preset {
+                model: DEFAULT_MODEL,
+                chunk_minutes: None,
+                chunk_overlap_sec: DEFAULT_CHUNK_OVERLAP_SEC,
+                beam_size: 1,
+                fuzzy_vocab: true,
+                two_pass: false,
+            },
+            Quality::Best => Preset {
+                model: DEFAULT_MODEL,
+                chunk_minutes: Some(20.0),
+                chunk_overlap_sec: 15.0,
+                beam_size: 4,
+                fuzzy_vocab: true,
+                two_pass: true,
+            },
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! `--redact emails,phones,cards,ssns`: replaces personal data in the final
+//! text with placeholders (`[EMAIL]`, `[PHONE]`, `[CARD]`, `[SSN]`) before
+//! anything is written, delivered or recorded in history. Autosave snapshots
+//! and caption files are redacted on every save with `text`.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use regex::Regex;
+
+use crate::{BackendResponse, PostprocessStep};
+
+const VERSION: u32 = 1;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Pii {
+    Emails,
+    Phones,
+    /// Credit-card-like numbers (13-19 digits passing the Luhn check).
+    Cards,
+    /// US social security numbers (`123-45-6789`).
+    Ssns,
+    All,
+}
+
+impl Pii {
+    // Most specific first: a card or SSN would otherwise pass for a phone number.
+    const ORDER: [Pii; 4] = [Pii::Emails, Pii::Cards, Pii::Ssns, Pii::Phones];
+
+    fn key(self) -> &'static str {
+        match self {
+            Pii::Emails => "email",
+            Pii::Phones => "phone",
+            Pii::Cards => "card",
+            Pii::Ssns => "ssn",
+            Pii::All => unreachable!("expanded by categories()"),
+        }
+    }
+
+    fn placeholder(self) -> &'static str {
+        match self {
+            Pii::Emails => "[EMAIL]",
+            Pii::Phones => "[PHONE]",
+            Pii::Cards => "[CARD]",
+            Pii::Ssns => "[SSN]",
+            Pii::All => unreachable!("expanded by categories()"),
+        }
+    }
+
+    fn regex(self) -> &'static Regex {
+        static EMAIL: OnceLock<Regex> = OnceLock::new();
+        static PHONE: OnceLock<Regex> = OnceLock::new();
+        static CARD: OnceLock<Regex> = OnceLock::new();
+        static SSN: OnceLock<Regex> = OnceLock::new();
+        let (cell, pattern) = match self {
+            // Written addresses, and spoken ones as models tend to write them
+            // ("jane dot doe at example dot com").
+            Pii::Emails => (
+                &EMAIL,
+                r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b|\b[a-z0-9]+(?: dot [a-z0-9]+)* at [a-z0-9-]+(?: dot [a-z]{2,})+\b",
+            ),
+            // Grouped numbers ("415 555 0134", "(415) 555-0134"), or a bare
+            // run of 10-12 digits; shorter bare runs are order numbers and
+            // dates ("12345678", "20240315") more often than phones.
+            Pii::Phones => (
+                &PHONE,
+                r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,4}\)[\s.-]?|\b\d{2,4}[\s.-])\d{3,4}[\s.-]?\d{3,4}\b|(?:\+|\b)\d{10,12}\b",
+            ),
+            Pii::Cards => (&CARD, r"\b(?:\d[ -]?){12,18}\d\b"),
+            Pii::Ssns => (&SSN, r"\b\d{3}[- ]\d{2}[- ]\d{4}\b"),
+            Pii::All => unreachable!("expanded by categories()"),
+        };
+        cell.get_or_init(|| Regex::new(pattern).expect("valid PII pattern"))
+    }
+}
+
+/// The categories to redact, in matching order.
+fn categories(requested: &[Pii]) -> Vec<Pii> {
+    Pii::ORDER
+        .into_iter()
+        .filter(|pii| requested.contains(pii) || requested.contains(&Pii::All))
+        .collect()
+}
+
+/// Redacts the transcript, segments and alternatives and records the counts
+/// per category in `redactions`. Word timings are dropped when anything was
+/// redacted, since a number can be spread over several word tokens.
+pub fn apply(parsed: &mut BackendResponse, requested: &[Pii]) {
+    let categories = categories(requested);
+    if categories.is_empty() {
+        return;
+    }
+    let mut counts = BTreeMap::new();
+    parsed.transcript = redact(&parsed.transcript, &categories, &mut counts);
+    // Counted once, on the transcript; segments hold the same text.
+    let mut ignored = BTreeMap::new();
+    for seg in parsed.segments.iter_mut().flatten() {
+        seg.text = redact(&seg.text, &categories, &mut ignored);
+    }
+    for alt in parsed.alternatives.iter_mut().flatten() {
+        alt.text = redact(&alt.text, &categories, &mut ignored);
+    }
+    let redacted = !counts.is_empty() || !ignored.is_empty();
+    let words_dropped = redacted && parsed.words.take().is_some();
+    parsed.postprocessing.push(PostprocessStep::new(
+        "redact",
+        VERSION,
+        serde_json::json!({
+            "categories": categories.iter().map(|pii| pii.key()).collect::<Vec<_>>(),
+            "words_dropped": words_dropped,
+        }),
+    ));
+    parsed.redactions = counts;
+}
+
+/// Redacts a text on its own, for files written while the transcript is
+/// still being assembled (`--autosave`, `--caption-file`); None when nothing
+/// matched.
+pub fn text(text: &str, requested: &[Pii]) -> Option<String> {
+    let mut counts = BTreeMap::new();
+    let out = redact(text, &categories(requested), &mut counts);
+    (!counts.is_empty()).then_some(out)
+}
+
+fn redact(text: &str, categories: &[Pii], counts: &mut BTreeMap<String, usize>) -> String {
+    let mut out = text.to_string();
+    for &pii in categories {
+        let mut hits = 0;
+        out = pii
+            .regex()
+            .replace_all(&out, |caps: &regex::Captures| {
+                let found = &caps[0];
+                if pii == Pii::Cards && !luhn(found) {
+                    return found.to_string();
+                }
+                hits += 1;
+                pii.placeholder().to_string()
+            })
+            .into_owned();
+        if hits > 0 {
+            *counts.entry(pii.key().to_string()).or_default() += hits;
+        }
+    }
+    out
+}
+
+fn luhn(raw: &str) -> bool {
+    let digits: Vec<u32> = raw.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(idx, &d)| match (idx % 2 == 1, d * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => d,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all(text: &str) -> String {
+        redact(text, &categories(&[Pii::All]), &mut BTreeMap::new())
+    }
+
+    #[test]
+    fn tells_cards_ssns_and_phones_apart() {
+        assert_eq!(all("card 4111 1111 1111 1111."), "card [CARD].");
+        assert_eq!(all("card 4111-1111-1111-1111"), "card [CARD]");
+        assert_eq!(all("card 4111111111111111"), "card [CARD]");
+        assert_eq!(all("ssn 123-45-6789"), "ssn [SSN]");
+        assert_eq!(all("ssn 123 45 6789"), "ssn [SSN]");
+        assert_eq!(all("call 415-555-0134"), "call [PHONE]");
+        assert_eq!(all("call (415) 555-0134"), "call [PHONE]");
+        assert_eq!(all("call +44 20 7946 0958"), "call [PHONE]");
+        assert_eq!(all("call 4155550134"), "call [PHONE]");
+    }
+
+    #[test]
+    fn leaves_other_numbers() {
+        // Fails the Luhn check, and is too long for a bare phone number.
+        assert_eq!(all("ref 4111111111111112"), "ref 4111111111111112");
+        assert_eq!(all("order 12345678"), "order 12345678");
+        assert_eq!(all("on 20240315"), "on 20240315");
+        assert_eq!(all("in 2024, 150 people"), "in 2024, 150 people");
+    }
+
+    #[test]
+    fn redacts_written_and_spoken_emails() {
+        assert_eq!(all("mail jane.doe@example.com"), "mail [EMAIL]");
+        assert_eq!(
+            all("mail jane dot doe at example dot co dot uk please"),
+            "mail [EMAIL] please"
+        );
+        assert_eq!(all("meet at noon"), "meet at noon");
+    }
+
+    #[test]
+    fn counts_per_category() {
+        let mut counts = BTreeMap::new();
+        let out = redact(
+            "a@b.io or 415-555-0134 or 415-555-0135",
+            &categories(&[Pii::Emails, Pii::Phones]),
+            &mut counts,
+        );
+        assert_eq!(out, "[EMAIL] or [PHONE] or [PHONE]");
+        assert_eq!(counts.get("email"), Some(&1));
+        assert_eq!(counts.get("phone"), Some(&2));
+        assert_eq!(text("nothing here", &[Pii::All]), None);
+    }
+
+    #[test]
+    fn checks_luhn() {
+        assert!(luhn("4111 1111 1111 1111"));
+        assert!(luhn("378282246310005"));
+        assert!(!luhn("4111 1111 1111 1112"));
+        assert!(!luhn("411111111111"));
+    }
+}
@@ -0,0 +1,134 @@
+//! `--refine MODEL`: after the first pass, re-transcribe only the stretches
+//! whose segment confidence is below `--refine-below` with a larger model and
+//! splice its text back in. Each stretch is a separate `--start`/`--end` cut,
+//! so the big model only sees a fraction of the recording.
+
+use anyhow::Result;
+
+use crate::progress::Progress;
+use crate::{
+    BackendRequest, BackendResponse, PostprocessStep, Segment, TranscribeCli, request_backend,
+};
+
+// Low segments this close together are re-transcribed as one cut.
+const MERGE_GAP_SEC: f64 = 0.5;
+// Audio added around a cut (never past the neighbouring segments) so word
+// edges are not clipped.
+const PAD_SEC: f64 = 0.15;
+
+pub async fn refine(
+    cli: &TranscribeCli,
+    request: &BackendRequest<'_>,
+    model: &str,
+    below: f64,
+    mut parsed: BackendResponse,
+    progress: &Progress,
+) -> Result<BackendResponse> {
+    let Some(mut segments) = parsed.segments.take() else {
+        return Ok(parsed);
+    };
+    if segments.iter().all(|seg| seg.confidence.is_none()) {
+        eprintln!(
+            "[parakeet] warning: --refine: the first pass has no confidence scores; nothing refined"
+        );
+        parsed.segments = Some(segments);
+        return Ok(parsed);
+    }
+    let regions = low_regions(&segments, below);
+    let mut refined = Vec::new();
+    // Back to front, so splicing keeps the earlier indices valid.
+    for (done, &(first, last)) in regions.iter().rev().enumerate() {
+        progress.set_message(format!("refining {}/{}", done + 1, regions.len()));
+        let start = (segments[first].start - PAD_SEC).max(
+            first
+                .checked_sub(1)
+                .map_or(request.start_sec.unwrap_or_default(), |prev| {
+                    segments[prev].end
+                }),
+        );
+        let end = (segments[last].end + PAD_SEC).min(
+            segments
+                .get(last + 1)
+                .map(|next| next.start)
+                .or(request.end_sec)
+                .unwrap_or(f64::INFINITY),
+        );
+        let cut = BackendRequest {
+            model,
+            start_sec: Some(start),
+            end_sec: Some(end),
+            timestamps: false,
+            word_timestamps: false,
+            only_language: None,
+            language: None,
+            language_models: None,
+            nbest: None,
+            confidence: false,
+            ..request.clone()
+        };
+        let answer = request_backend(cli, &cut, progress).await?;
+        if let (Some(total), Some(extra)) = (parsed.metrics.as_mut(), answer.metrics.as_ref()) {
+            total.add(extra);
+        }
+        let text = answer.transcript.trim();
+        let before = segments[first..=last]
+            .iter()
+            .map(|seg| seg.text.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if text.is_empty() {
+            continue;
+        }
+        if cli.verbose {
+            eprintln!("[parakeet] refined {before:?} -> {text:?}");
+        }
+        let merged = Segment {
+            start: segments[first].start,
+            end: segments[last].end,
+            text: text.to_string(),
+            confidence: None,
+            speaker: None,
+        };
+        segments.splice(first..=last, [merged]);
+        refined.push(serde_json::json!({
+            "start": start,
+            "end": end,
+            "before": before,
+            "after": text,
+        }));
+    }
+    refined.reverse();
+    if !refined.is_empty() {
+        parsed.transcript = segments
+            .iter()
+            .map(|seg| seg.text.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+    parsed.postprocessing.push(PostprocessStep::new(
+        "refine",
+        1,
+        serde_json::json!({ "model": model, "below": below, "regions": refined }),
+    ));
+    parsed.segments = Some(segments);
+    Ok(parsed)
+}
+
+/// Index ranges (inclusive) of runs of segments scoring below `below`.
+fn low_regions(segments: &[Segment], below: f64) -> Vec<(usize, usize)> {
+    let mut regions: Vec<(usize, usize)> = Vec::new();
+    for (idx, seg) in segments.iter().enumerate() {
+        if !seg.confidence.is_some_and(|score| score < below) {
+            continue;
+        }
+        match regions.last_mut() {
+            Some((_, last))
+                if *last + 1 == idx && seg.start - segments[*last].end < MERGE_GAP_SEC =>
+            {
+                *last = idx;
+            }
+            _ => regions.push((idx, idx)),
+        }
+    }
+    regions
+}
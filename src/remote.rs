@@ -0,0 +1,176 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+
+use crate::tools::{self, Tool};
+use crate::userhome;
+
+/// Cloud storage schemes accepted as `--input`. Credentials come from the
+/// provider CLI's own chain (env vars, profiles, instance metadata).
+const SCHEMES: &[&str] = &["s3://", "gs://"];
+
+pub fn remote_url(input: &Path) -> Option<&str> {
+    input
+        .to_str()
+        .filter(|raw| SCHEMES.iter().any(|scheme| raw.starts_with(scheme)))
+}
+
+/// Downloads a cloud object into the local cache and returns its path.
+///
+/// Cache entries are keyed on the URL and the object's ETag, so a re-uploaded
+/// object is fetched again while repeated runs on the same recording reuse it.
+pub fn fetch(url: &str, verbose: bool) -> Result<PathBuf> {
+    let Some((bucket, key)) = url
+        .split_once("://")
+        .and_then(|(_, rest)| rest.split_once('/'))
+    else {
+        bail!("invalid storage URL (expected scheme://bucket/key): {url}");
+    };
+    if bucket.is_empty() || key.is_empty() || key.ends_with('/') {
+        bail!("invalid storage URL (expected scheme://bucket/key): {url}");
+    }
+    tools::require(provider_cli(url))?;
+    let etag = object_etag(url, bucket, key)?;
+    let name = key.rsplit('/').next().unwrap_or(key);
+    let dir = userhome::dir()
+        .join(".cache/remote")
+        .join(format!("{:016x}", fnv1a(&format!("{url}\n{etag}"))));
+    let path = dir.join(name);
+    if path.exists() {
+        if verbose {
+            eprintln!("[parakeet] using cached {url}: {}", path.display());
+        }
+        return Ok(path);
+    }
+
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("failed creating cache dir: {}", dir.display()))?;
+    let partial = dir.join(format!("{name}.part"));
+    let file = File::create(&partial)
+        .with_context(|| format!("failed creating download file: {}", partial.display()))?;
+    if verbose {
+        eprintln!("[parakeet] downloading {url}");
+    }
+    // The provider CLI streams the object to stdout, straight into the cache file.
+    let status = download_command(url)
+        .stdout(Stdio::from(file))
+        .status()
+        .with_context(|| {
+            format!(
+                "failed running {} to download {url}",
+                provider_cli(url).name
+            )
+        })?;
+    if !status.success() {
+        let _ = fs::remove_file(&partial);
+        bail!("download failed for {url} ({status})");
+    }
+    fs::rename(&partial, &path)
+        .with_context(|| format!("failed finalizing download: {}", path.display()))?;
+    Ok(path)
+}
+
+/// Uploads `content` to an `s3://` / `gs://` object, streaming it through the provider CLI.
+pub fn upload(url: &str, content: &[u8]) -> Result<()> {
+    tools::require(provider_cli(url))?;
+    let mut cmd = if url.starts_with("s3://") {
+        let mut cmd = Command::new("aws");
+        cmd.args(["s3", "cp", "--only-show-errors", "-", url]);
+        cmd
+    } else {
+        let mut cmd = Command::new("gcloud");
+        cmd.args(["storage", "cp", "-", url]);
+        cmd
+    };
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "failed running {} (is it installed?)",
+                provider_cli(url).name
+            )
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(content)
+            .with_context(|| format!("failed streaming upload to {url}"))?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "upload failed for {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+fn object_etag(url: &str, bucket: &str, key: &str) -> Result<String> {
+    let output = if url.starts_with("s3://") {
+        Command::new("aws")
+            .args(["s3api", "head-object", "--bucket", bucket, "--key", key])
+            .args(["--query", "ETag", "--output", "text"])
+            .output()
+    } else {
+        Command::new("gcloud")
+            .args([
+                "storage",
+                "objects",
+                "describe",
+                url,
+                "--format=value(etag)",
+            ])
+            .output()
+    }
+    .with_context(|| {
+        format!(
+            "failed running {} (is it installed?)",
+            provider_cli(url).name
+        )
+    })?;
+    if !output.status.success() {
+        bail!(
+            "cannot access {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .trim_matches('"')
+        .to_string())
+}
+
+fn download_command(url: &str) -> Command {
+    let mut cmd = if url.starts_with("s3://") {
+        let mut cmd = Command::new("aws");
+        cmd.args(["s3", "cp", "--only-show-errors", url, "-"]);
+        cmd
+    } else {
+        let mut cmd = Command::new("gcloud");
+        cmd.args(["storage", "cat", url]);
+        cmd
+    };
+    cmd.stdin(Stdio::null());
+    cmd
+}
+
+fn provider_cli(url: &str) -> &'static Tool {
+    if url.starts_with("s3://") {
+        &tools::AWS
+    } else {
+        &tools::GCLOUD
+    }
+}
+
+// Stable across builds, unlike std's hasher, so cache keys survive upgrades.
+fn fnv1a(raw: &str) -> u64 {
+    raw.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -0,0 +1,240 @@
+//! `--resample`: decodes the input in-process (symphonia) and writes the
+//! 16 kHz mono WAV Parakeet expects (rubato for the rate change), so the
+//! backend neither resamples nor needs ffmpeg for formats symphonia reads.
+//! Inputs symphonia cannot decode are left to the backend as before.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use rubato::{FftFixedIn, Resampler};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CODEC_TYPE_NULL, DecoderOptions};
+use symphonia::core::errors::Error as DecodeError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use crate::{AudioFormat, PreprocessStep};
+
+const MODEL_RATE: u32 = 16_000;
+const CHUNK_FRAMES: usize = 1024;
+const WAV_HEADER_BYTES: u64 = 44;
+
+/// Writes the model-format WAV of `input` into `dir`. `None` when the input
+/// already is one, or when symphonia cannot decode it.
+pub fn to_model_format(
+    input: &Path,
+    dir: &Path,
+    verbose: bool,
+) -> Result<Option<(PathBuf, PreprocessStep)>> {
+    let file = File::open(input).with_context(|| format!("failed opening {}", input.display()))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    let ext = input
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    if let Some(ext) = &ext {
+        hint.with_extension(ext);
+    }
+    let skip = |reason: &str| {
+        if verbose {
+            eprintln!("[parakeet] resample: {reason}; leaving the input to the backend");
+        }
+        Ok(None)
+    };
+    let Ok(probed) = symphonia::default::get_probe().format(
+        &hint,
+        stream,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    ) else {
+        return skip("format not readable in-process");
+    };
+    let mut format = probed.format;
+    let Some(track) = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+    else {
+        return skip("no audio track");
+    };
+    let params = track.codec_params.clone();
+    let track_id = track.id;
+    let Some(rate) = params.sample_rate else {
+        return skip("unknown sample rate");
+    };
+    let channels = params.channels.map(|channels| channels.count() as u32);
+    if ext.as_deref() == Some("wav") && rate == MODEL_RATE && channels == Some(1) {
+        return Ok(None);
+    }
+    let codecs = symphonia::default::get_codecs();
+    let Ok(mut decoder) = codecs.make(&params, &DecoderOptions::default()) else {
+        return skip("codec not decodable in-process");
+    };
+
+    let out = dir.join("resampled.wav");
+    if verbose {
+        eprintln!(
+            "[parakeet] resampling {rate} Hz to {MODEL_RATE} Hz mono: {}",
+            out.display()
+        );
+    }
+    let mut resampler = (rate != MODEL_RATE)
+        .then(|| FftFixedIn::<f32>::new(rate as usize, MODEL_RATE as usize, CHUNK_FRAMES, 2, 1))
+        .transpose()
+        .context("failed setting up the resampler")?;
+    let mut wav = WavWriter::create(&out, resampler.as_ref().map_or(0, |r| r.output_delay()))?;
+    let mut pending: Vec<f32> = Vec::new();
+    let mut frames_in = 0u64;
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(err)) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(err) => bail!("failed reading {}: {err}", input.display()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A corrupt packet costs its few milliseconds, not the run.
+            Err(DecodeError::DecodeError(_)) => continue,
+            Err(err) => bail!("failed decoding {}: {err}", input.display()),
+        };
+        let spec = *decoded.spec();
+        let mut samples = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        samples.copy_interleaved_ref(decoded);
+        let width = spec.channels.count().max(1);
+        for frame in samples.samples().chunks_exact(width) {
+            pending.push(frame.iter().sum::<f32>() / width as f32);
+        }
+        frames_in += (samples.samples().len() / width) as u64;
+        match resampler.as_mut() {
+            Some(resampler) => {
+                while pending.len() >= resampler.input_frames_next() {
+                    let take = resampler.input_frames_next();
+                    let chunk = resampler.process(&[&pending[..take]], None)?;
+                    wav.push(&chunk[0])?;
+                    pending.drain(..take);
+                }
+            }
+            None => {
+                wav.push(&pending)?;
+                pending.clear();
+            }
+        }
+    }
+    if frames_in == 0 {
+        bail!("no audio decoded from {}", input.display());
+    }
+    let expected = frames_in * u64::from(MODEL_RATE) / u64::from(rate);
+    if let Some(resampler) = resampler.as_mut() {
+        // The tail, then zeros until the resampler's delay has drained.
+        let mut tail = Some(pending);
+        while wav.written < expected {
+            let chunk = match tail.take() {
+                Some(rest) => resampler.process_partial(Some(&[rest]), None)?,
+                None => resampler.process_partial::<Vec<f32>>(None, None)?,
+            };
+            if chunk[0].is_empty() {
+                break;
+            }
+            wav.push(&chunk[0])?;
+        }
+    }
+    wav.finish(expected)?;
+
+    let codec = codecs
+        .get_codec(params.codec)
+        .map(|codec| codec.short_name.to_string());
+    let mut changes = Vec::new();
+    if rate != MODEL_RATE {
+        changes.push(format!("resample {rate} -> {MODEL_RATE} Hz"));
+    }
+    if let Some(count) = channels.filter(|count| *count != 1) {
+        changes.push(format!("downmix {count} -> 1 channels"));
+    }
+    changes.push("convert to 16-bit PCM WAV".to_string());
+    let step = PreprocessStep {
+        name: "resample".to_string(),
+        tool: "rubato".to_string(),
+        from: Some(AudioFormat {
+            codec,
+            sample_rate: Some(rate),
+            channels,
+            bit_depth: params.bits_per_sample,
+        }),
+        to: Some(AudioFormat {
+            codec: Some("pcm_s16le".to_string()),
+            sample_rate: Some(MODEL_RATE),
+            channels: Some(1),
+            bit_depth: Some(16),
+        }),
+        changes,
+    };
+    Ok(Some((out, step)))
+}
+
+// 16 kHz mono 16-bit PCM, streamed; sizes are patched in on finish.
+struct WavWriter {
+    file: BufWriter<File>,
+    // Leading resampler-delay frames still to drop.
+    skip: usize,
+    written: u64,
+}
+
+impl WavWriter {
+    fn create(path: &Path, skip: usize) -> Result<Self> {
+        let file =
+            File::create(path).with_context(|| format!("failed creating {}", path.display()))?;
+        let mut writer = Self {
+            file: BufWriter::new(file),
+            skip,
+            written: 0,
+        };
+        writer.header(0)?;
+        Ok(writer)
+    }
+
+    fn header(&mut self, frames: u64) -> io::Result<()> {
+        let data = (frames * 2) as u32;
+        let out = &mut self.file;
+        out.write_all(b"RIFF")?;
+        out.write_all(&(36 + data).to_le_bytes())?;
+        out.write_all(b"WAVEfmt ")?;
+        out.write_all(&16u32.to_le_bytes())?;
+        out.write_all(&1u16.to_le_bytes())?; // PCM
+        out.write_all(&1u16.to_le_bytes())?; // mono
+        out.write_all(&MODEL_RATE.to_le_bytes())?;
+        out.write_all(&(MODEL_RATE * 2).to_le_bytes())?;
+        out.write_all(&2u16.to_le_bytes())?;
+        out.write_all(&16u16.to_le_bytes())?;
+        out.write_all(b"data")?;
+        out.write_all(&data.to_le_bytes())
+    }
+
+    fn push(&mut self, samples: &[f32]) -> io::Result<()> {
+        let dropped = self.skip.min(samples.len());
+        self.skip -= dropped;
+        for sample in &samples[dropped..] {
+            let value = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16;
+            self.file.write_all(&value.to_le_bytes())?;
+        }
+        self.written += (samples.len() - dropped) as u64;
+        Ok(())
+    }
+
+    // Cuts the flush padding past `frames` and writes the final sizes.
+    fn finish(mut self, frames: u64) -> io::Result<()> {
+        let frames = frames.min(self.written);
+        self.file.flush()?;
+        self.file.get_ref().set_len(WAV_HEADER_BYTES + frames * 2)?;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.header(frames)?;
+        self.file.flush()
+    }
+}
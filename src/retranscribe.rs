@@ -0,0 +1,444 @@
+//! `history retranscribe`: re-runs past inputs with a newer model and records
+//! the result as a new history entry linked to the original
+//! (`retranscribed_from`), so both versions stay around for comparison.
+//!
+//! `--where` takes a small filter language over history entries:
+//! comparisons (`rtf>1.0`, `model=nvidia/parakeet-tdt-0.6b-v2`,
+//! `tag.project=alpha`) and flags (`needs_review`, `edited`), combined with
+//! `and`, `or`, `not` and parentheses.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+
+use crate::history::{self, HistoryEntry};
+use crate::porcelain::{self, Status};
+use crate::{TranscribeCli, config, output};
+
+const NUMBER_FIELDS: &[&str] = &["rtf", "audio_sec", "total_sec", "age_days", "words"];
+
+#[derive(Debug, clap::Args)]
+pub struct RetranscribeCli {
+    /// Model to re-run the selected inputs with.
+    #[arg(long)]
+    model: String,
+    /// Filter, e.g. `rtf>1.0 or needs_review`; every run when omitted.
+    #[arg(long = "where", value_name = "EXPR")]
+    filter: Option<String>,
+    /// Re-run at most this many (oldest first).
+    #[arg(long)]
+    limit: Option<usize>,
+    /// List the runs that would be re-transcribed.
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+    /// Also rewrite each run's output file, guarded like `--diff-guard`:
+    /// without a percentage (here or `diff_guard_pct` in config.json) the new
+    /// transcript always goes to `<file>.new`.
+    #[arg(long, default_value_t = false)]
+    write_outputs: bool,
+    #[arg(long, value_name = "PCT", requires = "write_outputs", value_parser = crate::parse_percent)]
+    diff_guard: Option<f64>,
+    /// One status line per run (see `porcelain`); the output column names the
+    /// written file or the new `history:<id>`.
+    #[arg(long)]
+    porcelain: bool,
+}
+
+pub async fn run(cli: RetranscribeCli) -> Result<()> {
+    let filter = match &cli.filter {
+        Some(raw) => Some(parse(raw).with_context(|| format!("invalid --where: {raw}"))?),
+        None => None,
+    };
+    let entries = history::load_all()?;
+    let now = output::unix_now();
+    // Runs already redone with this model are not picked again.
+    let done: Vec<&str> = entries
+        .iter()
+        .filter(|entry| entry.response.model == cli.model)
+        .filter_map(|entry| entry.retranscribed_from.as_deref())
+        .collect();
+    let selected: Vec<&HistoryEntry> = entries
+        .iter()
+        .filter(|entry| entry.response.model != cli.model && !done.contains(&entry.id.as_str()))
+        .filter(|entry| filter.as_ref().is_none_or(|expr| expr.eval(entry, now)))
+        .take(cli.limit.unwrap_or(usize::MAX))
+        .collect();
+    if cli.porcelain {
+        porcelain::enable();
+    }
+    let audio_sec = |entry: &HistoryEntry| entry.response.metrics.as_ref()?.audio_sec;
+    if selected.is_empty() {
+        if !cli.porcelain {
+            println!("no runs to re-transcribe with {}", cli.model);
+        }
+        return Ok(());
+    }
+    if cli.dry_run {
+        for entry in &selected {
+            match cli.porcelain {
+                true => porcelain::record(&entry.input, Status::Planned, audio_sec(entry), ""),
+                false => println!("{}\t{}\t{}", entry.id, entry.response.model, entry.input),
+            }
+        }
+        if !cli.porcelain {
+            println!("would re-transcribe {} run(s)", selected.len());
+        }
+        return Ok(());
+    }
+
+    let guard = match cli.write_outputs {
+        true => Some(
+            cli.diff_guard
+                .or(config::load()?.diff_guard_pct)
+                .unwrap_or(100.0),
+        ),
+        false => None,
+    };
+    let mut failed = 0;
+    for entry in &selected {
+        match retranscribe(entry, &cli.model, guard).await {
+            Ok(new) if cli.porcelain => {
+                let output = new
+                    .output_path
+                    .clone()
+                    .unwrap_or_else(|| format!("history:{}", new.id));
+                porcelain::record(&entry.input, Status::Ok, audio_sec(&new), &output);
+            }
+            Ok(new) => println!(
+                "{} -> {}\t{} -> {}\t{:.1}% of words changed",
+                entry.id,
+                new.id,
+                entry.response.model,
+                new.response.model,
+                100.0 * output::word_change(&entry.response.transcript, &new.response.transcript)
+            ),
+            Err(err) => {
+                failed += 1;
+                eprintln!("[parakeet] re-transcribing {} failed: {err:#}", entry.id);
+                porcelain::failed(&entry.input, &err);
+            }
+        }
+    }
+    if failed > 0 {
+        bail!(
+            "{failed} of {} run(s) failed to re-transcribe",
+            selected.len()
+        );
+    }
+    Ok(())
+}
+
+/// Runs the input through the usual path (daemon if running) with the original
+/// tags and metadata. The original output file is only touched with a `guard`
+/// (see `output::guard_existing`) and while it is not archived.
+async fn retranscribe(
+    entry: &HistoryEntry,
+    model: &str,
+    guard: Option<f64>,
+) -> Result<HistoryEntry> {
+    let target = guard.and(entry.output_path.as_deref()).and_then(|path| {
+        let format = match Path::new(path).extension()?.to_str()? {
+            "txt" => "text",
+            ext @ ("md" | "srt" | "vtt") => ext,
+            _ => return None,
+        };
+        Some((path, format))
+    });
+    if guard.is_some() && entry.archived.is_none() && target.is_none() {
+        eprintln!(
+            "[parakeet] {} has no output file in a known format; recording history only",
+            entry.id
+        );
+    }
+    let target = target.filter(|_| entry.archived.is_none());
+    let format = match (target, &entry.response.segments) {
+        (Some((_, format)), _) => format,
+        (None, Some(_)) => "srt",
+        (None, None) => "text",
+    };
+    let argv = [
+        "parakeet",
+        "--progress",
+        "none",
+        "--model",
+        model,
+        "--format",
+        format,
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .chain(["--input".to_string(), entry.input.clone()])
+    .chain(
+        entry
+            .response
+            .tags
+            .iter()
+            .flat_map(|(key, value)| ["--tag".to_string(), format!("{key}={value}")]),
+    );
+    let output = target.into_iter().zip(guard).flat_map(|((path, _), pct)| {
+        [
+            "--out".to_string(),
+            path.to_string(),
+            "--diff-guard".to_string(),
+            pct.to_string(),
+        ]
+    });
+    let mut cli = TranscribeCli::try_parse_from(argv.chain(output))
+        .context("invalid re-transcription flags")?;
+    cli.metadata = entry.response.metadata.clone();
+    let (_, response) = crate::transcribe_job(cli).await?;
+    let id = response
+        .history_id
+        .context("the re-transcription was not recorded in history")?;
+    let mut new = history::load(&id)?;
+    new.retranscribed_from = Some(entry.id.clone());
+    history::save(&new)?;
+    Ok(new)
+}
+
+enum Value {
+    Number(f64),
+    Text(String),
+    Flag(bool),
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Flag(String),
+    Compare(String, Op, String),
+}
+
+impl Expr {
+    fn eval(&self, entry: &HistoryEntry, now: u64) -> bool {
+        match self {
+            Self::Or(a, b) => a.eval(entry, now) || b.eval(entry, now),
+            Self::And(a, b) => a.eval(entry, now) && b.eval(entry, now),
+            Self::Not(inner) => !inner.eval(entry, now),
+            Self::Flag(name) => match field(entry, name, now) {
+                Some(Value::Flag(set)) => set,
+                Some(Value::Text(text)) => truthy(&text),
+                Some(Value::Number(n)) => n != 0.0,
+                None => false,
+            },
+            Self::Compare(name, op, wanted) => match field(entry, name, now) {
+                Some(Value::Number(n)) => wanted.parse::<f64>().is_ok_and(|w| compare(n, *op, w)),
+                Some(Value::Text(text)) => match op {
+                    Op::Eq => text == *wanted,
+                    Op::Ne => text != *wanted,
+                    _ => match (text.parse::<f64>(), wanted.parse::<f64>()) {
+                        (Ok(n), Ok(w)) => compare(n, *op, w),
+                        _ => false,
+                    },
+                },
+                Some(Value::Flag(set)) => match op {
+                    Op::Eq => set == truthy(wanted),
+                    Op::Ne => set != truthy(wanted),
+                    _ => false,
+                },
+                None => false,
+            },
+        }
+    }
+}
+
+fn compare(a: f64, op: Op, b: f64) -> bool {
+    match op {
+        Op::Eq => a == b,
+        Op::Ne => a != b,
+        Op::Lt => a < b,
+        Op::Le => a <= b,
+        Op::Gt => a > b,
+        Op::Ge => a >= b,
+    }
+}
+
+fn truthy(raw: &str) -> bool {
+    !matches!(
+        raw.to_ascii_lowercase().as_str(),
+        "" | "0" | "false" | "no" | "off"
+    )
+}
+
+/// `tag.KEY` and `meta.KEY` read the run's tags and metadata; any other
+/// unknown name is looked up as a tag, so `needs_review` means
+/// `--tag needs_review=1`.
+fn field(entry: &HistoryEntry, name: &str, now: u64) -> Option<Value> {
+    let response = &entry.response;
+    let metrics = response.metrics.as_ref();
+    let value = match name {
+        "rtf" => Value::Number(
+            metrics.and_then(|m| m.audio_sec.filter(|a| *a > 0.0).map(|a| m.total_sec / a))?,
+        ),
+        "audio_sec" => Value::Number(metrics?.audio_sec?),
+        "total_sec" => Value::Number(metrics?.total_sec),
+        "age_days" => Value::Number(now.saturating_sub(entry.created_at) as f64 / 86_400.0),
+        "words" => Value::Number(response.transcript.split_whitespace().count() as f64),
+        "model" => Value::Text(response.model.clone()),
+        "device" => Value::Text(response.device.clone()),
+        "language" => Value::Text(response.language.clone()?),
+        "input" => Value::Text(entry.input.clone()),
+        "id" => Value::Text(entry.id.clone()),
+        "edited" => Value::Flag(entry.edited_at.is_some()),
+        "archived" => Value::Flag(entry.archived.is_some()),
+        "language_mismatch" => Value::Flag(response.language_mismatch),
+        "retranscribed" => Value::Flag(entry.retranscribed_from.is_some()),
+        _ => match name.split_once('.') {
+            Some(("meta", key)) => Value::Text(response.metadata.get(key)?.clone()),
+            Some(("tag", key)) => Value::Text(response.tags.get(key)?.clone()),
+            _ => Value::Text(response.tags.get(name)?.clone()),
+        },
+    };
+    Some(value)
+}
+
+#[derive(Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+fn tokenize(raw: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            '\'' | '"' => {
+                chars.next();
+                let text: String = chars.by_ref().take_while(|&d| d != c).collect();
+                tokens.push(Token::Word(text));
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let double = chars.next_if_eq(&'=').is_some();
+                tokens.push(Token::Op(match (c, double) {
+                    ('=', _) => "=",
+                    ('!', true) => "!=",
+                    ('<', false) => "<",
+                    ('<', true) => "<=",
+                    ('>', false) => ">",
+                    ('>', true) => ">=",
+                    _ => bail!("unexpected `!`"),
+                }));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(d) =
+                    chars.next_if(|d| !d.is_whitespace() && !"()=!<>'\"".contains(*d))
+                {
+                    word.push(d);
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse(raw: &str) -> Result<Expr> {
+    let tokens = tokenize(raw)?;
+    let mut pos = 0;
+    let expr = parse_or(&tokens, &mut pos)?;
+    if pos < tokens.len() {
+        bail!("unexpected input after position {pos}");
+    }
+    Ok(expr)
+}
+
+fn keyword(tokens: &[Token], pos: usize, word: &str) -> bool {
+    matches!(tokens.get(pos), Some(Token::Word(w)) if w.eq_ignore_ascii_case(word))
+}
+
+fn parse_or(tokens: &[Token], pos: &mut usize) -> Result<Expr> {
+    let mut expr = parse_and(tokens, pos)?;
+    while keyword(tokens, *pos, "or") {
+        *pos += 1;
+        expr = Expr::Or(Box::new(expr), Box::new(parse_and(tokens, pos)?));
+    }
+    Ok(expr)
+}
+
+fn parse_and(tokens: &[Token], pos: &mut usize) -> Result<Expr> {
+    let mut expr = parse_unary(tokens, pos)?;
+    while keyword(tokens, *pos, "and") {
+        *pos += 1;
+        expr = Expr::And(Box::new(expr), Box::new(parse_unary(tokens, pos)?));
+    }
+    Ok(expr)
+}
+
+fn parse_unary(tokens: &[Token], pos: &mut usize) -> Result<Expr> {
+    if keyword(tokens, *pos, "not") {
+        *pos += 1;
+        return Ok(Expr::Not(Box::new(parse_unary(tokens, pos)?)));
+    }
+    match tokens.get(*pos).cloned() {
+        Some(Token::Open) => {
+            *pos += 1;
+            let expr = parse_or(tokens, pos)?;
+            if tokens.get(*pos) != Some(&Token::Close) {
+                bail!("missing `)`");
+            }
+            *pos += 1;
+            Ok(expr)
+        }
+        Some(Token::Word(name)) => {
+            *pos += 1;
+            let op = match tokens.get(*pos) {
+                Some(Token::Op(op)) => *op,
+                _ => return Ok(Expr::Flag(name)),
+            };
+            *pos += 1;
+            let Some(Token::Word(value)) = tokens.get(*pos).cloned() else {
+                bail!("`{name} {op}` needs a value");
+            };
+            *pos += 1;
+            let op = match op {
+                "=" => Op::Eq,
+                "!=" => Op::Ne,
+                "<" => Op::Lt,
+                "<=" => Op::Le,
+                ">" => Op::Gt,
+                _ => Op::Ge,
+            };
+            if !matches!(op, Op::Eq | Op::Ne) {
+                check_ordered(&name)?;
+            }
+            Ok(Expr::Compare(name, op, value))
+        }
+        _ => bail!("expected a field name at position {}", *pos),
+    }
+}
+
+// Ordering only makes sense on known fields; catches typos such as `rft>1`,
+// which would otherwise be read as a tag and match nothing.
+fn check_ordered(name: &str) -> Result<()> {
+    if NUMBER_FIELDS.contains(&name) || name.starts_with("tag.") || name.starts_with("meta.") {
+        return Ok(());
+    }
+    bail!(
+        "`{name}` cannot be compared with < or >; numeric fields: {}",
+        NUMBER_FIELDS.join(", ")
+    )
+}
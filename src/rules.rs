@@ -0,0 +1,123 @@
+//! `--rules FILE`: ordered regex replacements applied to the final text,
+//! for misrecognitions a project keeps running into. The file is TOML:
+//!
+//! ```toml
+//! [[rule]]
+//! pattern = "cool ?bernetes"
+//! replace = "Kubernetes"
+//! # Optional: match case (default false) and only whole words (default false).
+//! case_sensitive = false
+//! whole_word = true
+//! ```
+//!
+//! Rules run top to bottom, each on the output of the one before; `replace`
+//! may refer to capture groups (`$1`, `${name}`).
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use regex::{Regex, RegexBuilder};
+
+use crate::{BackendResponse, PostprocessStep};
+
+const VERSION: u32 = 1;
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<RawRule>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRule {
+    pattern: String,
+    replace: String,
+    #[serde(default)]
+    case_sensitive: bool,
+    #[serde(default)]
+    whole_word: bool,
+}
+
+pub struct Rule {
+    regex: Regex,
+    replace: String,
+}
+
+/// Reads and compiles the rules, naming the offending rule on errors.
+pub fn load(path: &Path) -> Result<Vec<Rule>> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("failed reading rules: {}", path.display()))?;
+    let file: RulesFile =
+        toml::from_str(&raw).with_context(|| format!("invalid rules file: {}", path.display()))?;
+    if file.rules.is_empty() {
+        bail!("no [[rule]] entries in {}", path.display());
+    }
+    file.rules
+        .into_iter()
+        .enumerate()
+        .map(|(idx, rule)| {
+            let pattern = if rule.whole_word {
+                format!(r"\b(?:{})\b", rule.pattern)
+            } else {
+                rule.pattern.clone()
+            };
+            let regex = RegexBuilder::new(&pattern)
+                .case_insensitive(!rule.case_sensitive)
+                .build()
+                .with_context(|| {
+                    format!(
+                        "invalid pattern in rule {} of {}: {}",
+                        idx + 1,
+                        path.display(),
+                        rule.pattern
+                    )
+                })?;
+            Ok(Rule {
+                regex,
+                replace: rule.replace,
+            })
+        })
+        .collect()
+}
+
+/// Applies `rules` to the transcript, segments and alternatives, and records
+/// how many replacements were made.
+pub fn apply(parsed: &mut BackendResponse, path: &Path, rules: &[Rule]) {
+    let mut replaced = 0;
+    parsed.transcript = rewrite(&parsed.transcript, rules, &mut replaced);
+    // Counted once, on the transcript; segments hold the same text.
+    let mut ignored = 0;
+    for seg in parsed.segments.iter_mut().flatten() {
+        seg.text = rewrite(&seg.text, rules, &mut ignored);
+    }
+    for alt in parsed.alternatives.iter_mut().flatten() {
+        alt.text = rewrite(&alt.text, rules, &mut ignored);
+    }
+    parsed.postprocessing.push(PostprocessStep::new(
+        "rules",
+        VERSION,
+        serde_json::json!({
+            "file": path.display().to_string(),
+            "rules": rules.len(),
+            "replacements": replaced,
+        }),
+    ));
+}
+
+fn rewrite(text: &str, rules: &[Rule], replaced: &mut usize) -> String {
+    let mut out = text.to_string();
+    for rule in rules {
+        let hits = rule.regex.find_iter(&out).count();
+        if hits > 0 {
+            *replaced += hits;
+            out = rule
+                .regex
+                .replace_all(&out, rule.replace.as_str())
+                .into_owned();
+        }
+    }
+    out
+}
@@ -0,0 +1,263 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+use crate::tools;
+
+const UNIT_NAME: &str = "parakeetd";
+const LAUNCHD_LABEL: &str = "com.artile.parakeetd";
+// systemd passes activated sockets starting at this fd (SD_LISTEN_FDS_START).
+const LISTEN_FDS_START: i32 = 3;
+
+/// Paths baked into generated service definitions.
+pub struct ServicePaths {
+    pub exe: PathBuf,
+    pub home: PathBuf,
+    pub socket: PathBuf,
+    pub logfile: PathBuf,
+}
+
+/// Writes `parakeetd.service` (and with `socket_activation`, `parakeetd.socket`)
+/// into `unit_dir`, or the standard system/user unit directory, and prints how
+/// to enable them. Units are only written; `systemctl` is left to the caller.
+pub fn install_systemd(
+    paths: &ServicePaths,
+    user: bool,
+    socket_activation: bool,
+    unit_dir: Option<PathBuf>,
+) -> Result<()> {
+    let unit_dir = match unit_dir {
+        Some(dir) => dir,
+        None if user => user_unit_dir()?,
+        None => PathBuf::from("/etc/systemd/system"),
+    };
+    fs::create_dir_all(&unit_dir)
+        .with_context(|| format!("failed creating unit dir: {}", unit_dir.display()))?;
+
+    let service_path = unit_dir.join(format!("{UNIT_NAME}.service"));
+    write_unit(
+        &service_path,
+        &systemd_service(paths, user, socket_activation),
+    )?;
+    let mut enable = format!("{UNIT_NAME}.service");
+    if socket_activation {
+        let socket_path = unit_dir.join(format!("{UNIT_NAME}.socket"));
+        write_unit(&socket_path, &systemd_socket(paths))?;
+        enable = format!("{UNIT_NAME}.socket");
+    }
+
+    let systemctl = if user {
+        "systemctl --user"
+    } else {
+        "systemctl"
+    };
+    println!("enable with: {systemctl} daemon-reload && {systemctl} enable --now {enable}");
+    if user {
+        println!("to keep it running while logged out: loginctl enable-linger");
+    }
+    Ok(())
+}
+
+fn user_unit_dir() -> Result<PathBuf> {
+    if let Some(config) = std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(config).join("systemd/user"));
+    }
+    let home = std::env::var_os("HOME").context("HOME is not set; pass --unit-dir")?;
+    Ok(PathBuf::from(home).join(".config/systemd/user"))
+}
+
+/// Writes a LaunchAgent plist (`~/Library/LaunchAgents/<label>.plist` unless
+/// `agent_dir` is given) and, unless `no_load`, (re)loads it into the user's
+/// launchd session so the daemon runs now and at every login.
+pub fn install_launchd(
+    paths: &ServicePaths,
+    agent_dir: Option<PathBuf>,
+    no_load: bool,
+) -> Result<()> {
+    let agent_dir = match agent_dir {
+        Some(dir) => dir,
+        None => {
+            let home = std::env::var_os("HOME").context("HOME is not set; pass --agent-dir")?;
+            PathBuf::from(home).join("Library/LaunchAgents")
+        }
+    };
+    let launchctl = if no_load {
+        None
+    } else {
+        Some(tools::require(&tools::LAUNCHCTL)?)
+    };
+    fs::create_dir_all(&agent_dir)
+        .with_context(|| format!("failed creating agent dir: {}", agent_dir.display()))?;
+    let plist = agent_dir.join(format!("{LAUNCHD_LABEL}.plist"));
+    write_unit(&plist, &launchd_plist(paths))?;
+
+    let domain = format!("gui/{}", current_uid()?);
+    let Some(launchctl) = launchctl else {
+        println!(
+            "load with: launchctl bootstrap {domain} {}",
+            plist.display()
+        );
+        return Ok(());
+    };
+    // Replacing a loaded agent needs a bootout first; failure just means it was not loaded.
+    let _ = Command::new(&launchctl)
+        .args(["bootout", &format!("{domain}/{LAUNCHD_LABEL}")])
+        .output();
+    let output = Command::new(&launchctl)
+        .args(["bootstrap", &domain])
+        .arg(&plist)
+        .output()
+        .context("failed running launchctl")?;
+    if !output.status.success() {
+        bail!(
+            "launchctl bootstrap failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    println!("loaded {LAUNCHD_LABEL}; stop with: launchctl bootout {domain}/{LAUNCHD_LABEL}");
+    Ok(())
+}
+
+fn current_uid() -> Result<u32> {
+    let output = Command::new("id")
+        .arg("-u")
+        .output()
+        .context("failed running id -u")?;
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .context("unexpected output from id -u")
+}
+
+fn launchd_plist(paths: &ServicePaths) -> String {
+    let args: String = [
+        paths.exe.display().to_string(),
+        "daemon".to_string(),
+        "serve".to_string(),
+        "--socket".to_string(),
+        paths.socket.display().to_string(),
+    ]
+    .iter()
+    .map(|arg| format!("        <string>{}</string>\n", xml_escape(arg)))
+    .collect();
+    let home = xml_escape(&paths.home.display().to_string());
+    let log = xml_escape(&paths.logfile.display().to_string());
+    let timeout = crate::DEFAULT_STOP_TIMEOUT_SEC as u64;
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{LAUNCHD_LABEL}</string>
+    <key>ProgramArguments</key>
+    <array>
+{args}    </array>
+    <key>EnvironmentVariables</key>
+    <dict>
+        <key>PARAKEET_HOME</key>
+        <string>{home}</string>
+    </dict>
+    <key>RunAtLoad</key>
+    <true/>
+    <!-- Restart after crashes, not after a clean stop. -->
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <!-- `daemon serve` lets in-flight requests finish on SIGTERM. -->
+    <key>ExitTimeOut</key>
+    <integer>{timeout}</integer>
+    <key>StandardOutPath</key>
+    <string>{log}</string>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#
+    )
+}
+
+fn xml_escape(raw: &str) -> String {
+    raw.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn write_unit(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("failed writing unit: {}", path.display()))?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+fn systemd_service(paths: &ServicePaths, user: bool, socket_activation: bool) -> String {
+    let mut unit = String::from("[Unit]\nDescription=Parakeet transcription daemon\n");
+    if socket_activation {
+        unit.push_str(&format!(
+            "Requires={UNIT_NAME}.socket\nAfter={UNIT_NAME}.socket\n"
+        ));
+    }
+    unit.push_str(&format!(
+        "\n[Service]\n\
+         Type=simple\n\
+         ExecStart={} daemon serve --socket {}\n\
+         Environment={}\n\
+         Restart=on-failure\n\
+         # `daemon serve` lets in-flight requests finish on SIGTERM.\n\
+         TimeoutStopSec={}\n\
+         StandardOutput=append:{}\n\
+         StandardError=inherit\n",
+        quote(&paths.exe),
+        quote(&paths.socket),
+        quote_str(&format!("PARAKEET_HOME={}", paths.home.display())),
+        crate::DEFAULT_STOP_TIMEOUT_SEC as u64,
+        paths.logfile.display(),
+    ));
+    let wanted_by = if user {
+        "default.target"
+    } else {
+        "multi-user.target"
+    };
+    unit.push_str(&format!("\n[Install]\nWantedBy={wanted_by}\n"));
+    unit
+}
+
+fn systemd_socket(paths: &ServicePaths) -> String {
+    format!(
+        "[Unit]\n\
+         Description=Parakeet transcription daemon socket\n\
+         \n\
+         [Socket]\n\
+         ListenStream={}\n\
+         SocketMode=0600\n\
+         RemoveOnStop=true\n\
+         \n\
+         [Install]\n\
+         WantedBy=sockets.target\n",
+        paths.socket.display(),
+    )
+}
+
+fn quote(path: &Path) -> String {
+    quote_str(&path.display().to_string())
+}
+
+/// Double-quotes a unit file value when it contains whitespace or quotes.
+fn quote_str(raw: &str) -> String {
+    if raw.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+        format!("\"{}\"", raw.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        raw.to_string()
+    }
+}
+
+/// The listening socket inherited from systemd socket activation, if this
+/// process is the one it was meant for (`LISTEN_PID`).
+pub fn inherited_listen_fd() -> Option<i32> {
+    let pid: u32 = std::env::var("LISTEN_PID").ok()?.parse().ok()?;
+    let fds: u32 = std::env::var("LISTEN_FDS").ok()?.parse().ok()?;
+    (pid == std::process::id() && fds >= 1).then_some(LISTEN_FDS_START)
+}
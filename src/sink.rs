@@ -0,0 +1,123 @@
+use std::io::{Write, stdout};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{OutputFormat, output, remote, tools};
+
+/// A rendered transcript ready to be delivered.
+pub struct Document<'a> {
+    pub content: &'a str,
+    // Suggested file/object name (from `--name-template`, else `{stem}.{ext}`).
+    pub name: &'a str,
+    pub format: OutputFormat,
+    pub source: &'a str,
+}
+
+/// A destination for rendered output. Every configured sink receives the same
+/// document, so any format can go to any combination of destinations.
+pub trait Sink {
+    /// Delivers the document and returns where it went (path, URL, ...).
+    fn deliver(&self, doc: &Document<'_>) -> Result<String>;
+}
+
+pub struct FileSink(pub PathBuf);
+
+impl Sink for FileSink {
+    fn deliver(&self, doc: &Document<'_>) -> Result<String> {
+        output::write_output(&self.0, doc.content)?;
+        Ok(self.0.display().to_string())
+    }
+}
+
+pub struct StdoutSink;
+
+impl Sink for StdoutSink {
+    fn deliver(&self, doc: &Document<'_>) -> Result<String> {
+        let mut out = stdout().lock();
+        writeln!(out, "{}", doc.content).context("failed writing to stdout")?;
+        Ok("stdout".to_string())
+    }
+}
+
+/// `s3://` / `gs://` URL; one ending in `/` is a prefix the document name is appended to.
+pub struct ObjectStoreSink(pub String);
+
+impl Sink for ObjectStoreSink {
+    fn deliver(&self, doc: &Document<'_>) -> Result<String> {
+        let url = if self.0.ends_with('/') {
+            format!("{}{}", self.0, doc.name)
+        } else {
+            self.0.clone()
+        };
+        remote::upload(&url, doc.content.as_bytes())?;
+        Ok(url)
+    }
+}
+
+/// POSTs the document as JSON (`name`, `format`, `source`, `content`).
+pub struct WebhookSink(pub String);
+
+impl Sink for WebhookSink {
+    fn deliver(&self, doc: &Document<'_>) -> Result<String> {
+        let body = serde_json::json!({
+            "name": doc.name,
+            "format": doc.format.as_str(),
+            "source": doc.source,
+            "content": doc.content,
+        });
+        let mut child = Command::new(tools::require(&tools::CURL)?)
+            .args(["-sS", "-f", "--max-time", "30", "-X", "POST"])
+            .args([
+                "-H",
+                "Content-Type: application/json",
+                "--data-binary",
+                "@-",
+            ])
+            .arg(&self.0)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("failed running curl for webhook sink")?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(body.to_string().as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "webhook {} failed: {}",
+                self.0,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(self.0.clone())
+    }
+}
+
+/// Parses a `--sink` spec: `stdout` (or `-`), `file:PATH`, `s3://...`,
+/// `gs://...`, `webhook:URL` or a bare `http(s)://` URL.
+pub fn parse(spec: &str) -> Result<Box<dyn Sink>> {
+    let sink: Box<dyn Sink> = match spec {
+        "stdout" | "-" => Box::new(StdoutSink),
+        _ if spec.starts_with("s3://") || spec.starts_with("gs://") => {
+            Box::new(ObjectStoreSink(spec.to_string()))
+        }
+        _ if spec.starts_with("http://") || spec.starts_with("https://") => {
+            Box::new(WebhookSink(spec.to_string()))
+        }
+        _ => match spec.split_once(':') {
+            Some(("file", path)) if !path.is_empty() => Box::new(FileSink(PathBuf::from(path))),
+            Some(("webhook", url)) if !url.is_empty() => Box::new(WebhookSink(url.to_string())),
+            _ => bail!(
+                "invalid --sink {spec:?} (expected stdout, file:PATH, s3://..., gs://..., webhook:URL)"
+            ),
+        },
+    };
+    Ok(sink)
+}
+
+pub fn is_stdout(spec: &str) -> bool {
+    matches!(spec, "stdout" | "-")
+}
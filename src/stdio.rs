@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::oneshot;
+
+use crate::error::{self, ErrorKind, Failure};
+use crate::{EmitMode, TranscribeCli};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+// Running jobs and the channel that cancels each one.
+type Jobs = Arc<Mutex<HashMap<u64, oneshot::Sender<()>>>>;
+
+/// `parakeet serve-stdio`: line-delimited JSON-RPC 2.0 on stdin/stdout, for
+/// desktop frontends that embed the CLI as a child process.
+///
+/// Requests: `submit {input, args?}` -> `{job}`, `cancel {job}` ->
+/// `{cancelled}`, `shutdown`. Each job then reports through notifications:
+/// `progress` (the `--progress json` events plus `job`), and exactly one of
+/// `result {job, response}` or `failed {job, exit_code, error}`.
+///
+/// Jobs go through the usual backend path, so a running daemon is reused.
+/// Closing stdin cancels outstanding jobs and exits.
+pub async fn serve() -> Result<()> {
+    let jobs: Jobs = Arc::default();
+    let mut next_job = 1;
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await.context("failed reading stdin")? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: Value = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(err) => {
+                reply_error(&Value::Null, PARSE_ERROR, &format!("parse error: {err}"));
+                continue;
+            }
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            reply_error(&id, INVALID_REQUEST, "missing method");
+            continue;
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        match method {
+            "submit" => match parse_submit(&params) {
+                Ok(cli) => {
+                    let job = next_job;
+                    next_job += 1;
+                    spawn_job(&jobs, job, cli);
+                    reply(&id, json!({ "job": job }));
+                }
+                Err(message) => reply_error(&id, INVALID_PARAMS, &message),
+            },
+            "cancel" => match params.get("job").and_then(Value::as_u64) {
+                Some(job) => {
+                    // The job reports `failed` itself once its backend is stopped.
+                    let cancelled = jobs
+                        .lock()
+                        .unwrap()
+                        .remove(&job)
+                        .is_some_and(|cancel| cancel.send(()).is_ok());
+                    reply(&id, json!({ "cancelled": cancelled }));
+                }
+                None => reply_error(&id, INVALID_PARAMS, "cancel needs {\"job\": <id>}"),
+            },
+            "shutdown" => {
+                reply(&id, json!({}));
+                break;
+            }
+            _ => reply_error(&id, METHOD_NOT_FOUND, &format!("unknown method: {method}")),
+        }
+    }
+    // Returning shuts the runtime down, dropping each job's future, which
+    // kills its backend subprocess.
+    Ok(())
+}
+
+/// Builds the job's options as if `parakeet --input <input> <args...>` had
+/// been run, with the result always returned as JSON.
+fn parse_submit(params: &Value) -> std::result::Result<TranscribeCli, String> {
+    let input = params
+        .get("input")
+        .and_then(Value::as_str)
+        .ok_or("submit needs {\"input\": <path or URL>, \"args\": [...]}")?;
+    let args = match params.get("args") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(args)) => args
+            .iter()
+            .map(|arg| arg.as_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("submit args must be strings")?,
+        Some(_) => return Err("submit args must be an array of strings".to_string()),
+    };
+    let argv = ["parakeet", "--input", input]
+        .into_iter()
+        .map(str::to_string)
+        .chain(args);
+    let mut cli = TranscribeCli::try_parse_from(argv).map_err(|err| err.to_string())?;
+    cli.emit = EmitMode::Json;
+    Ok(cli)
+}
+
+fn spawn_job(jobs: &Jobs, job: u64, mut cli: TranscribeCli) {
+    cli.rpc_job = Some(job);
+    let timeout = cli.timeout;
+    let (cancel, cancelled) = oneshot::channel();
+    jobs.lock().unwrap().insert(job, cancel);
+    let registry = Arc::clone(jobs);
+    tokio::spawn(async move {
+        // Dropping the transcription future kills the backend subprocess (kill_on_drop).
+        let result = tokio::select! {
+            result = crate::transcribe_job(cli) => result,
+            _ = cancelled => Err(Failure::new(ErrorKind::Cancelled, "cancelled").into()),
+            _ = crate::sleep_until_timeout(timeout) => Err(Failure::new(
+                ErrorKind::Timeout,
+                format!("timed out after {}s", timeout.unwrap_or_default()),
+            )
+            .into()),
+        };
+        registry.lock().unwrap().remove(&job);
+        match result {
+            Ok((_, response)) => notify("result", json!({ "job": job, "response": response })),
+            Err(err) => notify_failed(job, &err),
+        }
+    });
+}
+
+fn notify_failed(job: u64, err: &anyhow::Error) {
+    let mut params = error::to_json(err);
+    params["job"] = json!(job);
+    params["exit_code"] = json!(error::exit_code_of(err) as i32);
+    notify("failed", params);
+}
+
+/// Writes a JSON-RPC notification as one line on stdout.
+pub fn notify(method: &str, params: Value) {
+    write_line(&json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+}
+
+fn reply(id: &Value, result: Value) {
+    write_line(&json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+}
+
+fn reply_error(id: &Value, code: i64, message: &str) {
+    write_line(&json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    }));
+}
+
+fn write_line(message: &Value) {
+    // One locked write per message keeps lines from concurrent jobs intact.
+    let mut out = std::io::stdout().lock();
+    let _ = writeln!(out, "{message}").and_then(|()| out.flush());
+}
@@ -0,0 +1,401 @@
+use crate::{Segment, Word};
+
+// Silence that always closes a segment, even mid-sentence.
+const SEGMENT_GAP_SEC: f64 = 1.0;
+
+/// One transcribed window of a longer recording. Word times are absolute
+/// (already offset by the window start).
+pub struct Chunk {
+    pub window_start: f64,
+    pub window_end: f64,
+    pub words: Vec<Word>,
+}
+
+/// Joins consecutive chunk transcripts into one word sequence, a chunk at a
+/// time, so a caller can save the words so far after every chunk.
+///
+/// Inside each overlap the two hypotheses are aligned on normalized tokens.
+/// Where they agree the word is kept once; where they disagree the chunk whose
+/// window centre is closer wins the vote, since ASR quality drops near a
+/// window edge (truncated words, missing context).
+#[derive(Default)]
+pub struct Stitcher {
+    words: Vec<Word>,
+    // End of the last window pushed.
+    end: Option<f64>,
+}
+
+impl Stitcher {
+    /// Adds the next chunk in window order. Only the words reaching into its
+    /// overlap are revisited, so stitching a recording stays linear.
+    pub fn push(&mut self, chunk: Chunk) {
+        let Chunk {
+            window_start,
+            window_end,
+            mut words,
+        } = chunk;
+        let Some(merged_end) = self.end.replace(window_end) else {
+            self.words = words;
+            return;
+        };
+        let overlap_start = window_start;
+        let overlap_end = merged_end.min(window_end);
+        if overlap_end <= overlap_start {
+            self.words.extend(words);
+            return;
+        }
+
+        // Words are in time order, so the overlap is found from the end.
+        let split = self
+            .words
+            .iter()
+            .rposition(|w| midpoint(w) < overlap_start)
+            .map_or(0, |idx| idx + 1);
+        let tail = self.words.split_off(split);
+        let head_len = words
+            .iter()
+            .position(|w| midpoint(w) >= overlap_end)
+            .unwrap_or(words.len());
+        let rest = words.split_off(head_len);
+
+        let seam = (overlap_start + overlap_end) / 2.0;
+        self.words.extend(merge_overlap(&tail, &words, seam));
+        self.words.extend(rest);
+    }
+
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    pub fn into_words(self) -> Vec<Word> {
+        self.words
+    }
+}
+
+/// Gives words without a confidence the score of the chunk segment they fall
+/// in, for backends that score segments only (external engines, older
+/// backends).
+pub fn fill_confidence(words: &mut [Word], segments: &[Segment]) {
+    for word in words.iter_mut().filter(|w| w.confidence.is_none()) {
+        let mid = midpoint(word);
+        word.confidence = segments
+            .iter()
+            .find(|seg| seg.start <= mid && mid <= seg.end)
+            .and_then(|seg| seg.confidence);
+    }
+}
+
+/// Regroups stitched words into sentence-like segments (sentence punctuation
+/// or a long pause). Each segment takes its weakest word's confidence, as the
+/// backend scores its own segments.
+pub fn segments_from_words(words: &[Word]) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut open = false;
+    for (idx, word) in words.iter().enumerate() {
+        let gap = idx > 0 && word.start - words[idx - 1].end >= SEGMENT_GAP_SEC;
+        match segments.last_mut() {
+            Some(seg) if open && !gap => {
+                seg.text.push(' ');
+                seg.text.push_str(&word.text);
+                seg.end = word.end;
+                seg.confidence = match (seg.confidence, word.confidence) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            _ => segments.push(Segment {
+                start: word.start,
+                end: word.end,
+                text: word.text.clone(),
+                confidence: word.confidence,
+                speaker: None,
+            }),
+        }
+        open = !word.text.ends_with(['.', '!', '?']);
+    }
+    segments
+}
+
+fn merge_overlap(left: &[Word], right: &[Word], seam: f64) -> Vec<Word> {
+    // One side heard nothing in the overlap: nothing to vote against.
+    if left.is_empty() || right.is_empty() {
+        return left.iter().chain(right).cloned().collect();
+    }
+    let anchors = align(left, right);
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (ai, aj) in anchors
+        .iter()
+        .copied()
+        .chain(std::iter::once((left.len(), right.len())))
+    {
+        out.extend(vote_gap(&left[i..ai], &right[j..aj], seam));
+        if ai < left.len() && aj < right.len() {
+            let pick = if midpoint(&left[ai]) < seam {
+                &left[ai]
+            } else {
+                &right[aj]
+            };
+            out.push(pick.clone());
+        }
+        (i, j) = (ai + 1, aj + 1);
+    }
+    out
+}
+
+// Unaligned stretch: keep one side wholesale, chosen by which side of the seam it sits on.
+fn vote_gap(left: &[Word], right: &[Word], seam: f64) -> Vec<Word> {
+    if left.is_empty() || right.is_empty() {
+        let side = if left.is_empty() { right } else { left };
+        let Some(first) = side.first() else {
+            return Vec::new();
+        };
+        // A lone stretch is only trusted on its own chunk's side of the seam.
+        let owned = if left.is_empty() {
+            midpoint(first) >= seam
+        } else {
+            midpoint(first) < seam
+        };
+        return if owned { side.to_vec() } else { Vec::new() };
+    }
+    let mids: Vec<f64> = left.iter().chain(right).map(midpoint).collect();
+    let centre = mids.iter().sum::<f64>() / mids.len() as f64;
+    if centre < seam {
+        left.to_vec()
+    } else {
+        right.to_vec()
+    }
+}
+
+/// Longest common subsequence over normalized tokens; returns matched index pairs.
+fn align(left: &[Word], right: &[Word]) -> Vec<(usize, usize)> {
+    let a: Vec<String> = left.iter().map(|w| normalize(&w.text)).collect();
+    let b: Vec<String> = right.iter().map(|w| normalize(&w.text)).collect();
+    let mut table = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            table[i][j] = if !a[i].is_empty() && a[i] == b[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if !a[i].is_empty() && a[i] == b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+fn normalize(token: &str) -> String {
+    token
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn midpoint(word: &Word) -> f64 {
+    (word.start + word.end) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(spec: &[(&str, f64)]) -> Vec<Word> {
+        spec.iter()
+            .map(|(text, start)| Word {
+                text: text.to_string(),
+                start: *start,
+                end: start + 0.4,
+                confidence: None,
+            })
+            .collect()
+    }
+
+    fn stitch<const N: usize>(chunks: [Chunk; N]) -> Vec<Word> {
+        let mut stitcher = Stitcher::default();
+        for chunk in chunks {
+            stitcher.push(chunk);
+        }
+        stitcher.into_words()
+    }
+
+    fn texts(words: &[Word]) -> Vec<&str> {
+        words.iter().map(|w| w.text.as_str()).collect()
+    }
+
+    #[test]
+    fn identical_overlap_is_kept_once() {
+        let chunks = [
+            Chunk {
+                window_start: 0.0,
+                window_end: 4.0,
+                words: words(&[("the", 0.0), ("quick", 1.0), ("brown", 2.0), ("fox", 3.0)]),
+            },
+            Chunk {
+                window_start: 2.0,
+                window_end: 6.0,
+                words: words(&[("brown", 2.0), ("fox", 3.0), ("jumps", 4.0), ("over", 5.0)]),
+            },
+        ];
+        assert_eq!(
+            texts(&stitch(chunks)),
+            ["the", "quick", "brown", "fox", "jumps", "over"]
+        );
+    }
+
+    #[test]
+    fn truncated_word_at_left_edge_loses_vote() {
+        let chunks = [
+            Chunk {
+                window_start: 0.0,
+                window_end: 4.0,
+                words: words(&[("we", 0.0), ("ship", 1.0), ("the", 2.0), ("proj", 3.5)]),
+            },
+            Chunk {
+                window_start: 2.0,
+                window_end: 6.0,
+                words: words(&[("the", 2.0), ("project", 3.5), ("today", 5.0)]),
+            },
+        ];
+        assert_eq!(
+            texts(&stitch(chunks)),
+            ["we", "ship", "the", "project", "today"]
+        );
+    }
+
+    #[test]
+    fn word_missing_at_right_chunk_start_is_recovered() {
+        let chunks = [
+            Chunk {
+                window_start: 0.0,
+                window_end: 4.0,
+                words: words(&[("call", 0.0), ("me", 1.0), ("back", 2.1), ("soon", 3.0)]),
+            },
+            Chunk {
+                window_start: 2.0,
+                window_end: 6.0,
+                words: words(&[("soon", 3.0), ("please", 4.5)]),
+            },
+        ];
+        assert_eq!(
+            texts(&stitch(chunks)),
+            ["call", "me", "back", "soon", "please"]
+        );
+    }
+
+    #[test]
+    fn agreeing_words_take_punctuation_from_the_closer_chunk() {
+        let chunks = [
+            Chunk {
+                window_start: 0.0,
+                window_end: 4.0,
+                words: words(&[("Done", 2.0), ("here", 3.5)]),
+            },
+            Chunk {
+                window_start: 2.0,
+                window_end: 6.0,
+                words: words(&[("done", 2.0), ("here.", 3.5), ("Next", 4.5)]),
+            },
+        ];
+        assert_eq!(texts(&stitch(chunks)), ["Done", "here.", "Next"]);
+    }
+
+    #[test]
+    fn chunks_without_overlap_concatenate() {
+        let chunks = [
+            Chunk {
+                window_start: 0.0,
+                window_end: 2.0,
+                words: words(&[("one", 0.5)]),
+            },
+            Chunk {
+                window_start: 2.0,
+                window_end: 4.0,
+                words: words(&[("two", 2.5)]),
+            },
+        ];
+        assert_eq!(texts(&stitch(chunks)), ["one", "two"]);
+    }
+
+    #[test]
+    fn empty_chunks_are_tolerated() {
+        let chunks = [
+            Chunk {
+                window_start: 0.0,
+                window_end: 4.0,
+                words: Vec::new(),
+            },
+            Chunk {
+                window_start: 2.0,
+                window_end: 6.0,
+                words: words(&[("silence", 2.5), ("then", 4.0), ("speech", 5.0)]),
+            },
+        ];
+        assert_eq!(texts(&stitch(chunks)), ["silence", "then", "speech"]);
+    }
+
+    fn scored(spec: &[(&str, f64, f64)]) -> Vec<Word> {
+        spec.iter()
+            .map(|(text, start, confidence)| Word {
+                confidence: Some(*confidence),
+                ..words(&[(text, *start)]).remove(0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stitched_segments_keep_the_weakest_word_confidence() {
+        let chunks = [
+            Chunk {
+                window_start: 0.0,
+                window_end: 4.0,
+                words: scored(&[("Hello", 0.0, 0.9), ("there.", 1.0, 0.4), ("How", 3.0, 0.8)]),
+            },
+            Chunk {
+                window_start: 2.0,
+                window_end: 6.0,
+                words: scored(&[("how", 3.0, 0.7), ("are", 4.0, 0.95), ("you?", 4.5, 0.6)]),
+            },
+        ];
+        let segments = segments_from_words(&stitch(chunks));
+        let scores: Vec<(&str, Option<f64>)> = segments
+            .iter()
+            .map(|seg| (seg.text.as_str(), seg.confidence))
+            .collect();
+        assert_eq!(
+            scores,
+            [("Hello there.", Some(0.4)), ("how are you?", Some(0.6))]
+        );
+    }
+
+    #[test]
+    fn words_take_their_segment_confidence_when_unscored() {
+        let mut unscored = words(&[("one", 0.0), ("two", 1.0), ("three", 5.0)]);
+        let segments = [Segment {
+            start: 0.0,
+            end: 2.0,
+            text: "one two".to_string(),
+            confidence: Some(0.5),
+            speaker: None,
+        }];
+        fill_confidence(&mut unscored, &segments);
+        let scores: Vec<Option<f64>> = unscored.iter().map(|w| w.confidence).collect();
+        assert_eq!(scores, [Some(0.5), Some(0.5), None]);
+        let segments = segments_from_words(&unscored);
+        assert_eq!(segments[0].confidence, Some(0.5));
+        assert_eq!(segments[1].confidence, None);
+    }
+}
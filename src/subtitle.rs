@@ -0,0 +1,179 @@
+use anyhow::{Result, bail};
+
+use crate::Segment;
+
+pub fn parse_srt(raw: &str) -> Result<Vec<Segment>> {
+    parse_cues(raw)
+}
+
+pub fn parse_vtt(raw: &str) -> Result<Vec<Segment>> {
+    let body = raw.trim_start_matches('\u{feff}');
+    if !body.trim_start().starts_with("WEBVTT") {
+        bail!("missing WEBVTT header");
+    }
+    parse_cues(body)
+}
+
+// SRT and VTT share the same cue shape: optional identifier, a `start --> end`
+// timing line, then text lines until a blank line.
+fn parse_cues(raw: &str) -> Result<Vec<Segment>> {
+    let normalized = raw.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let mut segments = Vec::new();
+    for block in normalized.split("\n\n") {
+        let mut lines = block
+            .lines()
+            .map(str::trim_end)
+            .skip_while(|l| l.is_empty());
+        let Some(timing) = lines.by_ref().find(|l| l.contains("-->")) else {
+            continue;
+        };
+        let (start_raw, rest) = timing.split_once("-->").unwrap_or_default();
+        let end_raw = rest.split_whitespace().next().unwrap_or_default();
+        let start = parse_timestamp(start_raw.trim())?;
+        let end = parse_timestamp(end_raw)?;
+        if end < start {
+            bail!("cue ends before it starts: {timing}");
+        }
+        let text = lines
+            .map(strip_tags)
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if text.is_empty() {
+            continue;
+        }
+        segments.push(Segment {
+            start,
+            end,
+            text,
+            confidence: None,
+            speaker: None,
+        });
+    }
+    if segments.is_empty() {
+        bail!("no subtitle cues found");
+    }
+    Ok(segments)
+}
+
+/// Parses `hh:mm:ss,mmm`, `hh:mm:ss.mmm` or `mm:ss.mmm` into seconds.
+pub fn parse_timestamp(raw: &str) -> Result<f64> {
+    let raw = raw.replace(',', ".");
+    let parts: Vec<&str> = raw.split(':').collect();
+    let (h, m, s) = match parts.as_slice() {
+        [h, m, s] => (*h, *m, *s),
+        [m, s] => ("0", *m, *s),
+        _ => bail!("invalid subtitle timestamp: {raw}"),
+    };
+    let parse = |v: &str| {
+        v.trim()
+            .parse::<f64>()
+            .map_err(|_| anyhow::anyhow!("invalid subtitle timestamp: {raw}"))
+    };
+    Ok(parse(h)? * 3600.0 + parse(m)? * 60.0 + parse(s)?)
+}
+
+fn strip_tags(line: &str) -> String {
+    let mut out = String::new();
+    let mut in_tag = false;
+    for c in line.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.trim().to_string()
+}
+
+pub fn to_srt(segments: &[Segment]) -> String {
+    let mut out = String::new();
+    for (idx, seg) in segments.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            idx + 1,
+            format_timestamp(seg.start, ','),
+            format_timestamp(seg.end, ','),
+            seg.text.trim()
+        ));
+    }
+    out
+}
+
+pub fn to_vtt(segments: &[Segment]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for seg in segments {
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_timestamp(seg.start, '.'),
+            format_timestamp(seg.end, '.'),
+            seg.text.trim()
+        ));
+    }
+    out
+}
+
+fn format_timestamp(sec: f64, millis_sep: char) -> String {
+    let total_ms = (sec.max(0.0) * 1000.0).round() as u64;
+    let (h, m, s, ms) = (
+        total_ms / 3_600_000,
+        total_ms % 3_600_000 / 60_000,
+        total_ms % 60_000 / 1000,
+        total_ms % 1000,
+    );
+    format!("{h:02}:{m:02}:{s:02}{millis_sep}{ms:03}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cues(segments: &[Segment]) -> Vec<(f64, f64, &str)> {
+        segments
+            .iter()
+            .map(|seg| (seg.start, seg.end, seg.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_srt_with_bom_crlf_and_multi_line_cues() {
+        let segments = parse_srt(include_str!("../tests/fixtures/subtitle/crlf-bom.srt")).unwrap();
+        // Cue 3 has no text and is skipped.
+        assert_eq!(
+            cues(&segments),
+            [
+                (1.0, 3.5, "First line second line"),
+                (4.0, 6.25, "Next cue"),
+                (3600.1, 3601.9, "Last cue"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_vtt_with_bom_crlf_notes_and_settings() {
+        let segments = parse_vtt(include_str!("../tests/fixtures/subtitle/crlf-bom.vtt")).unwrap();
+        assert_eq!(
+            cues(&segments),
+            [
+                (1.0, 3.5, "Hello there, and welcome."),
+                (4.0, 5.0, "Second cue")
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(parse_vtt("1\n00:00:01.000 --> 00:00:02.000\nhi\n").is_err());
+        assert!(parse_srt("1\n00:00:03,000 --> 00:00:02,000\nbackwards\n").is_err());
+        assert!(parse_srt("just some text\n").is_err());
+        assert!(parse_timestamp("1:2:3:4").is_err());
+    }
+
+    #[test]
+    fn round_trips_srt() {
+        let segments = parse_srt(include_str!("../tests/fixtures/subtitle/crlf-bom.srt")).unwrap();
+        assert_eq!(parse_srt(&to_srt(&segments)).unwrap().len(), segments.len());
+        assert!(to_srt(&segments).contains("01:00:00,100 --> 01:00:01,900\nLast cue"));
+    }
+}
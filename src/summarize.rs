@@ -0,0 +1,147 @@
+//! `--summarize`: asks an LLM for a summary and action items once the
+//! transcript is final, for meeting notes. The `summarize` section of
+//! config.json picks the backend: a `command` (run by `sh -c`, transcript on
+//! stdin, summary on stdout) or an OpenAI-compatible chat completions
+//! endpoint (`url`, `model`, `api_key_env`).
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+
+use crate::tools;
+
+const DEFAULT_URL: &str = "https://api.openai.com/v1/chat/completions";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+const DEFAULT_KEY_ENV: &str = "OPENAI_API_KEY";
+const DEFAULT_PROMPT: &str = "Summarize this transcript in a few sentences. Then list the action \
+    items (who does what, by when if stated) as Markdown bullets under an \"### Action items\" \
+    heading, or write \"No action items.\" Answer in the transcript's language, in Markdown, \
+    without a top-level heading.";
+const MAX_TIME_SEC: &str = "120";
+
+/// The `summarize` section of config.json.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct SummarizeConfig {
+    /// Shell command that reads the transcript on stdin and prints the summary;
+    /// takes precedence over the endpoint settings.
+    pub command: Option<String>,
+    /// Chat completions endpoint (default OpenAI's).
+    pub url: Option<String>,
+    pub model: Option<String>,
+    /// Env var holding the endpoint's API key (default `OPENAI_API_KEY`).
+    pub api_key_env: Option<String>,
+    /// Instructions sent ahead of the transcript.
+    pub prompt: Option<String>,
+}
+
+impl SummarizeConfig {
+    /// Fails early when the endpoint would be called without a key.
+    pub fn check(&self) -> Result<()> {
+        if self.command.is_none() && self.url.is_none() && self.api_key().is_none() {
+            bail!(
+                "--summarize needs an API key in {} (or summarize.command / summarize.url in config.json)",
+                self.key_env()
+            );
+        }
+        Ok(())
+    }
+
+    fn key_env(&self) -> &str {
+        self.api_key_env.as_deref().unwrap_or(DEFAULT_KEY_ENV)
+    }
+
+    fn api_key(&self) -> Option<String> {
+        std::env::var(self.key_env())
+            .ok()
+            .filter(|key| !key.is_empty())
+    }
+}
+
+/// Summary of `transcript` in Markdown.
+pub fn summarize(settings: &SummarizeConfig, transcript: &str) -> Result<String> {
+    let summary = match &settings.command {
+        Some(command) => run_command(command, transcript)?,
+        None => chat(settings, transcript)?,
+    };
+    let summary = summary.trim();
+    if summary.is_empty() {
+        bail!("the summarizer returned nothing");
+    }
+    Ok(summary.to_string())
+}
+
+fn run_command(command: &str, transcript: &str) -> Result<String> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed starting summarize command: {command}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(transcript.as_bytes());
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "summarize command {command:?} failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn chat(settings: &SummarizeConfig, transcript: &str) -> Result<String> {
+    let url = settings.url.as_deref().unwrap_or(DEFAULT_URL);
+    let body = serde_json::json!({
+        "model": settings.model.as_deref().unwrap_or(DEFAULT_MODEL),
+        "messages": [
+            { "role": "system", "content": settings.prompt.as_deref().unwrap_or(DEFAULT_PROMPT) },
+            { "role": "user", "content": transcript },
+        ],
+    });
+    // Key and body go through a curl config on stdin, out of `ps` and argv limits.
+    let mut config = format!("data-binary = \"{}\"\n", config_quote(&body.to_string()));
+    if let Some(key) = settings.api_key() {
+        config.push_str(&format!(
+            "header = \"Authorization: Bearer {}\"\n",
+            key.replace('"', "")
+        ));
+    }
+    let mut child = Command::new(tools::require(&tools::CURL)?)
+        .args(["-sS", "--fail-with-body", "--max-time", MAX_TIME_SEC])
+        .args(["-H", "Content-Type: application/json"])
+        .args(["--config", "-"])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed running curl for --summarize")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    let reply = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        let detail = match reply.trim() {
+            "" => String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            reply => reply.to_string(),
+        };
+        bail!("summarize request to {url} failed: {detail}");
+    }
+    let reply: serde_json::Value = serde_json::from_str(&reply)
+        .with_context(|| format!("invalid summarize response from {url}"))?;
+    reply["choices"][0]["message"]["content"]
+        .as_str()
+        .map(str::to_string)
+        .with_context(|| format!("summarize response from {url} has no message content"))
+}
+
+// Quoted strings in a curl config take `\\` and `\"` escapes.
+fn config_quote(raw: &str) -> String {
+    raw.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,226 @@
+//! `daemon serve` keeps the Python backend running: it owns the listening
+//! socket, hands it to the backend with `--listen-fd`, and restarts the
+//! backend with exponential backoff when it crashes.
+//!
+//! While the backend is down, connections are answered with an error frame
+//! naming the reason, so clients fall back (or fail) at once instead of
+//! hanging on a socket nobody accepts on.
+
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use tokio::signal::unix::{SignalKind, signal};
+
+use crate::protocol::{self, MessageType};
+use crate::{output, service};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+// A backend that ran this long before dying was healthy; backoff starts over.
+const STABLE_AFTER: Duration = Duration::from_secs(300);
+const REFUSE_POLL: Duration = Duration::from_millis(100);
+const REFUSE_READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Runs the backend built by `backend(listen_fd)` until SIGTERM/SIGINT, which
+/// is forwarded so in-flight requests can finish, or until the backend exits
+/// cleanly on its own. A `shared` socket accepts connections from every
+/// user; the backend decides whom it serves (SO_PEERCRED).
+pub async fn run(
+    socket: &Path,
+    shared: bool,
+    backend: impl Fn(RawFd) -> std::process::Command,
+) -> Result<()> {
+    let listener = Listener::open(socket, shared)?;
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let mut backoff = INITIAL_BACKOFF;
+    let mut restarts = 0u32;
+    loop {
+        listener.inner.set_nonblocking(false)?;
+        let mut cmd = backend(listener.fd());
+        let fd = listener.fd();
+        // SAFETY: fcntl is async-signal-safe.
+        unsafe {
+            cmd.pre_exec(move || {
+                // The listener is close-on-exec like every fd we open; the backend needs it.
+                let flags = libc::fcntl(fd, libc::F_GETFD);
+                if flags == -1 || libc::fcntl(fd, libc::F_SETFD, flags & !libc::FD_CLOEXEC) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let mut child = tokio::process::Command::from(cmd)
+            .spawn()
+            .context("failed launching daemon backend")?;
+        let started = Instant::now();
+        let status = tokio::select! {
+            status = child.wait() => status.context("failed waiting for daemon backend")?,
+            _ = terminate.recv() => return stop(child).await,
+            _ = interrupt.recv() => return stop(child).await,
+        };
+        if status.success() {
+            log("backend exited; supervisor stopping");
+            return Ok(());
+        }
+
+        if started.elapsed() >= STABLE_AFTER {
+            backoff = INITIAL_BACKOFF;
+        }
+        restarts += 1;
+        let reason = describe(status);
+        log(&format!(
+            "backend {reason}; restarting in {}s (restart #{restarts})",
+            backoff.as_secs()
+        ));
+        let message = format!("daemon backend {reason} and is restarting; request not run");
+        listener.inner.set_nonblocking(true)?;
+        let restart_at = tokio::time::sleep(backoff);
+        tokio::pin!(restart_at);
+        loop {
+            tokio::select! {
+                _ = &mut restart_at => break,
+                _ = tokio::time::sleep(REFUSE_POLL) => listener.refuse_pending(&message),
+                _ = terminate.recv() => return Ok(()),
+                _ = interrupt.recv() => return Ok(()),
+            }
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// Forwards SIGTERM and waits: the backend stops accepting at once but
+/// finishes in-flight requests. `daemon stop` kills the whole process group if
+/// that takes longer than its timeout.
+async fn stop(mut child: tokio::process::Child) -> Result<()> {
+    if let Some(pid) = child.id() {
+        // SAFETY: plain syscall on our own child's pid.
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGTERM);
+        }
+    }
+    child
+        .wait()
+        .await
+        .context("failed waiting for daemon backend")?;
+    Ok(())
+}
+
+fn describe(status: ExitStatus) -> String {
+    match (status.code(), status.signal()) {
+        (Some(code), _) => format!("exited with status {code}"),
+        (None, Some(sig)) => {
+            let name = match sig {
+                libc::SIGKILL => " (SIGKILL; out of memory?)",
+                libc::SIGSEGV => " (SIGSEGV)",
+                libc::SIGABRT => " (SIGABRT)",
+                libc::SIGBUS => " (SIGBUS)",
+                _ => "",
+            };
+            format!("was killed by signal {sig}{name}")
+        }
+        (None, None) => format!("stopped ({status})"),
+    }
+}
+
+// Same layout as the backend's own timestamped lines, so `daemon logs --since` sees these too.
+fn log(message: &str) {
+    let now = output::unix_now();
+    eprintln!(
+        "{}:{:02} [parakeetd] {message}",
+        output::format_datetime(now),
+        now % 60
+    );
+}
+
+struct Listener {
+    inner: UnixListener,
+    // Set when we bound the socket ourselves (not systemd); removed on exit.
+    path: Option<PathBuf>,
+}
+
+impl Listener {
+    fn open(socket: &Path, shared: bool) -> Result<Self> {
+        if let Some(fd) = service::inherited_listen_fd() {
+            // SAFETY: LISTEN_FDS promises an open listening socket at this fd, owned by us.
+            let inner = UnixListener::from(unsafe { OwnedFd::from_raw_fd(fd) });
+            return Ok(Self { inner, path: None });
+        }
+        if let Some(parent) = socket.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // A leftover socket from a crashed daemon; `daemon start` already checked nobody answers.
+        let _ = std::fs::remove_file(socket);
+        let inner = UnixListener::bind(socket)
+            .with_context(|| format!("failed binding daemon socket: {}", socket.display()))?;
+        if shared {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o666)).with_context(
+                || format!("failed opening up daemon socket: {}", socket.display()),
+            )?;
+        }
+        Ok(Self {
+            inner,
+            path: Some(socket.to_path_buf()),
+        })
+    }
+
+    fn fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+
+    fn refuse_pending(&self, message: &str) {
+        loop {
+            match self.inner.accept() {
+                Ok((stream, _)) => {
+                    if let Err(err) = refuse(stream, message) {
+                        log(&format!("failed answering a connection: {err:#}"));
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return,
+                Err(err) => {
+                    log(&format!("accept failed: {err}"));
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Answers one request (framed or legacy) with `message` as its error.
+fn refuse(mut stream: UnixStream, message: &str) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REFUSE_READ_TIMEOUT))?;
+    stream.set_write_timeout(Some(REFUSE_READ_TIMEOUT))?;
+    let payload = serde_json::json!({ "error": message }).to_string();
+    let mut magic = [0u8; 2];
+    match stream.read_exact(&mut magic) {
+        // Reachability checks connect and hang up without a request.
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+        result => result?,
+    }
+    if magic == protocol::MAGIC {
+        protocol::read_frame_after_magic(&mut stream)?;
+        protocol::write_frame(&mut stream, MessageType::Error, payload.as_bytes())
+    } else {
+        // Legacy clients send one JSON line; read it so closing does not reset the connection.
+        let mut line = Vec::new();
+        io::BufRead::read_until(&mut io::BufReader::new(&stream), b'\n', &mut line)?;
+        stream.write_all(format!("{payload}\n").as_bytes())?;
+        Ok(())
+    }
+}
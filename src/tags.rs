@@ -0,0 +1,33 @@
+use std::collections::BTreeMap;
+
+/// `--tag key=value` labels attached to a transcription, e.g. `project=alpha`.
+pub type Tags = BTreeMap<String, String>;
+
+/// Parses one `key=value` tag (clap value parser).
+pub fn parse(raw: &str) -> Result<(String, String), String> {
+    let Some((key, value)) = raw.split_once('=') else {
+        return Err(format!("invalid tag: {raw} (expected KEY=VALUE)"));
+    };
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(format!("invalid tag: {raw} (empty key)"));
+    }
+    Ok((key.to_string(), value.trim().to_string()))
+}
+
+/// Later `--tag` flags with the same key replace earlier ones.
+pub fn collect(pairs: &[(String, String)]) -> Tags {
+    pairs.iter().cloned().collect()
+}
+
+/// Whether `tags` carries every one of the `filters` (all must match).
+pub fn matches(tags: &Tags, filters: &[(String, String)]) -> bool {
+    filters
+        .iter()
+        .all(|(key, value)| tags.get(key) == Some(value))
+}
+
+/// `key=value` as the daemon keys its per-tag counters.
+pub fn label(key: &str, value: &str) -> String {
+    format!("{key}={value}")
+}
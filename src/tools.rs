@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::error::{ErrorKind, Failure};
+
+/// An external program some feature shells out to. None are needed for plain
+/// WAV/FLAC/MP3 transcription, so they are looked up when a feature needs them.
+pub struct Tool {
+    pub name: &'static str,
+    // Any one of these on PATH satisfies the tool.
+    pub binaries: &'static [&'static str],
+    pub needed_for: &'static str,
+    pub install_hint: &'static str,
+}
+
+pub const FFMPEG: Tool = Tool {
+    name: "ffmpeg",
+    binaries: &["ffmpeg"],
+    needed_for: "converting video/other containers and --start/--end cuts",
+    install_hint: "install ffmpeg (apt install ffmpeg / brew install ffmpeg)",
+};
+pub const FFPROBE: Tool = Tool {
+    name: "ffprobe",
+    binaries: &["ffprobe"],
+    needed_for: "--chunk, --duration and progress estimates",
+    install_hint: "install ffmpeg, which ships ffprobe (apt install ffmpeg / brew install ffmpeg)",
+};
+pub const FFPLAY: Tool = Tool {
+    name: "ffplay",
+    binaries: &["ffplay"],
+    needed_for: "grep --play",
+    install_hint: "install ffmpeg, which ships ffplay (apt install ffmpeg / brew install ffmpeg)",
+};
+pub const NVML: Tool = Tool {
+    name: "nvml",
+    binaries: &["nvidia-smi"],
+    needed_for: "GPU detection in preflight and doctor",
+    install_hint: "install the NVIDIA driver, which provides NVML and nvidia-smi",
+};
+pub const YT_DLP: Tool = Tool {
+    name: "yt-dlp",
+    binaries: &["yt-dlp"],
+    needed_for: "fetching media from video sites",
+    install_hint: "install yt-dlp (pipx install yt-dlp / brew install yt-dlp)",
+};
+pub const CLIPBOARD: Tool = Tool {
+    name: "clipboard",
+    binaries: &["wl-copy", "xclip", "xsel", "pbcopy"],
+    needed_for: "copying transcripts to the clipboard",
+    install_hint: "install wl-clipboard (Wayland) or xclip (X11); macOS ships pbcopy",
+};
+pub const AWS: Tool = Tool {
+    name: "aws",
+    binaries: &["aws"],
+    needed_for: "s3:// inputs and sinks",
+    install_hint: "install the AWS CLI v2 and run `aws configure`",
+};
+pub const GCLOUD: Tool = Tool {
+    name: "gcloud",
+    binaries: &["gcloud"],
+    needed_for: "gs:// inputs and sinks",
+    install_hint: "install the Google Cloud CLI and run `gcloud auth login`",
+};
+pub const CURL: Tool = Tool {
+    name: "curl",
+    binaries: &["curl"],
+    needed_for: "webhook sinks, --engine openai-api|generic-http and voicemail",
+    install_hint: "install curl (apt install curl)",
+};
+pub const NOTIFIER: Tool = Tool {
+    name: "notifier",
+    binaries: &["notify-send", "osascript"],
+    needed_for: "desktop notifications (--notify)",
+    install_hint: "install libnotify (apt install libnotify-bin); macOS ships osascript",
+};
+
+pub const TAR: Tool = Tool {
+    name: "tar",
+    binaries: &["tar"],
+    needed_for: "archiving old outputs (history archive)",
+    install_hint: "install tar (apt install tar); macOS ships bsdtar",
+};
+pub const ZSTD: Tool = Tool {
+    name: "zstd",
+    binaries: &["zstd"],
+    needed_for: "archiving old outputs (history archive)",
+    install_hint: "install zstd (apt install zstd / brew install zstd)",
+};
+
+// macOS-only, so not listed in OPTIONAL (doctor would flag it everywhere else).
+pub const LAUNCHCTL: Tool = Tool {
+    name: "launchctl",
+    binaries: &["launchctl"],
+    needed_for: "daemon install-launchd",
+    install_hint: "launchctl ships with macOS; on Linux use `parakeet daemon install-systemd`",
+};
+
+pub const OPTIONAL: &[&Tool] = &[
+    &FFMPEG, &FFPROBE, &FFPLAY, &NVML, &YT_DLP, &CLIPBOARD, &AWS, &GCLOUD, &CURL, &NOTIFIER, &TAR,
+    &ZSTD,
+];
+
+/// First of the tool's binaries found as an executable on PATH.
+pub fn find(tool: &Tool) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .flat_map(|dir| {
+            tool.binaries
+                .iter()
+                .map(move |bin| dir.join(format!("{bin}{}", std::env::consts::EXE_SUFFIX)))
+        })
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Like [`find`], but a missing tool is an `EnvironmentMissing` failure that
+/// says what needs it and how to install it.
+pub fn require(tool: &Tool) -> Result<PathBuf> {
+    find(tool).ok_or_else(|| {
+        Failure::new(
+            ErrorKind::ToolMissing,
+            format!(
+                "{} not found on PATH (needed for {})",
+                tool.name, tool.needed_for
+            ),
+        )
+        .with_hint(tool.install_hint)
+        .into()
+    })
+}
@@ -0,0 +1,96 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// A connected byte stream to the daemon. Unix domain sockets on unix, named
+/// pipes (`\\.\pipe\...`) on Windows; the framing on top is the same.
+pub trait Transport: Read + Write + Send {
+    fn set_timeouts(&self, read: Duration, write: Duration) -> io::Result<()>;
+}
+
+pub type Stream = Box<dyn Transport>;
+
+#[cfg(unix)]
+mod imp {
+    use std::io;
+    use std::os::unix::net::UnixStream;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    use super::{Stream, Transport};
+
+    impl Transport for UnixStream {
+        fn set_timeouts(&self, read: Duration, write: Duration) -> io::Result<()> {
+            self.set_read_timeout(Some(read))?;
+            self.set_write_timeout(Some(write))
+        }
+    }
+
+    pub fn connect(endpoint: &Path) -> io::Result<Stream> {
+        Ok(Box::new(UnixStream::connect(endpoint)?))
+    }
+
+    // sockaddr_un.sun_path holds 104 bytes on macOS and 108 on Linux, NUL included.
+    const MAX_SOCKET_PATH: usize = if cfg!(target_os = "macos") { 103 } else { 107 };
+
+    pub fn default_endpoint(home: &Path) -> PathBuf {
+        let socket = home.join("tmp/parakeet.sock");
+        if socket.as_os_str().len() <= MAX_SOCKET_PATH {
+            return socket;
+        }
+        // Too long to bind (common under macOS's deep home paths).
+        std::env::temp_dir().join(format!("parakeet-{:08x}.sock", super::home_key(home)))
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::fs::{File, OpenOptions};
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    use super::{Stream, Transport};
+
+    // All pipe instances are busy; worth retrying like a refused connection.
+    const ERROR_PIPE_BUSY: i32 = 231;
+
+    // Pipe handles opened as plain files have no per-call timeouts; the
+    // caller's overall --timeout still bounds a stuck request.
+    impl Transport for File {
+        fn set_timeouts(&self, _read: Duration, _write: Duration) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    pub fn connect(endpoint: &Path) -> io::Result<Stream> {
+        match OpenOptions::new().read(true).write(true).open(endpoint) {
+            Ok(pipe) => Ok(Box::new(pipe)),
+            Err(err) if err.raw_os_error() == Some(ERROR_PIPE_BUSY) => {
+                Err(io::Error::new(io::ErrorKind::WouldBlock, err))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn default_endpoint(home: &Path) -> PathBuf {
+        PathBuf::from(format!(r"\\.\pipe\parakeet-{:08x}", super::home_key(home)))
+    }
+}
+
+pub use imp::{connect, default_endpoint};
+
+/// Whether a daemon is accepting connections on `endpoint` right now.
+pub fn is_reachable(endpoint: &Path) -> bool {
+    // A missing socket file means no daemon; named pipes have no file to check.
+    (cfg!(windows) || endpoint.exists()) && connect(endpoint).is_ok()
+}
+
+// Keys per-install endpoints outside PARAKEET_HOME by the home path, so
+// separate installs don't share a daemon.
+fn home_key(home: &Path) -> u32 {
+    let mut hasher = DefaultHasher::new();
+    home.hash(&mut hasher);
+    hasher.finish() as u32
+}
@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+// Capitalized words that say nothing about the recording's names.
+const COMMON_CAPITALIZED: &[&str] = &[
+    "I",
+    "I'm",
+    "I've",
+    "I'll",
+    "I'd",
+    "OK",
+    "Okay",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const MIN_TERM_CHARS: usize = 3;
+
+/// Proper-noun candidates from a first-pass transcript: capitalized words
+/// (and runs of them, e.g. "Jane Doe") that appear mid-sentence, where
+/// capitalization is the model's own signal for a name.
+///
+/// Spellings that differ only in case are merged, keeping the most frequent
+/// form. Terms already in `known` are left out.
+pub fn learned_terms(transcript: &str, known: &BTreeSet<String>) -> Vec<String> {
+    let known_lower: BTreeSet<String> = known.iter().map(|t| t.to_lowercase()).collect();
+    let mut counts: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+    let mut run: Vec<String> = Vec::new();
+    let mut sentence_start = true;
+    for raw in transcript.split_whitespace() {
+        let word = raw.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'');
+        let capitalized = word.chars().next().is_some_and(char::is_uppercase);
+        if capitalized && !sentence_start && !COMMON_CAPITALIZED.contains(&word) {
+            run.push(word.to_string());
+        } else {
+            flush_run(&mut run, &mut counts);
+        }
+        sentence_start = raw.ends_with(['.', '!', '?']);
+        // Trailing punctuation (commas, periods) also closes a multi-word name.
+        if raw.ends_with(|c: char| !c.is_alphanumeric()) {
+            flush_run(&mut run, &mut counts);
+        }
+    }
+    flush_run(&mut run, &mut counts);
+
+    counts
+        .into_iter()
+        .filter(|(lower, _)| !known_lower.contains(lower))
+        .filter_map(|(_, spellings)| {
+            spellings
+                .into_iter()
+                .max_by_key(|(_, n)| *n)
+                .map(|(spelling, _)| spelling)
+        })
+        .collect()
+}
+
+fn flush_run(run: &mut Vec<String>, counts: &mut BTreeMap<String, BTreeMap<String, usize>>) {
+    if run.is_empty() {
+        return;
+    }
+    let term = run.join(" ");
+    run.clear();
+    if term.chars().count() < MIN_TERM_CHARS {
+        return;
+    }
+    *counts
+        .entry(term.to_lowercase())
+        .or_default()
+        .entry(term)
+        .or_default() += 1;
+}
+
+/// Writes the first-pass vocab plus learned terms as the second-pass vocab file.
+pub fn write_vocab(work_dir: &Path, base: Option<&Path>, learned: &[String]) -> Result<PathBuf> {
+    let mut terms: BTreeSet<String> = read_terms(base)?;
+    terms.extend(learned.iter().cloned());
+    let path = work_dir.join("two_pass_vocab.txt");
+    let mut out = String::new();
+    for term in terms {
+        out.push_str(&term);
+        out.push('\n');
+    }
+    fs::write(&path, out)
+        .with_context(|| format!("failed writing two-pass vocab: {}", path.display()))?;
+    Ok(path)
+}
+
+pub fn read_terms(path: Option<&Path>) -> Result<BTreeSet<String>> {
+    let Some(path) = path else {
+        return Ok(BTreeSet::new());
+    };
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("failed reading vocab: {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|t| !t.is_empty() && !t.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
@@ -0,0 +1,85 @@
+//! Per-user areas in a shared `$PARAKEET_HOME` (`per_user_homes` in
+//! config.json). One install and its daemon can then serve several UNIX
+//! users on a GPU box: each user's history, archive, downloads and job
+//! workdirs live in `users/<uid>` (mode 0700), and the daemon checks every
+//! request against the caller's credentials (SO_PEERCRED).
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result, bail};
+
+use crate::{config, parakeet_home};
+
+pub fn enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    // An unreadable config is reported by the command that loads it.
+    *ENABLED.get_or_init(|| config::load().is_ok_and(|config| config.per_user_homes))
+}
+
+/// This user's area: `$PARAKEET_HOME/users/<uid>`, or the home itself when
+/// `per_user_homes` is off.
+pub fn dir() -> PathBuf {
+    match uid() {
+        Some(uid) if enabled() => parakeet_home().join("users").join(uid.to_string()),
+        _ => parakeet_home(),
+    }
+}
+
+/// Creates this user's area. `users/` itself is world-writable and sticky
+/// (like /tmp) so every user can add their own directory, which must belong
+/// to them and be closed to everyone else.
+#[cfg(unix)]
+pub fn prepare() -> Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    let Some(uid) = uid().filter(|_| enabled()) else {
+        return Ok(());
+    };
+    let users = parakeet_home().join("users");
+    if !users.exists() {
+        fs::create_dir_all(&users)
+            .with_context(|| format!("failed creating {}", users.display()))?;
+        fs::set_permissions(&users, fs::Permissions::from_mode(0o1777))
+            .with_context(|| format!("failed setting permissions of {}", users.display()))?;
+    }
+    let dir = users.join(uid.to_string());
+    if let Err(err) = fs::DirBuilder::new().mode(0o700).create(&dir)
+        && err.kind() != std::io::ErrorKind::AlreadyExists
+    {
+        return Err(err).with_context(|| format!("failed creating {}", dir.display()));
+    }
+    let meta =
+        fs::symlink_metadata(&dir).with_context(|| format!("failed reading {}", dir.display()))?;
+    if !meta.is_dir() || meta.uid() != uid {
+        bail!(
+            "{} is not a directory owned by uid {uid}; refusing to keep transcripts there",
+            dir.display()
+        );
+    }
+    if meta.mode() & 0o077 != 0 {
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))
+            .with_context(|| format!("failed setting permissions of {}", dir.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn prepare() -> Result<()> {
+    if enabled() {
+        eprintln!("[parakeet] per_user_homes needs unix; using the shared home");
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn uid() -> Option<u32> {
+    // SAFETY: getuid cannot fail.
+    Some(unsafe { libc::getuid() })
+}
+
+#[cfg(not(unix))]
+fn uid() -> Option<u32> {
+    None
+}
@@ -0,0 +1,373 @@
+//! Region transcription: finds what is worth transcribing before calling the
+//! backend and sends only those regions, with their original offsets, so
+//! timestamps match the input.
+//!
+//! - `--vad` skips silence, for sparse recordings (lectures with long breaks,
+//!   voicemail, surveillance-style captures). Detection is energy based: each
+//!   30 ms frame is compared with the recording's own noise floor, and speech
+//!   runs closer than `--vad-min-silence` are merged and padded.
+//! - `--skip-music` drops the regions `music::regions` classifies as music
+//!   and marks them `[music]` in the output.
+//!
+//! Both read 16 kHz mono PCM decoded by ffmpeg.
+
+use std::io::Read;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+
+use crate::progress::Progress;
+use crate::{
+    BackendRequest, BackendResponse, PostprocessStep, TranscribeCli, chunk, media, music, tools,
+};
+
+/// Silences shorter than this stay inside a region [seconds].
+pub const DEFAULT_MIN_SILENCE_SEC: f64 = 2.0;
+
+const SAMPLE_RATE: usize = 16_000;
+pub const FRAME_SEC: f64 = 0.03;
+// A frame is speech this far above the noise floor, within the clamp below.
+const MARGIN_DB: f64 = 12.0;
+const THRESHOLD_DB: (f64, f64) = (-60.0, -35.0);
+// Speech runs shorter than this are clicks and bumps.
+const MIN_SPEECH_SEC: f64 = 0.25;
+// Kept around each region so word onsets and tails are not clipped.
+const PAD_SEC: f64 = 0.4;
+
+/// One 30 ms frame of the decoded audio.
+pub struct Frame {
+    /// RMS level in dBFS.
+    pub level_db: f64,
+    /// Sign changes per sample (0-1).
+    pub crossing_rate: f64,
+}
+
+/// Transcribes only the regions `--vad` and `--skip-music` keep. `None`
+/// when detection finds nothing to skip, in which case the caller
+/// transcribes the whole range as usual.
+pub async fn transcribe(
+    cli: &TranscribeCli,
+    request: &BackendRequest<'_>,
+    progress: &Progress,
+) -> Result<Option<BackendResponse>> {
+    let min_silence = cli.vad_min_silence.unwrap_or(DEFAULT_MIN_SILENCE_SEC);
+    if min_silence <= 0.0 {
+        bail!("--vad-min-silence must be positive");
+    }
+    let duration = media::probe_duration(request.input)?;
+    let range_start = request.start_sec.unwrap_or_default();
+    let range_end = request.end_sec.unwrap_or(duration).min(duration);
+    let range = (range_start, range_end);
+
+    progress.set_message("analyzing audio".to_string());
+    let frames = frames(request, range)?;
+    let mut regions = vec![range];
+    let mut steps = Vec::new();
+    if cli.vad {
+        regions = speech_regions(&frames, range, min_silence);
+        let speech_sec: f64 = regions.iter().map(|(start, end)| end - start).sum();
+        if cli.verbose {
+            eprintln!(
+                "[parakeet] vad: {} speech region(s), {speech_sec:.1}s of {:.1}s",
+                regions.len(),
+                range_end - range_start
+            );
+        }
+        if regions.is_empty() {
+            eprintln!("[parakeet] warning: --vad found no speech; transcribing everything");
+            return Ok(None);
+        }
+        steps.push(PostprocessStep::new(
+            "vad",
+            1,
+            serde_json::json!({
+                "regions": regions.len(),
+                "speech_sec": speech_sec,
+                "audio_sec": range_end - range_start,
+                "min_silence_sec": min_silence,
+            }),
+        ));
+    }
+    let music = if cli.skip_music {
+        music::regions(&frames, range)
+    } else {
+        Vec::new()
+    };
+    if !music.is_empty() {
+        regions = subtract(&regions, &music);
+        let music_sec: f64 = music.iter().map(|(start, end)| end - start).sum();
+        if cli.verbose {
+            eprintln!(
+                "[parakeet] skip-music: {} music region(s), {music_sec:.1}s",
+                music.len()
+            );
+        }
+        if regions.is_empty() {
+            eprintln!("[parakeet] warning: --skip-music found only music; transcribing everything");
+            return Ok(None);
+        }
+        steps.push(PostprocessStep::new(
+            "skip_music",
+            1,
+            serde_json::json!({ "regions": music.len(), "music_sec": music_sec }),
+        ));
+    }
+    if let [(start, end)] = regions.as_slice()
+        && *start <= range_start
+        && *end >= range_end
+    {
+        return Ok(None);
+    }
+
+    // Regions longer than a chunk are chunked as usual.
+    let windows: Vec<(f64, f64)> = match cli.chunk_minutes {
+        Some(minutes) => {
+            let overlap = cli
+                .chunk_overlap
+                .unwrap_or(crate::quality::DEFAULT_CHUNK_OVERLAP_SEC);
+            let mut windows = Vec::new();
+            for &(start, end) in &regions {
+                windows.extend(chunk::plan_windows(start, end, minutes * 60.0, overlap)?);
+            }
+            windows
+        }
+        None => regions.clone(),
+    };
+    if cli.no_daemon && windows.len() > 1 {
+        eprintln!(
+            "[parakeet] warning: transcribing regions without the daemon reloads the model per region"
+        );
+    }
+    let (mut combined, parallel) =
+        chunk::transcribe_windows(cli, request, &windows, range, progress).await?;
+    music::mark(&mut combined, &music);
+    for step in &mut steps {
+        step.params["windows"] = windows.len().into();
+        step.params["parallel"] = parallel.into();
+    }
+    combined.postprocessing.extend(steps);
+    Ok(Some(combined))
+}
+
+// `regions` without the parts inside `cut`; both sorted and disjoint.
+fn subtract(regions: &[(f64, f64)], cut: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut kept = Vec::new();
+    for &(mut start, end) in regions {
+        for &(cut_start, cut_end) in cut {
+            if cut_end <= start || cut_start >= end {
+                continue;
+            }
+            if cut_start - start >= MIN_SPEECH_SEC {
+                kept.push((start, cut_start));
+            }
+            start = start.max(cut_end);
+        }
+        if end - start >= MIN_SPEECH_SEC {
+            kept.push((start, end));
+        }
+    }
+    kept
+}
+
+/// Absolute `(start, end)` of the padded speech regions within `range`.
+fn speech_regions(
+    frames: &[Frame],
+    (range_start, range_end): (f64, f64),
+    min_silence: f64,
+) -> Vec<(f64, f64)> {
+    let mut sorted: Vec<f64> = frames.iter().map(|frame| frame.level_db).collect();
+    sorted.sort_by(f64::total_cmp);
+    let Some(&floor) = sorted.get(sorted.len() / 10) else {
+        return Vec::new();
+    };
+    let threshold = (floor + MARGIN_DB).clamp(THRESHOLD_DB.0, THRESHOLD_DB.1);
+
+    // Speech runs as frame times relative to range_start.
+    let mut runs: Vec<(f64, f64)> = Vec::new();
+    for (idx, frame) in frames.iter().enumerate() {
+        if frame.level_db < threshold {
+            continue;
+        }
+        let (start, end) = (idx as f64 * FRAME_SEC, (idx + 1) as f64 * FRAME_SEC);
+        match runs.last_mut() {
+            Some(run) if start - run.1 < min_silence => run.1 = end,
+            _ => runs.push((start, end)),
+        }
+    }
+
+    let mut regions: Vec<(f64, f64)> = Vec::new();
+    for (start, end) in runs {
+        if end - start < MIN_SPEECH_SEC {
+            continue;
+        }
+        let start = (range_start + start - PAD_SEC).max(range_start);
+        let end = (range_start + end + PAD_SEC).min(range_end);
+        match regions.last_mut() {
+            Some(region) if start <= region.1 => region.1 = end,
+            _ => regions.push((start, end)),
+        }
+    }
+    regions
+}
+
+fn frames(request: &BackendRequest<'_>, (start, end): (f64, f64)) -> Result<Vec<Frame>> {
+    let mut cmd = Command::new(tools::require(&tools::FFMPEG)?);
+    cmd.args(["-v", "error"]);
+    if start > 0.0 {
+        cmd.arg("-ss").arg(format!("{start:.3}"));
+    }
+    cmd.arg("-i").arg(request.input);
+    cmd.arg("-t").arg(format!("{:.3}", end - start));
+    cmd.args(["-ac", "1", "-ar", "16000", "-f", "s16le", "-"]);
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed running ffmpeg to analyze the audio")?;
+
+    let frame_bytes = (SAMPLE_RATE as f64 * FRAME_SEC) as usize * 2;
+    let mut stdout = child.stdout.take().context("ffmpeg stdout missing")?;
+    let mut frame = vec![0u8; frame_bytes];
+    let mut frames = Vec::new();
+    loop {
+        let mut filled = 0;
+        while filled < frame_bytes {
+            match stdout.read(&mut frame[filled..])? {
+                0 => break,
+                read => filled += read,
+            }
+        }
+        if filled < frame_bytes {
+            break;
+        }
+        frames.push(analyze(&frame));
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "ffmpeg failed decoding {} for analysis: {}",
+            request.input.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(frames)
+}
+
+fn analyze(frame: &[u8]) -> Frame {
+    let samples: Vec<f64> = frame
+        .chunks_exact(2)
+        .map(|pair| f64::from(i16::from_le_bytes([pair[0], pair[1]])) / 32768.0)
+        .collect();
+    let energy: f64 = samples.iter().map(|sample| sample * sample).sum();
+    let rms = (energy / samples.len() as f64).sqrt();
+    let crossings = samples
+        .windows(2)
+        .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+        .count();
+    Frame {
+        level_db: 20.0 * rms.max(1e-10).log10(),
+        crossing_rate: crossings as f64 / samples.len() as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frames from `(seconds, dBFS)` runs.
+    fn frames(spec: &[(f64, f64)]) -> Vec<Frame> {
+        spec.iter()
+            .flat_map(|&(sec, level_db)| {
+                (0..(sec / FRAME_SEC).round() as usize).map(move |_| Frame {
+                    level_db,
+                    crossing_rate: 0.1,
+                })
+            })
+            .collect()
+    }
+
+    fn assert_regions(actual: &[(f64, f64)], expected: &[(f64, f64)]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a.0 - e.0).abs() < 1e-6 && (a.1 - e.1).abs() < 1e-6,
+                "{actual:?} != {expected:?}"
+            );
+        }
+    }
+
+    const QUIET: f64 = -70.0;
+    const SPEECH: f64 = -20.0;
+
+    #[test]
+    fn pads_speech_runs_split_by_long_silence() {
+        let frames = frames(&[
+            (3.0, QUIET),
+            (1.5, SPEECH),
+            (3.0, QUIET),
+            (1.5, SPEECH),
+            (3.0, QUIET),
+        ]);
+        assert_regions(
+            &speech_regions(&frames, (0.0, 12.0), 2.0),
+            &[
+                (3.0 - PAD_SEC, 4.5 + PAD_SEC),
+                (7.5 - PAD_SEC, 9.0 + PAD_SEC),
+            ],
+        );
+    }
+
+    #[test]
+    fn merges_across_short_silences_and_overlapping_pads() {
+        // A pause under --vad-min-silence stays inside the region.
+        let short_pause = frames(&[(3.0, QUIET), (1.2, SPEECH), (0.9, QUIET), (1.2, SPEECH)]);
+        assert_regions(
+            &speech_regions(&short_pause, (0.0, 9.3), 2.0),
+            &[(3.0 - PAD_SEC, 6.3 + PAD_SEC)],
+        );
+        // Separate runs whose padding meets become one region.
+        let close = frames(&[(3.0, QUIET), (1.2, SPEECH), (0.6, QUIET), (1.2, SPEECH)]);
+        assert_regions(
+            &speech_regions(&close, (0.0, 9.0), 0.5),
+            &[(3.0 - PAD_SEC, 6.0 + PAD_SEC)],
+        );
+    }
+
+    #[test]
+    fn drops_runs_shorter_than_min_speech() {
+        let click = frames(&[(3.0, QUIET), (MIN_SPEECH_SEC - 0.1, SPEECH), (3.0, QUIET)]);
+        assert!(speech_regions(&click, (0.0, 6.15), 2.0).is_empty());
+        assert!(speech_regions(&[], (0.0, 1.0), 2.0).is_empty());
+    }
+
+    #[test]
+    fn regions_are_in_original_time() {
+        // Frames of a --start 100 range; speech runs to its end.
+        let frames = frames(&[(3.0, QUIET), (2.1, SPEECH), (3.0, QUIET), (1.8, SPEECH)]);
+        assert_regions(
+            &speech_regions(&frames, (100.0, 109.9), 2.0),
+            &[(102.6, 105.5), (107.7, 109.9)],
+        );
+    }
+
+    #[test]
+    fn subtracts_cut_regions() {
+        // A cut inside a region splits it.
+        assert_regions(
+            &subtract(&[(0.0, 10.0)], &[(4.0, 6.0)]),
+            &[(0.0, 4.0), (6.0, 10.0)],
+        );
+        // Leftovers shorter than MIN_SPEECH_SEC go.
+        assert_regions(&subtract(&[(0.0, 10.0)], &[(0.1, 9.9)]), &[]);
+        // Cuts spanning several regions, or missing them.
+        assert_regions(
+            &subtract(
+                &[(0.0, 2.0), (3.0, 5.0), (8.0, 9.0)],
+                &[(1.0, 4.0), (6.0, 7.0)],
+            ),
+            &[(0.0, 1.0), (4.0, 5.0), (8.0, 9.0)],
+        );
+        assert_regions(&subtract(&[(0.0, 2.0)], &[(0.0, 2.0)]), &[]);
+    }
+}
@@ -0,0 +1,466 @@
+//! `parakeet voicemail`: polls an IMAP mailbox for messages with audio
+//! attachments (voicemail-to-email systems), transcribes each attachment, files
+//! audio and transcript in a directory and optionally replies to the sender
+//! with the text. IMAP and SMTP go through curl, like the webhook sink.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+
+use crate::workdir::Workdir;
+use crate::{
+    BackendResponse, TranscribeCli, VoicemailCli, config, output, porcelain, tools, userhome,
+};
+
+const DEFAULT_INTERVAL_SEC: u64 = 300;
+const DEFAULT_PASSWORD_ENV: &str = "PARAKEET_IMAP_PASSWORD";
+const CONNECT_TIMEOUT_SEC: &str = "30";
+const AUDIO_EXTENSIONS: &[&str] = &["wav", "mp3", "m4a", "ogg", "opus", "amr", "gsm", "flac"];
+
+/// The `voicemail` section of config.json.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct VoicemailConfig {
+    /// Mailbox to poll, e.g. `imaps://mail.example.com/INBOX`.
+    pub imap_url: Option<String>,
+    pub user: Option<String>,
+    /// Env var holding the IMAP/SMTP password (default `PARAKEET_IMAP_PASSWORD`).
+    pub password_env: Option<String>,
+    /// Where audio and transcripts are filed (default `<home>/voicemail`).
+    pub out_dir: Option<PathBuf>,
+    /// Move handled messages to this folder; otherwise they stay, marked read.
+    pub done_folder: Option<String>,
+    /// Reply to the sender with the transcript through this server, e.g.
+    /// `smtps://mail.example.com`.
+    pub smtp_url: Option<String>,
+    /// Sender address of replies (default `user`).
+    pub reply_from: Option<String>,
+    /// Extra transcribe flags, e.g. `["--model", "fast"]`.
+    pub args: Vec<String>,
+    pub interval_sec: Option<u64>,
+}
+
+pub async fn run(cli: VoicemailCli) -> Result<()> {
+    if cli.porcelain {
+        porcelain::enable();
+    }
+    let settings = config::load()?.voicemail;
+    let Some(url) = cli.imap_url.clone().or(settings.imap_url.clone()) else {
+        bail!(
+            "voicemail needs a mailbox: set voicemail.imap_url in config.json or pass --imap-url"
+        );
+    };
+    let password = match &settings.user {
+        Some(_) => {
+            let env = settings
+                .password_env
+                .as_deref()
+                .unwrap_or(DEFAULT_PASSWORD_ENV);
+            Some(
+                std::env::var(env)
+                    .with_context(|| format!("voicemail password not set in {env}"))?,
+            )
+        }
+        None => None,
+    };
+    let mailbox = Mailbox {
+        url: url.trim_end_matches('/').to_string(),
+        user: settings.user.clone(),
+        password,
+    };
+    let out_dir = settings
+        .out_dir
+        .clone()
+        .unwrap_or_else(|| userhome::dir().join("voicemail"));
+    let interval = cli
+        .interval
+        .or(settings.interval_sec)
+        .unwrap_or(DEFAULT_INTERVAL_SEC);
+    // Messages that failed in this run; retried by the next run, not every poll.
+    let mut failed = HashSet::new();
+    loop {
+        match poll(&mailbox, &settings, &out_dir, &mut failed).await {
+            Ok(0) => {}
+            Ok(handled) => eprintln!("[parakeet] voicemail: handled {handled} message(s)"),
+            Err(err) if cli.once => return Err(err),
+            Err(err) => eprintln!("[parakeet] voicemail: {err:#}"),
+        }
+        if cli.once {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+async fn poll(
+    mailbox: &Mailbox,
+    settings: &VoicemailConfig,
+    out_dir: &Path,
+    failed: &mut HashSet<u64>,
+) -> Result<usize> {
+    let mut handled = 0;
+    for uid in mailbox.unseen()? {
+        if failed.contains(&uid) {
+            continue;
+        }
+        match handle(mailbox, settings, out_dir, uid).await {
+            Ok(true) => handled += 1,
+            Ok(false) => {}
+            Err(err) => {
+                eprintln!("[parakeet] voicemail: message {uid}: {err:#}");
+                failed.insert(uid);
+                // Fetching marked it read; leave it for the next run instead.
+                if let Err(err) = mailbox.command(&format!("UID STORE {uid} -FLAGS (\\Seen)")) {
+                    eprintln!("[parakeet] voicemail: failed marking message {uid} unread: {err:#}");
+                }
+            }
+        }
+    }
+    Ok(handled)
+}
+
+/// Transcribes the audio attachments of one message. Messages without any
+/// are left alone (but read).
+async fn handle(
+    mailbox: &Mailbox,
+    settings: &VoicemailConfig,
+    out_dir: &Path,
+    uid: u64,
+) -> Result<bool> {
+    let message = parse_message(&mailbox.fetch(uid)?);
+    if message.attachments.is_empty() {
+        return Ok(false);
+    }
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("failed creating voicemail dir: {}", out_dir.display()))?;
+    let mut transcripts = Vec::new();
+    for attachment in &message.attachments {
+        let name = format!("{}-{uid}-{}", output::today(), attachment.name);
+        let audio = output::unique_path(out_dir, &name);
+        fs::write(&audio, &attachment.data)
+            .with_context(|| format!("failed writing {}", audio.display()))?;
+        let path = audio.display().to_string();
+        let response = match transcribe(&audio, out_dir, &settings.args).await {
+            Ok(response) => response,
+            Err(err) => {
+                porcelain::failed(&path, &err);
+                return Err(err);
+            }
+        };
+        porcelain::transcribed(&path, &response);
+        eprintln!(
+            "[parakeet] voicemail: transcribed {} from {}",
+            audio.display(),
+            message.from
+        );
+        transcripts.push((attachment.name.clone(), response.transcript));
+    }
+    if let Some(smtp_url) = &settings.smtp_url {
+        reply(mailbox, settings, smtp_url, &message, &transcripts)?;
+    }
+    if let Some(folder) = &settings.done_folder {
+        mailbox.command(&format!("UID MOVE {uid} {}", imap_quote(folder)))?;
+    }
+    Ok(true)
+}
+
+/// Runs the usual transcription path (daemon if running) and files the
+/// transcript next to the audio as `<stem>.<ext>`.
+async fn transcribe(audio: &Path, out_dir: &Path, args: &[String]) -> Result<BackendResponse> {
+    let argv = [
+        "parakeet",
+        "--progress",
+        "none",
+        "--name-template",
+        "{stem}.{ext}",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .chain(["--input".to_string(), audio.display().to_string()])
+    .chain(["--out".to_string(), out_dir.display().to_string()])
+    .chain(args.iter().cloned());
+    let cli = TranscribeCli::try_parse_from(argv).context("invalid voicemail.args")?;
+    let (_, response) = crate::transcribe_job(cli).await?;
+    Ok(response)
+}
+
+fn reply(
+    mailbox: &Mailbox,
+    settings: &VoicemailConfig,
+    smtp_url: &str,
+    message: &Message,
+    transcripts: &[(String, String)],
+) -> Result<()> {
+    let Some(from) = settings.reply_from.as_ref().or(settings.user.as_ref()) else {
+        bail!("voicemail replies need voicemail.reply_from (or user)");
+    };
+    let to = address(message.reply_to.as_deref().unwrap_or(&message.from));
+    let mut mail = format!(
+        "From: {from}\r\nTo: {to}\r\nSubject: Re: {}\r\nMIME-Version: 1.0\r\n\
+         Content-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n",
+        message.subject
+    );
+    if let Some(id) = &message.message_id {
+        mail.push_str(&format!("In-Reply-To: {id}\r\nReferences: {id}\r\n"));
+    }
+    mail.push_str("\r\n");
+    for (name, text) in transcripts {
+        mail.push_str(&format!("Transcript of {name}:\r\n\r\n"));
+        // curl dot-stuffs the upload itself.
+        for line in text.lines() {
+            mail.push_str(line);
+            mail.push_str("\r\n");
+        }
+        mail.push_str("\r\n");
+    }
+    // Credentials take curl's stdin, so the mail goes through a file.
+    let scratch = Workdir::create(&userhome::dir(), false)?;
+    let path = scratch.path().join("reply.eml");
+    fs::write(&path, mail).with_context(|| format!("failed writing {}", path.display()))?;
+    let mut cmd = curl()?;
+    cmd.arg("--url")
+        .arg(smtp_url)
+        .arg("--mail-from")
+        .arg(address(from))
+        .arg("--mail-rcpt")
+        .arg(&to)
+        .arg("--upload-file")
+        .arg(&path);
+    mailbox.run(cmd)?;
+    Ok(())
+}
+
+struct Mailbox {
+    url: String,
+    user: Option<String>,
+    password: Option<String>,
+}
+
+impl Mailbox {
+    /// UIDs of unread messages.
+    fn unseen(&self) -> Result<Vec<u64>> {
+        let raw = self.command("UID SEARCH UNSEEN")?;
+        Ok(raw
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("* SEARCH"))
+            .flat_map(|ids| ids.split_whitespace().filter_map(|id| id.parse().ok()))
+            .collect())
+    }
+
+    /// The whole message; the server marks it read.
+    fn fetch(&self, uid: u64) -> Result<Vec<u8>> {
+        let mut cmd = curl()?;
+        cmd.arg(format!("{};UID={uid}", self.url));
+        self.run(cmd)
+    }
+
+    fn command(&self, request: &str) -> Result<String> {
+        let mut cmd = curl()?;
+        cmd.arg(&self.url).arg("-X").arg(request);
+        Ok(String::from_utf8_lossy(&self.run(cmd)?).into_owned())
+    }
+
+    /// Runs curl with the credentials on stdin, so they never show up in `ps`.
+    fn run(&self, mut cmd: Command) -> Result<Vec<u8>> {
+        let mut child = cmd
+            .args(["--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("failed running curl for voicemail")?;
+        let mut input = String::new();
+        if let Some(user) = &self.user {
+            let login = format!("{user}:{}", self.password.as_deref().unwrap_or_default());
+            input.push_str(&format!("user = \"{}\"\n", curl_quote(&login)));
+        }
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "curl failed for {}: {}",
+                self.url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(output.stdout)
+    }
+}
+
+fn curl() -> Result<Command> {
+    let mut cmd = Command::new(tools::require(&tools::CURL)?);
+    cmd.args(["-sS", "--connect-timeout", CONNECT_TIMEOUT_SEC]);
+    Ok(cmd)
+}
+
+fn curl_quote(raw: &str) -> String {
+    raw.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn imap_quote(raw: &str) -> String {
+    format!("\"{}\"", raw.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// `Name <addr@host>` -> `addr@host`.
+fn address(raw: &str) -> String {
+    match (raw.find('<'), raw.rfind('>')) {
+        (Some(start), Some(end)) if start < end => raw[start + 1..end].trim().to_string(),
+        _ => raw.trim().to_string(),
+    }
+}
+
+struct Message {
+    from: String,
+    reply_to: Option<String>,
+    subject: String,
+    message_id: Option<String>,
+    attachments: Vec<Attachment>,
+}
+
+struct Attachment {
+    name: String,
+    data: Vec<u8>,
+}
+
+fn parse_message(raw: &[u8]) -> Message {
+    let text = String::from_utf8_lossy(raw).replace("\r\n", "\n");
+    let (headers, body) = split_headers(&text);
+    let mut attachments = Vec::new();
+    collect_audio(&headers, body, &mut attachments);
+    Message {
+        from: header(&headers, "from").unwrap_or_default().to_string(),
+        reply_to: header(&headers, "reply-to").map(str::to_string),
+        subject: header(&headers, "subject").unwrap_or_default().to_string(),
+        message_id: header(&headers, "message-id").map(str::to_string),
+        attachments,
+    }
+}
+
+/// Header lines (continuations unfolded) and the body after the blank line.
+fn split_headers(part: &str) -> (Vec<(String, String)>, &str) {
+    let (head, body) = part.split_once("\n\n").unwrap_or((part, ""));
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// `boundary="abc"` style parameter of a header value.
+fn param(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|item| {
+        let (key, raw) = item.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| raw.trim().trim_matches('"').to_string())
+    })
+}
+
+fn collect_audio(headers: &[(String, String)], body: &str, out: &mut Vec<Attachment>) {
+    let content_type = header(headers, "content-type").unwrap_or("text/plain");
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    if mime.starts_with("multipart/") {
+        let Some(boundary) = param(content_type, "boundary") else {
+            return;
+        };
+        let delimiter = format!("--{boundary}");
+        for section in body.split(&delimiter).skip(1) {
+            if section.starts_with("--") {
+                break;
+            }
+            let section = section.strip_prefix('\n').unwrap_or(section);
+            let (part_headers, part_body) = split_headers(section);
+            collect_audio(&part_headers, part_body, out);
+        }
+        return;
+    }
+    let filename = header(headers, "content-disposition")
+        .and_then(|value| param(value, "filename"))
+        .or_else(|| param(content_type, "name"));
+    let extension = filename
+        .as_deref()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    let is_audio = mime.starts_with("audio/")
+        || extension
+            .as_deref()
+            .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext));
+    if !is_audio {
+        return;
+    }
+    let encoding = header(headers, "content-transfer-encoding").unwrap_or("7bit");
+    let data = if encoding.eq_ignore_ascii_case("base64") {
+        decode_base64(body)
+    } else {
+        body.as_bytes().to_vec()
+    };
+    let name = filename
+        .map(|name| sanitize(&name))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| format!("voicemail.{}", mime.rsplit('/').next().unwrap_or("wav")));
+    out.push(Attachment { name, data });
+}
+
+// Attachment names come from the sender; keep them to one plain file name.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string()
+}
+
+fn decode_base64(raw: &str) -> Vec<u8> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+    let mut out = Vec::with_capacity(raw.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for sextet in raw.bytes().filter_map(value) {
+        buffer = (buffer << 6) | u32::from(sextet);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    out
+}
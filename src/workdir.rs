@@ -0,0 +1,57 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+
+/// Scratch directory for one transcription job (`$PARAKEET_HOME/tmp/<job-id>`):
+/// converted audio, merged vocab and per-chunk responses live here so
+/// concurrent jobs never share files.
+///
+/// Removed on drop unless the job failed (or was cancelled) with
+/// `--keep-workdir`, in which case it is left behind for debugging.
+pub struct Workdir {
+    path: PathBuf,
+    keep_on_failure: bool,
+    succeeded: bool,
+}
+
+impl Workdir {
+    pub fn create(root_dir: &Path, keep_on_failure: bool) -> Result<Self> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let id = format!(
+            "job-{}-{}-{:04x}",
+            now.as_secs(),
+            std::process::id(),
+            now.subsec_nanos() & 0xffff
+        );
+        let path = root_dir.join("tmp").join(id);
+        fs::create_dir_all(&path)
+            .with_context(|| format!("failed creating job workdir: {}", path.display()))?;
+        Ok(Self {
+            path,
+            keep_on_failure,
+            succeeded: false,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn mark_succeeded(&mut self) {
+        self.succeeded = true;
+    }
+}
+
+impl Drop for Workdir {
+    fn drop(&mut self) {
+        if !self.succeeded && self.keep_on_failure {
+            eprintln!("[parakeet] kept job workdir: {}", self.path.display());
+            return;
+        }
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}